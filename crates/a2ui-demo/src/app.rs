@@ -110,6 +110,7 @@ live_design! {
 
     use makepad_component::theme::colors::*;
     use makepad_component::a2ui::surface::*;
+    use makepad_component::a2ui::inspector::*;
     use makepad_component::widgets::dropdown::*;
     use makepad_component::widgets::button::*;
 
@@ -201,6 +202,17 @@ live_design! {
                             }
                         }
 
+                        // Toggle the time-travel inspector panel
+                        inspector_btn = <MpButton> {
+                            text: "🔍 Inspector"
+                            draw_text: { color: #FFFFFF }
+                            draw_bg: {
+                                color: #555577
+                                color_hover: #444466
+                                color_pressed: #333355
+                            }
+                        }
+
                         // Server URL input
                         server_url = <Label> {
                             text: "localhost:8081"
@@ -217,19 +229,41 @@ live_design! {
                         }
                     }
 
-                    // A2UI Surface container with scroll
-                    surface_container = <ScrollYView> {
+                    // Surface with the inspector docked on the right
+                    content_row = <View> {
                         width: Fill
                         height: Fill
-                        show_bg: true
-                        draw_bg: { color: #222244 }
+                        flow: Right
+                        spacing: 12.0
 
-                        <View> {
+                        // A2UI Surface container with scroll
+                        surface_container = <ScrollYView> {
                             width: Fill
-                            height: Fit
-                            padding: 16.0
+                            height: Fill
+                            show_bg: true
+                            draw_bg: { color: #222244 }
+
+                            <View> {
+                                width: Fill
+                                height: Fit
+                                padding: 16.0
+
+                                a2ui_surface = <A2uiSurface> {
+                                    width: Fill
+                                    height: Fit
+                                }
+                            }
+                        }
+
+                        // Time-travel inspector (hidden until toggled)
+                        inspector_container = <ScrollYView> {
+                            visible: false
+                            width: 420
+                            height: Fill
+                            show_bg: true
+                            draw_bg: { color: #11111f }
 
-                            a2ui_surface = <A2uiSurface> {
+                            a2ui_inspector = <A2uiInspector> {
                                 width: Fill
                                 height: Fit
                             }
@@ -256,7 +290,7 @@ pub struct App {
 
     /// Live SSE connection for real-time streaming updates
     #[rust]
-    live_host: Option<A2uiHost>,
+    live_host: Option<A2uiHost<LiveStreamTransport>>,
 
     #[rust]
     is_streaming: bool,
@@ -276,6 +310,10 @@ pub struct App {
     /// Currently playing audio URL (None = not playing)
     #[rust]
     playing_audio_component_id: Option<String>,
+
+    /// Whether the inspector panel is shown
+    #[rust]
+    inspector_visible: bool,
 }
 
 impl LiveRegister for App {
@@ -440,6 +478,39 @@ impl App {
             }
         }
 
+        // Handle "Inspector" button click (MpButton)
+        let inspector_btn_ref = self.ui.widget(ids!(inspector_btn));
+        if let Some(item) = actions.find_widget_action(inspector_btn_ref.widget_uid()) {
+            if matches!(item.cast::<MpButtonAction>(), MpButtonAction::Clicked) {
+                self.inspector_visible = !self.inspector_visible;
+                self.ui
+                    .view(ids!(inspector_container))
+                    .set_visible(cx, self.inspector_visible);
+
+                // Keep message history only while the inspector can rewind it
+                let surface_ref = self.ui.widget(ids!(a2ui_surface));
+                if let Some(mut surface) = surface_ref.borrow_mut::<A2uiSurface>() {
+                    if self.inspector_visible {
+                        surface.enable_history(DEFAULT_HISTORY_CAPACITY);
+                    } else {
+                        surface.disable_history();
+                    }
+                }
+                self.ui.redraw(cx);
+            }
+        }
+
+        // Handle inspector message selection: rewind the surface to that message
+        if let Some(sequence) = self.ui.a2ui_inspector(ids!(a2ui_inspector)).rewind_requested(&actions) {
+            match self.ui.a2ui_surface(ids!(a2ui_surface)).rewind_to(cx, sequence) {
+                Ok(_) => {
+                    self.ui.label(ids!(status_label))
+                        .set_text(cx, &format!("⏪ Showing state after message #{}", sequence));
+                }
+                Err(e) => log!("Failed to rewind: {}", e),
+            }
+        }
+
         // Handle A2UI surface actions
         let surface_ref = self.ui.widget(ids!(a2ui_surface));
         if let Some(item) = actions.find_widget_action(surface_ref.widget_uid()) {
@@ -586,39 +657,41 @@ impl App {
                     self.ui.redraw(cx);
                 }
                 A2uiSurfaceAction::DataModelChanged { surface_id, path, value } => {
-                    // Update the data model with the new value
+                    // Update the data model with the new value (also synced upstream
+                    // if data sync is enabled on the processor)
                     if let Some(mut surface) = surface_ref.borrow_mut::<A2uiSurface>() {
                         if let Some(processor) = surface.processor_mut() {
-                            if let Some(data_model) = processor.get_data_model_mut(&surface_id) {
-                                // Radio button behavior for payment methods (streaming mode)
-                                let payment_methods = [
-                                    "/payment/creditCard",
-                                    "/payment/paypal",
-                                    "/payment/alipay",
-                                    "/payment/wechat",
-                                ];
-
-                                if payment_methods.contains(&path.as_str()) {
-                                    // If setting to true, deselect all others first
-                                    if value == serde_json::Value::Bool(true) {
-                                        for method in &payment_methods {
-                                            if *method != path {
-                                                data_model.set(method, serde_json::Value::Bool(false));
-                                            }
+                            // Radio button behavior for payment methods (streaming mode)
+                            let payment_methods = [
+                                "/payment/creditCard",
+                                "/payment/paypal",
+                                "/payment/alipay",
+                                "/payment/wechat",
+                            ];
+
+                            if payment_methods.contains(&path.as_str()) {
+                                // If setting to true, deselect all others first
+                                if value == serde_json::Value::Bool(true) {
+                                    for method in &payment_methods {
+                                        if *method != path {
+                                            processor.set_local_value(&surface_id, method, serde_json::Value::Bool(false));
                                         }
                                     }
                                 }
+                            }
 
-                                data_model.set(&path, value.clone());
+                            processor.set_local_value(&surface_id, &path, value.clone());
 
-                                // Computed value: when maxPrice changes, update maxPriceDisplay
-                                if path == "/filters/maxPrice" {
-                                    if let Some(price) = value.as_f64() {
-                                        let display = format!("${:.0}", price);
-                                        data_model.set("/filters/maxPriceDisplay", serde_json::Value::String(display));
-                                    }
+                            // Computed value: when maxPrice changes, update maxPriceDisplay
+                            if path == "/filters/maxPrice" {
+                                if let Some(price) = value.as_f64() {
+                                    let display = format!("${:.0}", price);
+                                    processor.set_local_value(&surface_id, "/filters/maxPriceDisplay", serde_json::Value::String(display));
                                 }
                             }
+
+                            // Persist the edit if autosave is enabled
+                            processor.autosave();
                         }
                     }
                     // Update status to show the change
@@ -655,10 +728,7 @@ impl App {
         self.ui.label(ids!(title_label)).set_text(cx, "🎨 Live A2UI Editor");
 
         // Connect to /rpc for initial UI load
-        let config = A2uiHostConfig {
-            url: "http://localhost:8081/rpc".to_string(),
-            auth_token: None,
-        };
+        let config = A2uiHostConfig::new("http://localhost:8081/rpc");
 
         let mut host = A2uiHost::new(config);
 
@@ -684,15 +754,12 @@ impl App {
 
     fn connect_live_stream(&mut self, _cx: &mut Cx) {
         // Connect to /live SSE endpoint for real-time component updates (using GET)
-        let live_config = A2uiHostConfig {
-            url: "http://localhost:8081/live".to_string(),
-            auth_token: None,
-        };
+        let live_config = A2uiHostConfig::new("http://localhost:8081/live");
 
-        let mut live_host = A2uiHost::new(live_config);
+        let mut live_host = A2uiHost::with_transport(LiveStreamTransport::new(live_config));
 
-        // Use connect_sse for GET-based SSE connection
-        match live_host.connect_sse() {
+        // The live stream is a GET-based SSE connection, no message is sent
+        match live_host.connect(MessageInput::default()) {
             Ok(()) => {
                 log!("🔴 Connected to /live SSE for real-time streaming");
                 self.live_host = Some(live_host);
@@ -705,10 +772,7 @@ impl App {
 
     fn reconnect_live(&mut self, cx: &mut Cx) {
         // Reconnect to get updates (don't clear surface - we want incremental updates)
-        let config = A2uiHostConfig {
-            url: "http://localhost:8081/rpc".to_string(),
-            auth_token: None,
-        };
+        let config = A2uiHostConfig::new("http://localhost:8081/rpc");
 
         let mut host = A2uiHost::new(config);
 
@@ -724,6 +788,12 @@ impl App {
     }
 
     fn disconnect(&mut self, cx: &mut Cx) {
+        // Cancel the agent task too, not just the local stream
+        if let Some(host) = &mut self.host {
+            if let Err(e) = host.stop() {
+                log!("Task not canceled: {}", e);
+            }
+        }
         self.host = None;
         self.is_streaming = false;
         self.ui.label(ids!(status_label)).set_text(cx, "🔌 Disconnected from server");
@@ -735,12 +805,20 @@ impl App {
             return;
         };
 
+        let surface_ref = self.ui.widget(ids!(a2ui_surface));
+
+        // Send local edits upstream (only if data sync is enabled on the processor)
+        if let Some(mut surface) = surface_ref.borrow_mut::<A2uiSurface>() {
+            if let Some(processor) = surface.processor_mut() {
+                host.sync_data(processor);
+            }
+        }
+
         let events = host.poll_all();
         if events.is_empty() {
             return;
         }
 
-        let surface_ref = self.ui.widget(ids!(a2ui_surface));
         let mut needs_redraw = false;
 
         for event in events {
@@ -779,14 +857,18 @@ impl App {
                         log!("ERROR: Could not borrow A2uiSurface!");
                     }
                     if self.live_mode {
-                        self.ui.label(ids!(status_label)).set_text(cx, "🎨 UI Updated from ui_live.json");
+                        self.ui.label(ids!(status_label)).set_text(cx, "🎨 UI Updated from live server");
                     } else {
                         self.ui.label(ids!(status_label)).set_text(cx, "💳 Streaming payment UI...");
                     }
                     needs_redraw = true;
                 }
+                A2uiHostEvent::Text(text) => {
+                    self.ui.label(ids!(status_label)).set_text(cx, &format!("💬 {}", text));
+                    needs_redraw = true;
+                }
                 A2uiHostEvent::TaskStatus { task_id: _, state } => {
-                    if state == "completed" {
+                    if state == TaskState::Completed {
                         self.ui.label(ids!(status_label)).set_text(cx, "✅ Payment page ready");
                     } else {
                         self.ui.label(ids!(status_label)).set_text(cx, &format!("💳 {}", state));
@@ -807,6 +889,24 @@ impl App {
                     }
                     needs_redraw = true;
                 }
+                A2uiHostEvent::ActionDelivery(event) => {
+                    match event {
+                        ActionDeliveryEvent::Failed { error, retry_in: Some(delay), .. } => {
+                            self.ui.label(ids!(status_label)).set_text(
+                                cx,
+                                &format!("⏳ Action not sent ({}), retrying in {:.1}s", error, delay.as_secs_f32()),
+                            );
+                        }
+                        ActionDeliveryEvent::Failed { error, retry_in: None, .. } => {
+                            self.ui.label(ids!(status_label)).set_text(cx, &format!("❌ Action dropped: {}", error));
+                        }
+                        other => log!("Action delivery: {:?}", other),
+                    }
+                    needs_redraw = true;
+                }
+                A2uiHostEvent::DataSyncFailed { surface_id, error } => {
+                    log!("Data sync for {} failed: {}", surface_id, error);
+                }
             }
         }
 
@@ -844,6 +944,9 @@ impl App {
                     self.ui.label(ids!(status_label)).set_text(cx, "🔴 Streaming component...");
                     needs_redraw = true;
                 }
+                A2uiHostEvent::Text(text) => {
+                    log!("Live stream agent text: {}", text);
+                }
                 A2uiHostEvent::TaskStatus { task_id: _, state } => {
                     log!("Live stream task status: {}", state);
                 }
@@ -854,6 +957,7 @@ impl App {
                     log!("Live stream disconnected, will reconnect...");
                     self.live_host = None;
                 }
+                A2uiHostEvent::ActionDelivery(_) | A2uiHostEvent::DataSyncFailed { .. } => {}
             }
         }
 
@@ -916,6 +1020,16 @@ impl App {
 
         self.ui.redraw(cx);
     }
+
+    /// Refresh the inspector panel from the surface's processor
+    fn sync_inspector(&mut self, cx: &mut Cx) {
+        let surface_ref = self.ui.widget(ids!(a2ui_surface));
+        if let Some(surface) = surface_ref.borrow::<A2uiSurface>() {
+            if let Some(processor) = surface.processor() {
+                self.ui.a2ui_inspector(ids!(a2ui_inspector)).update(cx, processor);
+            }
+        }
+    }
 }
 
 impl AppMain for App {
@@ -959,6 +1073,11 @@ impl AppMain for App {
 
         // Handle captured actions
         self.handle_actions(cx, &actions);
+
+        // Keep the inspector in sync with processed messages and edits
+        if self.inspector_visible {
+            self.sync_inspector(cx);
+        }
     }
 }
 
@@ -1,13 +1,23 @@
 //! Kimi A2UI Bridge Server
 //!
-//! Connects Kimi K2.5 LLM with A2UI Makepad renderer via tool use.
+//! Connects an LLM (Kimi K2.5 by default) with A2UI Makepad renderer via tool use.
+//! The provider is chosen with `LLM_PROVIDER` (see `llm_provider`).
 //!
 //! Architecture:
 //! 1. User sends chat message via POST /chat
-//! 2. Server calls Kimi K2.5 with A2UI component tools
-//! 3. Kimi returns tool_calls to build UI
+//! 2. Server calls the LLM with A2UI component tools
+//! 3. The LLM returns tool_calls to build UI
 //! 4. Server converts tool_calls to A2UI JSON
 //! 5. Streams A2UI JSON to connected Makepad clients via SSE
+//!
+//! Each A2A `contextId` has its own session (see `session`): `/chat` and
+//! `/rpc` take it from the request body, `/live` and `/reset` from a
+//! `?contextId=` query. Requests without one share the default session, and
+//! `/rpc` only joins sessions started by `/chat` or `/live`, so contexts an A2A
+//! client generated for itself share the default session too.
+
+mod llm_provider;
+mod session;
 
 use http_body_util::Full;
 use hyper::body::{Bytes, Incoming};
@@ -15,16 +25,25 @@ use hyper::server::conn::http1;
 use hyper::service::service_fn;
 use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
+use llm_provider::{
+    tool_call_messages, LlmConfig, LlmProvider, LlmRequest, ToolCall, ToolCallEvent,
+};
+use makepad_component::a2ui::{
+    parse_partial_json, referenced_ids, A2uiMessage, A2uiToolkit, BeginRendering,
+    ComponentDefinition, ComponentRegistry, ComponentType, SkeletonComponent, SurfaceState,
+    ToolCallBuilder, ToolOutput,
+};
+#[cfg(feature = "mureka")]
+use makepad_component::a2ui::StringValue;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use session::{Session, SessionStore, DEFAULT_MAX_SESSIONS, DEFAULT_SESSION, DEFAULT_SESSION_TTL};
+use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tokio::sync::broadcast;
-use tokio::sync::RwLock;
 
-const KIMI_API_URL: &str = "https://api.moonshot.ai/v1/chat/completions";
 #[cfg(feature = "mureka")]
 const MUREKA_API_URL: &str = "https://api.mureka.ai";
 
@@ -156,170 +175,15 @@ impl MurekaClient {
 // A2UI Component Tools Definition
 // ============================================================================
 
+/// Tools for every component in the standard catalog
+fn a2ui_toolkit() -> A2uiToolkit {
+    A2uiToolkit::from_registry(&ComponentRegistry::with_standard_catalog())
+}
+
 fn get_a2ui_tools() -> Value {
-    json!([
-        {
-            "type": "function",
-            "function": {
-                "name": "create_text",
-                "description": "Create a text/label component to display static or dynamic text",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID (e.g., 'title', 'label-1')"},
-                        "text": {"type": "string", "description": "Static text to display"},
-                        "dataPath": {"type": "string", "description": "JSON pointer for dynamic text binding (e.g., '/user/name')"},
-                        "style": {"type": "string", "enum": ["h1", "h3", "caption", "body"], "description": "Text style: h1=large title, h3=subtitle, caption=small, body=normal"}
-                    },
-                    "required": ["id"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "create_button",
-                "description": "Create a clickable button that triggers an action",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID"},
-                        "label": {"type": "string", "description": "Button text label"},
-                        "action": {"type": "string", "description": "Action name triggered on click (e.g., 'submit', 'cancel')"},
-                        "primary": {"type": "boolean", "description": "If true, button is highlighted as primary action"}
-                    },
-                    "required": ["id", "label", "action"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "create_textfield",
-                "description": "Create a text input field for user input",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID"},
-                        "dataPath": {"type": "string", "description": "JSON pointer for data binding (e.g., '/form/email')"},
-                        "placeholder": {"type": "string", "description": "Placeholder text shown when empty"}
-                    },
-                    "required": ["id", "dataPath"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "create_checkbox",
-                "description": "Create a checkbox toggle for boolean values",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID"},
-                        "label": {"type": "string", "description": "Label text next to checkbox"},
-                        "dataPath": {"type": "string", "description": "JSON pointer for boolean binding (e.g., '/settings/darkMode')"}
-                    },
-                    "required": ["id", "label", "dataPath"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "create_slider",
-                "description": "Create a slider for numeric value selection",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID"},
-                        "dataPath": {"type": "string", "description": "JSON pointer for numeric binding (e.g., '/volume')"},
-                        "min": {"type": "number", "description": "Minimum value"},
-                        "max": {"type": "number", "description": "Maximum value"},
-                        "step": {"type": "number", "description": "Step increment (default: 1)"}
-                    },
-                    "required": ["id", "dataPath", "min", "max"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "create_card",
-                "description": "Create a card container with visual styling (elevation, border)",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID"},
-                        "childId": {"type": "string", "description": "ID of the child component inside the card"}
-                    },
-                    "required": ["id", "childId"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "create_column",
-                "description": "Create a vertical layout container (stacks children top to bottom)",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID"},
-                        "children": {"type": "array", "items": {"type": "string"}, "description": "Array of child component IDs in order"}
-                    },
-                    "required": ["id", "children"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "create_row",
-                "description": "Create a horizontal layout container (arranges children left to right)",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID"},
-                        "children": {"type": "array", "items": {"type": "string"}, "description": "Array of child component IDs in order"}
-                    },
-                    "required": ["id", "children"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "set_data",
-                "description": "Set initial data value in the data model",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "path": {"type": "string", "description": "JSON pointer path (e.g., '/volume', '/user/name')"},
-                        "stringValue": {"type": "string", "description": "String value to set"},
-                        "numberValue": {"type": "number", "description": "Number value to set"},
-                        "booleanValue": {"type": "boolean", "description": "Boolean value to set"}
-                    },
-                    "required": ["path"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "render_ui",
-                "description": "Finalize and render the UI with the specified root component. Call this LAST after creating all components.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "rootId": {"type": "string", "description": "ID of the root component (usually a column or row)"},
-                        "title": {"type": "string", "description": "Optional title for the UI surface"}
-                    },
-                    "required": ["rootId"]
-                }
-            }
-        },
-        {
+    let mut tools = a2ui_toolkit().tools();
+    if let Some(tools) = tools.as_array_mut() {
+        tools.push(json!({
             "type": "function",
             "function": {
                 "name": "generate_music",
@@ -333,68 +197,20 @@ fn get_a2ui_tools() -> Value {
                     "required": ["prompt"]
                 }
             }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "create_audio_player",
-                "description": "Create an audio player component to play music. Use this after generate_music returns an audio URL.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "id": {"type": "string", "description": "Unique component ID"},
-                        "url": {"type": "string", "description": "Audio URL to play"},
-                        "title": {"type": "string", "description": "Song title"},
-                        "artist": {"type": "string", "description": "Artist name (optional)"}
-                    },
-                    "required": ["id", "url", "title"]
-                }
-            }
-        }
-    ])
-}
-
-// ============================================================================
-// Kimi API Types
-// ============================================================================
-
-#[derive(Debug, Deserialize)]
-struct KimiResponse {
-    choices: Vec<KimiChoice>,
-}
-
-#[derive(Debug, Deserialize)]
-struct KimiChoice {
-    message: KimiMessage,
-}
-
-#[derive(Debug, Deserialize)]
-struct KimiMessage {
-    content: Option<String>,
-    reasoning_content: Option<String>,
-    tool_calls: Option<Vec<KimiToolCall>>,
+        }));
+    }
+    tools
 }
 
-#[derive(Debug, Deserialize)]
-struct KimiToolCall {
-    id: String,
-    function: KimiFunctionCall,
-}
-
-#[derive(Debug, Deserialize)]
-struct KimiFunctionCall {
-    name: String,
-    arguments: String,
-}
+/// Root of a new surface while it streams in, until `render_ui` names the real one
+const STREAMING_ROOT: &str = "streaming-root";
 
 // ============================================================================
 // A2UI Builder - Converts tool calls to A2UI JSON
 // ============================================================================
 
 struct A2uiBuilder {
-    components: Vec<Value>,
-    data_contents: Vec<Value>,
-    root_id: Option<String>,
+    tools: ToolCallBuilder,
     #[cfg(feature = "mureka")]
     /// Pending music generation requests (prompt, instrumental)
     pending_music: Vec<(String, bool)>,
@@ -405,10 +221,13 @@ struct A2uiBuilder {
 
 impl A2uiBuilder {
     fn new() -> Self {
+        Self::continue_from(ToolCallBuilder::new(a2ui_toolkit(), "main"))
+    }
+
+    /// Apply tool calls on top of an earlier surface
+    fn continue_from(tools: ToolCallBuilder) -> Self {
         A2uiBuilder {
-            components: Vec::new(),
-            data_contents: Vec::new(),
-            root_id: None,
+            tools,
             #[cfg(feature = "mureka")]
             pending_music: Vec::new(),
             #[cfg(feature = "mureka")]
@@ -416,22 +235,14 @@ impl A2uiBuilder {
         }
     }
 
-    fn process_tool_call(&mut self, name: &str, args: &Value) {
+    fn process_tool_call(&mut self, name: &str, args: &Value) -> Result<(), String> {
         match name {
-            "create_text" => self.create_text(args),
-            "create_button" => self.create_button(args),
-            "create_textfield" => self.create_textfield(args),
-            "create_checkbox" => self.create_checkbox(args),
-            "create_slider" => self.create_slider(args),
-            "create_card" => self.create_card(args),
-            "create_column" => self.create_column(args),
-            "create_row" => self.create_row(args),
-            "set_data" => self.set_data(args),
-            "render_ui" => self.render_ui(args),
             #[cfg(feature = "mureka")]
-            "generate_music" => self.generate_music(args),
-            "create_audio_player" => self.create_audio_player(args),
-            _ => eprintln!("Unknown tool: {}", name),
+            "generate_music" => {
+                self.generate_music(args);
+                Ok(())
+            }
+            _ => self.tools.call(name, args).map(|_| ()),
         }
     }
 
@@ -442,236 +253,6 @@ impl A2uiBuilder {
         self.pending_music.push((prompt, instrumental));
     }
 
-    fn create_audio_player(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("audio-player");
-        let url = args["url"].as_str().unwrap_or("");
-        let title = args["title"].as_str().unwrap_or("Audio");
-        let artist = args["artist"].as_str();
-
-        let mut audio_player = json!({
-            "url": {"literalString": url},
-            "title": {"literalString": title}
-        });
-
-        if let Some(artist_name) = artist {
-            audio_player["artist"] = json!({"literalString": artist_name});
-        }
-
-        self.components.push(json!({
-            "id": id,
-            "component": {
-                "AudioPlayer": audio_player
-            }
-        }));
-    }
-
-    fn create_text(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("text");
-
-        let text_value = if let Some(data_path) = args["dataPath"].as_str() {
-            json!({"path": data_path})
-        } else if let Some(text) = args["text"].as_str() {
-            json!({"literalString": text})
-        } else {
-            json!({"literalString": ""})
-        };
-
-        let mut component = json!({
-            "Text": {
-                "text": text_value
-            }
-        });
-
-        if let Some(style) = args["style"].as_str() {
-            component["Text"]["usageHint"] = json!(style);
-        }
-
-        self.components.push(json!({
-            "id": id,
-            "component": component
-        }));
-    }
-
-    fn create_button(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("button");
-        let label = args["label"].as_str().unwrap_or("Button");
-        let action = args["action"].as_str().unwrap_or("click");
-        let primary = args["primary"].as_bool().unwrap_or(false);
-
-        // Create button text component
-        let text_id = format!("{}-text", id);
-        self.components.push(json!({
-            "id": text_id,
-            "component": {
-                "Text": {
-                    "text": {"literalString": label}
-                }
-            }
-        }));
-
-        // Create button
-        self.components.push(json!({
-            "id": id,
-            "component": {
-                "Button": {
-                    "child": text_id,
-                    "primary": primary,
-                    "action": {
-                        "name": action,
-                        "context": []
-                    }
-                }
-            }
-        }));
-    }
-
-    fn create_textfield(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("textfield");
-        let data_path = args["dataPath"].as_str().unwrap_or("/input");
-        let placeholder = args["placeholder"].as_str().unwrap_or("");
-
-        self.components.push(json!({
-            "id": id,
-            "component": {
-                "TextField": {
-                    "text": {"path": data_path},
-                    "placeholder": {"literalString": placeholder}
-                }
-            }
-        }));
-    }
-
-    fn create_checkbox(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("checkbox");
-        let label = args["label"].as_str().unwrap_or("Option");
-        let data_path = args["dataPath"].as_str().unwrap_or("/checked");
-
-        self.components.push(json!({
-            "id": id,
-            "component": {
-                "CheckBox": {
-                    "label": {"literalString": label},
-                    "value": {"path": data_path}
-                }
-            }
-        }));
-    }
-
-    fn create_slider(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("slider");
-        let data_path = args["dataPath"].as_str().unwrap_or("/value");
-        let min = args["min"].as_f64().unwrap_or(0.0);
-        let max = args["max"].as_f64().unwrap_or(100.0);
-        let step = args["step"].as_f64().unwrap_or(1.0);
-
-        self.components.push(json!({
-            "id": id,
-            "component": {
-                "Slider": {
-                    "value": {"path": data_path},
-                    "min": min,
-                    "max": max,
-                    "step": step
-                }
-            }
-        }));
-    }
-
-    fn create_card(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("card");
-        let child_id = args["childId"].as_str().unwrap_or("card-content");
-
-        self.components.push(json!({
-            "id": id,
-            "component": {
-                "Card": {
-                    "child": child_id
-                }
-            }
-        }));
-    }
-
-    fn create_column(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("column");
-        let children: Vec<String> = args["children"]
-            .as_array()
-            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
-            .unwrap_or_default();
-
-        self.components.push(json!({
-            "id": id,
-            "component": {
-                "Column": {
-                    "children": {"explicitList": children}
-                }
-            }
-        }));
-    }
-
-    fn create_row(&mut self, args: &Value) {
-        let id = args["id"].as_str().unwrap_or("row");
-        let children: Vec<String> = args["children"]
-            .as_array()
-            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
-            .unwrap_or_default();
-
-        self.components.push(json!({
-            "id": id,
-            "component": {
-                "Row": {
-                    "children": {"explicitList": children}
-                }
-            }
-        }));
-    }
-
-    fn set_data(&mut self, args: &Value) {
-        let path = args["path"].as_str().unwrap_or("/");
-
-        // Parse the path to build nested structure
-        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
-
-        if parts.is_empty() || parts[0].is_empty() {
-            return;
-        }
-
-        let value = if let Some(s) = args["stringValue"].as_str() {
-            json!({"valueString": s})
-        } else if let Some(n) = args["numberValue"].as_f64() {
-            json!({"valueNumber": n})
-        } else if let Some(b) = args["booleanValue"].as_bool() {
-            json!({"valueBoolean": b})
-        } else if let Some(n) = args["value"].as_f64() {
-            // Fallback for simple "value" field
-            json!({"valueNumber": n})
-        } else if let Some(s) = args["value"].as_str() {
-            json!({"valueString": s})
-        } else if let Some(b) = args["value"].as_bool() {
-            json!({"valueBoolean": b})
-        } else {
-            json!({"valueString": ""})
-        };
-
-        // For now, store as flat key-value (simplified)
-        let key = parts.last().unwrap_or(&"");
-        let mut content = json!({"key": key});
-
-        // Merge value fields
-        if let Some(obj) = value.as_object() {
-            for (k, v) in obj {
-                content[k] = v.clone();
-            }
-        }
-
-        self.data_contents.push(content);
-    }
-
-    fn render_ui(&mut self, args: &Value) {
-        if let Some(root_id) = args["rootId"].as_str() {
-            self.root_id = Some(root_id.to_string());
-        }
-    }
-
     #[cfg(feature = "mureka")]
     fn has_pending_music(&self) -> bool {
         !self.pending_music.is_empty()
@@ -686,32 +267,6 @@ impl A2uiBuilder {
     fn set_generated_audio(&mut self, songs: Vec<MurekaSong>) {
         self.generated_audio = songs;
     }
-
-    fn build_a2ui_json(&self) -> Value {
-        let root = self.root_id.as_deref().unwrap_or("root");
-
-        json!([
-            {
-                "beginRendering": {
-                    "surfaceId": "main",
-                    "root": root
-                }
-            },
-            {
-                "surfaceUpdate": {
-                    "surfaceId": "main",
-                    "components": self.components
-                }
-            },
-            {
-                "dataModelUpdate": {
-                    "surfaceId": "main",
-                    "path": "/",
-                    "contents": self.data_contents
-                }
-            }
-        ])
-    }
 }
 
 // ============================================================================
@@ -719,256 +274,391 @@ impl A2uiBuilder {
 // ============================================================================
 
 struct ServerState {
-    api_key: String,
+    provider: Box<dyn LlmProvider>,
     #[cfg(feature = "mureka")]
     mureka_client: Option<MurekaClient>,
-    tx: broadcast::Sender<String>,
-    conversation: RwLock<Vec<Value>>,
-    latest_a2ui: RwLock<Option<Value>>,
+    sessions: SessionStore,
+    /// Bearer token for the `/sessions` admin endpoints (`ADMIN_TOKEN`);
+    /// they are disabled without one
+    admin_token: Option<String>,
 }
 
 // ============================================================================
-// Kimi API Client
+// Chat
 // ============================================================================
 
-async fn call_kimi(api_key: &str, messages: Vec<Value>) -> Result<KimiResponse, String> {
-    let client = reqwest::Client::new();
+const SYSTEM_PROMPT: &str = r#"You are an A2UI generator assistant. Your job is to create user interfaces by calling the provided tools.
 
-    let request_body = json!({
-        "model": "kimi-k2.5",
-        "messages": messages,
-        "tools": get_a2ui_tools(),
-        "temperature": 1,
-        "max_tokens": 8192,
-        "stream": false
-    });
+IMPORTANT RULES:
+1. Create components using the tools (create_text, create_button, create_slider, etc.)
+2. Use create_column for vertical layouts, create_row for horizontal layouts
+3. Use create_card to wrap sections in styled containers
+4. Set initial data values with set_data for any bound components
+5. ALWAYS call render_ui as the LAST step with the root component ID
+6. Use descriptive IDs like "title", "volume-slider", "submit-btn"
+7. For sliders/checkboxes, always set initial data with set_data
+8. Use emojis in text labels to make the UI visually appealing
+9. Text values are plain strings ("Hello") or data bindings ({"path": "/user/name"})
+10. Buttons show a child component: create the button's text first and pass its ID as child
 
-    let response = client
-        .post(KIMI_API_URL)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+Example flow for "create a volume control":
+1. create_text(id="volume-label", text="🔊 Volume", usageHint="body")
+2. create_slider(id="volume-slider", value={"path": "/volume"}, min=0, max=100, step=1)
+3. create_text(id="volume-value", text={"path": "/volumeDisplay"}, usageHint="caption")
+4. create_row(id="volume-row", children=["volume-label", "volume-slider", "volume-value"])
+5. set_data(path="/volume", value=50)
+6. set_data(path="/volumeDisplay", value="50%")
+7. render_ui(rootId="volume-row")
 
-    let status = response.status();
-    let body = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+MUSIC GENERATION:
+When the user asks you to generate music (e.g., "生成一首轻松的钢琴曲", "create relaxing music"):
+1. First call generate_music(prompt="description of the music", instrumental=true/false)
+2. The system will wait for Mureka AI to generate the music (~45 seconds)
+3. The audio URL will be provided to you automatically
+4. Then create an audio player: create_audio_player(id="player", url="<audio_url>", title="Song Title")
+5. Wrap it in a nice UI with a title and card
+6. Call render_ui() at the end
 
-    if !status.is_success() {
-        return Err(format!("API error ({}): {}", status, body));
-    }
+Example for music generation:
+1. create_text(id="title", text="🎵 AI Generated Music", usageHint="h1")
+2. generate_music(prompt="relaxing piano melody with soft ambient sounds", instrumental=true)
+3. create_audio_player(id="player", url="<will be filled>", title="Relaxing Piano")
+4. create_column(id="root", children=["title", "player"])
+5. render_ui(rootId="root")
+
+USER ACTIONS:
+When the user clicks a button or triggers another action in your UI, you receive a user message holding a JSON
+object {"userAction": {...}} with actionName, sourceComponentId and resolvedContext (the values the action
+carried, e.g. form fields).
+React by updating the UI:
+1. Re-create only the components that change, using their existing IDs (a new definition replaces the old one)
+2. Use set_data to change bound values
+3. Only call render_ui if the root component changes"#;
+
+/// A user action, from the `a2uiEvent` data part of a `message/send` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserEvent {
+    action_name: String,
+    source_component_id: String,
+    #[serde(default)]
+    timestamp: Option<String>,
+    #[serde(default)]
+    resolved_context: serde_json::Map<String, Value>,
+}
 
-    serde_json::from_str(&body).map_err(|e| format!("Failed to parse response: {} - Body: {}", e, body))
+/// Find the user action in a JSON-RPC `message/send` request
+fn decode_action(request: &Value) -> Option<UserEvent> {
+    request["params"]["message"]["parts"]
+        .as_array()?
+        .iter()
+        .find_map(|part| serde_json::from_value(part["data"]["a2uiEvent"].clone()).ok())
 }
 
-/// Streaming version of call_kimi - broadcasts components as they arrive
-async fn call_kimi_stream(
-    api_key: &str,
-    messages: Vec<Value>,
-    tx: &broadcast::Sender<String>,
-) -> Result<KimiResponse, String> {
-    use futures_util::StreamExt;
-
-    let client = reqwest::Client::new();
-
-    let request_body = json!({
-        "model": "kimi-k2.5",
-        "messages": messages,
-        "tools": get_a2ui_tools(),
-        "temperature": 1,
-        "max_tokens": 8192,
-        "stream": true
-    });
+/// Ask the model for a UI from a chat message
+///
+/// Returns the body of the /chat response.
+async fn chat(state: &ServerState, session: &Session, message: &str) -> Result<Value, String> {
+    run_turn(state, session, vec![json!({"role": "user", "content": message})], false).await
+}
 
-    let response = client
-        .post(KIMI_API_URL)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+/// Report a user action to the model and let it update the UI the action came from
+///
+/// The action is a user message holding `{"userAction": {...}}`, not the
+/// result of a tool call the model never made, which providers reject for
+/// tools they weren't offered.
+async fn handle_action(state: &ServerState, session: &Session, event: &UserEvent) -> Result<Value, String> {
+    println!("[Kimi Bridge] User action ({}): {} from {}", session.context_id, event.action_name, event.source_component_id);
+    let content = json!({"userAction": event}).to_string();
+    let continue_surface = session.surface.read().await.is_some();
+    run_turn(state, session, vec![json!({"role": "user", "content": content})], continue_surface).await
+}
+
+/// Send the final status of an action turn's task to the session's /live clients
+///
+/// The A2A `status-update` goes out like A2UI updates, as a one-element array;
+/// clients reading only A2UI messages skip it.
+fn publish_task_status(session: &Session, task_id: &str, error: Option<String>) {
+    let status = match error {
+        None => json!({"state": "completed"}),
+        Some(e) => json!({
+            "state": "failed",
+            "message": {"kind": "message", "role": "agent", "parts": [{"kind": "text", "text": e}]}
+        }),
+    };
+    let update = json!([{
+        "kind": "status-update",
+        "taskId": task_id,
+        "contextId": session.context_id,
+        "status": status,
+        "final": true
+    }]);
+    let _ = session.tx.send(update.to_string());
+}
 
-    let status = response.status();
-    if !status.is_success() {
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("API error ({}): {}", status, body));
+/// Add what a tool call shows so far to `streamed`, returning whether it changed
+///
+/// Arguments still streaming in give a provisional component, so text grows as
+/// the model writes it. Components that are referenced but not defined yet get
+/// a `Skeleton` placeholder. On a new surface, `STREAMING_ROOT` lists the
+/// components nothing else refers to, so they show before `render_ui`.
+fn stream_tool_call(toolkit: &A2uiToolkit, streamed: &mut SurfaceState, call: &ToolCall) -> bool {
+    let Some(args) = parse_partial_json(&call.arguments) else {
+        return false;
+    };
+    // An ID cut short names the wrong component
+    if args.cut_off.as_deref() == Some("/id") {
+        return false;
+    }
+    // Build the component from the catalog tool
+    let Ok(ToolOutput::Component(definition)) = toolkit.dispatch("main", &call.name, &args.value) else {
+        return false;
+    };
+    if streamed.component(&definition.id) == Some(&definition) {
+        return false;
+    }
+    if streamed.root().is_none() {
+        streamed.apply(&A2uiMessage::BeginRendering(BeginRendering {
+            surface_id: "main".to_string(),
+            root: STREAMING_ROOT.to_string(),
+            styles: None,
+        }));
+    }
+    streamed.set_component(definition);
+
+    let cut_off_id = args
+        .cut_off
+        .as_deref()
+        .and_then(|pointer| args.value.pointer(pointer))
+        .and_then(Value::as_str);
+    let referenced: Vec<String> = streamed
+        .components()
+        .iter()
+        .filter(|c| c.id != STREAMING_ROOT)
+        .flat_map(|c| referenced_ids(&c.component))
+        .map(String::from)
+        .collect();
+    let missing: Vec<String> = referenced
+        .iter()
+        .filter(|id| streamed.component(id).is_none() && Some(id.as_str()) != cut_off_id)
+        .cloned()
+        .collect();
+    for id in missing {
+        streamed.set_component(ComponentDefinition {
+            id,
+            weight: None,
+            visible: None,
+            enabled: None,
+            component: ComponentType::Skeleton(SkeletonComponent::default()),
+        });
     }
 
-    // Accumulate tool calls from stream
-    let mut tool_calls: HashMap<i64, (String, String, String)> = HashMap::new(); // index -> (id, name, arguments)
-    let mut processed_indices: std::collections::HashSet<i64> = std::collections::HashSet::new();
-    let mut sent_begin = false;
-    let mut accumulated_components: Vec<Value> = Vec::new(); // For ui_live.json updates
+    if streamed.root() == Some(STREAMING_ROOT) {
+        let referenced: HashSet<&str> = referenced.iter().map(String::as_str).collect();
+        let top_level: Vec<&str> = streamed
+            .components()
+            .iter()
+            .map(|c| c.id.as_str())
+            .filter(|id| *id != STREAMING_ROOT && !referenced.contains(id))
+            .collect();
+        let root = json!({"id": STREAMING_ROOT, "children": top_level});
+        if let Ok(ToolOutput::Component(root)) = toolkit.dispatch("main", "create_column", &root) {
+            streamed.set_component(root);
+        }
+    }
+    true
+}
 
-    // Clear ui_live.json at start of new stream
-    let _ = std::fs::write("ui_live.json", "[]");
+/// Run the model on the conversation plus `messages`, streaming components
+/// to /live while their tool calls arrive (see `stream_tool_call`)
+///
+/// Clients only get what changed since the session's last update (see
+/// `SurfaceState::diff`), so their state survives conversation turns. A new
+/// UI replaces the session's components; with `continue_surface` the tool
+/// calls update the current ones instead. The conversation keeps `messages`,
+/// the model's tool calls and their results, or its text reply.
+async fn run_turn(
+    state: &ServerState,
+    session: &Session,
+    messages: Vec<Value>,
+    continue_surface: bool,
+) -> Result<Value, String> {
+    let _turn = session.turn_lock.lock().await;
+    let mut request_messages = session.conversation.read().await.clone();
+    request_messages.extend(messages.iter().cloned());
+    let request = LlmRequest {
+        system: SYSTEM_PROMPT.to_string(),
+        messages: request_messages,
+        tools: get_a2ui_tools(),
+    };
+
+    let toolkit = a2ui_toolkit();
+    let previous = session.sent.read().await.clone();
+    // Everything streamed so far, and what clients have of it
+    let mut streamed = previous.clone();
+    let mut shown = previous.clone();
+
+    let mut stream_component = |event: ToolCallEvent| {
+        let call = event.call();
+        if !stream_tool_call(&toolkit, &mut streamed, call) {
+            return;
+        }
+        let update = shown.diff(&streamed);
+        if update.is_empty() {
+            return;
+        }
+        for message in &update {
+            shown.apply(message);
+        }
+        let _ = session.tx.send(serde_json::to_string(&update).unwrap_or_default());
+        if event.is_complete() {
+            println!("[Stream] Sent component: {}", call.name);
+        }
 
-    let mut stream = response.bytes_stream();
-    let mut buffer = String::new();
+        // Write the live file for the watch-server
+        let _ = session.write_live_file(&serde_json::to_string(&shown.messages()).unwrap_or_default());
+    };
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
-        buffer.push_str(&String::from_utf8_lossy(&chunk));
+    let response = state.provider.chat(&request, &mut stream_component).await;
+    *session.sent.write().await = shown.clone();
+    let response = response?;
 
-        // Process complete SSE lines
-        while let Some(pos) = buffer.find("\n\n") {
-            let line = buffer[..pos].to_string();
-            buffer = buffer[pos + 2..].to_string();
+    // Log reasoning if present
+    if let Some(reasoning) = &response.reasoning {
+        println!("[Kimi Bridge] Reasoning: {}", reasoning);
+    }
 
-            if line.starts_with("data: ") {
-                let data = &line[6..];
-                if data == "[DONE]" {
-                    continue;
-                }
+    // Process tool calls
+    if !response.tool_calls.is_empty() {
+        println!("[Kimi Bridge] Received {} tool calls", response.tool_calls.len());
 
-                if let Ok(chunk_json) = serde_json::from_str::<Value>(data) {
-                    // Extract delta tool_calls
-                    if let Some(choices) = chunk_json.get("choices").and_then(|c| c.as_array()) {
-                        for choice in choices {
-                            if let Some(delta) = choice.get("delta") {
-                                if let Some(calls) = delta.get("tool_calls").and_then(|t| t.as_array()) {
-                                    for call in calls {
-                                        let index = call.get("index").and_then(|i| i.as_i64()).unwrap_or(0);
-                                        let id = call.get("id").and_then(|s| s.as_str()).unwrap_or("");
-                                        let func = call.get("function");
-                                        let name = func.and_then(|f| f.get("name")).and_then(|n| n.as_str()).unwrap_or("");
-                                        let args_chunk = func.and_then(|f| f.get("arguments")).and_then(|a| a.as_str()).unwrap_or("");
-
-                                        let entry = tool_calls.entry(index).or_insert_with(|| (String::new(), String::new(), String::new()));
-
-                                        if !id.is_empty() {
-                                            entry.0 = id.to_string();
-                                        }
-                                        if !name.is_empty() {
-                                            entry.1 = name.to_string();
-                                        }
-                                        entry.2.push_str(args_chunk);
-
-                                        // Try to parse complete arguments and send component immediately
-                                        if !entry.1.is_empty() && !entry.2.is_empty() && !processed_indices.contains(&index) {
-                                            if let Ok(args) = serde_json::from_str::<Value>(&entry.2) {
-                                                // Mark as processed
-                                                processed_indices.insert(index);
-
-                                                // Send beginRendering on first component
-                                                if !sent_begin {
-                                                    sent_begin = true;
-                                                    let begin_msg = json!([
-                                                        {"beginRendering": {"surfaceId": "main", "root": "streaming-root"}}
-                                                    ]);
-                                                    let _ = tx.send(begin_msg.to_string());
-                                                    println!("[Stream] Sent beginRendering");
-                                                }
+        let mut builder = match session.surface.read().await.clone() {
+            Some(surface) if continue_surface => A2uiBuilder::continue_from(surface),
+            _ => A2uiBuilder::new(),
+        };
+
+        let mut results = Vec::with_capacity(response.tool_calls.len());
+        for tc in &response.tool_calls {
+            let args: Value = serde_json::from_str(&tc.arguments).unwrap_or(json!({}));
+            println!("[Kimi Bridge] Tool: {}({})", tc.name, tc.arguments);
+            results.push(match builder.process_tool_call(&tc.name, &args) {
+                Ok(()) => json!({"status": "ok"}),
+                Err(e) => {
+                    eprintln!("[Kimi Bridge] {}", e);
+                    json!({"error": e})
+                }
+            });
+        }
 
-                                                // Build component JSON directly based on tool name
-                                                let component = build_component_json(&entry.1, &args);
-                                                if let Some(comp) = component {
-                                                    let update_msg = json!([
-                                                        {"surfaceUpdate": {"surfaceId": "main", "components": [comp.clone()]}}
-                                                    ]);
-                                                    let _ = tx.send(update_msg.to_string());
-                                                    println!("[Stream] Sent component: {}", entry.1);
-
-                                                    // Accumulate and write to ui_live.json for /rpc polling
-                                                    accumulated_components.push(comp);
-                                                    let a2ui = json!([
-                                                        {"beginRendering": {"surfaceId": "main", "root": "streaming-root"}},
-                                                        {"surfaceUpdate": {"surfaceId": "main", "components": accumulated_components}},
-                                                        {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": []}}
-                                                    ]);
-                                                    let _ = std::fs::write("ui_live.json", serde_json::to_string(&a2ui).unwrap_or_default());
-                                                    println!("[Stream] Updated ui_live.json ({} components)", accumulated_components.len());
+        // Handle pending music generation (only with mureka feature)
+        #[cfg(feature = "mureka")]
+        if builder.has_pending_music() {
+            if let Some(ref mureka) = state.mureka_client {
+                println!("[Kimi Bridge] Processing music generation requests...");
+
+                for (prompt, instrumental) in builder.get_pending_music() {
+                    println!("[Kimi Bridge] Generating music: '{}' (instrumental: {})", prompt, instrumental);
+
+                    match mureka.generate_music(&prompt, instrumental).await {
+                        Ok(job_id) => {
+                            println!("[Kimi Bridge] Mureka job started: {}", job_id);
+                            println!("[Kimi Bridge] Waiting for music generation (this may take ~45 seconds)...");
+
+                            // Poll for completion (max 20 attempts = ~60 seconds)
+                            match mureka.wait_for_completion(&job_id, 20).await {
+                                Ok(songs) => {
+                                    println!("[Kimi Bridge] Music generated! {} songs available", songs.len());
+                                    builder.set_generated_audio(songs.clone());
+
+                                    // Update audio player components with real URLs
+                                    if let Some(song) = songs.first() {
+                                        if let Some(url) = &song.audio_url {
+                                            println!("[Kimi Bridge] Audio URL: {}", url);
+                                            // Find and update AudioPlayer components
+                                            for comp in builder.tools.components_mut() {
+                                                if let ComponentType::AudioPlayer(audio_player) = &mut comp.component {
+                                                    audio_player.url = StringValue::literal(url.as_str());
+                                                    if let Some(title) = &song.title {
+                                                        audio_player.title = Some(StringValue::literal(title.as_str()));
+                                                    }
+                                                    audio_player.artist = Some(StringValue::literal("Mureka AI"));
                                                 }
                                             }
                                         }
                                     }
                                 }
+                                Err(e) => {
+                                    eprintln!("[Kimi Bridge] Music generation failed: {}", e);
+                                }
                             }
                         }
+                        Err(e) => {
+                            eprintln!("[Kimi Bridge] Failed to start music generation: {}", e);
+                        }
                     }
                 }
+            } else {
+                println!("[Kimi Bridge] Warning: Music generation requested but MUREKA_API_KEY not set");
             }
         }
-    }
 
-    // Build final response from accumulated tool calls
-    // Note: Components have already been streamed, this is for compatibility
-    let tool_call_list: Vec<KimiToolCall> = tool_calls.into_iter()
-        .filter(|(_, (_, name, _))| !name.is_empty())
-        .map(|(_, (id, name, args))| KimiToolCall {
-            id,
-            function: KimiFunctionCall { name, arguments: args },
-        })
-        .collect();
+        // The new surface keeps the data clients already have, and drops
+        // components the new tree no longer uses
+        let mut surface = previous;
+        for message in builder.tools.messages() {
+            surface.apply(&message);
+        }
+        let pruned = surface.prune();
+        if !pruned.is_empty() {
+            println!("[Kimi Bridge] Pruned orphaned components: {:?}", pruned);
+        }
 
-    Ok(KimiResponse {
-        choices: vec![KimiChoice {
-            message: KimiMessage {
-                content: None,
-                reasoning_content: None,
-                tool_calls: if tool_call_list.is_empty() { None } else { Some(tool_call_list) },
-            },
-        }],
-    })
-}
+        let a2ui_json = serde_json::to_value(surface.messages()).unwrap_or_else(|_| json!([]));
+        let a2ui_str = serde_json::to_string_pretty(&a2ui_json).unwrap();
+        // Clients only need what changed since the last update
+        let update_json = serde_json::to_value(shown.diff(&surface)).unwrap_or_else(|_| json!([]));
 
-/// Build component JSON directly from tool name and args (for streaming)
-fn build_component_json(name: &str, args: &Value) -> Option<Value> {
-    let id = args.get("id").and_then(|v| v.as_str()).unwrap_or("comp");
+        println!("[Kimi Bridge] Generated A2UI JSON, broadcasting {} messages...", update_json.as_array().map_or(0, Vec::len));
 
-    match name {
-        "create_text" => {
-            let text = args.get("text").and_then(|v| v.as_str()).unwrap_or("");
-            let usage_hint = args.get("usage_hint").and_then(|v| v.as_str());
-            let mut text_obj = json!({
-                "text": {"literalString": text}
-            });
-            if let Some(hint) = usage_hint {
-                text_obj["usageHint"] = json!(hint);
-            }
-            Some(json!({"id": id, "component": {"Text": text_obj}}))
-        }
-        "create_audio_player" => {
-            let url = args.get("url").and_then(|v| v.as_str()).unwrap_or("");
-            let title = args.get("title").and_then(|v| v.as_str()).unwrap_or("Audio");
-            let artist = args.get("artist").and_then(|v| v.as_str());
-            let mut audio_obj = json!({
-                "url": {"literalString": url},
-                "title": {"literalString": title}
-            });
-            if let Some(a) = artist {
-                audio_obj["artist"] = json!({"literalString": a});
-            }
-            Some(json!({"id": id, "component": {"AudioPlayer": audio_obj}}))
-        }
-        "create_column" => {
-            let children = args.get("children").and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect::<Vec<_>>())
-                .unwrap_or_default();
-            Some(json!({"id": id, "component": {"Column": {"children": {"explicitList": children}}}}))
-        }
-        "create_row" => {
-            let children = args.get("children").and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect::<Vec<_>>())
-                .unwrap_or_default();
-            Some(json!({"id": id, "component": {"Row": {"children": {"explicitList": children}}}}))
-        }
-        "create_button" => {
-            let label = args.get("label").and_then(|v| v.as_str()).unwrap_or("Button");
-            let label_id = format!("{}-label", id);
-            Some(json!({"id": id, "component": {"Button": {"child": label_id, "primary": true}}}))
-        }
-        "create_card" => {
-            let child = args.get("child").and_then(|v| v.as_str()).unwrap_or("");
-            Some(json!({"id": id, "component": {"Card": {"child": child}}}))
+        // Write the live file for the watch-server
+        if let Err(e) = session.write_live_file(&a2ui_str) {
+            eprintln!("[Kimi Bridge] Failed to write {}: {}", session.live_file.display(), e);
+        } else {
+            println!("[Kimi Bridge] Written to {}", session.live_file.display());
         }
-        "render_ui" => {
-            // This is the final layout - handled separately
-            None
+
+        // Store the surface for /rpc and the next diff
+        *session.sent.write().await = surface;
+        *session.surface.write().await = Some(builder.tools.clone());
+
+        // Broadcast to connected clients
+        let _ = session.tx.send(update_json.to_string());
+
+        // Save to conversation, with the tool calls the model made and their results
+        let turn = tool_call_messages(response.content.as_deref(), &response.tool_calls, &results);
+        session.record_turn(messages.into_iter().chain(turn)).await;
+
+        return Ok(json!({
+            "status": "success",
+            "contextId": session.context_id,
+            "components": response.tool_calls.len(),
+            "a2ui": a2ui_json,
+            "update": update_json
+        }));
+    }
+
+    // Text response (no tool calls)
+    match response.content {
+        Some(content) => {
+            let reply = json!({"role": "assistant", "content": content});
+            session.record_turn(messages.into_iter().chain([reply])).await;
+            Ok(json!({
+                "status": "text",
+                "contextId": session.context_id,
+                "message": content
+            }))
         }
-        _ => None
+        None => Err(format!("Empty response from {}", state.provider.name())),
     }
 }
 
@@ -976,6 +666,45 @@ fn build_component_json(name: &str, args: &Value) -> Option<Value> {
 // HTTP Handlers
 // ============================================================================
 
+/// Value of a query parameter, e.g. `contextId` in `/live?contextId=abc`
+fn query_param(req: &Request<Incoming>, name: &str) -> Option<String> {
+    req.uri().query()?.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == name && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Context of an A2A JSON-RPC request (`params.message.contextId`)
+fn rpc_context_id(request: &Value) -> Option<String> {
+    request["params"]["message"]["contextId"].as_str().map(str::to_string)
+}
+
+/// Error response for a `/sessions` request without the admin token
+fn admin_rejection(req: &Request<Incoming>, state: &ServerState) -> Option<Response<Full<Bytes>>> {
+    let Some(token) = &state.admin_token else {
+        return Some(json_response(
+            StatusCode::FORBIDDEN,
+            json!({"error": "Session admin is disabled (set ADMIN_TOKEN)"}),
+        ));
+    };
+    let authorized = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|value| value == token);
+    (!authorized).then(|| json_response(StatusCode::UNAUTHORIZED, json!({"error": "Invalid admin token"})))
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .unwrap()
+}
+
 async fn handle_request(
     req: Request<Incoming>,
     state: Arc<ServerState>,
@@ -998,6 +727,8 @@ async fn handle_request(
             #[derive(Deserialize)]
             struct ChatRequest {
                 message: String,
+                #[serde(rename = "contextId")]
+                context_id: Option<String>,
             }
 
             let chat_req: ChatRequest = match serde_json::from_str(&body_str) {
@@ -1011,195 +742,20 @@ async fn handle_request(
                 }
             };
 
-            println!("[Kimi Bridge] User message: {}", chat_req.message);
-
-            // Build messages with system prompt
-            let system_prompt = r#"You are an A2UI generator assistant. Your job is to create user interfaces by calling the provided tools.
-
-IMPORTANT RULES:
-1. Create components using the tools (create_text, create_button, create_slider, etc.)
-2. Use create_column for vertical layouts, create_row for horizontal layouts
-3. Use create_card to wrap sections in styled containers
-4. Set initial data values with set_data for any bound components
-5. ALWAYS call render_ui as the LAST step with the root component ID
-6. Use descriptive IDs like "title", "volume-slider", "submit-btn"
-7. For sliders/checkboxes, always set initial data with set_data
-8. Use emojis in text labels to make the UI visually appealing
-
-Example flow for "create a volume control":
-1. create_text(id="volume-label", text="🔊 Volume", style="body")
-2. create_slider(id="volume-slider", dataPath="/volume", min=0, max=100, step=1)
-3. create_text(id="volume-value", dataPath="/volumeDisplay", style="caption")
-4. create_row(id="volume-row", children=["volume-label", "volume-slider", "volume-value"])
-5. set_data(path="/volume", numberValue=50)
-6. set_data(path="/volumeDisplay", stringValue="50%")
-7. render_ui(rootId="volume-row")
-
-MUSIC GENERATION:
-When the user asks you to generate music (e.g., "生成一首轻松的钢琴曲", "create relaxing music"):
-1. First call generate_music(prompt="description of the music", instrumental=true/false)
-2. The system will wait for Mureka AI to generate the music (~45 seconds)
-3. The audio URL will be provided to you automatically
-4. Then create an audio player: create_audio_player(id="player", url="<audio_url>", title="Song Title")
-5. Wrap it in a nice UI with a title and card
-6. Call render_ui() at the end
-
-Example for music generation:
-1. create_text(id="title", text="🎵 AI Generated Music", style="h1")
-2. generate_music(prompt="relaxing piano melody with soft ambient sounds", instrumental=true)
-3. create_audio_player(id="player", url="<will be filled>", title="Relaxing Piano")
-4. create_column(id="root", children=["title", "player"])
-5. render_ui(rootId="root")"#;
-
-            let mut messages = vec![
-                json!({"role": "system", "content": system_prompt}),
-            ];
-
-            // Add conversation history
-            {
-                let history = state.conversation.read().await;
-                messages.extend(history.clone());
-            }
-
-            // Add new user message
-            messages.push(json!({"role": "user", "content": chat_req.message}));
-
-            // Call Kimi API with streaming (broadcasts components as they arrive)
-            match call_kimi_stream(&state.api_key, messages.clone(), &state.tx).await {
-                Ok(response) => {
-                    if let Some(choice) = response.choices.first() {
-                        // Log reasoning if present
-                        if let Some(reasoning) = &choice.message.reasoning_content {
-                            println!("[Kimi Bridge] Reasoning: {}", reasoning);
-                        }
-
-                        // Process tool calls
-                        if let Some(tool_calls) = &choice.message.tool_calls {
-                            println!("[Kimi Bridge] Received {} tool calls", tool_calls.len());
-
-                            let mut builder = A2uiBuilder::new();
-
-                            for tc in tool_calls {
-                                let args: Value = serde_json::from_str(&tc.function.arguments)
-                                    .unwrap_or(json!({}));
-                                println!("[Kimi Bridge] Tool: {}({})", tc.function.name, tc.function.arguments);
-                                builder.process_tool_call(&tc.function.name, &args);
-                            }
-
-                            // Handle pending music generation (only with mureka feature)
-                            #[cfg(feature = "mureka")]
-                            if builder.has_pending_music() {
-                                if let Some(ref mureka) = state.mureka_client {
-                                    println!("[Kimi Bridge] Processing music generation requests...");
-
-                                    for (prompt, instrumental) in builder.get_pending_music() {
-                                        println!("[Kimi Bridge] Generating music: '{}' (instrumental: {})", prompt, instrumental);
-
-                                        match mureka.generate_music(&prompt, instrumental).await {
-                                            Ok(job_id) => {
-                                                println!("[Kimi Bridge] Mureka job started: {}", job_id);
-                                                println!("[Kimi Bridge] Waiting for music generation (this may take ~45 seconds)...");
-
-                                                // Poll for completion (max 20 attempts = ~60 seconds)
-                                                match mureka.wait_for_completion(&job_id, 20).await {
-                                                    Ok(songs) => {
-                                                        println!("[Kimi Bridge] Music generated! {} songs available", songs.len());
-                                                        builder.set_generated_audio(songs.clone());
-
-                                                        // Update audio player components with real URLs
-                                                        if let Some(song) = songs.first() {
-                                                            if let Some(url) = &song.audio_url {
-                                                                println!("[Kimi Bridge] Audio URL: {}", url);
-                                                                // Find and update AudioPlayer components
-                                                                for comp in &mut builder.components {
-                                                                    if let Some(audio_player) = comp.get_mut("component")
-                                                                        .and_then(|c| c.get_mut("AudioPlayer"))
-                                                                    {
-                                                                        audio_player["url"] = json!({"literalString": url});
-                                                                        if let Some(title) = &song.title {
-                                                                            audio_player["title"] = json!({"literalString": title});
-                                                                        }
-                                                                        audio_player["artist"] = json!({"literalString": "Mureka AI"});
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                    Err(e) => {
-                                                        eprintln!("[Kimi Bridge] Music generation failed: {}", e);
-                                                    }
-                                                }
-                                            }
-                                            Err(e) => {
-                                                eprintln!("[Kimi Bridge] Failed to start music generation: {}", e);
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    println!("[Kimi Bridge] Warning: Music generation requested but MUREKA_API_KEY not set");
-                                }
-                            }
-
-                            let a2ui_json = builder.build_a2ui_json();
-                            let a2ui_str = serde_json::to_string_pretty(&a2ui_json).unwrap();
-
-                            println!("[Kimi Bridge] Generated A2UI JSON, broadcasting...");
-
-                            // Write to ui_live.json for watch-server
-                            if let Err(e) = std::fs::write("ui_live.json", &a2ui_str) {
-                                eprintln!("[Kimi Bridge] Failed to write ui_live.json: {}", e);
-                            } else {
-                                println!("[Kimi Bridge] Written to ui_live.json");
-                            }
-
-                            // Store latest A2UI for /rpc endpoint
-                            {
-                                let mut latest = state.latest_a2ui.write().await;
-                                *latest = Some(a2ui_json.clone());
-                            }
-
-                            // Broadcast to connected clients
-                            let _ = state.tx.send(a2ui_str.clone());
-
-                            // Save to conversation
-                            {
-                                let mut history = state.conversation.write().await;
-                                history.push(json!({"role": "user", "content": chat_req.message}));
-                                history.push(json!({"role": "assistant", "content": format!("Generated UI with {} components", tool_calls.len())}));
-                            }
-
-                            return Ok(Response::builder()
-                                .status(StatusCode::OK)
-                                .header("Content-Type", "application/json")
-                                .header("Access-Control-Allow-Origin", "*")
-                                .body(Full::new(Bytes::from(json!({
-                                    "status": "success",
-                                    "components": tool_calls.len(),
-                                    "a2ui": a2ui_json
-                                }).to_string())))
-                                .unwrap());
-                        }
-
-                        // Text response (no tool calls)
-                        if let Some(content) = &choice.message.content {
-                            return Ok(Response::builder()
-                                .status(StatusCode::OK)
-                                .header("Content-Type", "application/json")
-                                .header("Access-Control-Allow-Origin", "*")
-                                .body(Full::new(Bytes::from(json!({
-                                    "status": "text",
-                                    "message": content
-                                }).to_string())))
-                                .unwrap());
-                        }
-                    }
-
-                    Ok(Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .header("Content-Type", "application/json")
-                        .body(Full::new(Bytes::from(json!({"error": "Empty response from Kimi"}).to_string())))
-                        .unwrap())
-                }
+            let context_id = chat_req.context_id.as_deref().unwrap_or(DEFAULT_SESSION);
+            let session = match state.sessions.get_or_create(context_id).await {
+                Ok(session) => session,
+                Err(e) => return Ok(json_response(StatusCode::SERVICE_UNAVAILABLE, json!({"error": e}))),
+            };
+            println!("[Kimi Bridge] User message ({}): {}", context_id, chat_req.message);
+
+            match chat(&state, &session, &chat_req.message).await {
+                Ok(body) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .header("Access-Control-Allow-Origin", "*")
+                    .body(Full::new(Bytes::from(body.to_string())))
+                    .unwrap()),
                 Err(e) => {
                     eprintln!("[Kimi Bridge] Error: {}", e);
                     Ok(Response::builder()
@@ -1213,31 +769,70 @@ Example for music generation:
 
         // SSE endpoint for Makepad client (A2A protocol compatible)
         (Method::POST, "/rpc") => {
-            // Read from ui_live.json for real-time streaming updates
-            let ui_to_send = if let Ok(content) = std::fs::read_to_string("ui_live.json") {
-                if let Ok(json) = serde_json::from_str::<Value>(&content) {
-                    json
-                } else {
-                    // Fallback to latest_a2ui
-                    let latest = state.latest_a2ui.read().await;
-                    latest.clone().unwrap_or_else(|| json!([]))
+            let body_bytes = http_body_util::BodyExt::collect(req.into_body())
+                .await
+                .map(|b| b.to_bytes())
+                .unwrap_or_default();
+            let rpc_request: Value = serde_json::from_slice(&body_bytes).unwrap_or_default();
+            let context_id = rpc_context_id(&rpc_request).unwrap_or_else(|| DEFAULT_SESSION.to_string());
+            let session = match state.sessions.get_or_create(&context_id).await {
+                Ok(session) => session,
+                Err(e) => {
+                    return Ok(json_response(StatusCode::SERVICE_UNAVAILABLE, json!({
+                        "jsonrpc": "2.0",
+                        "id": rpc_request["id"],
+                        "error": {"code": -32000, "message": e}
+                    })));
                 }
+            };
+
+            // User actions go back to the model. The reply doesn't wait for
+            // it: the UI update is streamed to /live, followed by the task's
+            // final status, when the turn is done
+            if rpc_request["method"] == "message/send" {
+                let (task_id, task_state) = match decode_action(&rpc_request) {
+                    Some(event) => {
+                        let task_id = session.next_task_id();
+                        let (state, session, id) = (state.clone(), session.clone(), task_id.clone());
+                        tokio::spawn(async move {
+                            let result = handle_action(&state, &session, &event).await;
+                            if let Err(e) = &result {
+                                eprintln!("[Kimi Bridge] Error: {}", e);
+                            }
+                            publish_task_status(&session, &id, result.err());
+                        });
+                        (task_id, "working")
+                    }
+                    None => ("kimi-task".to_string(), "completed"),
+                };
+
+                return Ok(json_response(StatusCode::OK, json!({
+                    "jsonrpc": "2.0",
+                    "id": rpc_request["id"],
+                    "result": {
+                        "kind": "task",
+                        "id": task_id,
+                        "contextId": session.context_id,
+                        "status": {"state": task_state}
+                    }
+                })));
+            }
+
+            // The session's surface, including components still streaming in
+            let surface = session.sent.read().await.clone();
+            let ui_to_send = if surface.root().is_some() {
+                serde_json::to_value(surface.messages()).unwrap_or_else(|_| json!([]))
             } else {
-                // Fallback to latest_a2ui or default welcome
-                let latest = state.latest_a2ui.read().await;
-                if let Some(ref a2ui) = *latest {
-                    a2ui.clone()
-                } else {
-                    json!([
-                        {"beginRendering": {"surfaceId": "main", "root": "welcome"}},
-                        {"surfaceUpdate": {"surfaceId": "main", "components": [
-                            {"id": "welcome", "component": {"Column": {"children": {"explicitList": ["title", "subtitle"]}}}},
-                            {"id": "title", "component": {"Text": {"text": {"literalString": "🤖 Kimi A2UI Bridge"}, "usageHint": "h1"}}},
-                            {"id": "subtitle", "component": {"Text": {"text": {"literalString": "Send a message to /chat to generate UI"}, "usageHint": "caption"}}}
-                        ]}},
-                        {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": []}}
-                    ])
-                }
+                // Default welcome
+                json!([
+                    {"beginRendering": {"surfaceId": "main", "root": "welcome"}},
+                    {"surfaceUpdate": {"surfaceId": "main", "components": [
+                        {"id": "welcome", "component": {"Column": {"children": {"explicitList": ["title", "subtitle"]}}}},
+                        {"id": "title", "component": {"Text": {"text": {"literalString": "🤖 Kimi A2UI Bridge"}, "usageHint": "h1"}}},
+                        {"id": "subtitle", "component": {"Text": {"text": {"literalString": "Send a message to /chat to generate UI"}, "usageHint": "caption"}}}
+                    ]}},
+                    {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": []}}
+                ])
             };
 
             // Format as A2A SSE response with JSON-RPC wrapper
@@ -1249,7 +844,7 @@ Example for music generation:
                 "result": {
                     "kind": "task",
                     "id": "kimi-task",
-                    "contextId": "kimi-ctx",
+                    "contextId": session.context_id,
                     "status": {"state": "running"}
                 }
             });
@@ -1279,7 +874,12 @@ Example for music generation:
 
         // Live SSE endpoint for real-time streaming updates
         (Method::GET, "/live") => {
-            let mut rx = state.tx.subscribe();
+            let context_id = query_param(&req, "contextId").unwrap_or_else(|| DEFAULT_SESSION.to_string());
+            let session = match state.sessions.get_or_create(&context_id).await {
+                Ok(session) => session,
+                Err(e) => return Ok(json_response(StatusCode::SERVICE_UNAVAILABLE, json!({"error": e}))),
+            };
+            let mut rx = session.tx.subscribe();
             let mut sse_body = String::new();
 
             // Keep receiving messages until timeout or channel closes
@@ -1322,29 +922,53 @@ Example for music generation:
 
         // Reset conversation
         (Method::POST, "/reset") => {
-            let mut history = state.conversation.write().await;
-            history.clear();
+            let context_id = query_param(&req, "contextId").unwrap_or_else(|| DEFAULT_SESSION.to_string());
+            if let Some(session) = state.sessions.get(&context_id).await {
+                session.reset().await;
+            }
 
-            Ok(Response::builder()
-                .status(StatusCode::OK)
-                .header("Content-Type", "application/json")
-                .header("Access-Control-Allow-Origin", "*")
-                .body(Full::new(Bytes::from(json!({"status": "conversation reset"}).to_string())))
-                .unwrap())
+            Ok(json_response(StatusCode::OK, json!({"status": "conversation reset", "contextId": context_id})))
+        }
+
+        // Admin: list sessions
+        (Method::GET, "/sessions") => {
+            if let Some(response) = admin_rejection(&req, &state) {
+                return Ok(response);
+            }
+            let sessions = state.sessions.list().await;
+            Ok(json_response(StatusCode::OK, json!({
+                "ttlSecs": state.sessions.ttl().as_secs(),
+                "maxSessions": state.sessions.max_sessions(),
+                "sessions": sessions
+            })))
+        }
+
+        // Admin: drop a session
+        (Method::DELETE, path) if path.starts_with("/sessions/") => {
+            if let Some(response) = admin_rejection(&req, &state) {
+                return Ok(response);
+            }
+            let context_id = &path["/sessions/".len()..];
+            if state.sessions.remove(context_id).await {
+                Ok(json_response(StatusCode::OK, json!({"status": "session removed", "contextId": context_id})))
+            } else {
+                Ok(json_response(StatusCode::NOT_FOUND, json!({"error": format!("Unknown session: {}", context_id)})))
+            }
         }
 
         // Status endpoint
         (Method::GET, "/status") => {
-            let history = state.conversation.read().await;
             let status = json!({
                 "status": "running",
-                "model": "kimi-k2.5",
-                "conversation_turns": history.len() / 2,
+                "model": state.provider.name(),
+                "sessions": state.sessions.count().await,
                 "endpoints": {
                     "POST /chat": "Send message to generate UI",
-                    "POST /rpc": "A2A protocol endpoint (initial load)",
+                    "POST /rpc": "A2A protocol endpoint (initial load, user actions)",
                     "GET /live": "SSE for real-time updates",
-                    "POST /reset": "Reset conversation"
+                    "POST /reset": "Reset conversation",
+                    "GET /sessions": "List sessions",
+                    "DELETE /sessions/{contextId}": "Drop a session"
                 }
             });
 
@@ -1361,7 +985,7 @@ Example for music generation:
             Ok(Response::builder()
                 .status(StatusCode::OK)
                 .header("Access-Control-Allow-Origin", "*")
-                .header("Access-Control-Allow-Methods", "GET, POST, OPTIONS")
+                .header("Access-Control-Allow-Methods", "GET, POST, DELETE, OPTIONS")
                 .header("Access-Control-Allow-Headers", "Content-Type")
                 .body(Full::new(Bytes::new()))
                 .unwrap())
@@ -1384,9 +1008,10 @@ Example for music generation:
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Get API key from environment
-    let api_key = std::env::var("MOONSHOT_API_KEY")
-        .expect("MOONSHOT_API_KEY environment variable not set");
+    // Select the LLM provider from the environment (LLM_PROVIDER, default kimi)
+    let provider = LlmConfig::from_env()
+        .build()
+        .unwrap_or_else(|e| panic!("{} (set LLM_PROVIDER and its API key)", e));
 
     // Optional: Get Mureka API key for music generation (only with mureka feature)
     #[cfg(feature = "mureka")]
@@ -1405,15 +1030,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     #[cfg(not(feature = "mureka"))]
     println!("[Kimi Bridge] Mureka feature not enabled - music generation disabled");
 
-    let (tx, _rx) = broadcast::channel::<String>(16);
+    let ttl = std::env::var("SESSION_TTL_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SESSION_TTL);
+    let max_sessions = std::env::var("MAX_SESSIONS")
+        .ok()
+        .and_then(|max| max.parse().ok())
+        .unwrap_or(DEFAULT_MAX_SESSIONS);
 
     let state = Arc::new(ServerState {
-        api_key,
+        provider,
         #[cfg(feature = "mureka")]
         mureka_client,
-        tx,
-        conversation: RwLock::new(Vec::new()),
-        latest_a2ui: RwLock::new(None),
+        sessions: SessionStore::new(".", ttl).with_max_sessions(max_sessions),
+        admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|token| !token.is_empty()),
+    });
+
+    // Drop idle sessions
+    let expiry_state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ttl.clamp(Duration::from_secs(1), Duration::from_secs(60)));
+        loop {
+            interval.tick().await;
+            expiry_state.sessions.expire().await;
+        }
     });
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 8081));
@@ -1424,7 +1066,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("===========================================");
     println!();
     println!("Server:   http://127.0.0.1:8081");
-    println!("Model:    kimi-k2.5 (with tool use)");
+    println!("Model:    {} (with tool use)", state.provider.name());
+    println!("Sessions: expire after {}s idle (SESSION_TTL_SECS), at most {} (MAX_SESSIONS)", ttl.as_secs(), max_sessions);
     #[cfg(feature = "mureka")]
     println!("Music:    {} (set MUREKA_API_KEY to enable)",
         if state.mureka_client.is_some() { "enabled" } else { "disabled" });
@@ -1436,6 +1079,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("  POST /rpc    - A2A protocol (for Makepad)");
     println!("  GET  /live   - Live updates (SSE)");
     println!("  POST /reset  - Reset conversation");
+    println!("  GET  /sessions - List sessions (needs ADMIN_TOKEN)");
+    println!("  DELETE /sessions/<contextId> - Drop a session (needs ADMIN_TOKEN)");
     println!("  GET  /status - Server status");
     println!();
     println!("Example:");
@@ -1451,6 +1096,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("Press Ctrl+C to stop");
     println!();
 
+    serve(listener, state).await
+}
+
+/// Accept connections until the listener fails
+async fn serve(
+    listener: TcpListener,
+    state: Arc<ServerState>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     loop {
         let (stream, remote_addr) = listener.accept().await?;
         let io = TokioIo::new(stream);
@@ -1470,3 +1123,324 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_provider::ScriptedProvider;
+
+    const SCRIPT: &str = r#"[
+        {"toolCalls": [
+            {"name": "create_text", "arguments": {"id": "label", "text": "Volume"}},
+            {"name": "create_slider", "arguments": {"id": "volume", "value": {"path": "/volume"}, "min": 0, "max": 100}},
+            {"name": "create_row", "arguments": {"id": "root", "children": ["label", "volume"]}},
+            {"name": "set_data", "arguments": {"path": "/volume", "value": 50}},
+            {"name": "render_ui", "arguments": {"rootId": "root"}}
+        ]},
+        {"content": "Which color would you like?"}
+    ]"#;
+
+    /// Header authorizing the `/sessions` endpoints of `start`ed servers
+    const ADMIN_AUTH: &str = "Authorization: Bearer secret\r\n";
+
+    fn request(port: u16, method: &str, path: &str, body: &str) -> String {
+        request_with_headers(port, method, path, "", body)
+    }
+
+    fn request_with_headers(port: u16, method: &str, path: &str, headers: &str, body: &str) -> String {
+        use std::io::{Read, Write};
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            headers,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn post(port: u16, path: &str, body: &str) -> String {
+        request(port, "POST", path, body)
+    }
+
+    /// Serve a scripted model on a free port, with live files in a temp dir
+    async fn start(script: &str, name: &str) -> (Arc<ServerState>, u16, std::path::PathBuf) {
+        let live_dir = std::env::temp_dir().join(format!("kimi_bridge_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&live_dir).unwrap();
+        let state = Arc::new(ServerState {
+            provider: Box::new(ScriptedProvider::from_json(script).unwrap()),
+            #[cfg(feature = "mureka")]
+            mureka_client: None,
+            sessions: SessionStore::new(&live_dir, DEFAULT_SESSION_TTL),
+            admin_token: Some("secret".to_string()),
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(serve(listener, state.clone()));
+        (state, port, live_dir)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scripted_chat_pipeline() {
+        let (state, port, live_dir) = start(SCRIPT, "chat").await;
+        let session = state.sessions.get_or_create(DEFAULT_SESSION).await.unwrap();
+        let mut live = session.tx.subscribe();
+
+        // chat -> tool calls -> A2UI
+        let response = tokio::task::spawn_blocking(move || {
+            post(port, "/chat", r#"{"message": "Create a volume control"}"#)
+        })
+        .await
+        .unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.contains(r#""status":"success""#), "{}", response);
+
+        // Components were streamed to /live as their tool calls completed,
+        // under a provisional root listing the top-level ones, then the whole
+        // surface
+        let mut updates = Vec::new();
+        while let Ok(update) = live.try_recv() {
+            updates.push(serde_json::from_str::<Value>(&update).unwrap());
+        }
+        assert_eq!(updates.len(), 4, "{:?}", updates);
+        assert_eq!(updates[0][0]["beginRendering"]["root"], STREAMING_ROOT);
+        assert_eq!(updates[0][1]["surfaceUpdate"]["components"][0]["id"], "label");
+        let streaming_root = &updates[1][0]["surfaceUpdate"]["components"][0];
+        assert_eq!(streaming_root["id"], STREAMING_ROOT);
+        assert_eq!(
+            streaming_root["component"]["Column"]["children"]["explicitList"],
+            json!(["label", "volume"])
+        );
+        assert_eq!(updates[1][0]["surfaceUpdate"]["components"][1]["id"], "volume");
+        // render_ui moved the root off the streaming placeholder
+        let surface = &updates[3];
+        assert_eq!(surface[0]["beginRendering"]["root"], "root");
+        assert_eq!(surface[1]["surfaceUpdate"]["components"].as_array().unwrap().len(), 3);
+        assert_eq!(surface[2]["dataModelUpdate"]["contents"][0]["valueNumber"], 50.0);
+
+        // /rpc serves the surface as A2A events
+        let rpc = tokio::task::spawn_blocking(move || post(port, "/rpc", "{}"))
+            .await
+            .unwrap();
+        assert!(rpc.contains(r#""kind":"event""#), "{}", rpc);
+        assert!(rpc.contains(r#""valueNumber":50.0"#), "{}", rpc);
+
+        // The conversation has the model's tool calls and their results
+        let history = session.conversation.read().await.clone();
+        assert_eq!(history.len(), 7);
+        assert_eq!(history[1]["tool_calls"][1]["function"]["name"], "create_slider");
+        assert_eq!(history[6]["role"], "tool");
+        assert_eq!(history[6]["content"], r#"{"status":"ok"}"#);
+
+        // Another context gets its own conversation and surface
+        let response = tokio::task::spawn_blocking(move || {
+            post(port, "/chat", r#"{"message": "Make it nicer", "contextId": "ctx-2"}"#)
+        })
+        .await
+        .unwrap();
+        assert!(response.contains("Which color would you like?"), "{}", response);
+        assert!(live.try_recv().is_err());
+        let other = state.sessions.get("ctx-2").await.unwrap();
+        let history = other.conversation.read().await.clone();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1]["content"], "Which color would you like?");
+        assert!(other.sent.read().await.root().is_none());
+
+        // /rpc for the new context doesn't see the default session's UI
+        let rpc = tokio::task::spawn_blocking(move || {
+            post(port, "/rpc", r#"{"jsonrpc": "2.0", "id": 1, "method": "message/stream",
+                "params": {"message": {"contextId": "ctx-2", "parts": []}}}"#)
+        })
+        .await
+        .unwrap();
+        assert!(rpc.contains(r#""contextId":"ctx-2""#), "{}", rpc);
+        assert!(!rpc.contains("valueNumber"), "{}", rpc);
+
+        let sessions = tokio::task::spawn_blocking(move || {
+            request_with_headers(port, "GET", "/sessions", ADMIN_AUTH, "")
+        })
+        .await
+        .unwrap();
+        assert!(sessions.contains(r#""contextId":"ctx-2""#), "{}", sessions);
+        assert!(sessions.contains(r#""conversationTurns":1"#), "{}", sessions);
+
+        let removed = tokio::task::spawn_blocking(move || {
+            request_with_headers(port, "DELETE", "/sessions/ctx-2", ADMIN_AUTH, "")
+        })
+        .await
+        .unwrap();
+        assert!(removed.starts_with("HTTP/1.1 200"), "{}", removed);
+        assert!(state.sessions.get("ctx-2").await.is_none());
+
+        let _ = std::fs::remove_dir_all(live_dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_user_action_round_trip() {
+        let script = r#"[
+            {"toolCalls": [
+                {"name": "create_text", "arguments": {"id": "count", "text": "0"}},
+                {"name": "create_text", "arguments": {"id": "add-label", "text": "Add"}},
+                {"name": "create_button", "arguments": {"id": "add", "child": "add-label", "action": "increment"}},
+                {"name": "create_column", "arguments": {"id": "root", "children": ["count", "add"]}},
+                {"name": "render_ui", "arguments": {"rootId": "root"}}
+            ]},
+            {"toolCalls": [
+                {"name": "create_text", "arguments": {"id": "count", "text": "1"}},
+                {"name": "set_data", "arguments": {"path": "/count", "value": 1}}
+            ]}
+        ]"#;
+        let (state, port, live_dir) = start(script, "action").await;
+
+        let response = tokio::task::spawn_blocking(move || {
+            post(port, "/chat", r#"{"message": "A counter", "contextId": "c1"}"#)
+        })
+        .await
+        .unwrap();
+        assert!(response.contains(r#""status":"success""#), "{}", response);
+        let session = state.sessions.get("c1").await.unwrap();
+        let mut live = session.tx.subscribe();
+
+        // The button's action, as sent by A2aClient::send_action
+        let action = json!({
+            "jsonrpc": "2.0", "id": 7, "method": "message/send",
+            "params": {"message": {"contextId": "c1", "role": "user", "parts": [{"kind": "data", "data": {
+                "a2uiEvent": {"actionName": "increment", "sourceComponentId": "add",
+                    "timestamp": "2026-01-01T00:00:00Z", "resolvedContext": {"step": 1}}
+            }}]}}
+        });
+        let response = tokio::task::spawn_blocking(move || post(port, "/rpc", &action.to_string()))
+            .await
+            .unwrap();
+        assert!(response.contains(r#""state":"working""#), "{}", response);
+        assert!(response.contains(r#""id":"c1-action-1""#), "{}", response);
+
+        // The model runs after the reply; its updates reach /live, then the
+        // task's final status
+        let mut updates = Vec::new();
+        loop {
+            let message = tokio::time::timeout(Duration::from_secs(5), live.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let update = serde_json::from_str::<Value>(&message).unwrap();
+            if update[0]["kind"] == "status-update" {
+                assert_eq!(update[0]["taskId"], "c1-action-1");
+                assert_eq!(update[0]["status"]["state"], "completed");
+                break;
+            }
+            updates.push(update);
+        }
+
+        // The model saw the action as a user message, and its reaction is kept
+        let history = session.conversation.read().await.clone();
+        assert_eq!(history[7]["role"], "user");
+        let message: Value = serde_json::from_str(history[7]["content"].as_str().unwrap()).unwrap();
+        assert_eq!(message["userAction"]["actionName"], "increment");
+        assert_eq!(message["userAction"]["resolvedContext"]["step"], 1);
+        assert_eq!(history[8]["tool_calls"][1]["function"]["name"], "set_data");
+        assert_eq!(history.len(), 11);
+
+        // Only the changed component and data reach /live, without a
+        // beginRendering that would reset the client
+        assert_eq!(updates.len(), 2, "{:?}", updates);
+        let components = &updates[0][0]["surfaceUpdate"]["components"];
+        assert_eq!(components.as_array().unwrap().len(), 1);
+        assert_eq!(components[0]["component"]["Text"]["text"]["literalString"], "1");
+        assert_eq!(updates[1].as_array().unwrap().len(), 1);
+        assert_eq!(updates[1][0]["dataModelUpdate"]["contents"][0]["key"], "count");
+
+        // ...while the session keeps the whole surface
+        let sent = session.sent.read().await.clone();
+        assert_eq!(sent.root(), Some("root"));
+        assert_eq!(sent.components().len(), 4);
+        assert_eq!(sent.data()["count"], 1.0);
+
+        let _ = std::fs::remove_dir_all(live_dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_partial_arguments_stream_progressively() {
+        let script = r#"[
+            {"chunkSize": 6, "toolCalls": [
+                {"name": "create_column", "arguments": {"id": "root", "children": ["title", "body"]}},
+                {"name": "create_text", "arguments": {"id": "title", "text": "Text that arrives a few characters at a time"}},
+                {"name": "create_text", "arguments": {"id": "body", "text": "Done"}},
+                {"name": "render_ui", "arguments": {"rootId": "root"}}
+            ]}
+        ]"#;
+        let (state, port, live_dir) = start(script, "partial").await;
+        let session = state.sessions.get_or_create(DEFAULT_SESSION).await.unwrap();
+        let mut live = session.tx.subscribe();
+
+        let response = tokio::task::spawn_blocking(move || {
+            post(port, "/chat", r#"{"message": "Stream something"}"#)
+        })
+        .await
+        .unwrap();
+        assert!(response.contains(r#""status":"success""#), "{}", response);
+
+        let mut components = Vec::new();
+        while let Ok(update) = live.try_recv() {
+            let update: Vec<Value> = serde_json::from_str(&update).unwrap();
+            for message in update {
+                components.extend(message["surfaceUpdate"]["components"].as_array().cloned().unwrap_or_default());
+            }
+        }
+
+        // The column's children had placeholders until they were defined
+        let skeletons: Vec<&Value> = components.iter().filter(|c| c["component"]["Skeleton"].is_object()).collect();
+        assert_eq!(skeletons.len(), 2, "{:?}", skeletons);
+        assert_eq!(skeletons[0]["id"], "title");
+        assert_eq!(skeletons[1]["id"], "body");
+
+        // The title grew as its arguments arrived, and IDs were never cut short
+        let titles: Vec<&str> = components
+            .iter()
+            .filter(|c| c["id"] == "title")
+            .filter_map(|c| c["component"]["Text"]["text"]["literalString"].as_str())
+            .collect();
+        assert!(titles.len() > 2, "{:?}", titles);
+        assert!(titles.windows(2).all(|w| w[1].starts_with(w[0])), "{:?}", titles);
+        assert_eq!(titles.last(), Some(&"Text that arrives a few characters at a time"));
+        assert!(components.iter().all(|c| ["root", "title", "body", STREAMING_ROOT].contains(&c["id"].as_str().unwrap())));
+
+        // The final surface has no placeholders left
+        let sent = session.sent.read().await.clone();
+        assert_eq!(sent.root(), Some("root"));
+        assert_eq!(sent.components().len(), 3);
+        assert!(sent.components().iter().all(|c| !matches!(c.component, ComponentType::Skeleton(_))));
+
+        let _ = std::fs::remove_dir_all(live_dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_session_admin_requires_token() {
+        let (state, port, live_dir) = start(SCRIPT, "admin").await;
+        state.sessions.get_or_create("ctx-1").await.unwrap();
+
+        let responses = tokio::task::spawn_blocking(move || {
+            [
+                request(port, "GET", "/sessions", ""),
+                request(port, "DELETE", "/sessions/ctx-1", ""),
+                request_with_headers(port, "GET", "/sessions", "Authorization: Bearer wrong\r\n", ""),
+                request_with_headers(port, "DELETE", "/sessions/ctx-1", ADMIN_AUTH, ""),
+            ]
+        })
+        .await
+        .unwrap();
+        assert!(responses[0].starts_with("HTTP/1.1 401"), "{}", responses[0]);
+        assert!(responses[1].starts_with("HTTP/1.1 401"), "{}", responses[1]);
+        assert!(responses[2].starts_with("HTTP/1.1 401"), "{}", responses[2]);
+        assert!(responses[3].starts_with("HTTP/1.1 200"), "{}", responses[3]);
+        assert!(state.sessions.get("ctx-1").await.is_none());
+
+        let _ = std::fs::remove_dir_all(live_dir);
+    }
+}
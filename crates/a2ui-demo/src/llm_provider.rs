@@ -0,0 +1,971 @@
+//! LLM Providers for the A2UI Bridge
+//!
+//! The bridge talks to models through `LlmProvider`, so the chat → tool →
+//! A2UI pipeline doesn't depend on one vendor's API:
+//!
+//! - `OpenAiProvider`: OpenAI-compatible chat completions (OpenAI, Kimi, ...)
+//! - `AnthropicProvider`: Anthropic-style messages API
+//! - `OllamaProvider`: Ollama's chat API
+//! - `ScriptedProvider`: replays canned responses from a file, for offline
+//!   runs and tests
+//!
+//! `LlmConfig::from_env` selects and configures a provider:
+//!
+//! | Variable | Meaning |
+//! |----------|---------|
+//! | `LLM_PROVIDER` | `kimi` (default), `openai`, `anthropic`, `ollama` or `scripted` |
+//! | `LLM_MODEL` | Model name (provider default if unset) |
+//! | `LLM_API_URL` | Endpoint URL (provider default if unset) |
+//! | `LLM_API_KEY` | API key (else `MOONSHOT_API_KEY`, `OPENAI_API_KEY` or `ANTHROPIC_API_KEY`) |
+//! | `LLM_SCRIPT` | Script file for `scripted` |
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const KIMI_API_URL: &str = "https://api.moonshot.ai/v1/chat/completions";
+const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const OLLAMA_API_URL: &str = "http://localhost:11434/api/chat";
+const MAX_TOKENS: u32 = 8192;
+
+/// A chat request
+#[derive(Debug, Clone)]
+pub struct LlmRequest {
+    /// System prompt
+    pub system: String,
+    /// Conversation in OpenAI format (`{"role": "user", "content": "..."}`)
+    pub messages: Vec<Value>,
+    /// Tools in OpenAI format (see `A2uiToolkit::tools`)
+    pub tools: Value,
+}
+
+/// A tool call made by the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Arguments as JSON text
+    pub arguments: String,
+}
+
+/// An assistant turn with tool calls, followed by one result per call
+pub fn tool_call_messages(content: Option<&str>, calls: &[ToolCall], results: &[Value]) -> Vec<Value> {
+    let tool_calls: Vec<Value> = calls
+        .iter()
+        .map(|call| {
+            json!({
+                "id": call.id,
+                "type": "function",
+                "function": {"name": call.name, "arguments": call.arguments}
+            })
+        })
+        .collect();
+    let mut messages = vec![json!({
+        "role": "assistant",
+        "content": content,
+        "tool_calls": tool_calls
+    })];
+    for (call, result) in calls.iter().zip(results) {
+        messages.push(json!({
+            "role": "tool",
+            "tool_call_id": call.id,
+            "content": result.to_string()
+        }));
+    }
+    messages
+}
+
+/// The model's reply
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LlmResponse {
+    pub content: Option<String>,
+    pub reasoning: Option<String>,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Progress of a tool call while the response arrives
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToolCallEvent<'a> {
+    /// More of the arguments arrived; they may not parse yet
+    Partial(&'a ToolCall),
+    /// The arguments are complete
+    Complete(&'a ToolCall),
+}
+
+impl<'a> ToolCallEvent<'a> {
+    pub fn call(&self) -> &'a ToolCall {
+        match self {
+            ToolCallEvent::Partial(call) | ToolCallEvent::Complete(call) => call,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        matches!(self, ToolCallEvent::Complete(_))
+    }
+}
+
+/// Receives tool calls as they arrive
+pub type ToolCallSink<'a> = &'a mut (dyn FnMut(ToolCallEvent<'_>) + Send);
+
+/// A chat model with tool use
+pub trait LlmProvider: Send + Sync {
+    /// Provider and model, for logs and `/status` (e.g., "openai:gpt-4o-mini")
+    fn name(&self) -> String;
+
+    /// Send a request
+    ///
+    /// `on_tool_call` sees every tool call of the response as `Complete`, in
+    /// order. Streaming providers call it while the response is still
+    /// arriving, and also report `Partial` arguments as they grow.
+    fn chat<'a>(
+        &'a self,
+        request: &'a LlmRequest,
+        on_tool_call: ToolCallSink<'a>,
+    ) -> BoxFuture<'a, Result<LlmResponse, String>>;
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Provider selection
+#[derive(Debug, Clone, Default)]
+pub struct LlmConfig {
+    /// `kimi`, `openai`, `anthropic`, `ollama` or `scripted`
+    pub provider: String,
+    pub model: Option<String>,
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    pub script: Option<String>,
+}
+
+impl LlmConfig {
+    /// Read the configuration from `LLM_*` environment variables
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let provider = var("LLM_PROVIDER").unwrap_or_else(|| "kimi".to_string());
+        let fallback_key = match provider.as_str() {
+            "kimi" => var("MOONSHOT_API_KEY"),
+            "openai" => var("OPENAI_API_KEY"),
+            "anthropic" => var("ANTHROPIC_API_KEY"),
+            _ => None,
+        };
+        LlmConfig {
+            provider,
+            model: var("LLM_MODEL"),
+            api_url: var("LLM_API_URL"),
+            api_key: var("LLM_API_KEY").or(fallback_key),
+            script: var("LLM_SCRIPT"),
+        }
+    }
+
+    /// Create the configured provider
+    pub fn build(&self) -> Result<Box<dyn LlmProvider>, String> {
+        let require_key = || {
+            self.api_key
+                .clone()
+                .ok_or_else(|| format!("No API key for provider '{}'", self.provider))
+        };
+        let url = |default: &str| self.api_url.clone().unwrap_or_else(|| default.to_string());
+        let model = |default: &str| self.model.clone().unwrap_or_else(|| default.to_string());
+
+        Ok(match self.provider.as_str() {
+            "kimi" => Box::new(OpenAiProvider {
+                url: url(KIMI_API_URL),
+                model: model("kimi-k2.5"),
+                // Kimi K2.5 requires temperature 1 for tool use
+                temperature: Some(1.0),
+                ..OpenAiProvider::new(require_key()?)
+            }),
+            "openai" => Box::new(OpenAiProvider {
+                url: url(OPENAI_API_URL),
+                model: model("gpt-4o-mini"),
+                ..OpenAiProvider::new(require_key()?)
+            }),
+            "anthropic" => Box::new(AnthropicProvider {
+                url: url(ANTHROPIC_API_URL),
+                model: model("claude-3-5-sonnet-latest"),
+                ..AnthropicProvider::new(require_key()?)
+            }),
+            "ollama" => Box::new(OllamaProvider {
+                url: url(OLLAMA_API_URL),
+                model: model("llama3.1"),
+            }),
+            "scripted" => {
+                let script = self
+                    .script
+                    .as_deref()
+                    .ok_or("The scripted provider needs LLM_SCRIPT")?;
+                Box::new(ScriptedProvider::load(script)?)
+            }
+            other => return Err(format!("Unknown LLM provider: {}", other)),
+        })
+    }
+}
+
+async fn post_json(
+    request: reqwest::RequestBuilder,
+    body: &Value,
+) -> Result<reqwest::Response, String> {
+    let response = request
+        .header("Content-Type", "application/json")
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("API error ({}): {}", status, body));
+    }
+    Ok(response)
+}
+
+// ============================================================================
+// OpenAI-compatible
+// ============================================================================
+
+/// OpenAI-compatible chat completions API (OpenAI, Kimi, vLLM, ...)
+///
+/// Responses are streamed, so tool calls reach `on_tool_call` while the model
+/// writes their arguments, and again as it finishes each one.
+#[derive(Debug, Clone)]
+pub struct OpenAiProvider {
+    pub url: String,
+    pub api_key: String,
+    pub model: String,
+    pub temperature: Option<f64>,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        OpenAiProvider {
+            url: OPENAI_API_URL.to_string(),
+            api_key: api_key.into(),
+            model: "gpt-4o-mini".to_string(),
+            temperature: None,
+        }
+    }
+}
+
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> String {
+        format!("openai:{}", self.model)
+    }
+
+    fn chat<'a>(
+        &'a self,
+        request: &'a LlmRequest,
+        on_tool_call: ToolCallSink<'a>,
+    ) -> BoxFuture<'a, Result<LlmResponse, String>> {
+        Box::pin(async move {
+            use futures_util::StreamExt;
+
+            let mut messages = vec![json!({"role": "system", "content": request.system})];
+            messages.extend(request.messages.iter().cloned());
+            let mut body = json!({
+                "model": self.model,
+                "messages": messages,
+                "tools": request.tools,
+                "max_tokens": MAX_TOKENS,
+                "stream": true
+            });
+            if let Some(temperature) = self.temperature {
+                body["temperature"] = json!(temperature);
+            }
+
+            let client = reqwest::Client::new();
+            let response = post_json(
+                client
+                    .post(&self.url)
+                    .header("Authorization", format!("Bearer {}", self.api_key)),
+                &body,
+            )
+            .await?;
+
+            let mut decoder = OpenAiStreamDecoder::default();
+            let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
+                decoder.push(&chunk, on_tool_call);
+            }
+            Ok(decoder.finish(on_tool_call))
+        })
+    }
+}
+
+/// Decodes a streamed chat completion (`data: {...}` events)
+#[derive(Debug, Default)]
+pub struct OpenAiStreamDecoder {
+    /// Received bytes not yet forming a complete event
+    buffer: Vec<u8>,
+    content: String,
+    reasoning: String,
+    /// index -> tool call being received
+    tool_calls: BTreeMap<i64, ToolCall>,
+    /// Indices already passed to the sink
+    reported: Vec<i64>,
+}
+
+impl OpenAiStreamDecoder {
+    /// Feed received bytes
+    ///
+    /// Only complete events are decoded, so a character split across two
+    /// chunks is kept intact.
+    pub fn push(&mut self, bytes: &[u8], on_tool_call: ToolCallSink<'_>) {
+        self.buffer.extend_from_slice(bytes);
+        while let Some(pos) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..pos + 2).collect();
+            for line in String::from_utf8_lossy(&event).lines() {
+                let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    continue;
+                }
+                if let Ok(chunk) = serde_json::from_str::<Value>(data) {
+                    self.apply(&chunk, on_tool_call);
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, chunk: &Value, on_tool_call: ToolCallSink<'_>) {
+        let Some(choices) = chunk["choices"].as_array() else {
+            return;
+        };
+        for delta in choices.iter().map(|choice| &choice["delta"]) {
+            if let Some(content) = delta["content"].as_str() {
+                self.content.push_str(content);
+            }
+            if let Some(reasoning) = delta["reasoning_content"].as_str() {
+                self.reasoning.push_str(reasoning);
+            }
+            for call in delta["tool_calls"].as_array().into_iter().flatten() {
+                let index = call["index"].as_i64().unwrap_or(0);
+                let entry = self.tool_calls.entry(index).or_insert_with(|| ToolCall {
+                    id: String::new(),
+                    name: String::new(),
+                    arguments: String::new(),
+                });
+                if let Some(id) = call["id"].as_str().filter(|id| !id.is_empty()) {
+                    entry.id = id.to_string();
+                }
+                if let Some(name) = call["function"]["name"].as_str().filter(|n| !n.is_empty()) {
+                    entry.name = name.to_string();
+                }
+                if let Some(arguments) = call["function"]["arguments"].as_str() {
+                    entry.arguments.push_str(arguments);
+                }
+
+                // Report as soon as the arguments parse, and every delta before
+                if entry.name.is_empty() || self.reported.contains(&index) {
+                    continue;
+                }
+                if serde_json::from_str::<Value>(&entry.arguments).is_ok() {
+                    self.reported.push(index);
+                    on_tool_call(ToolCallEvent::Complete(entry));
+                } else {
+                    on_tool_call(ToolCallEvent::Partial(entry));
+                }
+            }
+        }
+    }
+
+    /// End of stream: report remaining tool calls and build the response
+    pub fn finish(mut self, on_tool_call: ToolCallSink<'_>) -> LlmResponse {
+        let tool_calls: Vec<ToolCall> = std::mem::take(&mut self.tool_calls)
+            .into_iter()
+            .filter(|(_, call)| !call.name.is_empty())
+            .map(|(index, call)| {
+                if !self.reported.contains(&index) {
+                    on_tool_call(ToolCallEvent::Complete(&call));
+                }
+                call
+            })
+            .collect();
+        LlmResponse {
+            content: Some(self.content).filter(|s| !s.is_empty()),
+            reasoning: Some(self.reasoning).filter(|s| !s.is_empty()),
+            tool_calls,
+        }
+    }
+}
+
+// ============================================================================
+// Anthropic-style
+// ============================================================================
+
+/// Anthropic-style messages API
+#[derive(Debug, Clone)]
+pub struct AnthropicProvider {
+    pub url: String,
+    pub api_key: String,
+    pub model: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        AnthropicProvider {
+            url: ANTHROPIC_API_URL.to_string(),
+            api_key: api_key.into(),
+            model: "claude-3-5-sonnet-latest".to_string(),
+        }
+    }
+}
+
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> String {
+        format!("anthropic:{}", self.model)
+    }
+
+    fn chat<'a>(
+        &'a self,
+        request: &'a LlmRequest,
+        on_tool_call: ToolCallSink<'a>,
+    ) -> BoxFuture<'a, Result<LlmResponse, String>> {
+        Box::pin(async move {
+            // Tools are {name, description, input_schema}
+            let tools: Vec<Value> = request
+                .tools
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|tool| {
+                    json!({
+                        "name": tool["function"]["name"],
+                        "description": tool["function"]["description"],
+                        "input_schema": tool["function"]["parameters"]
+                    })
+                })
+                .collect();
+            let messages = anthropic_messages(&request.messages);
+            let body = json!({
+                "model": self.model,
+                "system": request.system,
+                "messages": messages,
+                "tools": tools,
+                "max_tokens": MAX_TOKENS
+            });
+
+            let client = reqwest::Client::new();
+            let response = post_json(
+                client
+                    .post(&self.url)
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", ANTHROPIC_VERSION),
+                &body,
+            )
+            .await?;
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            let response = parse_anthropic_response(&body);
+            for call in &response.tool_calls {
+                on_tool_call(ToolCallEvent::Complete(call));
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Conversation in the messages API format
+///
+/// Tool calls become `tool_use` blocks and tool results `tool_result` blocks
+/// of a user turn; the results of one assistant turn share a user turn.
+pub fn anthropic_messages(messages: &[Value]) -> Vec<Value> {
+    let converted = messages
+        .iter()
+        .filter(|message| message["role"] != "system")
+        .map(|message| match message["role"].as_str() {
+            Some("assistant") if message["tool_calls"].is_array() => {
+                let mut content = Vec::new();
+                if let Some(text) = message["content"].as_str() {
+                    content.push(json!({"type": "text", "text": text}));
+                }
+                for call in message["tool_calls"].as_array().into_iter().flatten() {
+                    let arguments = call["function"]["arguments"].as_str().unwrap_or("{}");
+                    content.push(json!({
+                        "type": "tool_use",
+                        "id": call["id"],
+                        "name": call["function"]["name"],
+                        "input": serde_json::from_str::<Value>(arguments).unwrap_or_else(|_| json!({}))
+                    }));
+                }
+                json!({"role": "assistant", "content": content})
+            }
+            Some("tool") => json!({
+                "role": "user",
+                "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": message["tool_call_id"],
+                    "content": message["content"]
+                }]
+            }),
+            _ => message.clone(),
+        });
+
+    let mut merged: Vec<Value> = Vec::new();
+    for message in converted {
+        if let Some(previous) = merged.last_mut() {
+            if is_tool_result(previous) && is_tool_result(&message) {
+                let results = message["content"].as_array().cloned().unwrap_or_default();
+                if let Some(blocks) = previous["content"].as_array_mut() {
+                    blocks.extend(results);
+                }
+                continue;
+            }
+        }
+        merged.push(message);
+    }
+    merged
+}
+
+fn is_tool_result(message: &Value) -> bool {
+    message["role"] == "user" && message["content"][0]["type"] == "tool_result"
+}
+
+/// Text and `tool_use` blocks of a messages API response
+pub fn parse_anthropic_response(body: &Value) -> LlmResponse {
+    let mut response = LlmResponse::default();
+    for block in body["content"].as_array().into_iter().flatten() {
+        match block["type"].as_str() {
+            Some("text") => {
+                let text = block["text"].as_str().unwrap_or_default();
+                response
+                    .content
+                    .get_or_insert_with(String::new)
+                    .push_str(text);
+            }
+            Some("thinking") => {
+                let thinking = block["thinking"].as_str().unwrap_or_default();
+                response
+                    .reasoning
+                    .get_or_insert_with(String::new)
+                    .push_str(thinking);
+            }
+            Some("tool_use") => response.tool_calls.push(ToolCall {
+                id: block["id"].as_str().unwrap_or_default().to_string(),
+                name: block["name"].as_str().unwrap_or_default().to_string(),
+                arguments: block["input"].to_string(),
+            }),
+            _ => {}
+        }
+    }
+    response
+}
+
+// ============================================================================
+// Ollama
+// ============================================================================
+
+/// Ollama's chat API (`/api/chat`), for local models
+#[derive(Debug, Clone)]
+pub struct OllamaProvider {
+    pub url: String,
+    pub model: String,
+}
+
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> String {
+        format!("ollama:{}", self.model)
+    }
+
+    fn chat<'a>(
+        &'a self,
+        request: &'a LlmRequest,
+        on_tool_call: ToolCallSink<'a>,
+    ) -> BoxFuture<'a, Result<LlmResponse, String>> {
+        Box::pin(async move {
+            let mut messages = vec![json!({"role": "system", "content": request.system})];
+            messages.extend(request.messages.iter().map(ollama_message));
+            let body = json!({
+                "model": self.model,
+                "messages": messages,
+                "tools": request.tools,
+                "stream": false
+            });
+
+            let client = reqwest::Client::new();
+            let response = post_json(client.post(&self.url), &body).await?;
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            let response = parse_ollama_response(&body);
+            for call in &response.tool_calls {
+                on_tool_call(ToolCallEvent::Complete(call));
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Ollama takes tool call arguments as objects rather than JSON text
+fn ollama_message(message: &Value) -> Value {
+    let mut message = message.clone();
+    if let Some(calls) = message["tool_calls"].as_array_mut() {
+        for call in calls {
+            if let Some(arguments) = call["function"]["arguments"].as_str() {
+                call["function"]["arguments"] =
+                    serde_json::from_str(arguments).unwrap_or_else(|_| json!({}));
+            }
+        }
+    }
+    message
+}
+
+/// Message of an Ollama chat response; tool call arguments are objects there
+pub fn parse_ollama_response(body: &Value) -> LlmResponse {
+    let message = &body["message"];
+    let tool_calls = message["tool_calls"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, call)| ToolCall {
+            id: format!("call-{}", index),
+            name: call["function"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            arguments: match &call["function"]["arguments"] {
+                Value::String(arguments) => arguments.clone(),
+                arguments => arguments.to_string(),
+            },
+        })
+        .collect();
+    LlmResponse {
+        content: message["content"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(String::from),
+        reasoning: message["thinking"].as_str().map(String::from),
+        tool_calls,
+    }
+}
+
+// ============================================================================
+// Scripted
+// ============================================================================
+
+/// One canned response of a script
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptedTurn {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ScriptedToolCall>,
+    /// Report arguments this many characters at a time, like a streaming model
+    #[serde(default)]
+    chunk_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ScriptedToolCall {
+    name: String,
+    /// Object or JSON text
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// Replays canned responses, one per request, without a network
+///
+/// A script is a JSON array of turns:
+///
+/// ```json
+/// [
+///   {"toolCalls": [
+///     {"name": "create_text", "arguments": {"id": "title", "text": "Hello"}},
+///     {"name": "render_ui", "arguments": {"rootId": "title"}}
+///   ]},
+///   {"content": "Nothing to change."}
+/// ]
+/// ```
+///
+/// A turn with `"chunkSize": n` also reports each call's arguments as they
+/// would stream in, `n` characters at a time.
+#[derive(Debug)]
+pub struct ScriptedProvider {
+    turns: Vec<ScriptedTurn>,
+    next: Mutex<usize>,
+}
+
+impl ScriptedProvider {
+    /// Parse a script
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let turns = serde_json::from_str(json).map_err(|e| format!("Invalid script: {}", e))?;
+        Ok(ScriptedProvider {
+            turns,
+            next: Mutex::new(0),
+        })
+    }
+
+    /// Load a script file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&json)
+    }
+
+    fn next_turn(&self) -> Result<ScriptedTurn, String> {
+        let mut next = self.next.lock().map_err(|_| "Script lock poisoned")?;
+        let turn = self
+            .turns
+            .get(*next)
+            .cloned()
+            .ok_or("Script has no more responses")?;
+        *next += 1;
+        Ok(turn)
+    }
+}
+
+impl LlmProvider for ScriptedProvider {
+    fn name(&self) -> String {
+        "scripted".to_string()
+    }
+
+    fn chat<'a>(
+        &'a self,
+        _request: &'a LlmRequest,
+        on_tool_call: ToolCallSink<'a>,
+    ) -> BoxFuture<'a, Result<LlmResponse, String>> {
+        Box::pin(async move {
+            let turn = self.next_turn()?;
+            let tool_calls: Vec<ToolCall> = turn
+                .tool_calls
+                .into_iter()
+                .enumerate()
+                .map(|(index, call)| ToolCall {
+                    id: format!("scripted-{}", index),
+                    name: call.name,
+                    arguments: match call.arguments {
+                        Value::String(arguments) => arguments,
+                        Value::Null => "{}".to_string(),
+                        arguments => arguments.to_string(),
+                    },
+                })
+                .collect();
+            for call in &tool_calls {
+                if let Some(chunk_size) = turn.chunk_size.filter(|n| *n > 0) {
+                    let chars: Vec<char> = call.arguments.chars().collect();
+                    for end in (chunk_size..chars.len()).step_by(chunk_size) {
+                        on_tool_call(ToolCallEvent::Partial(&ToolCall {
+                            arguments: chars[..end].iter().collect(),
+                            ..call.clone()
+                        }));
+                    }
+                }
+                on_tool_call(ToolCallEvent::Complete(call));
+            }
+            Ok(LlmResponse {
+                content: turn.content,
+                reasoning: None,
+                tool_calls,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> LlmRequest {
+        LlmRequest {
+            system: "Build UIs".to_string(),
+            messages: vec![json!({"role": "user", "content": "Hi"})],
+            tools: json!([]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scripted_provider() {
+        let provider = ScriptedProvider::from_json(
+            r#"[
+                {"toolCalls": [
+                    {"name": "create_text", "arguments": {"id": "title", "text": "Hello"}},
+                    {"name": "render_ui", "arguments": "{\"rootId\": \"title\"}"}
+                ]},
+                {"content": "Done"}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut seen = Vec::new();
+        let response = provider
+            .chat(&request(), &mut |event| seen.push(event.call().name.clone()))
+            .await
+            .unwrap();
+        assert_eq!(seen, ["create_text", "render_ui"]);
+        assert_eq!(response.tool_calls[1].arguments, r#"{"rootId": "title"}"#);
+
+        let response = provider.chat(&request(), &mut |_| {}).await.unwrap();
+        assert_eq!(response.content.as_deref(), Some("Done"));
+        assert!(provider.chat(&request(), &mut |_| {}).await.is_err());
+
+        // Arguments replayed as a stream
+        let provider = ScriptedProvider::from_json(
+            r#"[{"chunkSize": 4, "toolCalls": [{"name": "create_text", "arguments": "{\"id\": \"t\"}"}]}]"#,
+        )
+        .unwrap();
+        let mut partial = Vec::new();
+        provider
+            .chat(&request(), &mut |event| {
+                partial.push((event.call().arguments.clone(), event.is_complete()))
+            })
+            .await
+            .unwrap();
+        assert_eq!(partial.len(), 3);
+        assert_eq!(partial[0], (r#"{"id"#.to_string(), false));
+        assert_eq!(partial[2], (r#"{"id": "t"}"#.to_string(), true));
+    }
+
+    #[test]
+    fn test_openai_stream_decoding() {
+        let events = [
+            r#"{"choices":[{"delta":{"reasoning_content":"Thinking"}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"a","function":{"name":"create_text","arguments":"{\"id\":"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"t\"}"}},{"index":1,"id":"b","function":{"name":"render_ui","arguments":"{\"rootId\""}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":1,"function":{"arguments":":\"t\"}"}}]}}]}"#,
+        ];
+        let sse = |events: &[&str]| -> String {
+            events
+                .iter()
+                .map(|event| format!("data: {}\n\n", event))
+                .collect()
+        };
+
+        let mut decoder = OpenAiStreamDecoder::default();
+        let mut events_seen: Vec<(ToolCall, bool)> = Vec::new();
+        let complete = |events_seen: &[(ToolCall, bool)]| -> Vec<ToolCall> {
+            events_seen
+                .iter()
+                .filter(|(_, complete)| *complete)
+                .map(|(call, _)| call.clone())
+                .collect()
+        };
+        decoder.push(sse(&events[..3]).as_bytes(), &mut |event| {
+            events_seen.push((event.call().clone(), event.is_complete()))
+        });
+        assert_eq!(complete(&events_seen).len(), 1);
+
+        // Unfinished arguments are reported as they grow
+        let partial: Vec<&str> = events_seen
+            .iter()
+            .filter(|(_, complete)| !complete)
+            .map(|(call, _)| call.arguments.as_str())
+            .collect();
+        assert_eq!(partial, [r#"{"id":"#, r#"{"rootId""#]);
+
+        // Chunks can end mid-event
+        let rest = sse(&events[3..]) + "data: [DONE]\n\n";
+        let (first, second) = rest.split_at(rest.len() / 2);
+        decoder.push(first.as_bytes(), &mut |event| {
+            events_seen.push((event.call().clone(), event.is_complete()))
+        });
+        assert_eq!(complete(&events_seen).len(), 1);
+        decoder.push(second.as_bytes(), &mut |event| {
+            events_seen.push((event.call().clone(), event.is_complete()))
+        });
+        assert_eq!(complete(&events_seen).len(), 2);
+        let response = decoder.finish(&mut |event| {
+            events_seen.push((event.call().clone(), event.is_complete()))
+        });
+        let seen = complete(&events_seen);
+
+        assert_eq!(seen, response.tool_calls);
+        assert_eq!(response.tool_calls[0].arguments, r#"{"id":"t"}"#);
+        assert_eq!(response.tool_calls[1].id, "b");
+        assert_eq!(response.reasoning.as_deref(), Some("Thinking"));
+        assert_eq!(response.content, None);
+    }
+
+    #[test]
+    fn test_openai_stream_split_character() {
+        let sse = "data: {\"choices\":[{\"delta\":{\"content\":\"caf\u{e9} \u{4f60}\u{597d}\"}}]}\n\n";
+        let bytes = sse.as_bytes();
+        let split = sse.find('\u{4f60}').unwrap() + 1;
+
+        let mut decoder = OpenAiStreamDecoder::default();
+        decoder.push(&bytes[..split], &mut |_| {});
+        decoder.push(&bytes[split..], &mut |_| {});
+        let response = decoder.finish(&mut |_| {});
+        assert_eq!(response.content.as_deref(), Some("caf\u{e9} \u{4f60}\u{597d}"));
+    }
+
+    #[test]
+    fn test_anthropic_and_ollama_responses() {
+        let response = parse_anthropic_response(&json!({
+            "content": [
+                {"type": "text", "text": "Here you go"},
+                {"type": "tool_use", "id": "toolu_1", "name": "render_ui", "input": {"rootId": "root"}}
+            ]
+        }));
+        assert_eq!(response.content.as_deref(), Some("Here you go"));
+        assert_eq!(response.tool_calls[0].name, "render_ui");
+        assert_eq!(response.tool_calls[0].arguments, r#"{"rootId":"root"}"#);
+
+        let response = parse_ollama_response(&json!({
+            "message": {"role": "assistant", "content": "", "tool_calls": [
+                {"function": {"name": "set_data", "arguments": {"path": "/volume", "value": 50}}}
+            ]}
+        }));
+        assert_eq!(response.content, None);
+        assert_eq!(response.tool_calls[0].id, "call-0");
+        assert_eq!(
+            serde_json::from_str::<Value>(&response.tool_calls[0].arguments).unwrap(),
+            json!({"path": "/volume", "value": 50})
+        );
+
+        // Tool results in the request
+        let call = ToolCall {
+            id: "call-1".to_string(),
+            name: "create_text".to_string(),
+            arguments: r#"{"id":"title"}"#.to_string(),
+        };
+        let messages = tool_call_messages(None, std::slice::from_ref(&call), &[json!({"status": "ok"})]);
+        let anthropic = anthropic_messages(&messages);
+        assert_eq!(anthropic[0]["content"][0]["type"], "tool_use");
+        assert_eq!(anthropic[0]["content"][0]["input"]["id"], "title");
+        assert_eq!(anthropic[1]["role"], "user");
+        assert_eq!(anthropic[1]["content"][0]["tool_use_id"], "call-1");
+        assert_eq!(
+            ollama_message(&messages[0])["tool_calls"][0]["function"]["arguments"]["id"],
+            "title"
+        );
+
+        // Results of one turn's tool calls are sent as one user turn
+        let render = ToolCall {
+            id: "call-2".to_string(),
+            name: "render_ui".to_string(),
+            arguments: r#"{"rootId":"root"}"#.to_string(),
+        };
+        let messages = tool_call_messages(
+            Some("Updated"),
+            &[call, render],
+            &[json!({"status": "ok"}), json!({"error": "Unknown component: root"})],
+        );
+        assert_eq!(messages.len(), 3);
+        let anthropic = anthropic_messages(&messages);
+        assert_eq!(anthropic.len(), 2);
+        assert_eq!(anthropic[0]["content"][0]["text"], "Updated");
+        assert_eq!(anthropic[0]["content"][2]["name"], "render_ui");
+        let results = anthropic[1]["content"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1]["tool_use_id"], "call-2");
+    }
+}
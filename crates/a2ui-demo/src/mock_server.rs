@@ -2,16 +2,38 @@
 //!
 //! Run: cargo run -p a2ui-demo --bin mock-a2a-server --features mock-server
 //! Then connect from Makepad app to http://localhost:8080/rpc
+//! (the agent card is served at http://localhost:8080/.well-known/agent-card.json)
+//! or over WebSocket to ws://localhost:8080/ws
+//!
+//! Without arguments the server streams a sample payment page. To play a
+//! scenario file instead (see `scenario.rs` and `scenarios/`):
+//!
+//! cargo run -p a2ui-demo --bin mock-a2a-server --features mock-server -- \
+//!     crates/a2ui-demo/scenarios/shopping_cart.yaml
+//!
+//! (or set `MOCK_SCENARIO` to the file's path)
 
-use http_body_util::{BodyExt, Full};
-use hyper::body::{Bytes, Incoming};
+mod scenario;
+
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full, StreamBody};
+use hyper::body::{Bytes, Frame, Incoming};
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
+use hyper::upgrade::Upgraded;
 use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
+use scenario::{Output, Scenario, Step};
+use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Message, Role};
+use tokio_tungstenite::WebSocketStream;
 
 /// Generate streaming A2UI messages for a Payment Page demo
 /// Messages are split to demonstrate progressive streaming rendering
@@ -24,7 +46,7 @@ fn sample_messages() -> Vec<serde_json::Value> {
                 "kind": "task",
                 "id": "task-pay-001",
                 "contextId": "ctx-pay",
-                "status": {"state": "running"}
+                "status": {"state": "working"}
             }
         }),
 
@@ -606,33 +628,417 @@ fn sample_messages() -> Vec<serde_json::Value> {
             }
         }),
 
-        // 11. Task completed
+        // 11. Task completed, with a closing agent message
         serde_json::json!({
             "jsonrpc": "2.0",
             "result": {
                 "kind": "task",
                 "id": "task-pay-001",
                 "contextId": "ctx-pay",
-                "status": {"state": "completed"}
+                "status": {
+                    "state": "completed",
+                    "message": {
+                        "kind": "message",
+                        "role": "agent",
+                        "parts": [{"kind": "text", "text": "Your payment page is ready."}]
+                    }
+                }
             }
         }),
     ]
 }
 
+/// Agent card advertising the A2UI extension and the RPC endpoint
+fn agent_card() -> serde_json::Value {
+    serde_json::json!({
+        "name": "Mock Payment Agent",
+        "description": "Streams a sample payment page built with A2UI",
+        "url": "http://localhost:8080/rpc",
+        "version": "0.1.0",
+        "protocolVersion": "0.3.0",
+        "preferredTransport": "JSONRPC",
+        "capabilities": {
+            "streaming": true,
+            "pushNotifications": false,
+            "extensions": [{
+                "uri": "https://a2ui.org/a2a-extension/a2ui/v0.8",
+                "description": "Renders agent UIs with A2UI",
+                "required": true
+            }]
+        },
+        "defaultInputModes": ["text/plain"],
+        "defaultOutputModes": ["application/json"],
+        "skills": [{
+            "id": "payment-page",
+            "name": "Payment page",
+            "description": "Shows a checkout form with payment methods and an order summary",
+            "tags": ["payment", "checkout"],
+            "examples": ["Show me a payment page"]
+        }]
+    })
+}
+
+/// ID of the payment page task
+const TASK_ID: &str = "task-pay-001";
+
+/// The payment page, played when no scenario file is given
+fn payment_scenario() -> Scenario {
+    Scenario {
+        name: "Payment Page Demo".to_string(),
+        task_id: TASK_ID.to_string(),
+        context_id: "ctx-pay".to_string(),
+        delay_ms: 100,
+        initial: sample_messages().into_iter().map(Step::raw).collect(),
+        actions: HashMap::new(),
+    }
+}
+
+// ============================================================================
+// Server State
+// ============================================================================
+
+/// Scenario being played and the streams waiting for user actions
+struct ServerState {
+    scenario: Scenario,
+    /// Open `message/stream` streams by context ID, fed the steps answering user actions
+    streams: Mutex<HashMap<String, mpsc::UnboundedSender<Vec<Step>>>>,
+}
+
+impl ServerState {
+    fn new(scenario: Scenario) -> Self {
+        ServerState {
+            scenario,
+            streams: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Context of a request, the scenario's if the request doesn't name one
+    fn context_of(&self, request: &serde_json::Value) -> String {
+        request["params"]["message"]["contextId"]
+            .as_str()
+            .unwrap_or(&self.scenario.context_id)
+            .to_string()
+    }
+
+    /// Route the user action of a `message/send` request to the stream of its context
+    ///
+    /// Fails when the context has no open stream. Data changes and actions the
+    /// scenario doesn't script are only logged.
+    fn route_action(&self, request: &serde_json::Value) -> Result<(), String> {
+        let parts = request["params"]["message"]["parts"].as_array();
+        let action = parts
+            .into_iter()
+            .flatten()
+            .find_map(|part| part["data"]["a2uiEvent"]["actionName"].as_str());
+        let Some(action) = action else {
+            println!(
+                "[Mock Server] Received: {}",
+                request["params"]["message"]["parts"]
+            );
+            return Ok(());
+        };
+        let Some(steps) = self.scenario.action(action) else {
+            println!("[Mock Server] No response scripted for action {}", action);
+            return Ok(());
+        };
+
+        let context_id = self.context_of(request);
+        let streams = self.streams.lock().unwrap();
+        match streams.get(&context_id) {
+            Some(stream) if stream.send(steps.to_vec()).is_ok() => {
+                println!("[Mock Server] Action {} -> {} step(s)", action, steps.len());
+                Ok(())
+            }
+            _ => Err(format!("No open task for context {}", context_id)),
+        }
+    }
+}
+
+/// Play `steps` for a streaming request, then the steps of each user action
+/// routed to its context
+///
+/// Stops after a terminal status or a disconnect step, or when the client goes
+/// away (`out` closes).
+async fn play(
+    state: Arc<ServerState>,
+    request: serde_json::Value,
+    mut steps: Vec<Step>,
+    out: mpsc::UnboundedSender<Output>,
+) {
+    let scenario = &state.scenario;
+    let context_id = state.context_of(&request);
+    let (actions_tx, mut actions) = mpsc::unbounded_channel();
+    state
+        .streams
+        .lock()
+        .unwrap()
+        .insert(context_id.clone(), actions_tx.clone());
+
+    'stream: loop {
+        for (i, step) in steps.iter().enumerate() {
+            tokio::select! {
+                _ = tokio::time::sleep(scenario.delay(step)) => {}
+                _ = out.closed() => break 'stream,
+            }
+            let output = step.output(&scenario.task_id, &context_id, &request["id"]);
+            let disconnect = output == Output::Disconnect;
+            if out.send(output).is_err() {
+                break 'stream;
+            }
+            println!("[Mock Server] Sent step {}/{}", i + 1, steps.len());
+            if disconnect || step.is_terminal() {
+                break 'stream;
+            }
+        }
+
+        tokio::select! {
+            Some(next) = actions.recv() => steps = next,
+            _ = out.closed() => break,
+        }
+    }
+
+    // A newer stream of the context may have taken over
+    let mut streams = state.streams.lock().unwrap();
+    if streams
+        .get(&context_id)
+        .is_some_and(|stream| stream.same_channel(&actions_tx))
+    {
+        streams.remove(&context_id);
+    }
+    println!("[Mock Server] Stream of {} ended", context_id);
+}
+
+/// Steps played for a streaming method (a resubscribed task only gets the last one)
+fn stream_steps(scenario: &Scenario, method: &str) -> Vec<Step> {
+    match method {
+        "tasks/resubscribe" => scenario.initial.last().cloned().into_iter().collect(),
+        _ => scenario.initial.clone(),
+    }
+}
+
+/// Start playing a stream, returning its output
+fn start_stream(
+    state: &Arc<ServerState>,
+    method: &str,
+    request: serde_json::Value,
+) -> mpsc::UnboundedReceiver<Output> {
+    let (out, rx) = mpsc::unbounded_channel();
+    let steps = stream_steps(&state.scenario, method);
+    tokio::task::spawn(play(state.clone(), request, steps, out));
+    rx
+}
+
+/// Answer a `message/send` request (the user action goes to the open stream)
+fn send_response(state: &ServerState, request: &serde_json::Value) -> serde_json::Value {
+    match state.route_action(request) {
+        Ok(()) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": {
+                "kind": "task",
+                "id": state.scenario.task_id,
+                "contextId": state.context_of(request),
+                "status": {"state": "working"}
+            }
+        }),
+        Err(e) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": {"code": -32001, "message": e}
+        }),
+    }
+}
+
+/// Answer the non-streaming task methods (`tasks/get`, `tasks/cancel`)
+///
+/// The mock doesn't track task progress, so the task always reads as
+/// completed: it can be read but not canceled.
+fn task_method_response(
+    scenario: &Scenario,
+    method: &str,
+    request: &serde_json::Value,
+) -> serde_json::Value {
+    let id = request["id"].clone();
+    if request["params"]["id"].as_str() != Some(scenario.task_id.as_str()) {
+        return serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": -32001, "message": "Task not found"}
+        });
+    }
+
+    match method {
+        "tasks/cancel" => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": -32002, "message": "Task cannot be canceled"}
+        }),
+        _ => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": {
+                "kind": "task",
+                "id": scenario.task_id,
+                "contextId": scenario.context_id,
+                "status": {"state": "completed"}
+            }
+        }),
+    }
+}
+
+/// Answer A2A requests sent over a WebSocket (one JSON-RPC request per text frame)
+///
+/// One stream plays at a time; a new streaming request replaces it.
+async fn serve_websocket(mut socket: WebSocketStream<TokioIo<Upgraded>>, state: Arc<ServerState>) {
+    println!("[Mock Server] WebSocket client connected");
+
+    let (_idle, mut stream) = mpsc::unbounded_channel::<Output>();
+    loop {
+        let text = tokio::select! {
+            frame = socket.next() => match frame {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                // Pings are answered by tungstenite
+                Some(Ok(_)) => continue,
+            },
+            Some(output) = stream.recv() => {
+                let Output::Send(reply) = output else {
+                    // Drop the connection without a close frame
+                    println!("[Mock Server] Scenario disconnect");
+                    return;
+                };
+                if socket.send(Message::Text(reply.to_string())).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        let request: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        let method = request["method"].as_str().unwrap_or("").to_string();
+        println!("[Mock Server] WebSocket request: {}", method);
+
+        let reply = match method.as_str() {
+            "message/stream" | "tasks/resubscribe" => {
+                stream = start_stream(&state, &method, request);
+                continue;
+            }
+            "tasks/get" | "tasks/cancel" => {
+                task_method_response(&state.scenario, &method, &request)
+            }
+            "message/send" => match state.route_action(&request) {
+                // User actions and data changes need no reply
+                Ok(()) => continue,
+                Err(_) => send_response(&state, &request),
+            },
+            _ => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": {"code": -32601, "message": "Method not found"}
+            }),
+        };
+
+        if socket.send(Message::Text(reply.to_string())).await.is_err() {
+            return;
+        }
+    }
+
+    println!("[Mock Server] WebSocket client disconnected");
+}
+
+/// Body of an SSE response, sent as the stream plays
+///
+/// A disconnect step fails the body, which aborts the connection mid-stream.
+fn sse_body(output: mpsc::UnboundedReceiver<Output>) -> Body {
+    let frames = futures_util::stream::unfold(output, |mut output| async move {
+        let frame = match output.recv().await? {
+            Output::Send(message) => Ok(Frame::data(Bytes::from(format!("data: {}\n\n", message)))),
+            Output::Disconnect => {
+                println!("[Mock Server] Scenario disconnect");
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionAborted,
+                    "scenario disconnect",
+                ))
+            }
+        };
+        Some((frame, output))
+    });
+    BodyExt::boxed(StreamBody::new(frames))
+}
+
+type Body = BoxBody<Bytes, std::io::Error>;
+
+fn full(body: impl Into<Bytes>) -> Body {
+    Full::new(body.into())
+        .map_err(|never| match never {})
+        .boxed()
+}
+
 /// Handle incoming HTTP requests
-async fn handle_request(req: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+async fn handle_request(
+    req: Request<Incoming>,
+    state: Arc<ServerState>,
+) -> Result<Response<Body>, Infallible> {
     match (req.method(), req.uri().path()) {
         // CORS preflight
         (&Method::OPTIONS, _) => {
             let response = Response::builder()
                 .status(StatusCode::OK)
                 .header("Access-Control-Allow-Origin", "*")
-                .header("Access-Control-Allow-Methods", "POST, OPTIONS")
+                .header("Access-Control-Allow-Methods", "GET, POST, OPTIONS")
                 .header(
                     "Access-Control-Allow-Headers",
                     "Content-Type, Accept, Authorization, X-A2A-Extensions",
                 )
-                .body(Full::new(Bytes::new()))
+                .body(full(Bytes::new()))
+                .unwrap();
+            Ok(response)
+        }
+
+        // Agent card (current and legacy well-known paths)
+        (&Method::GET, "/.well-known/agent-card.json" | "/.well-known/agent.json") => {
+            println!("[Mock Server] Agent card requested");
+            let response = Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .header("Access-Control-Allow-Origin", "*")
+                .body(full(agent_card().to_string()))
+                .unwrap();
+            Ok(response)
+        }
+
+        // WebSocket endpoint (A2A JSON-RPC over one socket)
+        (&Method::GET, "/ws") => {
+            let Some(key) = req.headers().get("Sec-WebSocket-Key") else {
+                let response = Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(full("Expected a WebSocket upgrade"))
+                    .unwrap();
+                return Ok(response);
+            };
+            let accept = derive_accept_key(key.as_bytes());
+
+            tokio::task::spawn(async move {
+                match hyper::upgrade::on(req).await {
+                    Ok(upgraded) => {
+                        let socket = WebSocketStream::from_raw_socket(
+                            TokioIo::new(upgraded),
+                            Role::Server,
+                            None,
+                        )
+                        .await;
+                        serve_websocket(socket, state).await;
+                    }
+                    Err(e) => eprintln!("[Mock Server] WebSocket upgrade failed: {}", e),
+                }
+            });
+
+            let response = Response::builder()
+                .status(StatusCode::SWITCHING_PROTOCOLS)
+                .header("Connection", "Upgrade")
+                .header("Upgrade", "websocket")
+                .header("Sec-WebSocket-Accept", accept)
+                .body(full(Bytes::new()))
                 .unwrap();
             Ok(response)
         }
@@ -644,25 +1050,39 @@ async fn handle_request(req: Request<Incoming>) -> Result<Response<Full<Bytes>>,
             let body_str = String::from_utf8_lossy(&body_bytes);
             println!("[Mock Server] Received request: {}...", &body_str[..body_str.len().min(100)]);
 
-            // Build SSE response body
-            let messages = sample_messages();
-            let mut sse_body = String::new();
+            let request: serde_json::Value =
+                serde_json::from_slice(&body_bytes).unwrap_or_default();
+            let method = request["method"]
+                .as_str()
+                .unwrap_or("message/stream")
+                .to_string();
 
-            for (i, msg) in messages.iter().enumerate() {
-                let data = serde_json::to_string(msg).unwrap();
-                sse_body.push_str(&format!("data: {}\n\n", data));
-                println!("[Mock Server] Queued message {}/{}", i + 1, messages.len());
+            let reply = match method.as_str() {
+                "tasks/get" | "tasks/cancel" => {
+                    Some(task_method_response(&state.scenario, &method, &request))
+                }
+                "message/send" => Some(send_response(&state, &request)),
+                _ => None,
+            };
+            if let Some(reply) = reply {
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .header("Access-Control-Allow-Origin", "*")
+                    .body(full(reply.to_string()))
+                    .unwrap();
+                return Ok(response);
             }
 
-            println!("[Mock Server] Stream complete - {} messages sent", messages.len());
-
+            // Stream the scenario as SSE while it plays
+            let output = start_stream(&state, &method, request);
             let response = Response::builder()
                 .status(StatusCode::OK)
                 .header("Content-Type", "text/event-stream")
                 .header("Cache-Control", "no-cache")
                 .header("Connection", "keep-alive")
                 .header("Access-Control-Allow-Origin", "*")
-                .body(Full::new(Bytes::from(sse_body)))
+                .body(sse_body(output))
                 .unwrap();
 
             Ok(response)
@@ -672,7 +1092,7 @@ async fn handle_request(req: Request<Incoming>) -> Result<Response<Full<Bytes>>,
         _ => {
             let response = Response::builder()
                 .status(StatusCode::NOT_FOUND)
-                .body(Full::new(Bytes::from("Not Found")))
+                .body(full("Not Found"))
                 .unwrap();
             Ok(response)
         }
@@ -681,25 +1101,52 @@ async fn handle_request(req: Request<Incoming>) -> Result<Response<Full<Bytes>>,
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let scenario = match std::env::args()
+        .nth(1)
+        .or_else(|| std::env::var("MOCK_SCENARIO").ok())
+    {
+        Some(path) => Scenario::load(&path)?,
+        None => payment_scenario(),
+    };
+
     let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
     let listener = TcpListener::bind(addr).await?;
 
     println!("===========================================");
-    println!("  Mock A2A Server - Payment Page Demo");
+    println!("  Mock A2A Server - {}", scenario.name);
     println!("===========================================");
     println!("Listening on http://{}/rpc", addr);
+    println!("WebSocket:    ws://{}/ws", addr);
+    if !scenario.actions.is_empty() {
+        let mut actions: Vec<&str> = scenario.actions.keys().map(String::as_str).collect();
+        actions.sort();
+        println!("Actions:      {}", actions.join(", "));
+    }
     println!("Press Ctrl+C to stop");
     println!();
 
+    serve(listener, Arc::new(ServerState::new(scenario))).await
+}
+
+/// Accept connections until the listener fails
+async fn serve(
+    listener: TcpListener,
+    state: Arc<ServerState>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     loop {
         let (stream, remote_addr) = listener.accept().await?;
         println!("[Mock Server] Connection from {}", remote_addr);
 
         let io = TokioIo::new(stream);
+        let state = state.clone();
 
         tokio::task::spawn(async move {
             if let Err(err) = http1::Builder::new()
-                .serve_connection(io, service_fn(handle_request))
+                .serve_connection(
+                    io,
+                    service_fn(move |req| handle_request(req, state.clone())),
+                )
+                .with_upgrades()
                 .await
             {
                 eprintln!("[Mock Server] Connection error: {:?}", err);
@@ -707,3 +1154,186 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use makepad_component::a2ui::*;
+    use std::time::{Duration, Instant};
+
+    /// The mock server's payment flow as an in-process agent
+    struct PaymentAgent;
+
+    impl A2uiAgent for PaymentAgent {
+        fn start(&mut self, _message: &MessageInput, emitter: &A2uiEmitter) -> Result<(), String> {
+            emitter.status(TaskState::Working)?;
+            // Same A2UI messages as the server streams, without the A2A envelopes
+            for response in sample_messages() {
+                let data = &response["result"]["data"];
+                if !data.is_null() {
+                    let message =
+                        serde_json::from_value(data.clone()).map_err(|e| e.to_string())?;
+                    emitter.emit(message)?;
+                }
+            }
+            emitter.text("Your payment page is ready.")?;
+            emitter.status(TaskState::InputRequired)
+        }
+
+        fn on_action(&mut self, action: &UserAction, emitter: &A2uiEmitter) -> Result<(), String> {
+            match action.action.name.as_str() {
+                "confirmPayment" => {
+                    let total = action.action.context.get("total").and_then(|v| v.as_str());
+                    emitter.text(format!("Paid {}", total.unwrap_or("?")))?;
+                    emitter.status(TaskState::Completed)?;
+                }
+                "cancelPayment" => emitter.status(TaskState::Canceled)?,
+                other => return Err(format!("Unknown action {}", other)),
+            }
+            emitter.finish();
+            Ok(())
+        }
+    }
+
+    /// Poll the host into the surface until `done` returns true for an event
+    fn poll_until(
+        host: &mut A2uiHost<AgentTransport<PaymentAgent>>,
+        surface: &mut HeadlessSurface,
+        texts: &mut Vec<String>,
+        done: impl Fn(&A2uiHostEvent) -> bool,
+    ) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            for event in host.poll_all() {
+                match &event {
+                    A2uiHostEvent::Message(message) => {
+                        surface.process_message(message.clone());
+                    }
+                    A2uiHostEvent::Text(text) => texts.push(text.clone()),
+                    A2uiHostEvent::Error(e) => panic!("agent error: {}", e),
+                    _ => {}
+                }
+                if done(&event) {
+                    return;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out, texts so far: {:?}", texts);
+    }
+
+    #[test]
+    fn test_payment_flow_as_agent() {
+        let mut host = A2uiHost::with_transport(AgentTransport::spawn(PaymentAgent));
+        let mut surface = HeadlessSurface::new(800.0, 1200.0);
+        let mut texts = Vec::new();
+
+        host.connect("Show me a payment page").unwrap();
+        poll_until(&mut host, &mut surface, &mut texts, |event| {
+            matches!(
+                event,
+                A2uiHostEvent::TaskStatus {
+                    state: TaskState::InputRequired,
+                    ..
+                }
+            )
+        });
+        assert_eq!(texts, vec!["Your payment page is ready."]);
+        assert!(surface.is_rendered("pay-btn"));
+
+        surface.click("pay-btn").unwrap();
+        for action in surface.take_user_actions() {
+            host.send_action(&action).unwrap();
+        }
+        poll_until(&mut host, &mut surface, &mut texts, |event| {
+            matches!(event, A2uiHostEvent::Disconnected)
+        });
+        assert_eq!(texts.last().map(String::as_str), Some("Paid $344.75"));
+    }
+
+    /// The example cart scenario without its delays, served on a free port
+    fn start_cart_server(runtime: &tokio::runtime::Runtime) -> String {
+        let mut scenario =
+            Scenario::from_yaml(include_str!("../scenarios/shopping_cart.yaml")).unwrap();
+        scenario.delay_ms = 0;
+        for step in scenario.actions.values_mut().flatten() {
+            step.delay_ms = None;
+        }
+
+        let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}/rpc", listener.local_addr().unwrap());
+        runtime.spawn(serve(listener, Arc::new(ServerState::new(scenario))));
+        url
+    }
+
+    /// Read events until one matches `done`, returning all of them
+    fn read_until(
+        stream: &mut A2aEventStream,
+        done: impl Fn(&A2aStreamEvent) -> bool,
+    ) -> Vec<A2aStreamEvent> {
+        let mut events = Vec::new();
+        for event in stream.by_ref() {
+            let finished = done(&event);
+            events.push(event);
+            if finished {
+                return events;
+            }
+        }
+        panic!("stream ended early: {:?}", events);
+    }
+
+    #[test]
+    fn test_scenario_over_http() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut client = A2aClient::new(start_cart_server(&runtime));
+
+        let mut stream = client.message_stream("Show my cart").unwrap();
+        let events = read_until(&mut stream, |event| {
+            matches!(
+                event,
+                A2aStreamEvent::TaskStatus {
+                    state: TaskState::InputRequired,
+                    ..
+                }
+            )
+        });
+        let messages = events
+            .iter()
+            .filter(|e| matches!(e, A2aStreamEvent::A2uiMessage(_)))
+            .count();
+        assert_eq!(messages, 3);
+        assert_eq!(client.task_id().as_deref(), Some("task-cart-001"));
+
+        // Actions are answered on the open stream
+        client
+            .send_action("addToCart", "add-btn", HashMap::new())
+            .unwrap();
+        let events = read_until(&mut stream, |e| matches!(e, A2aStreamEvent::A2uiMessage(_)));
+        let A2aStreamEvent::A2uiMessage(A2uiMessage::DataModelUpdate(update)) = &events[0] else {
+            panic!("expected a dataModelUpdate: {:?}", events);
+        };
+        assert_eq!(update.path, "/cart");
+
+        client
+            .send_action("checkout", "checkout-btn", HashMap::new())
+            .unwrap();
+        let events = read_until(&mut stream, |e| matches!(e, A2aStreamEvent::Error(_)));
+        let Some(A2aStreamEvent::Error(e)) = events.last() else {
+            unreachable!();
+        };
+        assert!(e.contains("Payment service unavailable"), "{}", e);
+
+        // A disconnect ends the stream without a final status
+        client
+            .send_action("dropConnection", "drop-btn", HashMap::new())
+            .unwrap();
+        let rest: Vec<A2aStreamEvent> = stream.collect();
+        assert!(
+            !rest
+                .iter()
+                .any(|e| matches!(e, A2aStreamEvent::TaskStatus { .. })),
+            "{:?}",
+            rest
+        );
+    }
+}
@@ -0,0 +1,376 @@
+//! Mock Server Scenarios
+//!
+//! A scenario scripts what the mock A2A server sends: the steps streamed for
+//! `message/stream`, the steps sent when the client reports a user action,
+//! the delays between them, and failures such as JSON-RPC errors or dropped
+//! connections. Scenarios are JSON or YAML files:
+//!
+//! ```yaml
+//! name: Shopping cart
+//! delayMs: 100                  # before each step, unless the step sets one
+//! initial:
+//!   - status: working
+//!   - a2ui: {beginRendering: {surfaceId: main, root: root}}
+//!   - a2ui: {surfaceUpdate: {surfaceId: main, components: [...]}}
+//!   - status: input-required
+//!     text: What would you like?
+//! actions:
+//!   addToCart:
+//!     - a2ui: {dataModelUpdate: {surfaceId: main, path: /cart, contents: [...]}}
+//!   checkout:
+//!     - delayMs: 2000
+//!       error: {code: -32000, message: Payment service unavailable}
+//!   crash:
+//!     - disconnect: true
+//! ```
+//!
+//! Each step does one thing:
+//!
+//! | Step | Sends |
+//! |------|-------|
+//! | `a2ui` | An A2UI message, as an event of the task |
+//! | `text` | An agent message |
+//! | `status` | A task status update (with `text` as its message) |
+//! | `error` | A JSON-RPC error response |
+//! | `raw` | A JSON-RPC response, as is |
+//! | `disconnect` | Nothing; the connection is dropped without a final status |
+//!
+//! The stream ends after a terminal status (`completed`, `failed`, ...);
+//! until then it stays open for the responses to user actions.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use makepad_component::a2ui::{A2uiMessage, TaskState};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// A scripted agent
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// ID of the task the scenario runs
+    #[serde(default = "default_task_id")]
+    pub task_id: String,
+    /// Context reported when the request doesn't name one
+    #[serde(default = "default_context_id")]
+    pub context_id: String,
+    /// Wait before each step, in milliseconds
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: u64,
+    /// Steps streamed for `message/stream`
+    #[serde(default)]
+    pub initial: Vec<Step>,
+    /// Steps sent for each user action, by action name
+    #[serde(default)]
+    pub actions: HashMap<String, Vec<Step>>,
+}
+
+fn default_name() -> String {
+    "Mock scenario".to_string()
+}
+
+fn default_task_id() -> String {
+    "task-mock".to_string()
+}
+
+fn default_context_id() -> String {
+    "ctx-mock".to_string()
+}
+
+fn default_delay_ms() -> u64 {
+    100
+}
+
+/// One thing the agent does
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Step {
+    /// Wait before this step (the scenario's `delayMs` if unset)
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
+    /// An A2UI message
+    #[serde(default)]
+    pub a2ui: Option<Value>,
+    /// Agent text, or the message of a `status` step
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub status: Option<TaskState>,
+    #[serde(default)]
+    pub error: Option<StepError>,
+    /// A complete JSON-RPC response
+    #[serde(default)]
+    pub raw: Option<Value>,
+    #[serde(default)]
+    pub disconnect: bool,
+}
+
+/// JSON-RPC error of an `error` step
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepError {
+    #[serde(default = "default_error_code")]
+    pub code: i64,
+    pub message: String,
+}
+
+fn default_error_code() -> i64 {
+    -32000
+}
+
+/// What a step sends
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output {
+    /// A JSON-RPC response for the client
+    Send(Value),
+    /// Drop the connection
+    Disconnect,
+}
+
+impl Scenario {
+    /// Parse a JSON scenario
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let scenario: Scenario =
+            serde_json::from_str(json).map_err(|e| format!("Invalid scenario: {}", e))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Parse a YAML scenario
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        let scenario: Scenario =
+            serde_yaml::from_str(yaml).map_err(|e| format!("Invalid scenario: {}", e))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Load a scenario file (YAML for `.yaml`/`.yml`, JSON otherwise)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let scenario = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => Self::from_yaml(&text),
+            _ => Self::from_json(&text),
+        };
+        scenario.map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Check that every step does exactly one thing and sends valid A2UI
+    pub fn validate(&self) -> Result<(), String> {
+        let mut steps: Vec<(String, &Step)> = self
+            .initial
+            .iter()
+            .enumerate()
+            .map(|(i, step)| (format!("initial[{}]", i), step))
+            .collect();
+        for (name, action) in &self.actions {
+            steps.extend(
+                action
+                    .iter()
+                    .enumerate()
+                    .map(|(i, step)| (format!("actions.{}[{}]", name, i), step)),
+            );
+        }
+
+        for (location, step) in steps {
+            step.validate()
+                .map_err(|e| format!("{}: {}", location, e))?;
+        }
+        Ok(())
+    }
+
+    /// Wait before a step
+    pub fn delay(&self, step: &Step) -> Duration {
+        Duration::from_millis(step.delay_ms.unwrap_or(self.delay_ms))
+    }
+
+    /// Steps answering a user action, if the scenario scripts one
+    pub fn action(&self, name: &str) -> Option<&[Step]> {
+        self.actions.get(name).map(Vec::as_slice)
+    }
+}
+
+impl Step {
+    /// A step sending a JSON-RPC response as is
+    pub fn raw(response: Value) -> Self {
+        Step {
+            raw: Some(response),
+            ..Step::default()
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let kinds = [
+            self.a2ui.is_some(),
+            // `text` is the message of a `status` step
+            self.text.is_some() && self.status.is_none(),
+            self.status.is_some(),
+            self.error.is_some(),
+            self.raw.is_some(),
+            self.disconnect,
+        ];
+        match kinds.iter().filter(|kind| **kind).count() {
+            0 => return Err("step does nothing".to_string()),
+            1 => {}
+            _ => return Err("step does more than one thing".to_string()),
+        }
+
+        if let Some(a2ui) = &self.a2ui {
+            serde_json::from_value::<A2uiMessage>(a2ui.clone())
+                .map_err(|e| format!("invalid A2UI message: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// What the step sends for the task, answering request `request_id`
+    pub fn output(&self, task_id: &str, context_id: &str, request_id: &Value) -> Output {
+        if self.disconnect {
+            return Output::Disconnect;
+        }
+        let result = if let Some(raw) = &self.raw {
+            return Output::Send(raw.clone());
+        } else if let Some(error) = &self.error {
+            return Output::Send(json!({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "error": {"code": error.code, "message": error.message}
+            }));
+        } else if let Some(a2ui) = &self.a2ui {
+            json!({"kind": "event", "taskId": task_id, "data": a2ui})
+        } else if let Some(state) = self.status {
+            let mut status = json!({"state": state});
+            if let Some(text) = &self.text {
+                status["message"] = agent_message(text);
+            }
+            json!({
+                "kind": "status-update",
+                "taskId": task_id,
+                "contextId": context_id,
+                "status": status,
+                "final": state.is_terminal()
+            })
+        } else {
+            let mut message = agent_message(self.text.as_deref().unwrap_or_default());
+            message["taskId"] = json!(task_id);
+            message["contextId"] = json!(context_id);
+            message
+        };
+        Output::Send(json!({"jsonrpc": "2.0", "id": request_id, "result": result}))
+    }
+
+    /// Whether the task is over after this step
+    pub fn is_terminal(&self) -> bool {
+        let raw_state = self
+            .raw
+            .as_ref()
+            .and_then(|raw| serde_json::from_value(raw["result"]["status"]["state"].clone()).ok());
+        self.status
+            .or(raw_state)
+            .is_some_and(|state: TaskState| state.is_terminal())
+    }
+}
+
+fn agent_message(text: &str) -> Value {
+    json!({
+        "kind": "message",
+        "role": "agent",
+        "parts": [{"kind": "text", "text": text}]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+name: Cart
+delayMs: 5
+initial:
+  - status: working
+  - a2ui: {beginRendering: {surfaceId: main, root: root}}
+  - status: input-required
+    text: Ready
+actions:
+  addToCart:
+    - delayMs: 0
+      a2ui: {dataModelUpdate: {surfaceId: main, path: /cart, contents: [{key: count, valueNumber: 1}]}}
+  checkout:
+    - error: {code: -32001, message: Payment service unavailable}
+  crash:
+    - disconnect: true
+"#;
+
+    #[test]
+    fn test_load_yaml_and_json() {
+        let scenario = Scenario::from_yaml(YAML).unwrap();
+        assert_eq!(scenario.name, "Cart");
+        assert_eq!(scenario.initial.len(), 3);
+        assert_eq!(
+            scenario.delay(&scenario.initial[0]),
+            Duration::from_millis(5)
+        );
+        let add = scenario.action("addToCart").unwrap();
+        assert_eq!(scenario.delay(&add[0]), Duration::ZERO);
+        assert!(scenario.action("unknown").is_none());
+
+        // Same scenario as JSON
+        let json: Value = serde_yaml::from_str(YAML).unwrap();
+        let from_json = Scenario::from_json(&json.to_string()).unwrap();
+        assert_eq!(from_json.actions.len(), 3);
+
+        // Steps are checked when loading
+        let e = Scenario::from_yaml("initial:\n  - text: Hi\n    raw: {}\n").unwrap_err();
+        assert!(
+            e.contains("initial[0]: step does more than one thing"),
+            "{}",
+            e
+        );
+        let e = Scenario::from_yaml("actions:\n  go:\n    - a2ui: {bogus: {}}\n").unwrap_err();
+        assert!(e.contains("actions.go[0]: invalid A2UI message"), "{}", e);
+        assert!(Scenario::from_yaml("initial:\n  - delayMs: 5\n").is_err());
+        assert!(Scenario::from_yaml("initail: []\n").is_err());
+    }
+
+    #[test]
+    fn test_step_outputs() {
+        let scenario = Scenario::from_yaml(YAML).unwrap();
+        let id = json!(3);
+        let output = |step: &Step| step.output(&scenario.task_id, "ctx-1", &id);
+
+        let Output::Send(event) = output(&scenario.initial[1]) else {
+            panic!("expected an event");
+        };
+        assert_eq!(event["id"], 3);
+        assert_eq!(event["result"]["kind"], "event");
+        assert_eq!(event["result"]["taskId"], "task-mock");
+        assert_eq!(event["result"]["data"]["beginRendering"]["root"], "root");
+
+        let Output::Send(status) = output(&scenario.initial[2]) else {
+            panic!("expected a status update");
+        };
+        assert_eq!(status["result"]["status"]["state"], "input-required");
+        assert_eq!(
+            status["result"]["status"]["message"]["parts"][0]["text"],
+            "Ready"
+        );
+        assert_eq!(status["result"]["contextId"], "ctx-1");
+        assert!(!scenario.initial[2].is_terminal());
+
+        let Output::Send(error) = output(&scenario.action("checkout").unwrap()[0]) else {
+            panic!("expected an error");
+        };
+        assert_eq!(error["error"]["code"], -32001);
+        assert_eq!(
+            output(&scenario.action("crash").unwrap()[0]),
+            Output::Disconnect
+        );
+
+        let done = Step::raw(json!({"result": {"kind": "task", "status": {"state": "completed"}}}));
+        assert!(done.is_terminal());
+    }
+}
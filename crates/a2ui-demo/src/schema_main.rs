@@ -0,0 +1,22 @@
+//! A2UI Schema Export
+//!
+//! Writes the JSON Schema of A2UI messages for the standard catalog:
+//!   cargo run -p a2ui-demo --bin a2ui-schema -- a2ui.schema.json
+//!
+//! Without a path the schema is printed to stdout.
+
+use makepad_component::a2ui::{CatalogSchema, ComponentRegistry};
+
+fn main() {
+    let schema = CatalogSchema::from_registry(&ComponentRegistry::with_standard_catalog());
+    match std::env::args().nth(1) {
+        Some(path) => {
+            if let Err(e) = schema.save(&path) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            println!("Wrote {}", path);
+        }
+        None => println!("{}", schema.to_json_pretty()),
+    }
+}
@@ -0,0 +1,372 @@
+//! Bridge Sessions
+//!
+//! Each A2A `contextId` gets its own `Session` with a separate conversation
+//! history, latest surface, `/live` channel and live file, so clients sharing
+//! one bridge don't see each other's UI. Requests without a context use
+//! `DEFAULT_SESSION`. Live files are watch-server surface files: the default
+//! session's is `main.a2ui.json`, the `main` surface of the demo app. Other
+//! sessions write to the `sessions` subdirectory, which a watch-server
+//! serving the live directory doesn't watch, so their UI stays private.
+//! Files are replaced atomically, so a watcher never reads half of one.
+//!
+//! Sessions idle for longer than the store's TTL are dropped by
+//! `SessionStore::expire`, unless a `/live` client is still connected. At most
+//! `max_sessions` contexts are kept besides the default one; a new context
+//! replaces the least recently used session without `/live` clients.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use makepad_component::a2ui::{SurfaceState, ToolCallBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::{broadcast, RwLock};
+
+/// Context used when a request doesn't name one
+pub const DEFAULT_SESSION: &str = "default";
+
+/// Idle time before a session expires (`SESSION_TTL_SECS` overrides)
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Sessions kept besides the default one (`MAX_SESSIONS` overrides)
+pub const DEFAULT_MAX_SESSIONS: usize = 64;
+
+/// State of one conversation
+pub struct Session {
+    pub context_id: String,
+    /// A2UI updates for this session's `/live` clients
+    pub tx: broadcast::Sender<String>,
+    pub conversation: RwLock<Vec<Value>>,
+    /// Turns recorded with `record_turn`
+    turns: AtomicUsize,
+    /// Action turns started, numbering their A2A tasks
+    tasks: AtomicUsize,
+    /// Held while the model runs, so the turns of a session don't interleave
+    pub turn_lock: tokio::sync::Mutex<()>,
+    /// What the session's clients have been sent, diffed against by the next update
+    pub sent: RwLock<SurfaceState>,
+    /// Tool calls behind the latest UI, continued when the user acts on it
+    pub surface: RwLock<Option<ToolCallBuilder>>,
+    /// Latest A2UI while streaming, read by /rpc and the watch-server
+    pub live_file: PathBuf,
+    created: Instant,
+    last_active: Mutex<Instant>,
+}
+
+impl Session {
+    fn new(context_id: &str, live_dir: &Path) -> Self {
+        let (tx, _rx) = broadcast::channel::<String>(16);
+        let now = Instant::now();
+        Session {
+            context_id: context_id.to_string(),
+            tx,
+            conversation: RwLock::new(Vec::new()),
+            turns: AtomicUsize::new(0),
+            tasks: AtomicUsize::new(0),
+            turn_lock: tokio::sync::Mutex::new(()),
+            sent: RwLock::new(SurfaceState::new("main")),
+            surface: RwLock::new(None),
+            live_file: live_dir.join(live_file_path(context_id)),
+            created: now,
+            last_active: Mutex::new(now),
+        }
+    }
+
+    /// Add the messages of a finished turn to the conversation
+    pub async fn record_turn(&self, messages: impl IntoIterator<Item = Value>) {
+        self.conversation.write().await.extend(messages);
+        self.turns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Forget the conversation
+    pub async fn reset(&self) {
+        self.conversation.write().await.clear();
+        self.turns.store(0, Ordering::Relaxed);
+    }
+
+    /// ID of the A2A task for a new action turn
+    pub fn next_task_id(&self) -> String {
+        let n = self.tasks.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("{}-action-{}", self.context_id, n)
+    }
+
+    /// Replace the live file with `content`
+    ///
+    /// The content is written to a temporary file next to it first, which
+    /// watchers ignore, then renamed over the live file.
+    pub fn write_live_file(&self, content: &str) -> std::io::Result<()> {
+        let dir = self.live_file.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(dir)?;
+        let name = self.live_file.file_name().unwrap_or_default().to_string_lossy();
+        let temp = dir.join(format!(".{}.tmp", name));
+        std::fs::write(&temp, content)?;
+        std::fs::rename(&temp, &self.live_file)
+    }
+
+    /// Mark the session as used now
+    pub fn touch(&self) {
+        *self.last_active.lock().unwrap() = Instant::now();
+    }
+
+    /// Time since the session was last used
+    pub fn idle(&self) -> Duration {
+        self.last_active.lock().unwrap().elapsed()
+    }
+
+    /// Summary for the `/sessions` listing
+    pub async fn info(&self) -> SessionInfo {
+        SessionInfo {
+            context_id: self.context_id.clone(),
+            conversation_turns: self.turns.load(Ordering::Relaxed),
+            has_ui: self.sent.read().await.root().is_some(),
+            live_clients: self.tx.receiver_count(),
+            age_secs: self.created.elapsed().as_secs(),
+            idle_secs: self.idle().as_secs(),
+        }
+    }
+}
+
+/// Entry in the `/sessions` listing
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub context_id: String,
+    pub conversation_turns: usize,
+    pub has_ui: bool,
+    pub live_clients: usize,
+    pub age_secs: u64,
+    pub idle_secs: u64,
+}
+
+/// Sessions keyed by A2A `contextId`
+pub struct SessionStore {
+    sessions: RwLock<HashMap<String, Arc<Session>>>,
+    live_dir: PathBuf,
+    ttl: Duration,
+    max_sessions: usize,
+}
+
+impl SessionStore {
+    /// Live files are written to `live_dir`
+    pub fn new(live_dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        SessionStore {
+            sessions: RwLock::new(HashMap::new()),
+            live_dir: live_dir.into(),
+            ttl,
+            max_sessions: DEFAULT_MAX_SESSIONS,
+        }
+    }
+
+    /// Keep at most `max_sessions` sessions besides the default one
+    pub fn with_max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = max_sessions;
+        self
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn max_sessions(&self) -> usize {
+        self.max_sessions
+    }
+
+    /// Get a session, creating it on first use
+    ///
+    /// When the store is full, the least recently used session without
+    /// `/live` clients is dropped to make room. Fails if every session has
+    /// clients. The default session can always be created.
+    pub async fn get_or_create(&self, context_id: &str) -> Result<Arc<Session>, String> {
+        if let Some(session) = self.get(context_id).await {
+            return Ok(session);
+        }
+
+        let mut sessions = self.sessions.write().await;
+        if let Some(session) = sessions.get(context_id) {
+            session.touch();
+            return Ok(session.clone());
+        }
+
+        let others = sessions.keys().filter(|id| *id != DEFAULT_SESSION).count();
+        if context_id != DEFAULT_SESSION && others >= self.max_sessions {
+            let evicted = sessions
+                .values()
+                .filter(|s| s.context_id != DEFAULT_SESSION && s.tx.receiver_count() == 0)
+                .max_by_key(|s| s.idle())
+                .map(|s| s.context_id.clone())
+                .ok_or_else(|| format!("Too many sessions (at most {})", self.max_sessions))?;
+            if let Some(session) = sessions.remove(&evicted) {
+                remove_live_file(&session);
+            }
+            println!("[Sessions] Dropped least recently used session: {}", evicted);
+        }
+
+        println!("[Sessions] New session: {}", context_id);
+        let session = Arc::new(Session::new(context_id, &self.live_dir));
+        sessions.insert(context_id.to_string(), session.clone());
+        Ok(session)
+    }
+
+    /// Get an existing session
+    pub async fn get(&self, context_id: &str) -> Option<Arc<Session>> {
+        let session = self.sessions.read().await.get(context_id).cloned();
+        if let Some(session) = &session {
+            session.touch();
+        }
+        session
+    }
+
+    /// Drop a session and its live file
+    pub async fn remove(&self, context_id: &str) -> bool {
+        let removed = self.sessions.write().await.remove(context_id);
+        if let Some(session) = &removed {
+            remove_live_file(session);
+        }
+        removed.is_some()
+    }
+
+    /// Drop sessions idle for longer than the TTL, returning their context IDs
+    ///
+    /// Sessions with connected `/live` clients are kept.
+    pub async fn expire(&self) -> Vec<String> {
+        let mut sessions = self.sessions.write().await;
+        let expired: Vec<String> = sessions
+            .values()
+            .filter(|s| s.idle() > self.ttl && s.tx.receiver_count() == 0)
+            .map(|s| s.context_id.clone())
+            .collect();
+
+        for context_id in &expired {
+            if let Some(session) = sessions.remove(context_id) {
+                remove_live_file(&session);
+            }
+            println!("[Sessions] Expired session: {}", context_id);
+        }
+        expired
+    }
+
+    /// Summaries of all sessions, most recently used first
+    pub async fn list(&self) -> Vec<SessionInfo> {
+        let sessions: Vec<Arc<Session>> = self.sessions.read().await.values().cloned().collect();
+        let mut infos = Vec::with_capacity(sessions.len());
+        for session in sessions {
+            infos.push(session.info().await);
+        }
+        infos.sort_by_key(|info| info.idle_secs);
+        infos
+    }
+
+    pub async fn count(&self) -> usize {
+        self.sessions.read().await.len()
+    }
+}
+
+/// `main.a2ui.json` for the default session, `sessions/<context>.a2ui.json` otherwise
+///
+/// Context IDs with characters other than `[A-Za-z0-9_-]` are sanitized and
+/// get a hash of the raw ID appended, so `a b` and `a_b` don't share a file.
+fn live_file_path(context_id: &str) -> PathBuf {
+    if context_id == DEFAULT_SESSION {
+        return PathBuf::from("main.a2ui.json");
+    }
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    let name = if context_id.chars().all(is_safe) {
+        context_id.to_string()
+    } else {
+        let safe: String = context_id
+            .chars()
+            .map(|c| if is_safe(c) { c } else { '_' })
+            .collect();
+        // FNV-1a, stable across runs and Rust versions
+        let hash = context_id
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325u64, |hash, b| {
+                (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+            });
+        format!("{}.{:016x}", safe, hash)
+    };
+    Path::new("sessions").join(format!("{}.a2ui.json", name))
+}
+
+fn remove_live_file(session: &Session) {
+    // The default file is shared with the watch-server
+    if session.context_id != DEFAULT_SESSION {
+        let _ = std::fs::remove_file(&session.live_file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_file_paths() {
+        assert_eq!(live_file_path(DEFAULT_SESSION), Path::new("main.a2ui.json"));
+        assert_eq!(live_file_path("ctx-1"), Path::new("sessions/ctx-1.a2ui.json"));
+        assert_eq!(live_file_path("a_b"), Path::new("sessions/a_b.a2ui.json"));
+        let sanitized = live_file_path("../a b");
+        assert!(sanitized.to_str().unwrap().starts_with("sessions/___a_b."));
+        // Sanitized IDs don't collide with each other or with safe IDs
+        let paths: std::collections::HashSet<PathBuf> =
+            ["a_b", "a b", "a/b", "a.b"].into_iter().map(live_file_path).collect();
+        assert_eq!(paths.len(), 4);
+
+        let dir = std::env::temp_dir().join(format!("bridge_sessions_{}", std::process::id()));
+        let session = Session::new("ctx-1", &dir);
+        session.write_live_file("[]").unwrap();
+        session.write_live_file("[{}]").unwrap();
+        assert_eq!(std::fs::read_to_string(&session.live_file).unwrap(), "[{}]");
+        // Only the live file is left in the (unwatched) sessions directory
+        assert_eq!(std::fs::read_dir(dir.join("sessions")).unwrap().count(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_isolated_and_expire() {
+        let store = SessionStore::new(std::env::temp_dir(), Duration::ZERO);
+
+        let a = store.get_or_create("a").await.unwrap();
+        let b = store.get_or_create("b").await.unwrap();
+        a.record_turn([Value::from("hello")]).await;
+        assert!(b.conversation.read().await.is_empty());
+        assert_eq!(a.info().await.conversation_turns, 1);
+        assert!(Arc::ptr_eq(&a, &store.get_or_create("a").await.unwrap()));
+        assert_ne!(a.live_file, b.live_file);
+        assert_eq!(store.list().await.len(), 2);
+
+        // A connected /live client keeps its session alive
+        let _live = b.tx.subscribe();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(store.expire().await, vec!["a".to_string()]);
+        assert!(store.get("a").await.is_none());
+        assert!(store.get("b").await.is_some());
+
+        assert!(store.remove("b").await);
+        assert_eq!(store.count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_session_limit() {
+        let store = SessionStore::new(std::env::temp_dir(), DEFAULT_SESSION_TTL).with_max_sessions(2);
+
+        let a = store.get_or_create("a").await.unwrap();
+        let _live = a.tx.subscribe();
+        store.get_or_create("b").await.unwrap();
+        assert!(Arc::ptr_eq(&a, &store.get_or_create("a").await.unwrap()));
+
+        // The least recently used session without /live clients makes room
+        store.get_or_create("c").await.unwrap();
+        assert!(store.get("b").await.is_none());
+        assert_eq!(store.count().await, 2);
+
+        // Every session has clients: new contexts are refused, the default
+        // session is still available
+        let _live = store.get("c").await.unwrap().tx.subscribe();
+        assert!(store.get_or_create("d").await.is_err());
+        assert!(store.get("d").await.is_none());
+        assert!(store.get_or_create(DEFAULT_SESSION).await.is_ok());
+    }
+}
@@ -3,6 +3,8 @@
 //! Demonstrates connecting to an A2A server and receiving streaming UI updates.
 //! Run the mock server first: python3 debug/mock_a2a_server.py
 
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
 use makepad_component::a2ui::*;
 use makepad_widgets::*;
 
@@ -109,6 +111,10 @@ pub struct StreamingApp {
 
     #[rust]
     is_connecting: bool,
+
+    /// Host being connected on a background thread
+    #[rust]
+    pending_host: Option<Receiver<Result<A2uiHost, String>>>,
 }
 
 impl LiveRegister for StreamingApp {
@@ -120,36 +126,74 @@ impl LiveRegister for StreamingApp {
 
 impl StreamingApp {
     fn connect(&mut self, cx: &mut Cx) {
-        if self.host.is_some() {
+        if self.host.is_some() || self.pending_host.is_some() {
             self.ui
                 .label(ids!(status_label))
                 .set_text(cx, "Already connected");
             return;
         }
 
-        let config = A2uiHostConfig {
-            url: "http://localhost:8080/rpc".to_string(),
-            auth_token: None,
+        // Set A2UI_WEBSOCKET=1 to use the mock server's /ws endpoint instead of SSE
+        let websocket = std::env::var_os("A2UI_WEBSOCKET").is_some();
+
+        // Over SSE the RPC endpoint is taken from the agent card, if the server has one
+        let config = if websocket {
+            A2uiHostConfig::new("ws://localhost:8080/ws")
+                .with_transport(A2uiTransportKind::WebSocket)
+        } else {
+            A2uiHostConfig::new("http://localhost:8080/rpc")
         };
 
-        let mut host = A2uiHost::new(config);
+        // Discovery and connecting block on the network, so keep them off the UI thread
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut host = A2uiHost::new(config);
+            if websocket {
+                log!("Using WebSocket transport");
+            } else if let Err(e) = host.discover() {
+                // Servers without an agent card (e.g., the watch server) still work
+                log!("Agent discovery failed, using the configured URL: {}", e);
+            }
+            let result = host
+                .connect("Hello, show me a greeting UI")
+                .map(|()| host);
+            let _ = tx.send(result);
+        });
 
-        match host.connect("Hello, show me a greeting UI") {
-            Ok(()) => {
-                self.ui
-                    .label(ids!(status_label))
-                    .set_text(cx, "Connecting...");
+        self.pending_host = Some(rx);
+        self.is_connecting = true;
+        self.ui
+            .label(ids!(status_label))
+            .set_text(cx, "Connecting...");
+        self.ui.redraw(cx);
+        cx.new_next_frame();
+    }
+
+    /// Pick up the host once the background connect finished
+    fn poll_pending_host(&mut self, cx: &mut Cx) {
+        let Some(rx) = &self.pending_host else {
+            return;
+        };
+
+        match rx.try_recv() {
+            Ok(Ok(host)) => {
                 self.host = Some(host);
-                self.is_connecting = true;
+                self.pending_host = None;
             }
-            Err(e) => {
+            Ok(Err(e)) => {
                 self.ui
                     .label(ids!(status_label))
                     .set_text(cx, &format!("Connection failed: {}", e));
+                self.pending_host = None;
+                self.is_connecting = false;
+                self.ui.redraw(cx);
+            }
+            Err(TryRecvError::Empty) => cx.new_next_frame(),
+            Err(TryRecvError::Disconnected) => {
+                self.pending_host = None;
+                self.is_connecting = false;
             }
         }
-
-        self.ui.redraw(cx);
     }
 
     fn poll_host(&mut self, cx: &mut Cx) {
@@ -157,14 +201,21 @@ impl StreamingApp {
             return;
         };
 
+        // Get surface widget
+        let surface_ref = self.ui.widget(ids!(a2ui_surface));
+
+        // Send local edits upstream (only if data sync is enabled on the processor)
+        if let Some(mut surface) = surface_ref.borrow_mut::<A2uiSurface>() {
+            if let Some(processor) = surface.processor_mut() {
+                host.sync_data(processor);
+            }
+        }
+
         let events = host.poll_all();
         if events.is_empty() {
             return;
         }
 
-        // Get surface widget
-        let surface_ref = self.ui.widget(ids!(a2ui_surface));
-
         for event in events {
             match event {
                 A2uiHostEvent::Connected => {
@@ -183,6 +234,9 @@ impl StreamingApp {
                         .label(ids!(status_label))
                         .set_text(cx, "Receiving UI updates...");
                 }
+                A2uiHostEvent::Text(text) => {
+                    self.ui.label(ids!(status_label)).set_text(cx, &text);
+                }
                 A2uiHostEvent::TaskStatus { task_id, state } => {
                     self.ui
                         .label(ids!(status_label))
@@ -199,6 +253,12 @@ impl StreamingApp {
                         .set_text(cx, "Disconnected");
                     self.host = None;
                 }
+                A2uiHostEvent::ActionDelivery(event) => {
+                    log!("Action delivery: {:?}", event);
+                }
+                A2uiHostEvent::DataSyncFailed { surface_id, error } => {
+                    log!("Data sync for {} failed: {}", surface_id, error);
+                }
             }
         }
 
@@ -236,29 +296,31 @@ impl StreamingApp {
                     // Update local data model
                     if let Some(mut surface) = surface_ref.borrow_mut::<A2uiSurface>() {
                         if let Some(processor) = surface.processor_mut() {
-                            if let Some(data_model) = processor.get_data_model_mut(&surface_id) {
-                                // Implement radio button behavior for payment methods
-                                // When one is selected, deselect all others
-                                let payment_methods = [
-                                    "/payment/creditCard",
-                                    "/payment/paypal",
-                                    "/payment/alipay",
-                                    "/payment/wechat",
-                                ];
-
-                                if payment_methods.contains(&path.as_str()) {
-                                    // If setting to true, deselect all others first
-                                    if value == serde_json::Value::Bool(true) {
-                                        for method in &payment_methods {
-                                            if *method != path {
-                                                data_model.set(method, serde_json::Value::Bool(false));
-                                            }
+                            // Implement radio button behavior for payment methods
+                            // When one is selected, deselect all others
+                            let payment_methods = [
+                                "/payment/creditCard",
+                                "/payment/paypal",
+                                "/payment/alipay",
+                                "/payment/wechat",
+                            ];
+
+                            if payment_methods.contains(&path.as_str()) {
+                                // If setting to true, deselect all others first
+                                if value == serde_json::Value::Bool(true) {
+                                    for method in &payment_methods {
+                                        if *method != path {
+                                            processor.set_local_value(
+                                                &surface_id,
+                                                method,
+                                                serde_json::Value::Bool(false),
+                                            );
                                         }
                                     }
                                 }
-
-                                data_model.set(&path, value);
                             }
+
+                            processor.set_local_value(&surface_id, &path, value);
                         }
                     }
                     self.ui.redraw(cx);
@@ -271,6 +333,8 @@ impl StreamingApp {
 
 impl AppMain for StreamingApp {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        self.poll_pending_host(cx);
+
         // Poll host for new messages (on every frame if connecting)
         if self.host.is_some() {
             self.poll_host(cx);
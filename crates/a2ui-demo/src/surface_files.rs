@@ -0,0 +1,266 @@
+//! Surface Files
+//!
+//! The watch-server serves a directory of `*.a2ui.json` files, one surface per
+//! file: `checkout.a2ui.json` holds the A2UI messages of surface `checkout`,
+//! as a JSON array (or a single message). `surfaceId` fields in the file may
+//! be left out; they're set to the file's surface.
+//!
+//! `SurfaceFiles` keeps what clients have been sent for each surface, so a
+//! changed file goes out as the components and data that changed. A file that
+//! isn't valid JSON or A2UI shows an error surface in its place until it's
+//! fixed.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use makepad_component::a2ui::{
+    referenced_ids, A2uiMessage, CatalogSchema, DeleteSurface, SurfaceBuilder, SurfaceState,
+    TextUsageHint,
+};
+use serde_json::Value;
+
+/// File name suffix of surface files
+pub const SURFACE_FILE_SUFFIX: &str = ".a2ui.json";
+
+/// Root of the surface shown for a broken file
+const ERROR_ROOT: &str = "a2ui-file-error";
+
+/// Surface of a surface file (`checkout.a2ui.json` -> `checkout`)
+pub fn surface_id_of(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let surface_id = name.strip_suffix(SURFACE_FILE_SUFFIX)?;
+    (!surface_id.is_empty() && !surface_id.starts_with('.')).then(|| surface_id.to_string())
+}
+
+/// Parse and check the content of a surface file
+///
+/// Messages must match the A2UI schema, the file must start rendering, and
+/// every referenced component (the root included) must be defined.
+pub fn parse_surface(
+    surface_id: &str,
+    content: &str,
+    schema: &CatalogSchema,
+) -> Result<SurfaceState, String> {
+    let values = match serde_json::from_str(content) {
+        Ok(Value::Array(values)) => values,
+        Ok(single) => vec![single],
+        Err(e) => return Err(format!("Invalid JSON: {}", e)),
+    };
+
+    let mut messages = Vec::with_capacity(values.len());
+    for (i, mut value) in values.into_iter().enumerate() {
+        if let Some(message) = value.as_object_mut() {
+            for body in message.values_mut().filter_map(Value::as_object_mut) {
+                body.insert("surfaceId".to_string(), Value::from(surface_id));
+            }
+        }
+        schema
+            .validate(&value)
+            .map_err(|e| format!("Message {}: {}", i, e))?;
+        let message: A2uiMessage =
+            serde_json::from_value(value).map_err(|e| format!("Message {}: {}", i, e))?;
+        messages.push(message);
+    }
+
+    let state = SurfaceState::from_messages(surface_id, &messages);
+    let Some(root) = state.root() else {
+        return Err("No beginRendering message".to_string());
+    };
+    let ids: HashSet<&str> = state.components().iter().map(|c| c.id.as_str()).collect();
+    if !ids.contains(root) {
+        return Err(format!("Root component '{}' is not defined", root));
+    }
+    for component in state.components() {
+        for reference in referenced_ids(&component.component) {
+            if !ids.contains(reference) {
+                return Err(format!(
+                    "Component '{}' references undefined component '{}'",
+                    component.id, reference
+                ));
+            }
+        }
+    }
+    Ok(state)
+}
+
+/// Surface telling the user what's wrong with a file
+pub fn error_surface(surface_id: &str, file: &str, error: &str) -> SurfaceState {
+    let messages = SurfaceBuilder::new(surface_id)
+        .card(ERROR_ROOT, |c| {
+            c.styled_text(format!("⚠️ {}", file), TextUsageHint::H3)
+                .id("a2ui-file-error-title")
+                .styled_text(error.to_string(), TextUsageHint::Code)
+                .id("a2ui-file-error-message")
+        })
+        .build()
+        .unwrap_or_default();
+    SurfaceState::from_messages(surface_id, &messages)
+}
+
+/// Surfaces of a directory of surface files, as clients have them
+pub struct SurfaceFiles {
+    schema: CatalogSchema,
+    shown: BTreeMap<String, SurfaceState>,
+    /// Problems of the files showing an error surface
+    errors: BTreeMap<String, String>,
+}
+
+impl Default for SurfaceFiles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SurfaceFiles {
+    pub fn new() -> Self {
+        SurfaceFiles {
+            schema: CatalogSchema::standard(),
+            shown: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        }
+    }
+
+    /// Load every surface file in `dir`, returning the messages for clients
+    pub fn scan(&mut self, dir: &Path) -> Vec<A2uiMessage> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| surface_id_of(path).is_some())
+            .collect();
+        paths.sort();
+
+        let mut messages = Vec::new();
+        for path in paths {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => messages.extend(self.update(&path, Some(&content))),
+                Err(e) => eprintln!("[Watcher] Failed to read {}: {}", path.display(), e),
+            }
+        }
+        messages
+    }
+
+    /// Take the new content of a surface file (`None` once it's removed),
+    /// returning the messages bringing clients up to date
+    pub fn update(&mut self, path: &Path, content: Option<&str>) -> Vec<A2uiMessage> {
+        let Some(surface_id) = surface_id_of(path) else {
+            return Vec::new();
+        };
+
+        let Some(content) = content else {
+            self.errors.remove(&surface_id);
+            return match self.shown.remove(&surface_id) {
+                Some(_) => vec![A2uiMessage::DeleteSurface(DeleteSurface { surface_id })],
+                None => Vec::new(),
+            };
+        };
+
+        let next = match parse_surface(&surface_id, content, &self.schema) {
+            Ok(state) => {
+                self.errors.remove(&surface_id);
+                state
+            }
+            Err(e) => {
+                let file = path.file_name().unwrap_or_default().to_string_lossy();
+                eprintln!("[Watcher] {}: {}", file, e);
+                let state = error_surface(&surface_id, &file, &e);
+                self.errors.insert(surface_id.clone(), e);
+                state
+            }
+        };
+
+        let shown = self
+            .shown
+            .entry(surface_id.clone())
+            .or_insert_with(|| SurfaceState::new(surface_id));
+        let messages = shown.diff(&next);
+        *shown = next;
+        messages
+    }
+
+    /// Messages building every surface on a new client
+    pub fn messages(&self) -> Vec<A2uiMessage> {
+        self.shown
+            .values()
+            .flat_map(SurfaceState::messages)
+            .collect()
+    }
+
+    /// IDs of the surfaces, in file name order
+    pub fn surface_ids(&self) -> impl Iterator<Item = &str> {
+        self.shown.keys().map(String::as_str)
+    }
+
+    /// Problems of the broken files, by surface
+    pub fn errors(&self) -> &BTreeMap<String, String> {
+        &self.errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNTER: &str = r#"[
+        {"beginRendering": {"root": "root"}},
+        {"surfaceUpdate": {"components": [
+            {"id": "root", "component": {"Column": {"children": {"explicitList": ["count"]}}}},
+            {"id": "count", "component": {"Text": {"text": {"path": "/count"}}}}
+        ]}},
+        {"dataModelUpdate": {"path": "/", "contents": [{"key": "count", "valueNumber": 1}]}}
+    ]"#;
+
+    #[test]
+    fn test_surface_ids_and_checks() {
+        assert_eq!(
+            surface_id_of(Path::new("ui/checkout.a2ui.json")).as_deref(),
+            Some("checkout")
+        );
+        assert_eq!(surface_id_of(Path::new("checkout.json")), None);
+        assert_eq!(surface_id_of(Path::new(".a2ui.json")), None);
+        assert_eq!(surface_id_of(Path::new(".#checkout.a2ui.json")), None);
+
+        let schema = CatalogSchema::standard();
+        let state = parse_surface("counter", COUNTER, &schema).unwrap();
+        assert_eq!(state.surface_id(), "counter");
+        assert_eq!(state.components().len(), 2);
+        assert_eq!(state.data()["count"], 1.0);
+
+        let e = parse_surface("counter", "[{\"beginRendering\": ", &schema).unwrap_err();
+        assert!(e.starts_with("Invalid JSON"), "{}", e);
+        let e = parse_surface("counter", r#"[{"beginRendering": {}}]"#, &schema).unwrap_err();
+        assert!(e.starts_with("Message 0:"), "{}", e);
+        let dangling = COUNTER.replace(r#"["count"]"#, r#"["count", "missing"]"#);
+        let e = parse_surface("counter", &dangling, &schema).unwrap_err();
+        assert!(e.contains("undefined component 'missing'"), "{}", e);
+    }
+
+    #[test]
+    fn test_updates_are_diffs_and_errors_show() {
+        let path = Path::new("counter.a2ui.json");
+        let mut files = SurfaceFiles::new();
+        assert_eq!(files.update(path, Some(COUNTER)).len(), 3);
+
+        // Only the changed data goes out
+        let changed = COUNTER.replace(r#""valueNumber": 1"#, r#""valueNumber": 2"#);
+        let update = files.update(path, Some(&changed));
+        assert_eq!(update.len(), 1);
+        assert!(matches!(&update[0], A2uiMessage::DataModelUpdate(u) if u.surface_id == "counter"));
+        assert!(files.update(path, Some(&changed)).is_empty());
+
+        // A broken file replaces the surface with the error until it's fixed
+        let update = files.update(path, Some("[{"));
+        assert!(matches!(&update[0], A2uiMessage::BeginRendering(b) if b.root == ERROR_ROOT));
+        assert!(files.errors()["counter"].starts_with("Invalid JSON"));
+        let update = files.update(path, Some(&changed));
+        assert!(matches!(&update[0], A2uiMessage::BeginRendering(b) if b.root == "root"));
+        assert!(files.errors().is_empty());
+        assert_eq!(files.messages().len(), 3);
+
+        let update = files.update(path, None);
+        assert!(
+            matches!(&update[..], [A2uiMessage::DeleteSurface(d)] if d.surface_id == "counter")
+        );
+        assert_eq!(files.surface_ids().count(), 0);
+    }
+}
@@ -1,122 +1,264 @@
 //! File-watching A2A Server for live A2UI development
 //!
-//! Watches a JSON file and streams changes to connected clients via SSE.
+//! Watches a directory of `*.a2ui.json` files, one surface per file (see
+//! `surface_files.rs`), and streams changes to connected clients via SSE or
+//! WebSocket (`/ws`). Only the components and data that changed are sent; a
+//! file with invalid JSON or A2UI shows an error surface until it's fixed.
 //!
-//! Run: cargo run -p a2ui-demo --bin watch-server --features mock-server
-//! Edit: ui_live.json to see changes in real-time
+//! Run: cargo run -p a2ui-demo --bin watch-server --features mock-server [-- <dir>]
+//! Edit: main.a2ui.json (surface `main`) in the directory, which defaults to the
+//! current one (or `WATCH_DIR`), to see changes in real-time
 
-use http_body_util::Full;
-use hyper::body::{Bytes, Incoming};
+mod surface_files;
+
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full, StreamBody};
+use hyper::body::{Bytes, Frame, Incoming};
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
+use hyper::upgrade::Upgraded;
 use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
+use makepad_component::a2ui::A2uiMessage;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
 use std::convert::Infallible;
 use std::net::SocketAddr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use surface_files::SurfaceFiles;
 use tokio::net::TcpListener;
-use tokio::sync::broadcast;
-use tokio::fs;
-
-const JSON_FILE: &str = "ui_live.json";
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Message, Role};
+use tokio_tungstenite::WebSocketStream;
+
+/// Time to gather the events of one save (editors often write in several steps)
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Watched directory and the surfaces of its files
+struct WatchState {
+    dir: PathBuf,
+    files: RwLock<SurfaceFiles>,
+    /// Changes for connected clients, sent while `files` is locked
+    tx: broadcast::Sender<Vec<A2uiMessage>>,
+    /// Number of the last SSE connection, naming its task
+    connections: AtomicU64,
+}
 
-/// Watch the JSON file for changes and broadcast updates
-async fn watch_file(tx: broadcast::Sender<String>) {
-    let path = Path::new(JSON_FILE);
-    let mut last_content = String::new();
-    let mut last_modified = std::time::SystemTime::UNIX_EPOCH;
+impl WatchState {
+    fn new(dir: PathBuf) -> Self {
+        let (tx, _) = broadcast::channel(16);
+        WatchState {
+            dir,
+            files: RwLock::new(SurfaceFiles::new()),
+            tx,
+            connections: AtomicU64::new(0),
+        }
+    }
 
-    println!("[Watcher] Watching file: {}", JSON_FILE);
+    /// Number a new SSE connection, whose events belong to its own task
+    fn next_connection(&self) -> u64 {
+        self.connections.fetch_add(1, Ordering::Relaxed) + 1
+    }
 
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    /// Reload a surface file and send its changes
+    async fn reload(&self, path: &Path) {
+        let content = match tokio::fs::read_to_string(path).await {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                eprintln!("[Watcher] Failed to read {}: {}", path.display(), e);
+                return;
+            }
+        };
 
-        // Check if file exists
-        if !path.exists() {
-            continue;
+        let mut files = self.files.write().await;
+        let update = files.update(path, content.as_deref());
+        if !update.is_empty() {
+            println!(
+                "[Watcher] {} changed, sending {} message(s)",
+                path.display(),
+                update.len()
+            );
+            let _ = self.tx.send(update);
         }
+    }
 
-        // Check modification time
-        let metadata = match fs::metadata(path).await {
-            Ok(m) => m,
-            Err(_) => continue,
-        };
+    /// The current surfaces and a receiver for the changes after them
+    async fn subscribe(&self) -> (Vec<A2uiMessage>, broadcast::Receiver<Vec<A2uiMessage>>) {
+        let files = self.files.read().await;
+        (files.messages(), self.tx.subscribe())
+    }
+
+    /// Wait for the next change
+    ///
+    /// A client that fell behind gets all surfaces again. `None` once the
+    /// server shuts down.
+    async fn next_update(
+        &self,
+        rx: &mut broadcast::Receiver<Vec<A2uiMessage>>,
+    ) -> Option<Vec<A2uiMessage>> {
+        match rx.recv().await {
+            Ok(update) => Some(update),
+            Err(broadcast::error::RecvError::Lagged(_)) => {
+                let (messages, resubscribed) = self.subscribe().await;
+                *rx = resubscribed;
+                Some(messages)
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+}
 
-        let modified = metadata.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+/// Start watching `dir`, returning the watcher (which must be kept) and its events
+fn watch_dir(
+    dir: &Path,
+) -> notify::Result<(
+    RecommendedWatcher,
+    mpsc::UnboundedReceiver<notify::Result<notify::Event>>,
+)> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok((watcher, rx))
+}
 
-        if modified > last_modified {
-            // Read file content
-            let content = match fs::read_to_string(path).await {
-                Ok(c) => c,
-                Err(e) => {
-                    eprintln!("[Watcher] Error reading file: {}", e);
-                    continue;
-                }
-            };
+/// Reload the surface files named by file system events
+async fn apply_changes(
+    state: Arc<WatchState>,
+    mut events: mpsc::UnboundedReceiver<notify::Result<notify::Event>>,
+) {
+    let collect =
+        |event: notify::Result<notify::Event>, changed: &mut BTreeSet<PathBuf>| match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => changed.extend(
+                event
+                    .paths
+                    .into_iter()
+                    .filter(|path| surface_files::surface_id_of(path).is_some()),
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!("[Watcher] Watch error: {}", e),
+        };
 
-            // Only broadcast if content actually changed
-            if content != last_content {
-                println!("[Watcher] File changed, broadcasting update...");
+    while let Some(event) = events.recv().await {
+        let mut changed = BTreeSet::new();
+        collect(event, &mut changed);
 
-                // Validate JSON
-                match serde_json::from_str::<serde_json::Value>(&content) {
-                    Ok(_) => {
-                        let _ = tx.send(content.clone());
-                        last_content = content;
-                        println!("[Watcher] Update broadcast complete");
-                    }
-                    Err(e) => {
-                        eprintln!("[Watcher] Invalid JSON: {}", e);
-                    }
-                }
+        let debounce = tokio::time::sleep(DEBOUNCE);
+        tokio::pin!(debounce);
+        loop {
+            tokio::select! {
+                Some(event) = events.recv() => collect(event, &mut changed),
+                _ = &mut debounce => break,
             }
+        }
 
-            last_modified = modified;
+        for path in changed {
+            state.reload(&path).await;
         }
     }
 }
 
-/// Convert JSON array to SSE messages
-fn json_to_sse(json_content: &str) -> String {
-    let mut sse_body = String::new();
-
-    // Parse as array of A2UI messages
-    let messages: Vec<serde_json::Value> = match serde_json::from_str(json_content) {
-        Ok(m) => m,
-        Err(_) => {
-            // Try as single message
-            if let Ok(single) = serde_json::from_str::<serde_json::Value>(json_content) {
-                vec![single]
-            } else {
-                return sse_body;
+/// A2A event of a task carrying an A2UI message
+fn event_json(task_id: &str, message: &A2uiMessage) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "result": {
+            "kind": "event",
+            "taskId": task_id,
+            "data": message
+        }
+    })
+}
+
+/// SSE events of A2UI messages
+fn to_sse(task_id: &str, messages: &[A2uiMessage]) -> String {
+    messages
+        .iter()
+        .map(|message| format!("data: {}\n\n", event_json(task_id, message)))
+        .collect()
+}
+
+type Body = BoxBody<Bytes, Infallible>;
+
+fn full(body: impl Into<Bytes>) -> Body {
+    Full::new(body.into()).boxed()
+}
+
+/// SSE body sending `initial`, then every change as events of `task_id`
+/// until the client goes away
+fn sse_body(
+    state: Arc<WatchState>,
+    task_id: String,
+    initial: String,
+    rx: broadcast::Receiver<Vec<A2uiMessage>>,
+) -> Body {
+    let first = futures_util::stream::iter(
+        (!initial.is_empty()).then(|| Ok(Frame::data(Bytes::from(initial)))),
+    );
+    let updates = futures_util::stream::unfold((state, rx), move |(state, mut rx)| {
+        let task_id = task_id.clone();
+        async move {
+            let update = state.next_update(&mut rx).await?;
+            println!("[Server] Sending live update over SSE");
+            let frame = Frame::data(Bytes::from(to_sse(&task_id, &update)));
+            Some((Ok(frame), (state, rx)))
+        }
+    });
+    BodyExt::boxed(StreamBody::new(first.chain(updates)))
+}
+
+/// Send the current surfaces and every change over a WebSocket
+///
+/// Each A2UI message goes out as one text frame.
+async fn serve_websocket(mut socket: WebSocketStream<TokioIo<Upgraded>>, state: Arc<WatchState>) {
+    println!("[Server] WebSocket client connected");
+
+    let (mut messages, mut rx) = state.subscribe().await;
+    'serve: loop {
+        for msg in &messages {
+            let text = serde_json::to_string(msg).unwrap_or_default();
+            if socket.send(Message::Text(text)).await.is_err() {
+                break 'serve;
             }
         }
-    };
-
-    // Wrap each message in JSON-RPC format for A2A protocol
-    for (i, msg) in messages.iter().enumerate() {
-        let wrapped = serde_json::json!({
-            "jsonrpc": "2.0",
-            "result": {
-                "kind": "event",
-                "taskId": "live-task",
-                "data": msg
+
+        // Wait for the next change, reading client frames meanwhile
+        messages = loop {
+            tokio::select! {
+                update = state.next_update(&mut rx) => match update {
+                    Some(update) => {
+                        println!("[Server] Sending live update over WebSocket");
+                        break update;
+                    }
+                    None => break 'serve,
+                },
+                frame = socket.next() => match frame {
+                    Some(Ok(Message::Text(text))) => {
+                        println!("[Server] Received from client: {}", text);
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break 'serve,
+                    // Pings are answered by tungstenite
+                    Some(Ok(_)) => {}
+                },
             }
-        });
-        let data = serde_json::to_string(&wrapped).unwrap();
-        sse_body.push_str(&format!("data: {}\n\n", data));
-        println!("[Server] Sending message {}", i + 1);
+        };
     }
 
-    sse_body
+    println!("[Server] WebSocket client disconnected");
 }
 
 /// Handle incoming HTTP requests
 async fn handle_request(
     req: Request<Incoming>,
-    tx: broadcast::Sender<String>,
-) -> Result<Response<Full<Bytes>>, Infallible> {
+    state: Arc<WatchState>,
+) -> Result<Response<Body>, Infallible> {
     match (req.method(), req.uri().path()) {
         // CORS preflight
         (&Method::OPTIONS, _) => {
@@ -124,37 +266,43 @@ async fn handle_request(
                 .status(StatusCode::OK)
                 .header("Access-Control-Allow-Origin", "*")
                 .header("Access-Control-Allow-Methods", "POST, GET, OPTIONS")
-                .header("Access-Control-Allow-Headers", "Content-Type, Accept, Authorization")
-                .body(Full::new(Bytes::new()))
+                .header(
+                    "Access-Control-Allow-Headers",
+                    "Content-Type, Accept, Authorization",
+                )
+                .body(full(Bytes::new()))
                 .unwrap();
             Ok(response)
         }
 
-        // Main RPC endpoint - initial load + subscribe to changes
+        // Main RPC endpoint - current surfaces, then their changes
         (&Method::POST, "/rpc") => {
             println!("[Server] Client connected, sending current UI...");
 
-            // Read current file content
-            let content = fs::read_to_string(JSON_FILE).await.unwrap_or_else(|_| {
-                // Default empty UI if file doesn't exist
-                r#"[{"beginRendering": {"surfaceId": "main", "root": "root"}}]"#.to_string()
-            });
+            // Each connection is a task of its own, in the client's context if it sent one
+            let body = req.into_body().collect().await.map(|b| b.to_bytes()).unwrap_or_default();
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            let connection = state.next_connection();
+            let task_id = format!("live-task-{}", connection);
+            let context_id = request["params"]["message"]["contextId"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("live-ctx-{}", connection));
+
+            let (messages, rx) = state.subscribe().await;
 
             // Send task started
-            let mut sse_body = String::new();
             let task_start = serde_json::json!({
                 "jsonrpc": "2.0",
+                "id": request["id"],
                 "result": {
                     "kind": "task",
-                    "id": "live-task",
-                    "contextId": "live-ctx",
-                    "status": {"state": "running"}
+                    "id": task_id,
+                    "contextId": context_id,
+                    "status": {"state": "working"}
                 }
             });
-            sse_body.push_str(&format!("data: {}\n\n", serde_json::to_string(&task_start).unwrap()));
-
-            // Send current content
-            sse_body.push_str(&json_to_sse(&content));
+            let initial = format!("data: {}\n\n{}", task_start, to_sse(&task_id, &messages));
 
             let response = Response::builder()
                 .status(StatusCode::OK)
@@ -162,59 +310,85 @@ async fn handle_request(
                 .header("Cache-Control", "no-cache")
                 .header("Connection", "keep-alive")
                 .header("Access-Control-Allow-Origin", "*")
-                .body(Full::new(Bytes::from(sse_body)))
+                .body(sse_body(state.clone(), task_id, initial, rx))
                 .unwrap();
 
             Ok(response)
         }
 
-        // SSE endpoint for live updates (long-polling style)
+        // SSE endpoint for live updates (changes only)
         (&Method::GET, "/live") => {
             println!("[Server] Live update client connected, waiting for changes...");
 
-            let mut rx = tx.subscribe();
-
-            // Wait for next update (with timeout)
-            let sse_body = match tokio::time::timeout(
-                tokio::time::Duration::from_secs(30),
-                rx.recv()
-            ).await {
-                Ok(Ok(content)) => {
-                    println!("[Server] Sending live update to client");
-                    json_to_sse(&content)
-                }
-                _ => {
-                    // Timeout - send keepalive
-                    "data: {\"keepalive\": true}\n\n".to_string()
-                }
-            };
-
+            let rx = state.tx.subscribe();
+            let task_id = format!("live-task-{}", state.next_connection());
             let response = Response::builder()
                 .status(StatusCode::OK)
                 .header("Content-Type", "text/event-stream")
                 .header("Cache-Control", "no-cache")
                 .header("Access-Control-Allow-Origin", "*")
-                .body(Full::new(Bytes::from(sse_body)))
+                .body(sse_body(state.clone(), task_id, String::new(), rx))
                 .unwrap();
 
             Ok(response)
         }
 
+        // WebSocket endpoint for live updates
+        (&Method::GET, "/ws") => {
+            let Some(key) = req.headers().get("Sec-WebSocket-Key") else {
+                let response = Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(full("Expected a WebSocket upgrade"))
+                    .unwrap();
+                return Ok(response);
+            };
+            let accept = derive_accept_key(key.as_bytes());
+
+            tokio::task::spawn(async move {
+                match hyper::upgrade::on(req).await {
+                    Ok(upgraded) => {
+                        let socket = WebSocketStream::from_raw_socket(
+                            TokioIo::new(upgraded),
+                            Role::Server,
+                            None,
+                        )
+                        .await;
+                        serve_websocket(socket, state).await;
+                    }
+                    Err(e) => eprintln!("[Server] WebSocket upgrade failed: {}", e),
+                }
+            });
+
+            let response = Response::builder()
+                .status(StatusCode::SWITCHING_PROTOCOLS)
+                .header("Connection", "Upgrade")
+                .header("Upgrade", "websocket")
+                .header("Sec-WebSocket-Accept", accept)
+                .body(full(Bytes::new()))
+                .unwrap();
+            Ok(response)
+        }
+
         // Status endpoint
         (&Method::GET, "/status") => {
+            let files = state.files.read().await;
             let status = serde_json::json!({
                 "status": "running",
-                "watching": JSON_FILE,
+                "watching": state.dir.display().to_string(),
+                "surfaces": files.surface_ids().collect::<Vec<_>>(),
+                "errors": files.errors(),
+                "clients": state.tx.receiver_count(),
                 "endpoints": {
-                    "POST /rpc": "Initial UI load (A2A protocol)",
-                    "GET /live": "Live updates (SSE)"
+                    "POST /rpc": "Current surfaces, then live updates (A2A protocol)",
+                    "GET /live": "Live updates (SSE)",
+                    "GET /ws": "Current surfaces, then live updates (WebSocket)"
                 }
             });
 
             let response = Response::builder()
                 .status(StatusCode::OK)
                 .header("Content-Type", "application/json")
-                .body(Full::new(Bytes::from(serde_json::to_string_pretty(&status).unwrap())))
+                .body(full(serde_json::to_string_pretty(&status).unwrap()))
                 .unwrap();
             Ok(response)
         }
@@ -223,7 +397,7 @@ async fn handle_request(
         _ => {
             let response = Response::builder()
                 .status(StatusCode::NOT_FOUND)
-                .body(Full::new(Bytes::from("Not Found")))
+                .body(full("Not Found"))
                 .unwrap();
             Ok(response)
         }
@@ -232,49 +406,68 @@ async fn handle_request(
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
-    let listener = TcpListener::bind(addr).await?;
+    let dir = std::env::args()
+        .nth(1)
+        .or_else(|| std::env::var("WATCH_DIR").ok())
+        .unwrap_or_else(|| ".".to_string());
+    let state = Arc::new(WatchState::new(PathBuf::from(&dir)));
 
-    // Create broadcast channel for file changes
-    let (tx, _) = broadcast::channel::<String>(16);
-    let tx_clone = tx.clone();
+    // Watch before the first scan so no change falls in between
+    let (_watcher, events) = watch_dir(&state.dir)?;
+    state.files.write().await.scan(&state.dir);
+    tokio::spawn(apply_changes(state.clone(), events));
 
-    // Start file watcher
-    tokio::spawn(async move {
-        watch_file(tx_clone).await;
-    });
+    let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
+    let listener = TcpListener::bind(addr).await?;
 
     println!("===========================================");
     println!("  A2UI Live Server - File Watcher Mode");
     println!("===========================================");
     println!();
-    println!("Watching: {}", JSON_FILE);
+    println!("Watching: {}/*.a2ui.json", dir);
+    println!(
+        "Surfaces: {}",
+        state
+            .files
+            .read()
+            .await
+            .surface_ids()
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
     println!("Server:   http://{}", addr);
     println!();
     println!("Endpoints:");
-    println!("  POST /rpc  - A2A protocol (initial load)");
+    println!("  POST /rpc  - A2A protocol (current UI, then live updates)");
     println!("  GET /live  - Live updates (SSE)");
+    println!("  GET /ws    - Live updates (WebSocket)");
     println!("  GET /status - Server status");
     println!();
-    println!("Edit {} to update the UI!", JSON_FILE);
+    println!("Edit a <surface>.a2ui.json file to update the UI!");
     println!("Press Ctrl+C to stop");
     println!();
 
+    serve(listener, state).await
+}
+
+/// Accept connections until the listener fails
+async fn serve(
+    listener: TcpListener,
+    state: Arc<WatchState>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     loop {
         let (stream, remote_addr) = listener.accept().await?;
         println!("[Server] Connection from {}", remote_addr);
 
         let io = TokioIo::new(stream);
-        let tx = tx.clone();
+        let state = state.clone();
 
         tokio::task::spawn(async move {
-            let service = service_fn(move |req| {
-                let tx = tx.clone();
-                async move { handle_request(req, tx).await }
-            });
+            let service = service_fn(move |req| handle_request(req, state.clone()));
 
             if let Err(err) = http1::Builder::new()
                 .serve_connection(io, service)
+                .with_upgrades()
                 .await
             {
                 eprintln!("[Server] Connection error: {:?}", err);
@@ -282,3 +475,92 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+
+    /// Read SSE events from a stream until `count` A2UI messages arrived
+    fn read_messages(reader: &mut impl BufRead, count: usize) -> Vec<serde_json::Value> {
+        let mut messages = Vec::new();
+        let mut line = String::new();
+        while messages.len() < count {
+            line.clear();
+            assert!(reader.read_line(&mut line).unwrap() > 0, "stream ended");
+            let Some(data) = line.trim_end().strip_prefix("data: ") else {
+                continue;
+            };
+            let event: serde_json::Value = serde_json::from_str(data).unwrap();
+            if event["result"]["kind"] == "event" {
+                messages.push(event["result"]["data"].clone());
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn test_file_changes_stream_as_diffs() {
+        let dir = std::env::temp_dir().join(format!("watch_server_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("main.a2ui.json");
+        std::fs::write(
+            &file,
+            r#"[{"beginRendering": {"root": "root"}},
+                {"surfaceUpdate": {"components": [
+                    {"id": "root", "component": {"Text": {"text": {"literalString": "One"}}}}]}}]"#,
+        )
+        .unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let state = Arc::new(WatchState::new(dir.clone()));
+        let port = runtime.block_on(async {
+            let (watcher, events) = watch_dir(&dir).unwrap();
+            state.files.write().await.scan(&dir);
+            let watched = state.clone();
+            tokio::spawn(async move {
+                let _watcher = watcher;
+                apply_changes(watched, events).await;
+            });
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            tokio::spawn(serve(listener, state));
+            port
+        });
+
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        write!(
+            stream,
+            "POST /rpc HTTP/1.1\r\nHost: localhost\r\nContent-Length: 2\r\n\r\n{{}}"
+        )
+        .unwrap();
+        let mut reader = BufReader::new(stream);
+        let initial = read_messages(&mut reader, 2);
+        assert_eq!(initial[0]["beginRendering"]["surfaceId"], "main");
+
+        // An edit sends just the changed component
+        let edited = std::fs::read_to_string(&file)
+            .unwrap()
+            .replace("One", "Two");
+        std::fs::write(&file, edited).unwrap();
+        let update = read_messages(&mut reader, 1);
+        let components = &update[0]["surfaceUpdate"]["components"];
+        assert_eq!(
+            components[0]["component"]["Text"]["text"]["literalString"],
+            "Two"
+        );
+
+        // A broken file shows the error in its place
+        std::fs::write(&file, "[{").unwrap();
+        let update = read_messages(&mut reader, 2);
+        assert_eq!(update[0]["beginRendering"]["root"], "a2ui-file-error");
+        assert!(update[1].to_string().contains("Invalid JSON"));
+
+        drop(reader);
+        runtime.shutdown_background();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
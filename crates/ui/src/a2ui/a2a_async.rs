@@ -0,0 +1,438 @@
+//! Async A2A Client
+//!
+//! Non-blocking counterpart of `A2aClient` for services running on an async
+//! runtime (enabled with the `async-client` feature). Requests go through
+//! `reqwest` and streaming responses are exposed as a
+//! `Stream<Item = A2aStreamEvent>`, so no thread is spawned per stream.
+//!
+//! Request building and event parsing are shared with the blocking client.
+//!
+//! ```rust,ignore
+//! use futures_util::StreamExt;
+//!
+//! let mut client = AsyncA2aClient::new("http://localhost:8080/rpc");
+//! let mut stream = client.message_stream("Show me a greeting UI").await?;
+//! while let Some(event) = stream.next().await {
+//!     println!("{:?}", event);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::a2a_client::{
+    action_data, parse_call_response, A2aClient, A2aStreamEvent, A2aTask, EventDecoder,
+    PushNotificationConfig, TaskIdParams, TaskPushNotificationConfig, A2UI_EXTENSION_URI,
+};
+use super::a2a_part::MessageInput;
+use super::agent_card::{card_urls, AgentCard, AGENT_CARD_TIMEOUT};
+use super::data_sync::DataModelChange;
+use super::sse::{SseEvent, SseParser};
+
+/// Async A2A client for communicating with agents
+pub struct AsyncA2aClient {
+    /// Request state (IDs, task, context, auth) shared with the blocking client
+    inner: A2aClient,
+    http: reqwest::Client,
+}
+
+impl AsyncA2aClient {
+    /// Create a new async A2A client
+    pub fn new(url: impl Into<String>) -> Self {
+        AsyncA2aClient {
+            inner: A2aClient::new(url),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Create a client for an agent by fetching its agent card
+    ///
+    /// See `A2aClient::discover`.
+    pub async fn discover(url: &str) -> Result<Self, String> {
+        let http = reqwest::Client::new();
+        let card = fetch_agent_card(&http, url).await?;
+        Ok(Self::from_agent_card(card)?.with_http_client(http))
+    }
+
+    /// Create a client from an agent card, using its JSON-RPC endpoint
+    pub fn from_agent_card(card: AgentCard) -> Result<Self, String> {
+        Ok(AsyncA2aClient {
+            inner: A2aClient::from_agent_card(card)?,
+            http: reqwest::Client::new(),
+        })
+    }
+
+    /// Use an existing HTTP client (e.g., to share its connection pool)
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Set authentication token
+    pub fn with_auth(mut self, token: impl Into<String>) -> Self {
+        self.inner = self.inner.with_auth(token);
+        self
+    }
+
+    /// Get the agent card the client was created from
+    pub fn agent_card(&self) -> Option<&AgentCard> {
+        self.inner.agent_card()
+    }
+
+    /// Get current task ID
+    pub fn task_id(&self) -> Option<String> {
+        self.inner.task_id()
+    }
+
+    /// Get current context ID
+    pub fn context_id(&self) -> Option<&str> {
+        self.inner.context_id()
+    }
+
+    /// Update task ID from received event
+    pub fn set_task_id(&mut self, task_id: impl Into<String>) {
+        self.inner.set_task_id(task_id);
+    }
+
+    /// Send a message and receive streaming A2UI updates
+    pub async fn message_stream(
+        &mut self,
+        message: impl Into<MessageInput>,
+    ) -> Result<A2aAsyncEventStream, String> {
+        let body = self.inner.message_stream_body(message.into())?;
+        self.stream(body).await
+    }
+
+    /// Resume streaming updates of an existing task
+    pub async fn resubscribe(&mut self, task_id: &str) -> Result<A2aAsyncEventStream, String> {
+        let body = self.inner.resubscribe_body(task_id)?;
+        self.stream(body).await
+    }
+
+    /// Get the current state of a task
+    pub async fn get_task(
+        &mut self,
+        task_id: &str,
+        history_length: Option<u32>,
+    ) -> Result<A2aTask, String> {
+        let params = TaskIdParams {
+            history_length,
+            ..TaskIdParams::new(task_id)
+        };
+        self.call("tasks/get", params).await
+    }
+
+    /// Cancel a task
+    pub async fn cancel_task(&mut self, task_id: &str) -> Result<A2aTask, String> {
+        self.call("tasks/cancel", TaskIdParams::new(task_id)).await
+    }
+
+    /// Ask the agent to post task updates to a webhook
+    pub async fn set_push_notification_config(
+        &mut self,
+        task_id: &str,
+        config: PushNotificationConfig,
+    ) -> Result<TaskPushNotificationConfig, String> {
+        let params = TaskPushNotificationConfig {
+            task_id: task_id.to_string(),
+            push_notification_config: config,
+        };
+        self.call("tasks/pushNotificationConfig/set", params).await
+    }
+
+    /// Get a push notification config of a task (the default one if `config_id` is `None`)
+    pub async fn get_push_notification_config(
+        &mut self,
+        task_id: &str,
+        config_id: Option<&str>,
+    ) -> Result<TaskPushNotificationConfig, String> {
+        let params = TaskIdParams {
+            push_notification_config_id: config_id.map(str::to_string),
+            ..TaskIdParams::new(task_id)
+        };
+        self.call("tasks/pushNotificationConfig/get", params).await
+    }
+
+    /// List the push notification configs of a task
+    pub async fn list_push_notification_configs(
+        &mut self,
+        task_id: &str,
+    ) -> Result<Vec<TaskPushNotificationConfig>, String> {
+        self.call(
+            "tasks/pushNotificationConfig/list",
+            TaskIdParams::new(task_id),
+        )
+        .await
+    }
+
+    /// Delete a push notification config of a task
+    pub async fn delete_push_notification_config(
+        &mut self,
+        task_id: &str,
+        config_id: &str,
+    ) -> Result<(), String> {
+        let params = TaskIdParams {
+            push_notification_config_id: Some(config_id.to_string()),
+            ..TaskIdParams::new(task_id)
+        };
+        self.call::<_, Value>("tasks/pushNotificationConfig/delete", params)
+            .await
+            .map(|_| ())
+    }
+
+    /// Send a user action back to the agent
+    pub async fn send_action(
+        &mut self,
+        action_name: &str,
+        source_component_id: &str,
+        context: HashMap<String, Value>,
+    ) -> Result<(), String> {
+        self.send_data(action_data(action_name, source_component_id, context))
+            .await
+            .map_err(|e| format!("Failed to send action: {}", e))
+    }
+
+    /// Send a batch of local data model edits to the agent (two-way data sync)
+    pub async fn send_data_change(&mut self, change: &DataModelChange) -> Result<(), String> {
+        self.send_data(serde_json::json!({ "a2uiDataChange": change }))
+            .await
+            .map_err(|e| format!("Failed to send data change: {}", e))
+    }
+
+    async fn send_data(&mut self, data: Value) -> Result<(), String> {
+        let body = self.inner.send_data_body(data)?;
+        self.post(body, "application/json").await?;
+        Ok(())
+    }
+
+    /// Post a JSON-RPC request body
+    async fn post(&self, body: String, accept: &str) -> Result<reqwest::Response, String> {
+        let mut request = self
+            .http
+            .post(self.inner.url())
+            .header("Content-Type", "application/json")
+            .header("Accept", accept)
+            .header("X-A2A-Extensions", A2UI_EXTENSION_URI)
+            .body(body);
+
+        if let Some(token) = self.inner.auth_token() {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+        Ok(response)
+    }
+
+    /// Send a non-streaming request and parse the JSON-RPC result
+    async fn call<P: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: P,
+    ) -> Result<R, String> {
+        let body = self.inner.request_body(method, params)?;
+        let text = self
+            .post(body, "application/json")
+            .await?
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read {} response: {}", method, e))?;
+        parse_call_response(method, &text)
+    }
+
+    /// Send a streaming request
+    async fn stream(&mut self, body: String) -> Result<A2aAsyncEventStream, String> {
+        let response = self
+            .post(body, "text/event-stream")
+            .await
+            .map_err(|e| format!("HTTP POST failed: {}", e))?;
+
+        let chunks = response
+            .bytes_stream()
+            .map(|chunk| chunk.map(|bytes| bytes.to_vec()).map_err(|e| e.to_string()))
+            .boxed();
+        Ok(A2aAsyncEventStream::new(chunks, self.inner.event_decoder()))
+    }
+}
+
+/// Fetch an agent card without blocking (see `AgentCard::fetch`)
+async fn fetch_agent_card(http: &reqwest::Client, url: &str) -> Result<AgentCard, String> {
+    let mut errors = Vec::new();
+    for card_url in card_urls(url) {
+        let response = http
+            .get(&card_url)
+            .header("Accept", "application/json")
+            .timeout(AGENT_CARD_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match response {
+            Ok(response) => {
+                let body = response
+                    .text()
+                    .await
+                    .map_err(|e| format!("Failed to read {}: {}", card_url, e))?;
+                return AgentCard::from_json(&body);
+            }
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    Err(format!("No agent card found ({})", errors.join("; ")))
+}
+
+/// Async stream of A2A events
+///
+/// Ends when the agent closes the response. Read errors are yielded as
+/// `A2aStreamEvent::Error` and end the stream.
+pub struct A2aAsyncEventStream {
+    chunks: BoxStream<'static, Result<Vec<u8>, String>>,
+    /// Bytes of an incomplete line
+    buffer: Vec<u8>,
+    parser: SseParser,
+    decoder: EventDecoder,
+    done: bool,
+}
+
+impl A2aAsyncEventStream {
+    fn new(chunks: BoxStream<'static, Result<Vec<u8>, String>>, decoder: EventDecoder) -> Self {
+        A2aAsyncEventStream {
+            chunks,
+            buffer: Vec::new(),
+            parser: SseParser::new(),
+            decoder,
+            done: false,
+        }
+    }
+
+    /// Get current task ID
+    pub fn task_id(&self) -> Option<&str> {
+        self.decoder.task_id()
+    }
+
+    /// Feed the complete lines of the buffer to the SSE parser
+    fn parse_lines(&mut self) {
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            self.parse_line(line.trim_end_matches(['\n', '\r']));
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        if let Some(SseEvent::Data(data)) = self.parser.parse_line(line) {
+            self.decoder.decode(&data);
+        }
+    }
+
+    /// Parse what is left when the response ends
+    fn finish(&mut self) {
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            self.parse_line(line.trim_end_matches('\r'));
+        }
+        if let Some(SseEvent::Data(data)) = self.parser.flush() {
+            self.decoder.decode(&data);
+        }
+        self.done = true;
+    }
+}
+
+impl Stream for A2aAsyncEventStream {
+    type Item = A2aStreamEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(event) = this.decoder.next_event() {
+                return Poll::Ready(Some(event));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            match this.chunks.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    this.buffer.extend_from_slice(&chunk);
+                    this.parse_lines();
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    this.done = true;
+                    return Poll::Ready(Some(A2aStreamEvent::Error(format!("Read error: {}", e))));
+                }
+                Poll::Ready(None) => this.finish(),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::TaskState;
+    use futures_util::stream;
+    use futures_util::task::noop_waker;
+
+    fn collect(mut stream: A2aAsyncEventStream) -> Vec<A2aStreamEvent> {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut events = Vec::new();
+        while let Poll::Ready(Some(event)) = stream.poll_next_unpin(&mut cx) {
+            events.push(event);
+        }
+        events
+    }
+
+    fn stream_of(chunks: Vec<Result<&'static str, String>>) -> A2aAsyncEventStream {
+        let chunks = stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| chunk.map(|text| text.as_bytes().to_vec())),
+        )
+        .boxed();
+        A2aAsyncEventStream::new(chunks, A2aClient::new("http://localhost").event_decoder())
+    }
+
+    #[test]
+    fn test_events_split_across_chunks() {
+        let stream = stream_of(vec![
+            Ok(": ping\r\ndata: {\"jsonrpc\": \"2.0\", \"result\": {\"kind\": \"task\", "),
+            Ok("\"id\": \"t1\", \"status\": {\"state\": \"working\"}}}\r\n\r\ndata: {\"jsonrpc\": \"2.0\", "),
+            Ok("\"result\": {\"kind\": \"message\", \"parts\": [{\"kind\": \"text\", \"text\": \"Hi\"}]}}"),
+        ]);
+
+        let events = collect(stream);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            A2aStreamEvent::TaskStatus { task_id, state: TaskState::Working } if task_id == "t1"
+        ));
+        // The last event has no trailing blank line and is flushed at the end
+        assert!(matches!(&events[1], A2aStreamEvent::Text(text) if text == "Hi"));
+    }
+
+    #[test]
+    fn test_read_error_ends_stream() {
+        let stream = stream_of(vec![
+            Ok("data: {\"jsonrpc\": \"2.0\", \"result\": {\"kind\": \"event\", \"data\": \n"),
+            Ok("data: {\"deleteSurface\": {\"surfaceId\": \"main\"}}}}\n\n"),
+            Err("connection reset".to_string()),
+            Ok("data: {}\n\n"),
+        ]);
+
+        let events = collect(stream);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], A2aStreamEvent::A2uiMessage(_)));
+        assert!(
+            matches!(&events[1], A2aStreamEvent::Error(e) if e == "Read error: connection reset")
+        );
+    }
+}
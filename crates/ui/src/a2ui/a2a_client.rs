@@ -1,17 +1,30 @@
 //! A2A (Agent-to-Agent) Client
 //!
 //! Implements the A2A JSON-RPC protocol for communicating with A2UI agents.
-//! Uses SSE streaming for receiving progressive UI updates.
+//! Uses SSE streaming for receiving progressive UI updates, or a single
+//! WebSocket for both directions (see `connect_websocket`, enabled with the
+//! `websocket` feature).
+//!
+//! Supported methods: `message/stream`, `message/send`, `tasks/get`,
+//! `tasks/cancel`, `tasks/resubscribe` and `tasks/pushNotificationConfig/*`.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
 
+use super::a2a_part::{MessageInput, Part};
+use super::agent_card::AgentCard;
+use super::data_sync::DataModelChange;
 use super::message::A2uiMessage;
 use super::sse::{SseClient, SseEvent};
+#[cfg(feature = "websocket")]
+use super::websocket::{WebSocketConnection, WebSocketTransport};
 
 /// A2A extension URI for A2UI protocol
 pub const A2UI_EXTENSION_URI: &str = "https://a2ui.org/a2a-extension/a2ui/v0.8";
@@ -21,8 +34,13 @@ pub struct A2aClient {
     url: String,
     auth_token: Option<String>,
     request_id: u64,
-    task_id: Option<String>,
+    /// Shared with event streams, which record the task the agent created
+    task_id: Arc<Mutex<Option<String>>>,
     context_id: Option<String>,
+    agent_card: Option<AgentCard>,
+    /// Socket carrying requests and responses instead of HTTP
+    #[cfg(feature = "websocket")]
+    websocket: Option<WebSocketConnection>,
 }
 
 impl A2aClient {
@@ -32,20 +50,61 @@ impl A2aClient {
             url: url.into(),
             auth_token: None,
             request_id: 1,
-            task_id: None,
+            task_id: Arc::new(Mutex::new(None)),
             context_id: None,
+            agent_card: None,
+            #[cfg(feature = "websocket")]
+            websocket: None,
         }
     }
 
+    /// Create a client for an agent by fetching its agent card
+    ///
+    /// `url` is the agent's base URL (or the URL of its card). Fails if the
+    /// agent doesn't support the A2UI extension or a compatible protocol version.
+    pub fn discover(url: &str) -> Result<Self, String> {
+        Self::from_agent_card(AgentCard::fetch(url)?)
+    }
+
+    /// Create a client from an agent card, using its JSON-RPC endpoint
+    pub fn from_agent_card(card: AgentCard) -> Result<Self, String> {
+        let mut client = Self::new(card.negotiate()?);
+        client.agent_card = Some(card);
+        Ok(client)
+    }
+
+    /// Get the agent card the client was created from
+    pub fn agent_card(&self) -> Option<&AgentCard> {
+        self.agent_card.as_ref()
+    }
+
     /// Set authentication token
     pub fn with_auth(mut self, token: impl Into<String>) -> Self {
         self.auth_token = Some(token.into());
         self
     }
 
+    /// Send requests over a WebSocket to the client URL instead of HTTP
+    ///
+    /// Streaming requests and user actions then share the socket. Requests
+    /// that wait for a single response (`get_task`, `cancel_task`, push
+    /// notification configs) are not available over WebSocket.
+    #[cfg(feature = "websocket")]
+    pub fn connect_websocket(&mut self) -> Result<(), String> {
+        let mut transport =
+            WebSocketTransport::new(&self.url).header("X-A2A-Extensions", A2UI_EXTENSION_URI);
+        if let Some(token) = &self.auth_token {
+            transport = transport.auth(token);
+        }
+        self.websocket = Some(transport.connect()?);
+        Ok(())
+    }
+
     /// Get current task ID
-    pub fn task_id(&self) -> Option<&str> {
-        self.task_id.as_deref()
+    ///
+    /// Set automatically when a stream started by this client reports a task.
+    pub fn task_id(&self) -> Option<String> {
+        self.task_id.lock().unwrap().clone()
     }
 
     /// Get current context ID
@@ -54,54 +113,127 @@ impl A2aClient {
     }
 
     /// Send a message and receive streaming A2UI updates
-    pub fn message_stream(&mut self, content: &str) -> Result<A2aEventStream, String> {
+    ///
+    /// Accepts plain text or a `MessageInput` with several parts (e.g., text
+    /// and an image) and metadata.
+    pub fn message_stream(
+        &mut self,
+        message: impl Into<MessageInput>,
+    ) -> Result<A2aEventStream, String> {
+        let body = self.message_stream_body(message.into())?;
+        self.stream(body)
+    }
+
+    /// Resume streaming updates of an existing task (e.g., after the connection dropped)
+    pub fn resubscribe(&mut self, task_id: &str) -> Result<A2aEventStream, String> {
+        let body = self.resubscribe_body(task_id)?;
+        self.stream(body)
+    }
+
+    /// Build a `message/stream` request, starting a new context if needed
+    pub(crate) fn message_stream_body(&mut self, message: MessageInput) -> Result<String, String> {
         let message_id = Uuid::new_v4().to_string();
         let context_id = self
             .context_id
             .clone()
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
-        // Build JSON-RPC request
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            method: "message/stream".to_string(),
-            params: MessageParams {
-                configuration: None,
-                metadata: None,
-                message: Message {
-                    message_id,
-                    role: "user".to_string(),
-                    parts: vec![Part::Text {
-                        text: content.to_string(),
-                    }],
-                    context_id: context_id.clone(),
-                    extensions: vec![A2UI_EXTENSION_URI.to_string()],
-                },
+        let params = MessageParams {
+            configuration: None,
+            metadata: None,
+            message: Message {
+                message_id,
+                role: "user".to_string(),
+                parts: message.parts,
+                context_id: context_id.clone(),
+                extensions: vec![A2UI_EXTENSION_URI.to_string()],
+                metadata: message.metadata,
             },
-            id: self.request_id,
         };
 
-        self.request_id += 1;
         self.context_id = Some(context_id);
+        self.request_body("message/stream", params)
+    }
 
-        let body = serde_json::to_string(&request)
-            .map_err(|e| format!("Failed to serialize request: {}", e))?;
+    /// Build a `tasks/resubscribe` request and make the task current
+    pub(crate) fn resubscribe_body(&mut self, task_id: &str) -> Result<String, String> {
+        *self.task_id.lock().unwrap() = Some(task_id.to_string());
+        self.request_body("tasks/resubscribe", TaskIdParams::new(task_id))
+    }
 
-        // Build SSE client
-        let mut client = SseClient::new(&self.url)
-            .header("X-A2A-Extensions", A2UI_EXTENSION_URI);
+    /// Get the current state of a task
+    ///
+    /// `history_length` limits the number of history messages returned.
+    pub fn get_task(
+        &mut self,
+        task_id: &str,
+        history_length: Option<u32>,
+    ) -> Result<A2aTask, String> {
+        let params = TaskIdParams {
+            history_length,
+            ..TaskIdParams::new(task_id)
+        };
+        self.call("tasks/get", params)
+    }
 
-        if let Some(token) = &self.auth_token {
-            client = client.auth(token);
-        }
+    /// Cancel a task
+    ///
+    /// Returns the task as reported by the agent (normally in the `canceled` state).
+    pub fn cancel_task(&mut self, task_id: &str) -> Result<A2aTask, String> {
+        self.call("tasks/cancel", TaskIdParams::new(task_id))
+    }
 
-        let rx = client.post(&body)?;
+    /// Build a `tasks/cancel` request to send later (see `cancel_task`)
+    pub(crate) fn cancel_request(&mut self, task_id: &str) -> Result<PreparedRequest, String> {
+        self.prepare_call("tasks/cancel", TaskIdParams::new(task_id))
+    }
 
-        Ok(A2aEventStream {
-            receiver: rx,
-            client_task_id: self.task_id.clone(),
-            client_context_id: self.context_id.clone(),
-        })
+    /// Ask the agent to post task updates to a webhook
+    pub fn set_push_notification_config(
+        &mut self,
+        task_id: &str,
+        config: PushNotificationConfig,
+    ) -> Result<TaskPushNotificationConfig, String> {
+        let params = TaskPushNotificationConfig {
+            task_id: task_id.to_string(),
+            push_notification_config: config,
+        };
+        self.call("tasks/pushNotificationConfig/set", params)
+    }
+
+    /// Get a push notification config of a task (the default one if `config_id` is `None`)
+    pub fn get_push_notification_config(
+        &mut self,
+        task_id: &str,
+        config_id: Option<&str>,
+    ) -> Result<TaskPushNotificationConfig, String> {
+        let params = TaskIdParams {
+            push_notification_config_id: config_id.map(str::to_string),
+            ..TaskIdParams::new(task_id)
+        };
+        self.call("tasks/pushNotificationConfig/get", params)
+    }
+
+    /// List the push notification configs of a task
+    pub fn list_push_notification_configs(
+        &mut self,
+        task_id: &str,
+    ) -> Result<Vec<TaskPushNotificationConfig>, String> {
+        self.call("tasks/pushNotificationConfig/list", TaskIdParams::new(task_id))
+    }
+
+    /// Delete a push notification config of a task
+    pub fn delete_push_notification_config(
+        &mut self,
+        task_id: &str,
+        config_id: &str,
+    ) -> Result<(), String> {
+        let params = TaskIdParams {
+            push_notification_config_id: Some(config_id.to_string()),
+            ..TaskIdParams::new(task_id)
+        };
+        self.call::<_, Value>("tasks/pushNotificationConfig/delete", params)
+            .map(|_| ())
     }
 
     /// Send a user action back to the agent
@@ -111,9 +243,54 @@ impl A2aClient {
         source_component_id: &str,
         context: HashMap<String, Value>,
     ) -> Result<(), String> {
-        let Some(task_id) = &self.task_id else {
-            return Err("No active task to send action to".to_string());
-        };
+        self.send_data(action_data(action_name, source_component_id, context))
+            .map_err(|e| format!("Failed to send action: {}", e))
+    }
+
+    /// Send a batch of local data model edits to the agent (two-way data sync)
+    pub fn send_data_change(&mut self, change: &DataModelChange) -> Result<(), String> {
+        self.data_change_request(change)
+            .and_then(PreparedRequest::send)
+            .map_err(|e| format!("Failed to send data change: {}", e))
+    }
+
+    /// Send a data part to the current task with a non-streaming request
+    fn send_data(&mut self, data: Value) -> Result<(), String> {
+        self.data_request(data)?.send()
+    }
+
+    /// Build a request sending a data part to the current task, to send later
+    ///
+    /// The request doesn't borrow the client, so it can be sent without
+    /// holding whatever guards the client.
+    fn data_request(&mut self, data: Value) -> Result<PreparedRequest, String> {
+        let body = self.send_data_body(data)?;
+        Ok(self.prepared(body))
+    }
+
+    /// Build a data change request to send later (see `send_data_change`)
+    pub(crate) fn data_change_request(
+        &mut self,
+        change: &DataModelChange,
+    ) -> Result<PreparedRequest, String> {
+        self.data_request(serde_json::json!({ "a2uiDataChange": change }))
+    }
+
+    /// Build a user action request to send later (see `send_action`)
+    pub(crate) fn action_request(
+        &mut self,
+        action_name: &str,
+        source_component_id: &str,
+        context: HashMap<String, Value>,
+    ) -> Result<PreparedRequest, String> {
+        self.data_request(action_data(action_name, source_component_id, context))
+    }
+
+    /// Build a `message/send` request with a data part for the current task
+    pub(crate) fn send_data_body(&mut self, data: Value) -> Result<String, String> {
+        if self.task_id.lock().unwrap().is_none() {
+            return Err("No active task".to_string());
+        }
 
         let Some(context_id) = &self.context_id else {
             return Err("No active context".to_string());
@@ -121,40 +298,173 @@ impl A2aClient {
 
         let message_id = Uuid::new_v4().to_string();
 
-        // Build A2UI event
-        let a2ui_event = A2uiEvent {
-            action_name: action_name.to_string(),
-            source_component_id: source_component_id.to_string(),
-            timestamp: chrono_now(),
-            resolved_context: context,
+        // Wrap in A2A message
+        let params = MessageParams {
+            configuration: None,
+            metadata: None,
+            message: Message {
+                message_id,
+                role: "user".to_string(),
+                parts: vec![Part::data(data)],
+                context_id: context_id.clone(),
+                extensions: vec![A2UI_EXTENSION_URI.to_string()],
+                metadata: None,
+            },
         };
 
-        // Wrap in A2A message
+        self.request_body("message/send", params)
+    }
+
+    /// Update task ID from received event
+    pub fn set_task_id(&mut self, task_id: impl Into<String>) {
+        *self.task_id.lock().unwrap() = Some(task_id.into());
+    }
+
+    /// Endpoint URL
+    #[cfg(feature = "async-client")]
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Authentication token set with `with_auth`
+    #[cfg(feature = "async-client")]
+    pub(crate) fn auth_token(&self) -> Option<&str> {
+        self.auth_token.as_deref()
+    }
+
+    /// Serialize a JSON-RPC request with the next request ID
+    pub(crate) fn request_body<P: Serialize>(&mut self, method: &str, params: P) -> Result<String, String> {
         let request = JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
-            method: "message/send".to_string(),
-            params: MessageParams {
-                configuration: None,
-                metadata: None,
-                message: Message {
-                    message_id,
-                    role: "user".to_string(),
-                    parts: vec![Part::Data {
-                        data: serde_json::json!({ "a2uiEvent": a2ui_event }),
-                    }],
-                    context_id: context_id.clone(),
-                    extensions: vec![A2UI_EXTENSION_URI.to_string()],
-                },
-            },
+            method: method.to_string(),
+            params,
             id: self.request_id,
         };
-
         self.request_id += 1;
 
-        let body = serde_json::to_string(&request)
-            .map_err(|e| format!("Failed to serialize request: {}", e))?;
+        serde_json::to_string(&request).map_err(|e| format!("Failed to serialize request: {}", e))
+    }
 
-        // Send non-streaming request
+    /// Wrap a request body with what's needed to send it
+    fn prepared(&self, body: String) -> PreparedRequest {
+        PreparedRequest {
+            url: self.url.clone(),
+            auth_token: self.auth_token.clone(),
+            #[cfg(feature = "websocket")]
+            websocket: self.websocket.clone(),
+            body,
+        }
+    }
+
+    /// Build a request waiting for its JSON-RPC result
+    ///
+    /// Fails over WebSocket, where the response would arrive on the event stream.
+    fn prepare_call<P: Serialize>(
+        &mut self,
+        method: &str,
+        params: P,
+    ) -> Result<PreparedRequest, String> {
+        #[cfg(feature = "websocket")]
+        if self.websocket.is_some() {
+            return Err(format!("{} is not supported over WebSocket", method));
+        }
+        let body = self.request_body(method, params)?;
+        Ok(self.prepared(body))
+    }
+
+    /// Send a non-streaming request and parse the JSON-RPC result
+    fn call<P: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: P,
+    ) -> Result<R, String> {
+        self.prepare_call(method, params)?.call(method)
+    }
+
+    /// Create the decoder for a stream started by this client
+    pub(crate) fn event_decoder(&self) -> EventDecoder {
+        EventDecoder {
+            client_task_id: self.task_id(),
+            client_context_id: self.context_id.clone(),
+            shared_task_id: Some(Arc::clone(&self.task_id)),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Send a streaming request
+    fn stream(&mut self, body: String) -> Result<A2aEventStream, String> {
+        #[cfg(feature = "websocket")]
+        if let Some(websocket) = &self.websocket {
+            let rx = websocket.subscribe();
+            websocket.send(body)?;
+            return Ok(A2aEventStream {
+                receiver: rx,
+                decoder: self.event_decoder(),
+            });
+        }
+
+        // Build SSE client
+        let mut client = SseClient::new(&self.url)
+            .header("X-A2A-Extensions", A2UI_EXTENSION_URI);
+
+        if let Some(token) = &self.auth_token {
+            client = client.auth(token);
+        }
+
+        let rx = client.post(&body)?;
+
+        Ok(A2aEventStream {
+            receiver: rx,
+            decoder: self.event_decoder(),
+        })
+    }
+}
+
+/// Serialize a user action as the data of an A2UI event
+pub(crate) fn action_data(
+    action_name: &str,
+    source_component_id: &str,
+    context: HashMap<String, Value>,
+) -> Value {
+    // Build A2UI event
+    let a2ui_event = A2uiEvent {
+        action_name: action_name.to_string(),
+        source_component_id: source_component_id.to_string(),
+        timestamp: chrono_now(),
+        resolved_context: context,
+    };
+    serde_json::json!({ "a2uiEvent": a2ui_event })
+}
+
+/// A request built by `A2aClient`, sent without borrowing the client
+pub(crate) struct PreparedRequest {
+    url: String,
+    auth_token: Option<String>,
+    #[cfg(feature = "websocket")]
+    websocket: Option<WebSocketConnection>,
+    body: String,
+}
+
+impl PreparedRequest {
+    /// Send the request, ignoring the response
+    pub(crate) fn send(self) -> Result<(), String> {
+        #[cfg(feature = "websocket")]
+        if let Some(websocket) = &self.websocket {
+            return websocket.send(self.body);
+        }
+        self.post().map(|_| ())
+    }
+
+    /// Send the request over HTTP and parse the JSON-RPC result
+    pub(crate) fn call<R: DeserializeOwned>(self, method: &str) -> Result<R, String> {
+        let text = self
+            .post()?
+            .into_string()
+            .map_err(|e| format!("Failed to read {} response: {}", method, e))?;
+        parse_call_response(method, &text)
+    }
+
+    fn post(&self) -> Result<ureq::Response, String> {
         let mut req = ureq::post(&self.url)
             .set("Content-Type", "application/json")
             .set("X-A2A-Extensions", A2UI_EXTENSION_URI);
@@ -163,58 +473,46 @@ impl A2aClient {
             req = req.set("Authorization", &format!("Bearer {}", token));
         }
 
-        req.send_string(&body)
-            .map_err(|e| format!("Failed to send action: {}", e))?;
-
-        Ok(())
+        req.send_string(&self.body).map_err(|e| e.to_string())
     }
+}
 
-    /// Update task ID from received event
-    pub fn set_task_id(&mut self, task_id: impl Into<String>) {
-        self.task_id = Some(task_id.into());
+/// Parse the JSON-RPC response of a non-streaming request
+pub(crate) fn parse_call_response<R: DeserializeOwned>(
+    method: &str,
+    text: &str,
+) -> Result<R, String> {
+    let response: JsonRpcResponse<Value> =
+        serde_json::from_str(text).map_err(|e| format!("Invalid {} response: {}", method, e))?;
+
+    if let Some(error) = response.error {
+        return Err(format!("JSON-RPC error: {} - {}", error.code, error.message));
     }
+    let result = response
+        .result
+        .ok_or_else(|| format!("Empty {} response", method))?;
+    serde_json::from_value(result).map_err(|e| format!("Invalid {} result: {}", method, e))
 }
 
 /// Stream of A2A events
 pub struct A2aEventStream {
     receiver: Receiver<SseEvent>,
-    client_task_id: Option<String>,
-    client_context_id: Option<String>,
+    decoder: EventDecoder,
 }
 
-impl A2aEventStream {
+impl Iterator for A2aEventStream {
+    type Item = A2aStreamEvent;
+
     /// Receive next A2UI message from stream
     /// Returns None when stream ends
-    pub fn next(&mut self) -> Option<A2aStreamEvent> {
+    fn next(&mut self) -> Option<A2aStreamEvent> {
         loop {
+            if let Some(event) = self.decoder.next_event() {
+                return Some(event);
+            }
+
             match self.receiver.recv() {
-                Ok(SseEvent::Data(data)) => {
-                    // Parse JSON-RPC response
-                    match serde_json::from_str::<JsonRpcResponse>(&data) {
-                        Ok(response) => {
-                            if let Some(error) = response.error {
-                                return Some(A2aStreamEvent::Error(format!(
-                                    "JSON-RPC error: {} - {}",
-                                    error.code, error.message
-                                )));
-                            }
-
-                            if let Some(result) = response.result {
-                                return self.process_result(result);
-                            }
-                        }
-                        Err(e) => {
-                            // Try parsing as direct A2UI message
-                            match serde_json::from_str::<A2uiMessage>(&data) {
-                                Ok(msg) => return Some(A2aStreamEvent::A2uiMessage(msg)),
-                                Err(_) => {
-                                    // Log parse error but continue
-                                    continue;
-                                }
-                            }
-                        }
-                    }
-                }
+                Ok(SseEvent::Data(data)) => self.decoder.decode(&data),
                 Ok(SseEvent::Comment(_)) => {
                     // Keep-alive, continue
                     continue;
@@ -232,60 +530,184 @@ impl A2aEventStream {
             }
         }
     }
+}
 
-    fn process_result(&mut self, result: ResultValue) -> Option<A2aStreamEvent> {
+impl A2aEventStream {
+    /// Get current task ID
+    pub fn task_id(&self) -> Option<&str> {
+        self.decoder.task_id()
+    }
+}
+
+/// Turns the SSE data of an A2A stream into stream events
+///
+/// Shared by the blocking and the async client. Tracks the task the agent
+/// reports and queues the events of responses that carry several.
+pub(crate) struct EventDecoder {
+    client_task_id: Option<String>,
+    client_context_id: Option<String>,
+    shared_task_id: Option<Arc<Mutex<Option<String>>>>,
+    /// Events of a response that carried more than one
+    pending: VecDeque<A2aStreamEvent>,
+}
+
+impl EventDecoder {
+    /// Take the next decoded event
+    pub(crate) fn next_event(&mut self) -> Option<A2aStreamEvent> {
+        self.pending.pop_front()
+    }
+
+    /// Decode the data of one SSE event
+    pub(crate) fn decode(&mut self, data: &str) {
+        // Parse JSON-RPC response
+        match serde_json::from_str::<JsonRpcResponse>(data) {
+            Ok(JsonRpcResponse {
+                error: Some(error), ..
+            }) => {
+                self.pending.push_back(A2aStreamEvent::Error(format!(
+                    "JSON-RPC error: {} - {}",
+                    error.code, error.message
+                )));
+            }
+            Ok(JsonRpcResponse {
+                result: Some(result),
+                ..
+            }) => {
+                let events = self.process_result(result);
+                self.pending.extend(events);
+            }
+            _ => {
+                // Try parsing as direct A2UI message, ignore anything else
+                if let Ok(msg) = serde_json::from_str::<A2uiMessage>(data) {
+                    self.pending.push_back(A2aStreamEvent::A2uiMessage(msg));
+                }
+            }
+        }
+    }
+
+    fn process_result(&mut self, result: ResultValue) -> Vec<A2aStreamEvent> {
         match result {
             ResultValue::Task(task) => {
-                // Update task ID
-                self.client_task_id = Some(task.id.clone());
-                Some(A2aStreamEvent::TaskStatus {
+                self.set_task_id(&task.id);
+                let mut events = status_message_events(&task.status);
+                for artifact in &task.artifacts {
+                    events.extend(artifact_events(artifact));
+                }
+                events.push(A2aStreamEvent::TaskStatus {
                     task_id: task.id,
                     state: task.status.state,
-                })
+                });
+                events
             }
             ResultValue::Event(event) => {
+                let mut events = Vec::new();
+
                 // Check for A2UI messages in data
                 if let Some(data) = event.data {
-                    eprintln!("[A2A] Event data: {}", serde_json::to_string_pretty(&data).unwrap_or_default());
+                    events.extend(a2ui_message_from_data(data).map(A2aStreamEvent::A2uiMessage));
+                }
 
-                    // Try to parse as A2UI message
-                    match serde_json::from_value::<A2uiMessage>(data.clone()) {
-                        Ok(msg) => {
-                            eprintln!("[A2A] Parsed A2uiMessage directly: {:?}", msg);
-                            return Some(A2aStreamEvent::A2uiMessage(msg));
-                        }
-                        Err(e) => {
-                            eprintln!("[A2A] Direct A2uiMessage parse failed: {}", e);
-                        }
-                    }
+                // Agent messages and artifacts carry parts (text, files, data)
+                if let Some(parts) = &event.parts {
+                    events.extend(parts_to_events(parts));
+                }
+                if let Some(artifact) = &event.artifact {
+                    events.extend(artifact_events(artifact));
+                }
 
-                    // Check for nested A2UI message keys
-                    if let Some(obj) = data.as_object() {
-                        for key in [
-                            "beginRendering",
-                            "surfaceUpdate",
-                            "dataModelUpdate",
-                            "deleteSurface",
-                        ] {
-                            if obj.contains_key(key) {
-                                if let Ok(msg) = serde_json::from_value::<A2uiMessage>(data.clone())
-                                {
-                                    return Some(A2aStreamEvent::A2uiMessage(msg));
-                                }
-                            }
+                if event.kind == "status-update" {
+                    if let Some(status) = event.status {
+                        events.extend(status_message_events(&status));
+                        let task_id = event.task_id.or_else(|| self.client_task_id.clone());
+                        if let Some(task_id) = task_id {
+                            self.set_task_id(&task_id);
+                            events.push(A2aStreamEvent::TaskStatus {
+                                task_id,
+                                state: status.state,
+                            });
                         }
                     }
                 }
-                None
+                events
             }
-            ResultValue::Other(_) => None,
+            ResultValue::Other(_) => Vec::new(),
         }
     }
 
     /// Get current task ID
-    pub fn task_id(&self) -> Option<&str> {
+    pub(crate) fn task_id(&self) -> Option<&str> {
         self.client_task_id.as_deref()
     }
+
+    /// Record the task ID, also on the client that started the stream
+    fn set_task_id(&mut self, task_id: &str) {
+        if self.client_task_id.as_deref() != Some(task_id) {
+            self.client_task_id = Some(task_id.to_string());
+            if let Some(shared) = &self.shared_task_id {
+                *shared.lock().unwrap() = Some(task_id.to_string());
+            }
+        }
+    }
+}
+
+/// Parse an A2UI message from the data of an event or data part
+fn a2ui_message_from_data(data: Value) -> Option<A2uiMessage> {
+    // Try to parse as A2UI message
+    if let Ok(msg) = serde_json::from_value::<A2uiMessage>(data.clone()) {
+        return Some(msg);
+    }
+
+    // Check for nested A2UI message keys
+    let obj = data.as_object()?;
+    for key in [
+        "beginRendering",
+        "surfaceUpdate",
+        "dataModelUpdate",
+        "deleteSurface",
+    ] {
+        if obj.contains_key(key) {
+            if let Ok(msg) = serde_json::from_value::<A2uiMessage>(data.clone()) {
+                return Some(msg);
+            }
+        }
+    }
+    None
+}
+
+/// Events for the parts of an agent message or artifact
+///
+/// Text parts become `Text` events and data parts A2UI messages. Files and
+/// unrecognized data are skipped.
+fn parts_to_events(parts: &[Value]) -> Vec<A2aStreamEvent> {
+    parts
+        .iter()
+        .filter_map(Part::from_value)
+        .filter_map(|part| match part {
+            Part::Text { text, .. } => Some(A2aStreamEvent::Text(text)),
+            Part::Data { data, .. } => a2ui_message_from_data(data).map(A2aStreamEvent::A2uiMessage),
+            Part::File { .. } => None,
+        })
+        .collect()
+}
+
+/// Events for the message attached to a task status
+fn status_message_events(status: &A2aTaskStatus) -> Vec<A2aStreamEvent> {
+    status
+        .message
+        .as_ref()
+        .and_then(|message| message.get("parts"))
+        .and_then(Value::as_array)
+        .map(|parts| parts_to_events(parts))
+        .unwrap_or_default()
+}
+
+/// Events for the parts of an artifact
+fn artifact_events(artifact: &Value) -> Vec<A2aStreamEvent> {
+    artifact
+        .get("parts")
+        .and_then(Value::as_array)
+        .map(|parts| parts_to_events(parts))
+        .unwrap_or_default()
 }
 
 /// Event from A2A stream
@@ -293,21 +715,174 @@ impl A2aEventStream {
 pub enum A2aStreamEvent {
     /// A2UI protocol message
     A2uiMessage(A2uiMessage),
+    /// Text from the agent (message, status or artifact text parts)
+    Text(String),
     /// Task status update
-    TaskStatus { task_id: String, state: String },
+    TaskStatus { task_id: String, state: TaskState },
     /// Error
     Error(String),
 }
 
+// ============================================================================
+// A2A task types
+// ============================================================================
+
+/// State of an A2A task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskState {
+    Submitted,
+    Working,
+    InputRequired,
+    Completed,
+    Canceled,
+    Failed,
+    Rejected,
+    AuthRequired,
+    /// Unknown or unrecognized state
+    #[serde(other)]
+    Unknown,
+}
+
+impl TaskState {
+    /// Protocol name of the state (e.g., "input-required")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskState::Submitted => "submitted",
+            TaskState::Working => "working",
+            TaskState::InputRequired => "input-required",
+            TaskState::Completed => "completed",
+            TaskState::Canceled => "canceled",
+            TaskState::Failed => "failed",
+            TaskState::Rejected => "rejected",
+            TaskState::AuthRequired => "auth-required",
+            TaskState::Unknown => "unknown",
+        }
+    }
+
+    /// Check if the task is finished and won't change anymore
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TaskState::Completed | TaskState::Canceled | TaskState::Failed | TaskState::Rejected
+        )
+    }
+
+    /// Check if the agent is waiting for the user
+    pub fn needs_input(&self) -> bool {
+        matches!(self, TaskState::InputRequired | TaskState::AuthRequired)
+    }
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An A2A task as returned by `tasks/get` and `tasks/cancel`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct A2aTask {
+    /// Task ID
+    pub id: String,
+
+    /// Context (conversation) ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<String>,
+
+    /// Current status
+    pub status: A2aTaskStatus,
+
+    /// Artifacts produced by the task (raw A2A artifacts)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<Value>,
+
+    /// Message history (raw A2A messages)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Value>,
+}
+
+/// Status of an A2A task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct A2aTaskStatus {
+    /// Task state
+    pub state: TaskState,
+
+    /// Optional status message from the agent (raw A2A message)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+
+    /// Time of the status change (ISO 8601)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+/// Webhook the agent posts task updates to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushNotificationConfig {
+    /// Config ID (assigned by the agent if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Webhook URL
+    pub url: String,
+
+    /// Token the agent includes in notifications, to validate them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Authentication the agent uses when calling the webhook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authentication: Option<PushNotificationAuthentication>,
+}
+
+impl PushNotificationConfig {
+    /// Create a config for a webhook URL
+    pub fn new(url: impl Into<String>) -> Self {
+        PushNotificationConfig {
+            id: None,
+            url: url.into(),
+            token: None,
+            authentication: None,
+        }
+    }
+}
+
+/// Authentication for push notifications
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushNotificationAuthentication {
+    /// Supported schemes (e.g., "Bearer")
+    pub schemes: Vec<String>,
+
+    /// Credentials for the schemes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<String>,
+}
+
+/// A push notification config bound to a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskPushNotificationConfig {
+    /// Task ID
+    pub task_id: String,
+
+    /// The config
+    pub push_notification_config: PushNotificationConfig,
+}
+
 // ============================================================================
 // JSON-RPC types
 // ============================================================================
 
 #[derive(Serialize)]
-struct JsonRpcRequest {
+struct JsonRpcRequest<P> {
     jsonrpc: String,
     method: String,
-    params: MessageParams,
+    params: P,
     id: u64,
 }
 
@@ -318,6 +893,26 @@ struct MessageParams {
     message: Message,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TaskIdParams {
+    pub(crate) id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) history_length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) push_notification_config_id: Option<String>,
+}
+
+impl TaskIdParams {
+    pub(crate) fn new(task_id: &str) -> Self {
+        TaskIdParams {
+            id: task_id.to_string(),
+            history_length: None,
+            push_notification_config_id: None,
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct Message {
     #[serde(rename = "messageId")]
@@ -327,13 +922,8 @@ struct Message {
     #[serde(rename = "contextId")]
     context_id: String,
     extensions: Vec<String>,
-}
-
-#[derive(Serialize)]
-#[serde(untagged)]
-enum Part {
-    Text { text: String },
-    Data { data: Value },
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<Value>,
 }
 
 #[derive(Serialize)]
@@ -348,8 +938,8 @@ struct A2uiEvent {
 }
 
 #[derive(Deserialize)]
-struct JsonRpcResponse {
-    result: Option<ResultValue>,
+struct JsonRpcResponse<R = ResultValue> {
+    result: Option<R>,
     error: Option<JsonRpcError>,
 }
 
@@ -362,31 +952,22 @@ struct JsonRpcError {
 #[derive(Deserialize)]
 #[serde(untagged)]
 enum ResultValue {
-    Task(TaskResult),
+    Task(A2aTask),
     Event(EventResult),
     Other(Value),
 }
 
-#[derive(Deserialize)]
-struct TaskResult {
-    kind: String,
-    id: String,
-    #[serde(rename = "contextId")]
-    context_id: Option<String>,
-    status: TaskStatus,
-}
-
-#[derive(Deserialize)]
-struct TaskStatus {
-    state: String,
-}
-
 #[derive(Deserialize)]
 struct EventResult {
     kind: String,
     #[serde(rename = "taskId")]
     task_id: Option<String>,
     data: Option<Value>,
+    status: Option<A2aTaskStatus>,
+    /// Parts of an agent message (`kind: "message"`)
+    parts: Option<Vec<Value>>,
+    /// Artifact of an artifact update (`kind: "artifact-update"`)
+    artifact: Option<Value>,
 }
 
 /// Get current timestamp in ISO format
@@ -398,3 +979,151 @@ fn chrono_now() -> String {
         .unwrap_or_default();
     format!("{}", duration.as_secs())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn stream_of(lines: &[&str]) -> A2aEventStream {
+        let (tx, rx) = mpsc::channel();
+        for line in lines {
+            tx.send(SseEvent::Data(line.to_string())).unwrap();
+        }
+        tx.send(SseEvent::Done).unwrap();
+        A2aEventStream {
+            receiver: rx,
+            decoder: A2aClient::new("http://localhost").event_decoder(),
+        }
+    }
+
+    #[test]
+    fn test_task_state_serde() {
+        let task: A2aTask = serde_json::from_str(
+            r#"{"kind": "task", "id": "t1", "contextId": "c1",
+                "status": {"state": "input-required", "timestamp": "2025-01-01T00:00:00Z"}}"#,
+        )
+        .unwrap();
+        assert_eq!(task.status.state, TaskState::InputRequired);
+        assert!(task.status.state.needs_input());
+        assert_eq!(task.context_id.as_deref(), Some("c1"));
+
+        let state: TaskState = serde_json::from_str(r#""paused""#).unwrap();
+        assert_eq!(state, TaskState::Unknown);
+        assert!(TaskState::Canceled.is_terminal());
+        assert_eq!(TaskState::AuthRequired.to_string(), "auth-required");
+
+        let config = TaskPushNotificationConfig {
+            task_id: "t1".to_string(),
+            push_notification_config: PushNotificationConfig::new("https://example.com/hook"),
+        };
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            serde_json::json!({
+                "taskId": "t1",
+                "pushNotificationConfig": {"url": "https://example.com/hook"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_prepared_requests_outlive_client() {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = r#"{"jsonrpc": "2.0", "id": 1, "result": {"kind": "task",
+                "id": "t1", "status": {"state": "canceled"}}}"#;
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+
+        // Built while the client is borrowed, sent after it's gone
+        let mut client = A2aClient::new(url);
+        let request = client.cancel_request("t1").unwrap();
+        drop(client);
+        let task: A2aTask = request.call("tasks/cancel").unwrap();
+        assert_eq!(task.status.state, TaskState::Canceled);
+
+        let body: Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(body["method"], "tasks/cancel");
+        assert_eq!(body["params"]["id"], "t1");
+    }
+
+    #[test]
+    fn test_stream_task_events() {
+        let mut stream = stream_of(&[
+            r#"{"jsonrpc": "2.0", "result": {"kind": "task", "id": "t1",
+                "status": {"state": "submitted"}}}"#,
+            r#"{"jsonrpc": "2.0", "result": {"kind": "event", "taskId": "t1",
+                "data": {"deleteSurface": {"surfaceId": "main"}}}}"#,
+            r#"{"jsonrpc": "2.0", "result": {"kind": "status-update", "taskId": "t1",
+                "status": {"state": "completed"}, "final": true}}"#,
+        ]);
+
+        assert!(matches!(
+            stream.next(),
+            Some(A2aStreamEvent::TaskStatus { state: TaskState::Submitted, .. })
+        ));
+        assert_eq!(stream.task_id(), Some("t1"));
+        let shared = stream.decoder.shared_task_id.clone().unwrap();
+        assert_eq!(shared.lock().unwrap().as_deref(), Some("t1"));
+
+        assert!(matches!(stream.next(), Some(A2aStreamEvent::A2uiMessage(_))));
+        match stream.next() {
+            Some(A2aStreamEvent::TaskStatus { task_id, state }) => {
+                assert_eq!(task_id, "t1");
+                assert_eq!(state, TaskState::Completed);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_stream_agent_parts() {
+        let mut stream = stream_of(&[
+            r#"{"jsonrpc": "2.0", "result": {"kind": "message", "role": "agent", "parts": [
+                {"kind": "text", "text": "Here is your page"},
+                {"kind": "data", "data": {"deleteSurface": {"surfaceId": "old"}}},
+                {"kind": "file", "file": {"uri": "https://example.com/a.png"}}
+            ]}}"#,
+            r#"{"jsonrpc": "2.0", "result": {"kind": "status-update", "taskId": "t1",
+                "status": {"state": "input-required", "message": {"role": "agent",
+                    "parts": [{"text": "Which size?"}]}}}}"#,
+        ]);
+
+        assert!(matches!(stream.next(), Some(A2aStreamEvent::Text(t)) if t == "Here is your page"));
+        assert!(matches!(stream.next(), Some(A2aStreamEvent::A2uiMessage(_))));
+        assert!(matches!(stream.next(), Some(A2aStreamEvent::Text(t)) if t == "Which size?"));
+        assert!(matches!(
+            stream.next(),
+            Some(A2aStreamEvent::TaskStatus { state: TaskState::InputRequired, .. })
+        ));
+        assert!(stream.next().is_none());
+    }
+}
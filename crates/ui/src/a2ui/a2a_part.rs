@@ -0,0 +1,353 @@
+//! A2A Message Parts
+//!
+//! A2A messages consist of parts: plain text, files (inline as base64 or by
+//! URI) and structured JSON data. Every part can carry metadata. Outgoing
+//! messages are built with `MessageInput`, e.g. a prompt plus an image:
+//!
+//! ```rust,ignore
+//! let message = MessageInput::text("Build a product page for this photo")
+//!     .with_part(Part::file_from_path("shoe.png")?);
+//! client.message_stream(message)?;
+//! ```
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A part of an A2A message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Part {
+    /// Plain text
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        metadata: Option<Value>,
+    },
+    /// A file, inline or by URI
+    File {
+        file: FileContent,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        metadata: Option<Value>,
+    },
+    /// Structured JSON data (A2UI messages and events travel as data parts)
+    Data {
+        data: Value,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        metadata: Option<Value>,
+    },
+}
+
+/// Content of a file part
+///
+/// Exactly one of `bytes` and `uri` is expected to be set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileContent {
+    /// File name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Media type (e.g., "image/png")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+
+    /// Base64 encoded content of an inline file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<String>,
+
+    /// Location of a referenced file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+}
+
+impl FileContent {
+    /// Decode the content of an inline file
+    pub fn decode_bytes(&self) -> Result<Vec<u8>, String> {
+        let bytes = self.bytes.as_deref().ok_or("File has no inline content")?;
+        base64_decode(bytes)
+    }
+}
+
+impl Part {
+    /// Create a text part
+    pub fn text(text: impl Into<String>) -> Self {
+        Part::Text {
+            text: text.into(),
+            metadata: None,
+        }
+    }
+
+    /// Create a data part
+    pub fn data(data: Value) -> Self {
+        Part::Data {
+            data,
+            metadata: None,
+        }
+    }
+
+    /// Create an inline file part
+    pub fn file_bytes(name: impl Into<String>, mime_type: impl Into<String>, bytes: &[u8]) -> Self {
+        Part::File {
+            file: FileContent {
+                name: Some(name.into()),
+                mime_type: Some(mime_type.into()),
+                bytes: Some(base64_encode(bytes)),
+                uri: None,
+            },
+            metadata: None,
+        }
+    }
+
+    /// Create a file part referencing a URI
+    pub fn file_uri(uri: impl Into<String>, mime_type: Option<&str>) -> Self {
+        Part::File {
+            file: FileContent {
+                name: None,
+                mime_type: mime_type.map(str::to_string),
+                bytes: None,
+                uri: Some(uri.into()),
+            },
+            metadata: None,
+        }
+    }
+
+    /// Read a file into an inline file part (media type guessed from the extension)
+    pub fn file_from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Self::file_bytes(name, mime_type_for_path(path), &bytes))
+    }
+
+    /// Attach metadata to the part
+    pub fn with_metadata(mut self, value: Value) -> Self {
+        match &mut self {
+            Part::Text { metadata, .. }
+            | Part::File { metadata, .. }
+            | Part::Data { metadata, .. } => *metadata = Some(value),
+        }
+        self
+    }
+
+    /// Metadata of the part
+    pub fn metadata(&self) -> Option<&Value> {
+        match self {
+            Part::Text { metadata, .. }
+            | Part::File { metadata, .. }
+            | Part::Data { metadata, .. } => metadata.as_ref(),
+        }
+    }
+
+    /// Parse a part received from an agent
+    ///
+    /// Lenient about the `kind` field, which older agents omit: the part type
+    /// is then inferred from the `text`, `file` or `data` field.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        if value.get("kind").is_some() {
+            return serde_json::from_value(value.clone()).ok();
+        }
+
+        let metadata = value.get("metadata").cloned();
+        if let Some(text) = value.get("text").and_then(Value::as_str) {
+            Some(Part::Text {
+                text: text.to_string(),
+                metadata,
+            })
+        } else if let Some(file) = value.get("file") {
+            Some(Part::File {
+                file: serde_json::from_value(file.clone()).ok()?,
+                metadata,
+            })
+        } else {
+            value.get("data").map(|data| Part::Data {
+                data: data.clone(),
+                metadata,
+            })
+        }
+    }
+}
+
+/// Content of an outgoing user message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageInput {
+    /// Message parts, in order
+    pub parts: Vec<Part>,
+
+    /// Message metadata
+    pub metadata: Option<Value>,
+}
+
+impl MessageInput {
+    /// Create an empty message
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a message with a single text part
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new().with_part(Part::text(text))
+    }
+
+    /// Append a part
+    pub fn with_part(mut self, part: Part) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// Set the message metadata
+    pub fn with_metadata(mut self, metadata: Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+impl From<&str> for MessageInput {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<String> for MessageInput {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<Vec<Part>> for MessageInput {
+    fn from(parts: Vec<Part>) -> Self {
+        MessageInput {
+            parts,
+            metadata: None,
+        }
+    }
+}
+
+/// Guess the media type of a file from its extension
+pub fn mime_type_for_path(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "json" => "application/json",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64 with padding
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode standard or URL-safe base64, ignoring padding and whitespace
+fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return Err(format!("Invalid base64 character '{}'", c as char)),
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_base64_roundtrip() {
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert_eq!(base64_encode(b"Ma"), "TWE=");
+        assert_eq!(base64_encode(b"M"), "TQ==");
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(base64_decode(&base64_encode(&bytes)).unwrap(), bytes);
+        assert!(base64_decode("TW*u").is_err());
+    }
+
+    #[test]
+    fn test_part_json() {
+        let part = Part::file_bytes("a.png", "image/png", b"PNG").with_metadata(json!({"page": 1}));
+        assert_eq!(
+            serde_json::to_value(&part).unwrap(),
+            json!({
+                "kind": "file",
+                "file": {"name": "a.png", "mimeType": "image/png", "bytes": "UE5H"},
+                "metadata": {"page": 1}
+            })
+        );
+        match Part::from_value(&serde_json::to_value(&part).unwrap()) {
+            Some(Part::File { file, .. }) => assert_eq!(file.decode_bytes().unwrap(), b"PNG"),
+            other => panic!("unexpected part: {:?}", other),
+        }
+
+        // Parts without a kind are inferred from their fields
+        assert_eq!(
+            Part::from_value(&json!({"text": "hi"})),
+            Some(Part::text("hi"))
+        );
+        assert_eq!(
+            Part::from_value(&json!({"file": {"uri": "https://x/y.pdf"}})),
+            Some(Part::File {
+                file: FileContent {
+                    uri: Some("https://x/y.pdf".to_string()),
+                    ..Default::default()
+                },
+                metadata: None
+            })
+        );
+
+        let message = MessageInput::from("hello").with_part(Part::data(json!({"a": 1})));
+        assert_eq!(message.parts.len(), 2);
+        assert_eq!(mime_type_for_path(Path::new("doc.PDF")), "application/pdf");
+    }
+}
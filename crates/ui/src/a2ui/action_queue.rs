@@ -0,0 +1,604 @@
+//! A2UI Outbound Action Queue
+//!
+//! User actions are delivered to the agent through a queue instead of being
+//! posted directly from the UI thread. The queue:
+//!
+//! - debounces actions per component, so a slider drag sends one action once
+//!   the component has been quiet for `debounce`;
+//! - coalesces a pending action that is repeated with the same surface,
+//!   component, name and context, keeping only the latest (with
+//!   `coalesce_by_name`, any context, so a slider drag sends only its final
+//!   value);
+//! - holds actions while offline, optionally persisting them to a JSON file so
+//!   they survive a restart;
+//! - retries failed deliveries with exponential backoff.
+//!
+//! `ActionQueue` is the synchronous state machine (time is passed in, which
+//! keeps it testable). `ActionWorker` drives it from a background thread and
+//! reports `ActionDeliveryEvent`s back to the owner, which is how `A2uiHost`
+//! uses it.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::message::UserAction;
+
+/// Action queue configuration
+#[derive(Clone, Debug)]
+pub struct ActionQueueConfig {
+    /// Quiet time required after the last action from a component before sending
+    pub debounce: Duration,
+    /// Delay before the first retry; doubled on every further failure
+    pub initial_backoff: Duration,
+    /// Upper bound for the retry delay
+    pub max_backoff: Duration,
+    /// Give up after this many failed attempts (`None` = retry until delivered)
+    pub max_attempts: Option<u32>,
+    /// File to persist unsent actions to (JSON array of user actions)
+    pub persist_path: Option<PathBuf>,
+    /// Coalesce pending actions by name even when their contexts differ
+    ///
+    /// Off by default: template items share a component ID and action name,
+    /// so their actions are only told apart by the resolved context.
+    pub coalesce_by_name: bool,
+}
+
+impl Default for ActionQueueConfig {
+    fn default() -> Self {
+        ActionQueueConfig {
+            debounce: Duration::from_millis(150),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_attempts: None,
+            persist_path: None,
+            coalesce_by_name: false,
+        }
+    }
+}
+
+/// Delivery status of a queued action
+#[derive(Clone, Debug, PartialEq)]
+pub enum ActionDeliveryEvent {
+    /// Action accepted by the queue
+    Queued {
+        id: u64,
+        action_name: String,
+        component_id: Option<String>,
+    },
+    /// A pending action was replaced by a newer one with the same name
+    /// (and context, unless `coalesce_by_name` is set)
+    Coalesced { id: u64, replaced_by: u64 },
+    /// Action delivered to the agent
+    Sent { id: u64 },
+    /// Delivery attempt failed; retried after `retry_in`, dropped when `None`
+    Failed {
+        id: u64,
+        error: String,
+        attempt: u32,
+        retry_in: Option<Duration>,
+    },
+}
+
+#[derive(Debug)]
+struct QueuedAction {
+    id: u64,
+    action: UserAction,
+    ready_at: Instant,
+    attempts: u32,
+    in_flight: bool,
+}
+
+fn same_component(a: &UserAction, b: &UserAction) -> bool {
+    a.surface_id == b.surface_id && a.component_id == b.component_id
+}
+
+/// Debouncing, coalescing retry queue for outbound user actions
+#[derive(Debug)]
+pub struct ActionQueue {
+    config: ActionQueueConfig,
+    entries: Vec<QueuedAction>,
+    next_id: u64,
+    online: bool,
+}
+
+impl ActionQueue {
+    /// Create an empty queue (initially online)
+    pub fn new(config: ActionQueueConfig) -> Self {
+        ActionQueue {
+            config,
+            entries: Vec::new(),
+            next_id: 1,
+            online: true,
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &ActionQueueConfig {
+        &self.config
+    }
+
+    /// Number of unsent actions (including in-flight ones)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if all actions were delivered
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Unsent actions in delivery order
+    pub fn pending_actions(&self) -> impl Iterator<Item = &UserAction> {
+        self.entries.iter().map(|e| &e.action)
+    }
+
+    /// Check if deliveries are attempted
+    pub fn is_online(&self) -> bool {
+        self.online
+    }
+
+    /// Pause or resume deliveries
+    ///
+    /// Setting online makes actions waiting for a retry due immediately.
+    pub fn set_online(&mut self, online: bool, now: Instant) {
+        if online {
+            for entry in self.entries.iter_mut().filter(|e| e.attempts > 0) {
+                entry.ready_at = entry.ready_at.min(now);
+            }
+        }
+        self.online = online;
+    }
+
+    /// Add an action
+    pub fn enqueue(&mut self, action: UserAction, now: Instant) -> Vec<ActionDeliveryEvent> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut events = Vec::new();
+
+        // Coalesce: a newer action with the same name (and context) replaces the pending one
+        if let Some(pos) = self.entries.iter().position(|e| {
+            !e.in_flight
+                && same_component(&e.action, &action)
+                && e.action.action.name == action.action.name
+                && (self.config.coalesce_by_name || e.action.action.context == action.action.context)
+        }) {
+            let replaced = self.entries.remove(pos);
+            events.push(ActionDeliveryEvent::Coalesced {
+                id: replaced.id,
+                replaced_by: id,
+            });
+        }
+
+        // Debounce: restart the quiet period of everything pending for this component
+        let ready_at = now + self.config.debounce;
+        for entry in self
+            .entries
+            .iter_mut()
+            .filter(|e| !e.in_flight && e.attempts == 0 && same_component(&e.action, &action))
+        {
+            entry.ready_at = ready_at;
+        }
+
+        events.push(ActionDeliveryEvent::Queued {
+            id,
+            action_name: action.action.name.clone(),
+            component_id: action.component_id.clone(),
+        });
+        self.entries.push(QueuedAction {
+            id,
+            action,
+            ready_at,
+            attempts: 0,
+            in_flight: false,
+        });
+        events
+    }
+
+    /// Take the actions that are due for delivery, marking them in flight
+    ///
+    /// Every taken action must be reported back with `mark_sent` or `mark_failed`.
+    pub fn take_due(&mut self, now: Instant) -> Vec<(u64, UserAction)> {
+        if !self.online {
+            return Vec::new();
+        }
+        self.entries
+            .iter_mut()
+            .filter(|e| !e.in_flight && e.ready_at <= now)
+            .map(|e| {
+                e.in_flight = true;
+                (e.id, e.action.clone())
+            })
+            .collect()
+    }
+
+    /// Report a successful delivery
+    pub fn mark_sent(&mut self, id: u64) -> Option<ActionDeliveryEvent> {
+        let pos = self.entries.iter().position(|e| e.id == id)?;
+        self.entries.remove(pos);
+        Some(ActionDeliveryEvent::Sent { id })
+    }
+
+    /// Report a failed delivery, scheduling a retry unless attempts are exhausted
+    pub fn mark_failed(
+        &mut self,
+        id: u64,
+        error: impl Into<String>,
+        now: Instant,
+    ) -> Option<ActionDeliveryEvent> {
+        let pos = self.entries.iter().position(|e| e.id == id)?;
+        let attempt = self.entries[pos].attempts + 1;
+
+        let retry_in = if self.config.max_attempts.is_some_and(|max| attempt >= max) {
+            self.entries.remove(pos);
+            None
+        } else {
+            let delay = self.backoff(attempt);
+            let entry = &mut self.entries[pos];
+            entry.attempts = attempt;
+            entry.in_flight = false;
+            entry.ready_at = now + delay;
+            Some(delay)
+        };
+
+        Some(ActionDeliveryEvent::Failed {
+            id,
+            error: error.into(),
+            attempt,
+            retry_in,
+        })
+    }
+
+    /// Retry delay after the given number of failed attempts
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        self.config
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.config.max_backoff)
+    }
+
+    /// When the next action becomes due (`None` if nothing is waiting or offline)
+    pub fn next_wakeup(&self) -> Option<Instant> {
+        if !self.online {
+            return None;
+        }
+        self.entries
+            .iter()
+            .filter(|e| !e.in_flight)
+            .map(|e| e.ready_at)
+            .min()
+    }
+
+    /// Write unsent actions to a file (removes the file when nothing is pending)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if self.entries.is_empty() {
+            return match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(format!("Failed to remove {}: {}", path.display(), e))
+                }
+                _ => Ok(()),
+            };
+        }
+
+        let actions: Vec<&UserAction> = self.pending_actions().collect();
+        let json = serde_json::to_string_pretty(&actions)
+            .map_err(|e| format!("Failed to serialize actions: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Queue actions persisted by `save` (due immediately, no debounce)
+    pub fn load(
+        &mut self,
+        path: impl AsRef<Path>,
+        now: Instant,
+    ) -> Result<Vec<ActionDeliveryEvent>, String> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let actions: Vec<UserAction> = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+        let mut events = Vec::new();
+        for action in actions {
+            let id = self.next_id;
+            self.next_id += 1;
+            events.push(ActionDeliveryEvent::Queued {
+                id,
+                action_name: action.action.name.clone(),
+                component_id: action.component_id.clone(),
+            });
+            self.entries.push(QueuedAction {
+                id,
+                action,
+                ready_at: now,
+                attempts: 0,
+                in_flight: false,
+            });
+        }
+        Ok(events)
+    }
+}
+
+// ============================================================================
+// Background worker
+// ============================================================================
+
+enum ActionCommand {
+    Send(UserAction),
+    SetOnline(bool),
+}
+
+/// Background thread delivering actions from an `ActionQueue`
+///
+/// Dropping the worker stops the thread after persisting unsent actions.
+pub struct ActionWorker {
+    commands: Sender<ActionCommand>,
+    events: Receiver<ActionDeliveryEvent>,
+}
+
+impl ActionWorker {
+    /// Start a worker that delivers actions with `deliver`
+    ///
+    /// Actions persisted at `config.persist_path` are loaded and queued first.
+    pub fn spawn<F>(config: ActionQueueConfig, online: bool, deliver: F) -> Self
+    where
+        F: FnMut(&UserAction) -> Result<(), String> + Send + 'static,
+    {
+        let (command_tx, command_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            Self::run(config, online, command_rx, event_tx, deliver);
+        });
+
+        ActionWorker {
+            commands: command_tx,
+            events: event_rx,
+        }
+    }
+
+    /// Queue an action for delivery
+    pub fn send(&self, action: UserAction) -> Result<(), String> {
+        self.commands
+            .send(ActionCommand::Send(action))
+            .map_err(|_| "Action worker stopped".to_string())
+    }
+
+    /// Pause (offline) or resume (online) deliveries
+    pub fn set_online(&self, online: bool) {
+        let _ = self.commands.send(ActionCommand::SetOnline(online));
+    }
+
+    /// Next delivery status event (non-blocking)
+    pub fn try_event(&self) -> Option<ActionDeliveryEvent> {
+        self.events.try_recv().ok()
+    }
+
+    fn run<F>(
+        config: ActionQueueConfig,
+        online: bool,
+        commands: Receiver<ActionCommand>,
+        events: Sender<ActionDeliveryEvent>,
+        mut deliver: F,
+    ) where
+        F: FnMut(&UserAction) -> Result<(), String>,
+    {
+        let persist_path = config.persist_path.clone();
+        let mut queue = ActionQueue::new(config);
+        queue.set_online(online, Instant::now());
+
+        let emit = |list: Vec<ActionDeliveryEvent>| {
+            for event in list {
+                let _ = events.send(event);
+            }
+        };
+
+        if let Some(path) = &persist_path {
+            match queue.load(path, Instant::now()) {
+                Ok(loaded) => emit(loaded),
+                Err(e) => makepad_widgets::log!("[ActionQueue] {}", e),
+            }
+        }
+
+        loop {
+            let command = match queue.next_wakeup() {
+                Some(at) => commands.recv_timeout(at.saturating_duration_since(Instant::now())),
+                None => commands.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            let mut changed = false;
+            match command {
+                Ok(ActionCommand::Send(action)) => {
+                    emit(queue.enqueue(action, Instant::now()));
+                    changed = true;
+                }
+                Ok(ActionCommand::SetOnline(online)) => queue.set_online(online, Instant::now()),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            for (id, action) in queue.take_due(Instant::now()) {
+                let event = match deliver(&action) {
+                    Ok(()) => queue.mark_sent(id),
+                    Err(error) => queue.mark_failed(id, error, Instant::now()),
+                };
+                emit(event.into_iter().collect());
+                changed = true;
+            }
+
+            if changed {
+                if let Some(path) = &persist_path {
+                    if let Err(e) = queue.save(path) {
+                        makepad_widgets::log!("[ActionQueue] {}", e);
+                    }
+                }
+            }
+        }
+
+        if let Some(path) = &persist_path {
+            if let Err(e) = queue.save(path) {
+                makepad_widgets::log!("[ActionQueue] {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::message::UserActionPayload;
+
+    fn action(component_id: &str, name: &str, value: i64) -> UserAction {
+        UserAction {
+            surface_id: "main".to_string(),
+            action: UserActionPayload {
+                name: name.to_string(),
+                context: [("value".to_string(), serde_json::json!(value))].into(),
+            },
+            component_id: Some(component_id.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_debounce_and_coalesce() {
+        let config = ActionQueueConfig {
+            coalesce_by_name: true,
+            ..ActionQueueConfig::default()
+        };
+        let mut queue = ActionQueue::new(config);
+        let start = Instant::now();
+
+        queue.enqueue(action("volume", "setVolume", 10), start);
+        queue.enqueue(action("buy", "addToCart", 1), start);
+        let events = queue.enqueue(
+            action("volume", "setVolume", 80),
+            start + Duration::from_millis(100),
+        );
+        assert_eq!(
+            events[0],
+            ActionDeliveryEvent::Coalesced {
+                id: 1,
+                replaced_by: 3
+            }
+        );
+        assert_eq!(queue.len(), 2);
+
+        // The other component's action is due, the slider is still debouncing
+        let due = queue.take_due(start + Duration::from_millis(160));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1.action.name, "addToCart");
+
+        let due = queue.take_due(start + Duration::from_millis(260));
+        assert_eq!(due[0].1.action.context["value"], 80);
+        assert_eq!(
+            queue.mark_sent(due[0].0),
+            Some(ActionDeliveryEvent::Sent { id: 3 })
+        );
+    }
+
+    #[test]
+    fn test_template_actions_with_different_context() {
+        let mut queue = ActionQueue::new(ActionQueueConfig::default());
+        let start = Instant::now();
+
+        // Two items of a list template: same component and action, different item
+        queue.enqueue(action("item_buy", "addToCart", 1), start);
+        let events = queue.enqueue(action("item_buy", "addToCart", 2), start);
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, ActionDeliveryEvent::Coalesced { .. })));
+        assert_eq!(queue.len(), 2);
+
+        // Repeating the same item's action still coalesces
+        let events = queue.enqueue(action("item_buy", "addToCart", 2), start);
+        assert_eq!(
+            events[0],
+            ActionDeliveryEvent::Coalesced {
+                id: 2,
+                replaced_by: 3
+            }
+        );
+
+        let due = queue.take_due(start + Duration::from_millis(150));
+        let values: Vec<_> = due.iter().map(|(_, a)| a.action.context["value"].clone()).collect();
+        assert_eq!(values, vec![serde_json::json!(1), serde_json::json!(2)]);
+    }
+
+    #[test]
+    fn test_retry_backoff_and_offline() {
+        let config = ActionQueueConfig {
+            debounce: Duration::ZERO,
+            max_attempts: Some(3),
+            ..ActionQueueConfig::default()
+        };
+        let mut queue = ActionQueue::new(config);
+        let start = Instant::now();
+        queue.enqueue(action("submit", "submit", 1), start);
+
+        let (id, _) = queue.take_due(start)[0].clone();
+        let event = queue.mark_failed(id, "Not connected", start);
+        assert!(matches!(
+            event,
+            Some(ActionDeliveryEvent::Failed { attempt: 1, retry_in: Some(d), .. })
+                if d == Duration::from_millis(500)
+        ));
+        assert!(queue.take_due(start).is_empty());
+        assert_eq!(queue.backoff(2), Duration::from_secs(1));
+
+        // Offline holds actions; going online retries right away
+        queue.set_online(false, start);
+        assert!(queue.take_due(start + Duration::from_secs(5)).is_empty());
+        assert_eq!(queue.next_wakeup(), None);
+        queue.set_online(true, start + Duration::from_millis(100));
+        let (id, _) = queue.take_due(start + Duration::from_millis(100))[0].clone();
+        queue.mark_failed(id, "timeout", start);
+
+        let (id, _) = queue.take_due(start + Duration::from_secs(2))[0].clone();
+        let event = queue.mark_failed(id, "timeout", start);
+        assert!(matches!(
+            event,
+            Some(ActionDeliveryEvent::Failed {
+                attempt: 3,
+                retry_in: None,
+                ..
+            })
+        ));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_persist_and_worker_delivery() {
+        let path = std::env::temp_dir().join(format!("a2ui-actions-{}.json", std::process::id()));
+        let start = Instant::now();
+
+        let mut offline = ActionQueue::new(ActionQueueConfig::default());
+        offline.enqueue(action("submit", "submit", 7), start);
+        offline.save(&path).unwrap();
+
+        let (sent_tx, sent_rx) = mpsc::channel();
+        let config = ActionQueueConfig {
+            persist_path: Some(path.clone()),
+            ..ActionQueueConfig::default()
+        };
+        let worker = ActionWorker::spawn(config, true, move |action| {
+            sent_tx.send(action.clone()).unwrap();
+            Ok(())
+        });
+
+        let delivered = sent_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(delivered.action.context["value"], 7);
+
+        drop(worker);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while path.exists() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,325 @@
+//! In-process A2UI Agents
+//!
+//! Agents written in Rust can drive an `A2uiHost` directly, without a server.
+//! An `A2uiAgent` is told about the host's initial message and each user
+//! action, and answers through an `A2uiEmitter`. `AgentTransport` adapts an
+//! agent to the host, running it either on its own thread or inline on the
+//! threads that call the transport.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! struct Counter(u32);
+//!
+//! impl A2uiAgent for Counter {
+//!     fn start(&mut self, _message: &MessageInput, emitter: &A2uiEmitter) -> Result<(), String> {
+//!         emitter.emit_all(counter_ui(self.0))
+//!     }
+//!
+//!     fn on_action(&mut self, action: &UserAction, emitter: &A2uiEmitter) -> Result<(), String> {
+//!         self.0 += 1;
+//!         emitter.emit_all(counter_ui(self.0))
+//!     }
+//! }
+//!
+//! let mut host = A2uiHost::with_transport(AgentTransport::spawn(Counter(0)));
+//! host.connect("Start")?;
+//! ```
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use super::a2a_client::{A2aStreamEvent, TaskState};
+use super::a2a_part::MessageInput;
+use super::data_sync::DataModelChange;
+use super::message::{A2uiMessage, UserAction};
+use super::transport::{A2uiEventStream, A2uiTransport};
+
+/// Agent logic running in the same process as the host
+///
+/// Errors returned by the callbacks are reported to the host as
+/// `A2aStreamEvent::Error`; the agent keeps running.
+pub trait A2uiAgent: Send + 'static {
+    /// Called when the host connects, with its initial message
+    fn start(&mut self, message: &MessageInput, emitter: &A2uiEmitter) -> Result<(), String>;
+
+    /// Called for each user action
+    fn on_action(&mut self, action: &UserAction, emitter: &A2uiEmitter) -> Result<(), String>;
+
+    /// Called for each batch of data model edits synced by the host
+    fn on_data_change(
+        &mut self,
+        _change: &DataModelChange,
+        _emitter: &A2uiEmitter,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Handle for an agent to send events to the host
+///
+/// Clones send to the same host, so an agent can hand one to its own threads.
+#[derive(Clone, Debug)]
+pub struct A2uiEmitter {
+    /// `None` ends the host's stream
+    events: Sender<Option<A2aStreamEvent>>,
+    task_id: String,
+}
+
+impl A2uiEmitter {
+    /// Send an A2UI message
+    pub fn emit(&self, message: A2uiMessage) -> Result<(), String> {
+        self.send(A2aStreamEvent::A2uiMessage(message))
+    }
+
+    /// Send several A2UI messages in order
+    pub fn emit_all(&self, messages: impl IntoIterator<Item = A2uiMessage>) -> Result<(), String> {
+        messages
+            .into_iter()
+            .try_for_each(|message| self.emit(message))
+    }
+
+    /// Send text for the host's chat transcript
+    pub fn text(&self, text: impl Into<String>) -> Result<(), String> {
+        self.send(A2aStreamEvent::Text(text.into()))
+    }
+
+    /// Report the state of the agent's task
+    pub fn status(&self, state: TaskState) -> Result<(), String> {
+        self.send(A2aStreamEvent::TaskStatus {
+            task_id: self.task_id.clone(),
+            state,
+        })
+    }
+
+    /// Report an error
+    pub fn error(&self, error: impl Into<String>) -> Result<(), String> {
+        self.send(A2aStreamEvent::Error(error.into()))
+    }
+
+    /// End the host's stream; later events are dropped
+    pub fn finish(&self) {
+        let _ = self.events.send(None);
+    }
+
+    /// ID reported with `status`
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    /// Send a stream event
+    pub fn send(&self, event: A2aStreamEvent) -> Result<(), String> {
+        self.events
+            .send(Some(event))
+            .map_err(|_| "Host disconnected".to_string())
+    }
+
+    /// Report the error of an agent callback
+    fn report(&self, result: Result<(), String>) {
+        if let Err(e) = result {
+            let _ = self.error(e);
+        }
+    }
+}
+
+/// Input for an agent running on its own thread
+enum AgentInput {
+    Start(MessageInput),
+    Action(UserAction),
+    DataChange(DataModelChange),
+}
+
+enum AgentRunner<A> {
+    Inline(A),
+    Background {
+        /// Agent waiting for `connect` to move it to its thread
+        agent: Option<A>,
+        inputs: Option<Sender<AgentInput>>,
+    },
+}
+
+/// Transport connecting `A2uiHost` to an in-process `A2uiAgent`
+///
+/// The host's stream ends when the agent calls `A2uiEmitter::finish`. A
+/// background agent's thread ends when the transport is dropped.
+pub struct AgentTransport<A: A2uiAgent> {
+    runner: AgentRunner<A>,
+    emitter: A2uiEmitter,
+    events: Option<Receiver<Option<A2aStreamEvent>>>,
+}
+
+impl<A: A2uiAgent> AgentTransport<A> {
+    /// Run the agent on its own thread, started on `connect`
+    pub fn spawn(agent: A) -> Self {
+        Self::with_runner(AgentRunner::Background {
+            agent: Some(agent),
+            inputs: None,
+        })
+    }
+
+    /// Run the agent on the calling threads
+    ///
+    /// `connect` runs `start` on the UI thread and actions are handled on the
+    /// host's action thread, so the agent shouldn't block.
+    pub fn inline(agent: A) -> Self {
+        Self::with_runner(AgentRunner::Inline(agent))
+    }
+
+    fn with_runner(runner: AgentRunner<A>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        AgentTransport {
+            runner,
+            emitter: A2uiEmitter {
+                events: tx,
+                task_id: format!("local-{}", uuid::Uuid::new_v4()),
+            },
+            events: Some(rx),
+        }
+    }
+
+    /// Handle for sending events to the host on behalf of the agent
+    pub fn emitter(&self) -> A2uiEmitter {
+        self.emitter.clone()
+    }
+
+    fn deliver(&mut self, input: AgentInput) -> Result<(), String> {
+        match &mut self.runner {
+            AgentRunner::Inline(agent) => {
+                handle_input(agent, input, &self.emitter);
+                Ok(())
+            }
+            AgentRunner::Background { inputs, .. } => inputs
+                .as_ref()
+                .ok_or("Not connected")?
+                .send(input)
+                .map_err(|_| "Agent stopped".to_string()),
+        }
+    }
+}
+
+impl<A: A2uiAgent> A2uiTransport for AgentTransport<A> {
+    fn connect(&mut self, initial_message: MessageInput) -> Result<A2uiEventStream, String> {
+        let events = self.events.take().ok_or("Already connected")?;
+
+        if let AgentRunner::Background { agent, inputs } = &mut self.runner {
+            let Some(mut agent) = agent.take() else {
+                return Err("Already connected".to_string());
+            };
+            let (tx, rx) = mpsc::channel();
+            let emitter = self.emitter.clone();
+            thread::spawn(move || {
+                while let Ok(input) = rx.recv() {
+                    handle_input(&mut agent, input, &emitter);
+                }
+            });
+            *inputs = Some(tx);
+        }
+        self.deliver(AgentInput::Start(initial_message))?;
+
+        Ok(Box::new(events.into_iter().map_while(|event| event)))
+    }
+
+    fn send_action(&mut self, action: &UserAction) -> Result<(), String> {
+        self.deliver(AgentInput::Action(action.clone()))
+    }
+
+    fn send_data_change(&mut self, change: &DataModelChange) -> Result<(), String> {
+        self.deliver(AgentInput::DataChange(change.clone()))
+    }
+}
+
+fn handle_input<A: A2uiAgent>(agent: &mut A, input: AgentInput, emitter: &A2uiEmitter) {
+    let result = match input {
+        AgentInput::Start(message) => agent.start(&message, emitter),
+        AgentInput::Action(action) => agent.on_action(&action, emitter),
+        AgentInput::DataChange(change) => agent.on_data_change(&change, emitter),
+    };
+    emitter.report(result);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::UserActionPayload;
+
+    /// Counts clicks, replying with the count as text
+    struct ClickCounter {
+        clicks: u32,
+    }
+
+    impl A2uiAgent for ClickCounter {
+        fn start(&mut self, message: &MessageInput, emitter: &A2uiEmitter) -> Result<(), String> {
+            if message.parts.is_empty() {
+                return Err("Empty message".to_string());
+            }
+            emitter.status(TaskState::Working)?;
+            emitter.emit(
+                serde_json::from_str(
+                    r#"{"beginRendering": {"surfaceId": "main", "root": "root"}}"#,
+                )
+                .unwrap(),
+            )
+        }
+
+        fn on_action(&mut self, action: &UserAction, emitter: &A2uiEmitter) -> Result<(), String> {
+            if action.action.name == "done" {
+                emitter.finish();
+                return Ok(());
+            }
+            self.clicks += 1;
+            emitter.text(self.clicks.to_string())
+        }
+    }
+
+    fn action(name: &str) -> UserAction {
+        UserAction {
+            surface_id: "main".to_string(),
+            action: UserActionPayload {
+                name: name.to_string(),
+                context: Default::default(),
+            },
+            component_id: Some("button".to_string()),
+        }
+    }
+
+    fn run(mut transport: AgentTransport<ClickCounter>) -> Vec<A2aStreamEvent> {
+        let events = transport.connect(MessageInput::from("Hello")).unwrap();
+        assert!(transport.connect(MessageInput::from("Again")).is_err());
+        transport.send_action(&action("click")).unwrap();
+        transport.send_action(&action("click")).unwrap();
+        transport.send_action(&action("done")).unwrap();
+        events.collect()
+    }
+
+    #[test]
+    fn test_inline_and_background_agents() {
+        for transport in [
+            AgentTransport::inline(ClickCounter { clicks: 0 }),
+            AgentTransport::spawn(ClickCounter { clicks: 0 }),
+        ] {
+            let events = run(transport);
+            assert_eq!(events.len(), 4, "{:?}", events);
+            assert!(matches!(
+                &events[0],
+                A2aStreamEvent::TaskStatus {
+                    state: TaskState::Working,
+                    ..
+                }
+            ));
+            assert!(matches!(&events[1], A2aStreamEvent::A2uiMessage(_)));
+            assert!(matches!(&events[2], A2aStreamEvent::Text(t) if t == "1"));
+            assert!(matches!(&events[3], A2aStreamEvent::Text(t) if t == "2"));
+        }
+    }
+
+    #[test]
+    fn test_agent_errors_are_reported() {
+        let mut transport = AgentTransport::spawn(ClickCounter { clicks: 0 });
+        let mut events = transport.connect(MessageInput::default()).unwrap();
+        assert!(matches!(events.next(), Some(A2aStreamEvent::Error(e)) if e == "Empty message"));
+
+        // The emitter can be used from outside the agent
+        transport.emitter().finish();
+        assert!(events.next().is_none());
+    }
+}
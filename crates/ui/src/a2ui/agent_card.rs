@@ -0,0 +1,480 @@
+//! A2A Agent Card
+//!
+//! Agents describe themselves with an agent card served from a well-known URL
+//! (`/.well-known/agent-card.json`, or `/.well-known/agent.json` for older
+//! agents). The card lists the agent's skills, the protocol extensions it
+//! supports, its security schemes and the endpoints it can be reached at.
+//!
+//! `A2aClient::discover` uses the card to pick the JSON-RPC endpoint and to
+//! check that the agent speaks A2UI before any message is sent.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::a2a_client::A2UI_EXTENSION_URI;
+
+/// Well-known agent card paths, in the order they are tried
+pub const AGENT_CARD_PATHS: [&str; 2] = ["/.well-known/agent-card.json", "/.well-known/agent.json"];
+
+/// A2A protocol versions (major.minor) this client can talk to
+pub const SUPPORTED_A2A_VERSIONS: [&str; 2] = ["0.2", "0.3"];
+
+/// How long to wait for an agent card before trying the next URL
+pub const AGENT_CARD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// URI prefix shared by all versions of the A2UI extension
+const A2UI_EXTENSION_PREFIX: &str = "https://a2ui.org/a2a-extension/a2ui/";
+
+/// Transport name of the JSON-RPC binding
+const JSONRPC_TRANSPORT: &str = "JSONRPC";
+
+/// Self-description of an A2A agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCard {
+    /// Human readable name
+    pub name: String,
+
+    /// What the agent does
+    #[serde(default)]
+    pub description: String,
+
+    /// Endpoint of the preferred transport
+    pub url: String,
+
+    /// Agent version
+    #[serde(default)]
+    pub version: String,
+
+    /// A2A protocol version the agent implements (e.g., "0.3.0")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+
+    /// Transport of `url` (defaults to "JSONRPC")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_transport: Option<String>,
+
+    /// Other endpoints and their transports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_interfaces: Vec<AgentInterface>,
+
+    /// Organization providing the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<AgentProvider>,
+
+    /// Optional protocol features
+    #[serde(default)]
+    pub capabilities: AgentCapabilities,
+
+    /// Security schemes by name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub security_schemes: HashMap<String, SecurityScheme>,
+
+    /// Alternative sets of schemes (and scopes) required to call the agent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security: Vec<HashMap<String, Vec<String>>>,
+
+    /// Default accepted input media types
+    #[serde(default)]
+    pub default_input_modes: Vec<String>,
+
+    /// Default produced output media types
+    #[serde(default)]
+    pub default_output_modes: Vec<String>,
+
+    /// Skills the agent offers
+    #[serde(default)]
+    pub skills: Vec<AgentSkill>,
+}
+
+/// An additional agent endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentInterface {
+    /// Endpoint URL
+    pub url: String,
+
+    /// Transport (e.g., "JSONRPC", "GRPC", "HTTP+JSON")
+    pub transport: String,
+}
+
+/// Organization providing an agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentProvider {
+    pub organization: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// Optional protocol features of an agent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCapabilities {
+    /// Supports `message/stream` and `tasks/resubscribe`
+    #[serde(default)]
+    pub streaming: bool,
+
+    /// Supports push notification configs
+    #[serde(default)]
+    pub push_notifications: bool,
+
+    /// Reports the history of task state transitions
+    #[serde(default)]
+    pub state_transition_history: bool,
+
+    /// Supported protocol extensions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<AgentExtension>,
+}
+
+/// A protocol extension supported by an agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentExtension {
+    /// Extension URI (identifies extension and version)
+    pub uri: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Clients must support the extension to talk to the agent
+    #[serde(default)]
+    pub required: bool,
+
+    /// Extension specific parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+/// A skill offered by an agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSkill {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Example prompts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+    /// Accepted input media types (overrides the card default)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input_modes: Vec<String>,
+    /// Produced output media types (overrides the card default)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output_modes: Vec<String>,
+}
+
+/// How a client authenticates with an agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SecurityScheme {
+    /// API key in a header, query parameter or cookie
+    #[serde(rename_all = "camelCase")]
+    ApiKey {
+        name: String,
+        #[serde(rename = "in")]
+        location: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// HTTP authentication (e.g., "bearer")
+    #[serde(rename_all = "camelCase")]
+    Http {
+        scheme: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bearer_format: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// OAuth 2.0 (flows kept as raw JSON)
+    #[serde(rename = "oauth2")]
+    OAuth2 { flows: Value },
+    /// OpenID Connect
+    #[serde(rename_all = "camelCase")]
+    OpenIdConnect { open_id_connect_url: String },
+    /// Mutual TLS
+    #[serde(rename = "mutualTLS")]
+    MutualTls,
+}
+
+impl AgentCard {
+    /// Parse an agent card from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid agent card: {}", e))
+    }
+
+    /// Fetch the agent card of an agent
+    ///
+    /// `url` is either the agent's base URL (the well-known paths are tried in
+    /// order) or the URL of a card `.json` file.
+    pub fn fetch(url: &str) -> Result<Self, String> {
+        let mut errors = Vec::new();
+        for card_url in card_urls(url) {
+            let response = ureq::get(&card_url)
+                .set("Accept", "application/json")
+                .timeout(AGENT_CARD_TIMEOUT)
+                .call();
+            match response {
+                Ok(response) => {
+                    let body = response
+                        .into_string()
+                        .map_err(|e| format!("Failed to read {}: {}", card_url, e))?;
+                    return Self::from_json(&body);
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        Err(format!("No agent card found ({})", errors.join("; ")))
+    }
+
+    /// Get a supported extension by URI
+    pub fn extension(&self, uri: &str) -> Option<&AgentExtension> {
+        self.capabilities.extensions.iter().find(|e| e.uri == uri)
+    }
+
+    /// Check if the agent supports an extension
+    pub fn supports_extension(&self, uri: &str) -> bool {
+        self.extension(uri).is_some()
+    }
+
+    /// Check that the agent supports the A2UI version this client implements
+    pub fn require_a2ui(&self) -> Result<&AgentExtension, String> {
+        if let Some(extension) = self.extension(A2UI_EXTENSION_URI) {
+            return Ok(extension);
+        }
+
+        let offered: Vec<&str> = self
+            .capabilities
+            .extensions
+            .iter()
+            .filter_map(|e| e.uri.strip_prefix(A2UI_EXTENSION_PREFIX))
+            .collect();
+        if offered.is_empty() {
+            Err(format!(
+                "Agent '{}' does not support the A2UI extension ({})",
+                self.name, A2UI_EXTENSION_URI
+            ))
+        } else {
+            Err(format!(
+                "Agent '{}' supports A2UI {} but this client implements {}",
+                self.name,
+                offered.join(", "),
+                A2UI_EXTENSION_URI.trim_start_matches(A2UI_EXTENSION_PREFIX)
+            ))
+        }
+    }
+
+    /// Check that the agent's A2A protocol version is supported
+    ///
+    /// Cards without a protocol version are accepted.
+    pub fn require_supported_version(&self) -> Result<(), String> {
+        self.a2a_version().map(|_| ())
+    }
+
+    /// Supported A2A version (major.minor) matching the card's protocol version
+    ///
+    /// Versions are compared numerically, so "0.3.0", "v0.3" and "0.3.1-rc1"
+    /// all select "0.3". `None` for cards without a protocol version.
+    pub fn a2a_version(&self) -> Result<Option<&'static str>, String> {
+        let Some(version) = &self.protocol_version else {
+            return Ok(None);
+        };
+        let parsed = parse_major_minor(version).ok_or_else(|| {
+            format!(
+                "Agent '{}' has an invalid A2A protocol version '{}'",
+                self.name, version
+            )
+        })?;
+        match SUPPORTED_A2A_VERSIONS
+            .iter()
+            .find(|supported| parse_major_minor(supported) == Some(parsed))
+        {
+            Some(supported) => Ok(Some(supported)),
+            None => Err(format!(
+                "Agent '{}' uses A2A protocol {}, supported versions are {}",
+                self.name,
+                version,
+                SUPPORTED_A2A_VERSIONS.join(", ")
+            )),
+        }
+    }
+
+    /// URL of the agent's JSON-RPC endpoint
+    pub fn jsonrpc_endpoint(&self) -> Result<&str, String> {
+        let preferred = self
+            .preferred_transport
+            .as_deref()
+            .unwrap_or(JSONRPC_TRANSPORT);
+        if preferred.eq_ignore_ascii_case(JSONRPC_TRANSPORT) && !self.url.is_empty() {
+            return Ok(&self.url);
+        }
+
+        self.additional_interfaces
+            .iter()
+            .find(|i| i.transport.eq_ignore_ascii_case(JSONRPC_TRANSPORT))
+            .map(|i| i.url.as_str())
+            .ok_or_else(|| {
+                let transports: Vec<&str> = std::iter::once(preferred)
+                    .chain(
+                        self.additional_interfaces
+                            .iter()
+                            .map(|i| i.transport.as_str()),
+                    )
+                    .collect();
+                format!(
+                    "Agent '{}' has no JSON-RPC endpoint (transports: {})",
+                    self.name,
+                    transports.join(", ")
+                )
+            })
+    }
+
+    /// Run all checks needed before an A2UI session and return the endpoint to use
+    pub fn negotiate(&self) -> Result<&str, String> {
+        self.require_supported_version()?;
+        self.require_a2ui()?;
+        self.jsonrpc_endpoint()
+    }
+
+    /// Check if the agent declares any security requirement
+    pub fn requires_auth(&self) -> bool {
+        self.security
+            .iter()
+            .any(|requirement| !requirement.is_empty())
+    }
+}
+
+/// Major and minor number of a version string such as "v0.3.1-rc1"
+fn parse_major_minor(version: &str) -> Option<(u32, u32)> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0");
+    let digits = minor.find(|c: char| !c.is_ascii_digit()).unwrap_or(minor.len());
+    Some((major, minor[..digits].parse().ok()?))
+}
+
+/// Candidate agent card URLs for a base or card URL
+pub(crate) fn card_urls(url: &str) -> Vec<String> {
+    if url.ends_with(".json") {
+        return vec![url.to_string()];
+    }
+
+    // Cards live at the origin, not below the RPC path
+    let origin_end = url
+        .find("://")
+        .map(|scheme_end| scheme_end + 3)
+        .and_then(|host_start| url[host_start..].find('/').map(|i| host_start + i))
+        .unwrap_or(url.len());
+    let origin = &url[..origin_end];
+
+    AGENT_CARD_PATHS
+        .iter()
+        .map(|path| format!("{}{}", origin, path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARD: &str = r#"{
+        "name": "Payment Agent",
+        "description": "Builds checkout pages",
+        "url": "http://localhost:8080/rpc",
+        "version": "1.0.0",
+        "protocolVersion": "0.3.0",
+        "capabilities": {
+            "streaming": true,
+            "extensions": [
+                {"uri": "https://a2ui.org/a2a-extension/a2ui/v0.8", "required": true}
+            ]
+        },
+        "securitySchemes": {
+            "bearer": {"type": "http", "scheme": "bearer", "bearerFormat": "JWT"},
+            "key": {"type": "apiKey", "name": "X-Key", "in": "header"}
+        },
+        "security": [{"bearer": []}],
+        "defaultInputModes": ["text/plain"],
+        "defaultOutputModes": ["application/json"],
+        "skills": [
+            {"id": "checkout", "name": "Checkout", "tags": ["payment"],
+             "examples": ["Show me a payment page"]}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_and_negotiate() {
+        let card = AgentCard::from_json(CARD).unwrap();
+        assert_eq!(card.skills[0].id, "checkout");
+        assert!(card.capabilities.streaming);
+        assert!(card.requires_auth());
+        assert!(matches!(
+            card.security_schemes["bearer"],
+            SecurityScheme::Http { ref scheme, .. } if scheme == "bearer"
+        ));
+        assert!(matches!(
+            card.security_schemes["key"],
+            SecurityScheme::ApiKey { ref location, .. } if location == "header"
+        ));
+        assert_eq!(card.negotiate(), Ok("http://localhost:8080/rpc"));
+    }
+
+    #[test]
+    fn test_negotiation_errors() {
+        let mut card = AgentCard::from_json(CARD).unwrap();
+
+        card.capabilities.extensions[0].uri = "https://a2ui.org/a2a-extension/a2ui/v0.9".into();
+        let error = card.require_a2ui().unwrap_err();
+        assert!(error.contains("supports A2UI v0.9"), "{}", error);
+
+        card.capabilities.extensions.clear();
+        let error = card.negotiate().unwrap_err();
+        assert!(
+            error.contains("does not support the A2UI extension"),
+            "{}",
+            error
+        );
+
+        card.protocol_version = Some("v0.3.1-rc1".into());
+        assert_eq!(card.a2a_version(), Ok(Some("0.3")));
+        card.protocol_version = Some("0.30".into());
+        assert!(card.require_supported_version().is_err());
+        card.protocol_version = Some("latest".into());
+        let error = card.negotiate().unwrap_err();
+        assert!(error.contains("invalid A2A protocol version"), "{}", error);
+        card.protocol_version = Some("1.0.0".into());
+        assert!(card.require_supported_version().is_err());
+
+        card.preferred_transport = Some("GRPC".into());
+        assert!(card.jsonrpc_endpoint().is_err());
+        card.additional_interfaces.push(AgentInterface {
+            url: "http://localhost:8080/jsonrpc".into(),
+            transport: "JSONRPC".into(),
+        });
+        assert_eq!(card.jsonrpc_endpoint(), Ok("http://localhost:8080/jsonrpc"));
+    }
+
+    #[test]
+    fn test_card_urls() {
+        assert_eq!(
+            card_urls("http://localhost:8080/rpc"),
+            vec![
+                "http://localhost:8080/.well-known/agent-card.json",
+                "http://localhost:8080/.well-known/agent.json",
+            ]
+        );
+        assert_eq!(
+            card_urls("https://agent.example")[1],
+            "https://agent.example/.well-known/agent.json"
+        );
+        assert_eq!(
+            card_urls("https://agent.example/card.json"),
+            vec!["https://agent.example/card.json"]
+        );
+    }
+}
@@ -0,0 +1,703 @@
+//! A2UI Message Builder
+//!
+//! Builds the messages of a surface from Rust instead of hand-written JSON.
+//! Components added inside a container get generated IDs (`text-3`,
+//! `button-4`, ...) unless one is set with `id`, and `build` checks that every
+//! referenced component exists.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let messages = SurfaceBuilder::new("main")
+//!     .column("root", |c| {
+//!         c.styled_text("Checkout", TextUsageHint::H1)
+//!             .text_field("Email", "/form/email")
+//!             .row(|r| {
+//!                 r.button("Cancel", "cancel")
+//!                     .primary_button("Pay", ActionDefinition::new("pay").with_path("total", "/total"))
+//!                     .id("pay-btn")
+//!             })
+//!     })
+//!     .data("total", "$12.00")
+//!     .build()?;
+//! ```
+
+use std::collections::HashSet;
+
+use super::message::*;
+use super::value::{BooleanValue, NumberValue, StringValue};
+
+/// Builds the messages that render one surface
+pub struct SurfaceBuilder {
+    surface_id: String,
+    root: Option<String>,
+    styles: Option<SurfaceStyles>,
+    /// Top-level components and everything nested in them
+    top: ChildrenBuilder,
+    data: Vec<DataContent>,
+}
+
+impl SurfaceBuilder {
+    /// Start building a surface
+    pub fn new(surface_id: impl Into<String>) -> Self {
+        SurfaceBuilder {
+            surface_id: surface_id.into(),
+            root: None,
+            styles: None,
+            top: ChildrenBuilder::default(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Add a top-level column
+    ///
+    /// The first top-level component is the root unless `root` is set.
+    pub fn column(self, id: &str, f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder) -> Self {
+        self.with_top(|top| top.column(f).id(id))
+    }
+
+    /// Add a top-level row
+    pub fn row(self, id: &str, f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder) -> Self {
+        self.with_top(|top| top.row(f).id(id))
+    }
+
+    /// Add a top-level card
+    pub fn card(self, id: &str, f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder) -> Self {
+        self.with_top(|top| top.card(f).id(id))
+    }
+
+    /// Add a top-level component
+    pub fn component(self, id: &str, component: ComponentType) -> Self {
+        self.with_top(|top| top.component(id, component))
+    }
+
+    /// Set the root component
+    pub fn root(mut self, id: impl Into<String>) -> Self {
+        self.root = Some(id.into());
+        self
+    }
+
+    /// Set the surface styles
+    pub fn styles(mut self, styles: SurfaceStyles) -> Self {
+        self.styles = Some(styles);
+        self
+    }
+
+    /// Set a value at the top of the data model
+    pub fn data(mut self, key: impl Into<String>, value: impl Into<DataValue>) -> Self {
+        self.data.push(DataContent {
+            key: key.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Build `beginRendering`, `surfaceUpdate` and (with data) `dataModelUpdate`
+    pub fn build(self) -> Result<Vec<A2uiMessage>, String> {
+        let surface_id = self.surface_id.clone();
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            None => self
+                .top
+                .children
+                .first()
+                .cloned()
+                .ok_or("Surface has no components")?,
+        };
+        let styles = self.styles.clone();
+        let mut messages = self.build_update()?;
+        if !messages.iter().any(|message| defines(message, &root)) {
+            return Err(format!("Root component '{}' is not defined", root));
+        }
+
+        messages.insert(
+            0,
+            A2uiMessage::BeginRendering(BeginRendering {
+                surface_id,
+                root,
+                styles,
+            }),
+        );
+        Ok(messages)
+    }
+
+    /// Build `surfaceUpdate` and (with data) `dataModelUpdate` for a surface
+    /// that is already rendering
+    pub fn build_update(self) -> Result<Vec<A2uiMessage>, String> {
+        let ChildrenBuilder {
+            components, errors, ..
+        } = self.top;
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error);
+        }
+        validate(&components)?;
+
+        let mut messages = vec![A2uiMessage::SurfaceUpdate(SurfaceUpdate {
+            surface_id: self.surface_id.clone(),
+            components,
+        })];
+        if !self.data.is_empty() {
+            messages.push(A2uiMessage::DataModelUpdate(DataModelUpdate {
+                surface_id: self.surface_id,
+                path: "/".to_string(),
+                contents: self.data,
+                version: None,
+            }));
+        }
+        Ok(messages)
+    }
+
+    fn with_top(mut self, f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder) -> Self {
+        self.top = f(self.top);
+        self
+    }
+}
+
+/// Adds the children of a container
+///
+/// Each method adds one child; `id`, `weight`, `visible` and `enabled` change
+/// the child added last.
+#[derive(Default)]
+pub struct ChildrenBuilder {
+    /// All components, including nested ones
+    components: Vec<ComponentDefinition>,
+    /// IDs of the direct children
+    children: Vec<String>,
+    /// Index in `components` of the last direct child
+    last: Option<usize>,
+    next_id: usize,
+    errors: Vec<String>,
+}
+
+impl ChildrenBuilder {
+    /// Add a text
+    pub fn text(self, text: impl Into<StringValue>) -> Self {
+        self.add_text(text.into(), None)
+    }
+
+    /// Add a text with a usage hint (e.g., `TextUsageHint::H1`)
+    pub fn styled_text(self, text: impl Into<StringValue>, hint: TextUsageHint) -> Self {
+        self.add_text(text.into(), Some(hint))
+    }
+
+    /// Add an image
+    pub fn image(self, url: impl Into<StringValue>) -> Self {
+        self.add(
+            "image",
+            ComponentType::Image(ImageComponent {
+                url: url.into(),
+                ..Default::default()
+            }),
+        )
+    }
+
+    /// Add an icon
+    pub fn icon(self, name: impl Into<StringValue>) -> Self {
+        self.add(
+            "icon",
+            ComponentType::Icon(IconComponent {
+                name: name.into(),
+                size: None,
+            }),
+        )
+    }
+
+    /// Add a divider
+    pub fn divider(self) -> Self {
+        self.add(
+            "divider",
+            ComponentType::Divider(DividerComponent::default()),
+        )
+    }
+
+    /// Add a button with a text label
+    pub fn button(
+        self,
+        label: impl Into<StringValue>,
+        action: impl Into<ActionDefinition>,
+    ) -> Self {
+        self.add_button(label.into(), action.into(), None)
+    }
+
+    /// Add a primary button with a text label
+    pub fn primary_button(
+        self,
+        label: impl Into<StringValue>,
+        action: impl Into<ActionDefinition>,
+    ) -> Self {
+        self.add_button(label.into(), action.into(), Some(true))
+    }
+
+    /// Add a text field bound to a data model path
+    pub fn text_field(self, label: impl Into<StringValue>, path: &str) -> Self {
+        self.add(
+            "text-field",
+            ComponentType::TextField(TextFieldComponent {
+                text: StringValue::path(path),
+                label: Some(label.into()),
+                ..Default::default()
+            }),
+        )
+    }
+
+    /// Add a checkbox bound to a data model path
+    pub fn checkbox(self, label: impl Into<StringValue>, path: &str) -> Self {
+        self.add(
+            "checkbox",
+            ComponentType::CheckBox(CheckBoxComponent {
+                value: BooleanValue::path(path),
+                label: Some(label.into()),
+            }),
+        )
+    }
+
+    /// Add a slider bound to a data model path
+    pub fn slider(self, path: &str, min: f64, max: f64) -> Self {
+        self.add(
+            "slider",
+            ComponentType::Slider(SliderComponent {
+                value: NumberValue::path(path),
+                min: Some(min),
+                max: Some(max),
+                step: None,
+            }),
+        )
+    }
+
+    /// Add a choice between `(value, label)` options, bound to a data model path
+    pub fn multiple_choice<'a>(
+        self,
+        path: &str,
+        options: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        let options = options
+            .into_iter()
+            .map(|(value, label)| ChoiceOption {
+                value: value.to_string(),
+                label: StringValue::literal(label),
+            })
+            .collect();
+        self.add(
+            "choice",
+            ComponentType::MultipleChoice(MultipleChoiceComponent {
+                value: StringValue::path(path),
+                options,
+                multi_select: None,
+            }),
+        )
+    }
+
+    /// Add an audio player
+    pub fn audio_player(self, url: impl Into<StringValue>, title: impl Into<StringValue>) -> Self {
+        self.add(
+            "audio",
+            ComponentType::AudioPlayer(AudioPlayerComponent {
+                url: url.into(),
+                title: Some(title.into()),
+                ..Default::default()
+            }),
+        )
+    }
+
+    /// Add a column
+    pub fn column(self, f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder) -> Self {
+        self.add_container("column", f, |children| {
+            ComponentType::Column(ColumnComponent {
+                children: ChildrenRef::ExplicitList(children),
+                ..Default::default()
+            })
+        })
+    }
+
+    /// Add a row
+    pub fn row(self, f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder) -> Self {
+        self.add_container("row", f, |children| {
+            ComponentType::Row(RowComponent {
+                children: ChildrenRef::ExplicitList(children),
+                ..Default::default()
+            })
+        })
+    }
+
+    /// Add a card (several children are wrapped in a column)
+    pub fn card(self, f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder) -> Self {
+        self.add_single_child_container("card", f, |child| {
+            ComponentType::Card(CardComponent {
+                child,
+                elevation: None,
+            })
+        })
+    }
+
+    /// Add a list repeating a template for each item of a data model array
+    ///
+    /// Paths in the template are relative to the item. Several template
+    /// components are wrapped in a column.
+    pub fn list(
+        self,
+        data_binding: &str,
+        f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder,
+    ) -> Self {
+        let data_binding = data_binding.to_string();
+        self.add_single_child_container("list", f, |component_id| {
+            ComponentType::List(ListComponent {
+                children: ChildrenRef::Template {
+                    component_id,
+                    data_binding,
+                },
+                direction: None,
+            })
+        })
+    }
+
+    /// Add a modal shown while a data model path is true
+    pub fn modal(
+        self,
+        visible_path: &str,
+        f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder,
+    ) -> Self {
+        let visible = BooleanValue::path(visible_path);
+        self.add_container("modal", f, |children| {
+            ComponentType::Modal(ModalComponent {
+                visible,
+                children: ChildrenRef::ExplicitList(children),
+            })
+        })
+    }
+
+    /// Add a component with an explicit ID
+    ///
+    /// The components it references must be added separately.
+    pub fn component(mut self, id: &str, component: ComponentType) -> Self {
+        self.push(id.to_string(), component);
+        self
+    }
+
+    /// Set the ID of the last added child
+    pub fn id(mut self, id: &str) -> Self {
+        match self.last {
+            Some(index) => {
+                self.components[index].id = id.to_string();
+                if let Some(child) = self.children.last_mut() {
+                    *child = id.to_string();
+                }
+            }
+            None => self
+                .errors
+                .push(format!("No component to set the ID '{}' on", id)),
+        }
+        self
+    }
+
+    /// Set the flex weight of the last added child
+    pub fn weight(self, weight: f64) -> Self {
+        self.modify_last("weight", |component| component.weight = Some(weight))
+    }
+
+    /// Set when the last added child is rendered
+    pub fn visible(self, visible: impl Into<BooleanValue>) -> Self {
+        let visible = visible.into();
+        self.modify_last("visible", |component| component.visible = Some(visible))
+    }
+
+    /// Set when the last added child accepts input
+    pub fn enabled(self, enabled: impl Into<BooleanValue>) -> Self {
+        let enabled = enabled.into();
+        self.modify_last("enabled", |component| component.enabled = Some(enabled))
+    }
+
+    fn add_text(self, text: StringValue, usage_hint: Option<TextUsageHint>) -> Self {
+        self.add(
+            "text",
+            ComponentType::Text(TextComponent { text, usage_hint }),
+        )
+    }
+
+    fn add_button(
+        mut self,
+        label: StringValue,
+        action: ActionDefinition,
+        primary: Option<bool>,
+    ) -> Self {
+        let id = self.generate_id("button");
+        let child = self.generate_id("text");
+        self.push(
+            id,
+            ComponentType::Button(ButtonComponent {
+                child: child.clone(),
+                primary,
+                action: Some(action),
+            }),
+        );
+        self.components.push(definition(
+            child,
+            ComponentType::Text(TextComponent {
+                text: label,
+                usage_hint: None,
+            }),
+        ));
+        self
+    }
+
+    fn add(mut self, kind: &str, component: ComponentType) -> Self {
+        let id = self.generate_id(kind);
+        self.push(id, component);
+        self
+    }
+
+    fn add_container(
+        mut self,
+        kind: &str,
+        f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder,
+        component: impl FnOnce(Vec<String>) -> ComponentType,
+    ) -> Self {
+        let id = self.generate_id(kind);
+        let nested = self.nested(f);
+        self.push(id, component(nested.children));
+        self.components.extend(nested.components);
+        self
+    }
+
+    /// Add a container with exactly one child, wrapping several in a column
+    fn add_single_child_container(
+        mut self,
+        kind: &str,
+        f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder,
+        component: impl FnOnce(String) -> ComponentType,
+    ) -> Self {
+        let id = self.generate_id(kind);
+        let mut nested = self.nested(f);
+        let child = match nested.children.len() {
+            0 => {
+                self.errors.push(format!("{} has no content", kind));
+                return self;
+            }
+            1 => nested.children.remove(0),
+            _ => {
+                let column = self.generate_id("column");
+                let children = std::mem::take(&mut nested.children);
+                nested.components.insert(
+                    0,
+                    definition(
+                        column.clone(),
+                        ComponentType::Column(ColumnComponent {
+                            children: ChildrenRef::ExplicitList(children),
+                            ..Default::default()
+                        }),
+                    ),
+                );
+                column
+            }
+        };
+        self.push(id, component(child));
+        self.components.extend(nested.components);
+        self
+    }
+
+    /// Run a nested builder sharing this one's ID counter
+    fn nested(&mut self, f: impl FnOnce(ChildrenBuilder) -> ChildrenBuilder) -> ChildrenBuilder {
+        let mut nested = f(ChildrenBuilder {
+            next_id: self.next_id,
+            ..Default::default()
+        });
+        self.next_id = nested.next_id;
+        self.errors.append(&mut nested.errors);
+        nested
+    }
+
+    fn generate_id(&mut self, kind: &str) -> String {
+        self.next_id += 1;
+        format!("{}-{}", kind, self.next_id)
+    }
+
+    /// Add a direct child
+    fn push(&mut self, id: String, component: ComponentType) {
+        self.children.push(id.clone());
+        self.last = Some(self.components.len());
+        self.components.push(definition(id, component));
+    }
+
+    fn modify_last(mut self, what: &str, f: impl FnOnce(&mut ComponentDefinition)) -> Self {
+        match self.last {
+            Some(index) => f(&mut self.components[index]),
+            None => self.errors.push(format!("No component to set {} on", what)),
+        }
+        self
+    }
+}
+
+fn definition(id: String, component: ComponentType) -> ComponentDefinition {
+    ComponentDefinition {
+        id,
+        weight: None,
+        visible: None,
+        enabled: None,
+        component,
+    }
+}
+
+fn defines(message: &A2uiMessage, id: &str) -> bool {
+    match message {
+        A2uiMessage::SurfaceUpdate(update) => update.components.iter().any(|c| c.id == id),
+        _ => false,
+    }
+}
+
+/// Check for duplicate IDs and references to undefined components
+fn validate(components: &[ComponentDefinition]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for component in components {
+        if !ids.insert(component.id.as_str()) {
+            return Err(format!("Duplicate component ID '{}'", component.id));
+        }
+    }
+    for component in components {
+        for reference in referenced_ids(&component.component) {
+            if !ids.contains(reference) {
+                return Err(format!(
+                    "Component '{}' references undefined component '{}'",
+                    component.id, reference
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// IDs of the components a component refers to
+pub fn referenced_ids(component: &ComponentType) -> Vec<&str> {
+    match component {
+        ComponentType::Column(c) => children_ids(&c.children),
+        ComponentType::Row(c) => children_ids(&c.children),
+        ComponentType::List(c) => children_ids(&c.children),
+        ComponentType::Modal(c) => children_ids(&c.children),
+        ComponentType::Card(c) => vec![c.child.as_str()],
+        ComponentType::Button(c) => vec![c.child.as_str()],
+        ComponentType::Tabs(c) => c.tabs.iter().map(|tab| tab.content.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn children_ids(children: &ChildrenRef) -> Vec<&str> {
+    match children {
+        ChildrenRef::ExplicitList(ids) => ids.iter().map(String::as_str).collect(),
+        ChildrenRef::Template { component_id, .. } => vec![component_id.as_str()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::A2uiMessageProcessor;
+
+    #[test]
+    fn test_build_surface() {
+        let messages = SurfaceBuilder::new("main")
+            .column("root", |c| {
+                c.styled_text("Checkout", TextUsageHint::H1)
+                    .text_field("Email", "/email")
+                    .row(|r| {
+                        r.button("Cancel", "cancel")
+                            .primary_button(
+                                "Pay",
+                                ActionDefinition::new("pay").with_path("total", "/total"),
+                            )
+                            .id("pay-btn")
+                    })
+            })
+            .data("total", "$12.00")
+            .build()
+            .unwrap();
+
+        assert_eq!(messages.len(), 3);
+        let A2uiMessage::BeginRendering(begin) = &messages[0] else {
+            panic!("expected beginRendering first");
+        };
+        assert_eq!(begin.root, "root");
+        let A2uiMessage::SurfaceUpdate(update) = &messages[1] else {
+            panic!("expected surfaceUpdate");
+        };
+        let ids: Vec<&str> = update.components.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "root",
+                "text-2",
+                "text-field-3",
+                "row-4",
+                "button-5",
+                "text-6",
+                "pay-btn",
+                "text-8"
+            ]
+        );
+
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        for message in messages {
+            processor.process_message(message);
+        }
+        let surface = processor.get_surface("main").unwrap();
+        assert!(surface.get_component("pay-btn").is_some());
+    }
+
+    #[test]
+    fn test_containers_with_one_child() {
+        let messages = SurfaceBuilder::new("main")
+            .card("root", |c| {
+                c.text("Title")
+                    .list("/items", |t| t.text(StringValue::path("name")))
+            })
+            .build_update()
+            .unwrap();
+        let A2uiMessage::SurfaceUpdate(update) = &messages[0] else {
+            panic!("expected surfaceUpdate");
+        };
+        let card = &update.components[0];
+        let ComponentType::Card(CardComponent { child, .. }) = &card.component else {
+            panic!("expected card");
+        };
+        // Two children are wrapped in a column
+        let column = update.components.iter().find(|c| &c.id == child).unwrap();
+        assert!(matches!(&column.component, ComponentType::Column(_)));
+        assert!(update
+            .components
+            .iter()
+            .any(|c| matches!(&c.component, ComponentType::List(l) if matches!(&l.children, ChildrenRef::Template { data_binding, .. } if data_binding == "/items"))));
+    }
+
+    #[test]
+    fn test_validation() {
+        let dangling = SurfaceBuilder::new("main")
+            .column("root", |c| {
+                c.component(
+                    "card",
+                    ComponentType::Card(CardComponent {
+                        child: "missing".to_string(),
+                        elevation: None,
+                    }),
+                )
+            })
+            .build();
+        assert_eq!(
+            dangling.unwrap_err(),
+            "Component 'card' references undefined component 'missing'"
+        );
+
+        let duplicate = SurfaceBuilder::new("main")
+            .column("root", |c| c.text("a").id("x").text("b").id("x"))
+            .build();
+        assert_eq!(duplicate.unwrap_err(), "Duplicate component ID 'x'");
+
+        let root = SurfaceBuilder::new("main")
+            .column("content", |c| c.text("a"))
+            .root("root")
+            .build();
+        assert_eq!(root.unwrap_err(), "Root component 'root' is not defined");
+
+        assert!(SurfaceBuilder::new("main").build().is_err());
+        assert!(SurfaceBuilder::new("main")
+            .column("root", |c| c.card(|c| c))
+            .build()
+            .is_err());
+    }
+}
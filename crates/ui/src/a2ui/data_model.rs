@@ -72,6 +72,15 @@ impl DataModel {
         }
     }
 
+    /// Create a data model with initial data and version (e.g., restored from a snapshot)
+    pub fn with_data_and_version(data: Value, version: u64) -> Self {
+        DataModel {
+            data,
+            dirty_paths: HashSet::new(),
+            version,
+        }
+    }
+
     /// Get the current version number
     pub fn version(&self) -> u64 {
         self.version
@@ -99,6 +108,11 @@ impl DataModel {
         self.dirty_paths.clear();
     }
 
+    /// Mark a path as dirty without changing its value
+    pub fn mark_dirty(&mut self, path: &str) {
+        self.dirty_paths.insert(path.to_string());
+    }
+
     /// Get all dirty paths
     pub fn dirty_paths(&self) -> &HashSet<String> {
         &self.dirty_paths
@@ -423,6 +437,11 @@ impl SurfaceDataModels {
         self.models.get_mut(surface_id)
     }
 
+    /// Insert a data model for a surface, replacing any existing one
+    pub fn insert(&mut self, surface_id: impl Into<String>, model: DataModel) {
+        self.models.insert(surface_id.into(), model);
+    }
+
     /// Remove a surface's data model
     pub fn remove(&mut self, surface_id: &str) -> Option<DataModel> {
         self.models.remove(surface_id)
@@ -0,0 +1,524 @@
+//! A2UI Data Model Sync
+//!
+//! Opt-in two-way sync of the data model. Local edits (text fields, check
+//! boxes, sliders) normally only update the client's `DataModel`, so an agent
+//! only learns the form state through the context of a user action. With sync
+//! enabled, local edits are sent upstream as `DataModelChange` messages:
+//!
+//! - edits are batched per surface and flushed at most once per `interval`;
+//!   repeated edits of the same path within a batch only send the last value;
+//! - every change carries the last server data model version the client saw
+//!   (`baseVersion`, from `DataModelUpdate::version`) and a per-surface
+//!   sequence number, so the server can detect edits based on stale data;
+//! - a server update touching a path with an unsent or unacknowledged local
+//!   edit is a conflict, resolved by the configured `ConflictPolicy`.
+//!
+//! A server update that carries the local value is treated as an
+//! acknowledgement, not a conflict. A versioned server update acknowledges all
+//! changes sent before it.
+//!
+//! `DataSync` is a synchronous state machine (time is passed in). The
+//! `A2uiMessageProcessor` owns it when sync is enabled, and `A2uiHost` sends
+//! the changes it produces.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::data_model::DataModel;
+use super::message::DataModelUpdate;
+
+/// A single changed value
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataChange {
+    /// Absolute data model path (e.g., "/form/email")
+    pub path: String,
+
+    /// New value
+    pub value: Value,
+}
+
+/// A batch of local data model edits sent to the agent
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataModelChange {
+    /// Surface whose data model changed
+    pub surface_id: String,
+
+    /// Last server data model version the client had seen (`None` if the
+    /// server never sent a versioned update)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_version: Option<u64>,
+
+    /// Per-surface sequence number of this batch, starting at 1
+    pub sequence: u64,
+
+    /// Changed values, ordered by path
+    pub changes: Vec<DataChange>,
+}
+
+/// A server update that touched a locally edited path
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataConflict {
+    /// Surface the conflict happened on
+    pub surface_id: String,
+
+    /// Locally edited path
+    pub path: String,
+
+    /// Value of the local edit
+    pub local_value: Value,
+
+    /// Value at the path after the server update (`None` if it was removed)
+    pub server_value: Option<Value>,
+
+    /// Server version the local edit was based on
+    pub base_version: Option<u64>,
+
+    /// Version carried by the server update
+    pub server_version: Option<u64>,
+}
+
+/// How a single conflict was resolved
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConflictResolution {
+    /// Keep the server value and drop the local edit
+    KeepServer,
+    /// Restore the local value and send it again
+    KeepLocal,
+    /// Use a merged value and send it
+    Merged(Value),
+}
+
+/// Callback deciding a conflict
+pub type ConflictCallback = Arc<dyn Fn(&DataConflict) -> ConflictResolution + Send + Sync>;
+
+/// Conflict resolution policy
+#[derive(Clone, Default)]
+pub enum ConflictPolicy {
+    /// The server value always wins
+    #[default]
+    ServerWins,
+    /// The local value always wins and is sent again
+    ClientWins,
+    /// Ask a callback for every conflict
+    Callback(ConflictCallback),
+}
+
+impl ConflictPolicy {
+    /// Create a callback policy
+    pub fn callback(
+        callback: impl Fn(&DataConflict) -> ConflictResolution + Send + Sync + 'static,
+    ) -> Self {
+        ConflictPolicy::Callback(Arc::new(callback))
+    }
+
+    /// Decide a conflict
+    pub fn resolve(&self, conflict: &DataConflict) -> ConflictResolution {
+        match self {
+            ConflictPolicy::ServerWins => ConflictResolution::KeepServer,
+            ConflictPolicy::ClientWins => ConflictResolution::KeepLocal,
+            ConflictPolicy::Callback(callback) => callback(conflict),
+        }
+    }
+}
+
+impl fmt::Debug for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictPolicy::ServerWins => write!(f, "ServerWins"),
+            ConflictPolicy::ClientWins => write!(f, "ClientWins"),
+            ConflictPolicy::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
+}
+
+/// Data sync configuration
+#[derive(Clone, Debug)]
+pub struct DataSyncConfig {
+    /// Minimum time between two batches of the same surface
+    pub interval: Duration,
+    /// Conflict resolution policy
+    pub policy: ConflictPolicy,
+}
+
+impl Default for DataSyncConfig {
+    fn default() -> Self {
+        DataSyncConfig {
+            interval: Duration::from_millis(250),
+            policy: ConflictPolicy::default(),
+        }
+    }
+}
+
+/// Sync state of one surface
+#[derive(Debug, Default)]
+struct SurfaceSync {
+    /// Version of the last versioned server update
+    server_version: Option<u64>,
+    /// Sequence number of the last batch taken
+    sequence: u64,
+    /// Local edits not yet taken for sending
+    pending: BTreeMap<String, Value>,
+    /// Local edits sent but not yet acknowledged by the server
+    in_flight: BTreeMap<String, Value>,
+    /// Time the last batch was taken
+    last_flush: Option<Instant>,
+}
+
+/// Batching, versioning and conflict detection for local data model edits
+#[derive(Debug)]
+pub struct DataSync {
+    config: DataSyncConfig,
+    surfaces: HashMap<String, SurfaceSync>,
+    conflicts: Vec<(DataConflict, ConflictResolution)>,
+}
+
+impl DataSync {
+    /// Create a sync state machine
+    pub fn new(config: DataSyncConfig) -> Self {
+        DataSync {
+            config,
+            surfaces: HashMap::new(),
+            conflicts: Vec::new(),
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &DataSyncConfig {
+        &self.config
+    }
+
+    /// Version of the last versioned server update for a surface
+    pub fn server_version(&self, surface_id: &str) -> Option<u64> {
+        self.surfaces.get(surface_id)?.server_version
+    }
+
+    /// Check if any surface has edits waiting to be sent
+    pub fn has_pending(&self) -> bool {
+        self.surfaces.values().any(|s| !s.pending.is_empty())
+    }
+
+    /// Number of sent edits not yet acknowledged by the server
+    pub fn unacknowledged(&self) -> usize {
+        self.surfaces.values().map(|s| s.in_flight.len()).sum()
+    }
+
+    /// Record a local edit
+    ///
+    /// Pending edits below `path` are superseded by the new value.
+    pub fn record_local_change(&mut self, surface_id: &str, path: &str, value: Value) {
+        let surface = self.surfaces.entry(surface_id.to_string()).or_default();
+        surface
+            .pending
+            .retain(|pending, _| !is_descendant(pending, path));
+        surface.pending.insert(path.to_string(), value);
+    }
+
+    /// Take the batches that are due
+    ///
+    /// A surface is flushed when it has pending edits and `interval` passed
+    /// since its previous batch. Taken edits wait for acknowledgement.
+    pub fn take_changes(&mut self, now: Instant) -> Vec<DataModelChange> {
+        let interval = self.config.interval;
+        let mut changes: Vec<DataModelChange> = self
+            .surfaces
+            .iter_mut()
+            .filter(|(_, s)| !s.pending.is_empty())
+            .filter(|(_, s)| s.last_flush.is_none_or(|t| now >= t + interval))
+            .map(|(surface_id, s)| {
+                s.sequence += 1;
+                s.last_flush = Some(now);
+                let pending = std::mem::take(&mut s.pending);
+                let changes = pending
+                    .iter()
+                    .map(|(path, value)| DataChange {
+                        path: path.clone(),
+                        value: value.clone(),
+                    })
+                    .collect();
+                s.in_flight.extend(pending);
+                DataModelChange {
+                    surface_id: surface_id.clone(),
+                    base_version: s.server_version,
+                    sequence: s.sequence,
+                    changes,
+                }
+            })
+            .collect();
+        changes.sort_by(|a, b| a.surface_id.cmp(&b.surface_id));
+        changes
+    }
+
+    /// Time the next batch becomes due, if any edits are pending
+    pub fn next_flush(&self, now: Instant) -> Option<Instant> {
+        self.surfaces
+            .values()
+            .filter(|s| !s.pending.is_empty())
+            .map(|s| s.last_flush.map_or(now, |t| t + self.config.interval))
+            .min()
+    }
+
+    /// Put the edits of a batch that couldn't be delivered back into the queue
+    ///
+    /// Edits made since the batch was taken take precedence.
+    pub fn requeue(&mut self, change: DataModelChange) {
+        let Some(surface) = self.surfaces.get_mut(&change.surface_id) else {
+            return;
+        };
+        for DataChange { path, value } in change.changes {
+            if surface.in_flight.get(&path) == Some(&value) {
+                surface.in_flight.remove(&path);
+            }
+            surface.pending.entry(path).or_insert(value);
+        }
+    }
+
+    /// Forget the sync state of a deleted surface
+    pub fn remove_surface(&mut self, surface_id: &str) {
+        self.surfaces.remove(surface_id);
+    }
+
+    /// Take the conflicts detected since the last call and how they were resolved
+    pub fn take_conflicts(&mut self) -> Vec<(DataConflict, ConflictResolution)> {
+        std::mem::take(&mut self.conflicts)
+    }
+
+    /// Reconcile local edits with a server update that was just applied
+    ///
+    /// Called by the processor after `update` was merged into `data_model`.
+    /// Local edits overlapping the update are acknowledged if the server value
+    /// matches and resolved by the policy otherwise; restored local or merged
+    /// values are written back into `data_model` and sent again.
+    pub fn reconcile(&mut self, update: &DataModelUpdate, data_model: &mut DataModel) {
+        let Some(surface) = self.surfaces.get_mut(&update.surface_id) else {
+            if let Some(version) = update.version {
+                self.surfaces
+                    .entry(update.surface_id.clone())
+                    .or_default()
+                    .server_version = Some(version);
+            }
+            return;
+        };
+
+        let base = update.path.trim_end_matches('/');
+        let updated: Vec<String> = update
+            .contents
+            .iter()
+            .map(|content| format!("{}/{}", base, content.key))
+            .collect();
+        let overlaps = |path: &str| {
+            updated
+                .iter()
+                .any(|u| is_descendant(path, u) || is_descendant(u, path))
+        };
+
+        let local: Vec<(String, Value)> = surface
+            .in_flight
+            .iter()
+            .chain(surface.pending.iter())
+            .filter(|(path, _)| overlaps(path))
+            .map(|(path, value)| (path.clone(), value.clone()))
+            .collect();
+
+        for (path, local_value) in local {
+            surface.pending.remove(&path);
+            surface.in_flight.remove(&path);
+
+            let server_value = data_model.get(&path).cloned();
+            if server_value
+                .as_ref()
+                .is_some_and(|v| same_value(v, &local_value))
+            {
+                continue;
+            }
+
+            let conflict = DataConflict {
+                surface_id: update.surface_id.clone(),
+                path: path.clone(),
+                local_value,
+                server_value,
+                base_version: surface.server_version,
+                server_version: update.version,
+            };
+            let resolution = self.config.policy.resolve(&conflict);
+            let value = match &resolution {
+                ConflictResolution::KeepServer => None,
+                ConflictResolution::KeepLocal => Some(conflict.local_value.clone()),
+                ConflictResolution::Merged(value) => Some(value.clone()),
+            };
+            if let Some(value) = value {
+                data_model.set(&path, value.clone());
+                surface.pending.insert(path, value);
+            }
+            self.conflicts.push((conflict, resolution));
+        }
+
+        if let Some(version) = update.version {
+            surface.server_version = Some(version);
+            surface.in_flight.clear();
+        }
+    }
+}
+
+impl Default for DataSync {
+    fn default() -> Self {
+        Self::new(DataSyncConfig::default())
+    }
+}
+
+/// Check if `path` is `ancestor` or lies below it
+fn is_descendant(path: &str, ancestor: &str) -> bool {
+    let ancestor = ancestor.trim_end_matches('/');
+    ancestor.is_empty()
+        || path == ancestor
+        || path
+            .strip_prefix(ancestor)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Compare values, treating numbers as equal when their f64 values are
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::message::{DataContent, DataValue};
+    use serde_json::json;
+
+    fn update(version: Option<u64>, key: &str, value: &str) -> DataModelUpdate {
+        DataModelUpdate {
+            surface_id: "main".to_string(),
+            path: "/form".to_string(),
+            contents: vec![DataContent {
+                key: key.to_string(),
+                value: DataValue::ValueString(value.to_string()),
+            }],
+            version,
+        }
+    }
+
+    fn apply(sync: &mut DataSync, model: &mut DataModel, update: DataModelUpdate) {
+        model.apply_updates(&update.path, &update.contents);
+        sync.reconcile(&update, model);
+    }
+
+    #[test]
+    fn test_batching_and_throttle() {
+        let mut sync = DataSync::default();
+        let mut model = DataModel::new();
+        let t0 = Instant::now();
+
+        apply(&mut sync, &mut model, update(Some(4), "name", ""));
+        sync.record_local_change("main", "/form/name", json!("A"));
+        sync.record_local_change("main", "/form/name", json!("Al"));
+        sync.record_local_change("main", "/form/agree", json!(true));
+
+        let batch = sync.take_changes(t0);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].base_version, Some(4));
+        assert_eq!(batch[0].sequence, 1);
+        assert_eq!(
+            batch[0].changes,
+            vec![
+                DataChange {
+                    path: "/form/agree".to_string(),
+                    value: json!(true)
+                },
+                DataChange {
+                    path: "/form/name".to_string(),
+                    value: json!("Al")
+                },
+            ]
+        );
+        assert_eq!(sync.unacknowledged(), 2);
+
+        // Throttled until the interval passed
+        sync.record_local_change("main", "/form/name", json!("Ali"));
+        assert!(sync
+            .take_changes(t0 + Duration::from_millis(100))
+            .is_empty());
+        assert_eq!(sync.next_flush(t0), Some(t0 + Duration::from_millis(250)));
+        let batch = sync.take_changes(t0 + Duration::from_millis(250));
+        assert_eq!(batch[0].sequence, 2);
+
+        // A failed batch is sent again, newer edits take precedence
+        sync.record_local_change("main", "/form/name", json!("Alic"));
+        sync.requeue(batch[0].clone());
+        let batch = sync.take_changes(t0 + Duration::from_millis(500));
+        assert_eq!(batch[0].changes[0].value, json!("Alic"));
+
+        let json = serde_json::to_value(&batch[0]).unwrap();
+        assert_eq!(json["surfaceId"], "main");
+        assert_eq!(json["baseVersion"], 4);
+    }
+
+    #[test]
+    fn test_acknowledgement_and_server_wins() {
+        let mut sync = DataSync::default();
+        let mut model = DataModel::new();
+
+        sync.record_local_change("main", "/form/name", json!("Alice"));
+        model.set("/form/name", json!("Alice"));
+        sync.take_changes(Instant::now());
+
+        // Server echoes the value: acknowledged, no conflict
+        apply(&mut sync, &mut model, update(Some(5), "name", "Alice"));
+        assert_eq!(sync.unacknowledged(), 0);
+        assert!(sync.take_conflicts().is_empty());
+        assert_eq!(sync.server_version("main"), Some(5));
+
+        // Concurrent server edit of a pending path: the server wins
+        sync.record_local_change("main", "/form/name", json!("Bob"));
+        model.set("/form/name", json!("Bob"));
+        apply(&mut sync, &mut model, update(Some(6), "name", "Carol"));
+        let conflicts = sync.take_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].0.base_version, Some(5));
+        assert_eq!(conflicts[0].0.server_value, Some(json!("Carol")));
+        assert_eq!(conflicts[0].1, ConflictResolution::KeepServer);
+        assert_eq!(model.get_string("/form/name"), Some("Carol"));
+        assert!(!sync.has_pending());
+    }
+
+    #[test]
+    fn test_client_wins_and_callback() {
+        let mut sync = DataSync::new(DataSyncConfig {
+            policy: ConflictPolicy::ClientWins,
+            ..Default::default()
+        });
+        let mut model = DataModel::new();
+        sync.record_local_change("main", "/form/name", json!("Bob"));
+        model.set("/form/name", json!("Bob"));
+        apply(&mut sync, &mut model, update(None, "name", "Carol"));
+        assert_eq!(model.get_string("/form/name"), Some("Bob"));
+        let batch = sync.take_changes(Instant::now());
+        assert_eq!(batch[0].changes[0].value, json!("Bob"));
+
+        let mut sync = DataSync::new(DataSyncConfig {
+            policy: ConflictPolicy::callback(|c| {
+                let server = c.server_value.as_ref().and_then(Value::as_str);
+                let local = c.local_value.as_str();
+                ConflictResolution::Merged(json!(format!(
+                    "{}+{}",
+                    server.unwrap_or(""),
+                    local.unwrap_or("")
+                )))
+            }),
+            ..Default::default()
+        });
+        sync.record_local_change("main", "/form/name", json!("Bob"));
+        apply(&mut sync, &mut model, update(Some(2), "name", "Carol"));
+        assert_eq!(model.get_string("/form/name"), Some("Carol+Bob"));
+    }
+}
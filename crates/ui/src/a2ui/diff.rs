@@ -0,0 +1,338 @@
+//! A2UI Surface Diffs
+//!
+//! `SurfaceState` mirrors what a client has been sent for one surface, so an
+//! agent can send only what changed between two versions of a UI instead of
+//! starting over with `beginRendering`, which resets the client's components
+//! (and with them state such as typed text and scroll positions).
+//!
+//! ```rust,ignore
+//! let mut sent = SurfaceState::new("main");
+//! let first = SurfaceState::from_messages("main", &turn_1);
+//! send(sent.diff(&first)); // everything
+//! sent = first;
+//!
+//! let second = SurfaceState::from_messages("main", &turn_2);
+//! send(sent.diff(&second)); // changed components and data keys only
+//! ```
+
+use std::collections::HashSet;
+
+use serde_json::{Map, Value};
+
+use super::builder::referenced_ids;
+use super::data_model::DataModel;
+use super::message::{
+    A2uiMessage, BeginRendering, ComponentDefinition, DataContent, DataModelUpdate, DataValue,
+    SurfaceStyles, SurfaceUpdate,
+};
+
+/// Components, root and data of one surface
+#[derive(Debug, Clone)]
+pub struct SurfaceState {
+    surface_id: String,
+    root: Option<String>,
+    styles: Option<SurfaceStyles>,
+    components: Vec<ComponentDefinition>,
+    data: DataModel,
+}
+
+impl SurfaceState {
+    /// An empty surface, as a new client has it
+    pub fn new(surface_id: impl Into<String>) -> Self {
+        SurfaceState {
+            surface_id: surface_id.into(),
+            root: None,
+            styles: None,
+            components: Vec::new(),
+            data: DataModel::new(),
+        }
+    }
+
+    /// State after applying the messages addressed to `surface_id`
+    pub fn from_messages(surface_id: impl Into<String>, messages: &[A2uiMessage]) -> Self {
+        let mut state = Self::new(surface_id);
+        for message in messages {
+            state.apply(message);
+        }
+        state
+    }
+
+    /// Apply a message the way `A2uiMessageProcessor` does
+    ///
+    /// `beginRendering` drops the components but keeps the data model.
+    /// Messages for other surfaces are ignored.
+    pub fn apply(&mut self, message: &A2uiMessage) {
+        match message {
+            A2uiMessage::BeginRendering(begin) if begin.surface_id == self.surface_id => {
+                self.root = Some(begin.root.clone());
+                self.styles = begin.styles.clone();
+                self.components.clear();
+            }
+            A2uiMessage::SurfaceUpdate(update) if update.surface_id == self.surface_id => {
+                for component in &update.components {
+                    self.set_component(component.clone());
+                }
+            }
+            A2uiMessage::DataModelUpdate(update) if update.surface_id == self.surface_id => {
+                self.data.apply_updates(&update.path, &update.contents);
+            }
+            A2uiMessage::DeleteSurface(delete) if delete.surface_id == self.surface_id => {
+                *self = Self::new(self.surface_id.clone());
+            }
+            _ => {}
+        }
+    }
+
+    /// Add a component or replace the one with the same ID
+    pub fn set_component(&mut self, component: ComponentDefinition) {
+        match self.components.iter_mut().find(|c| c.id == component.id) {
+            Some(existing) => *existing = component,
+            None => self.components.push(component),
+        }
+    }
+
+    pub fn surface_id(&self) -> &str {
+        &self.surface_id
+    }
+
+    /// Root component, `None` before the first `beginRendering`
+    pub fn root(&self) -> Option<&str> {
+        self.root.as_deref()
+    }
+
+    pub fn components(&self) -> &[ComponentDefinition] {
+        &self.components
+    }
+
+    pub fn component(&self, id: &str) -> Option<&ComponentDefinition> {
+        self.components.iter().find(|c| c.id == id)
+    }
+
+    pub fn data(&self) -> &Value {
+        self.data.as_value()
+    }
+
+    /// Drop components that can't be reached from the root, returning their IDs
+    pub fn prune(&mut self) -> Vec<String> {
+        let reachable: HashSet<String> = self.reachable().into_iter().map(String::from).collect();
+        let mut pruned = Vec::new();
+        self.components.retain(|c| {
+            let keep = reachable.contains(&c.id);
+            if !keep {
+                pruned.push(c.id.clone());
+            }
+            keep
+        });
+        pruned
+    }
+
+    /// Messages building this state on an empty client
+    pub fn messages(&self) -> Vec<A2uiMessage> {
+        Self::new(self.surface_id.clone()).diff(self)
+    }
+
+    /// Messages bringing a client from this state to `next`
+    ///
+    /// Only components reachable from `next`'s root are sent. While the root
+    /// and styles stay the same, that's just the added or changed components;
+    /// otherwise it's a `beginRendering` followed by all of them. Data is sent
+    /// as the changed keys, one `dataModelUpdate` per parent path. Keys missing
+    /// from `next` are left alone, as the protocol has no way to delete them.
+    pub fn diff(&self, next: &SurfaceState) -> Vec<A2uiMessage> {
+        let mut messages = Vec::new();
+
+        let reachable = next.reachable();
+        let restart = next.root.is_some() && (next.root != self.root || next.styles != self.styles);
+        if restart {
+            messages.push(A2uiMessage::BeginRendering(BeginRendering {
+                surface_id: next.surface_id.clone(),
+                root: next.root.clone().unwrap_or_default(),
+                styles: next.styles.clone(),
+            }));
+        }
+
+        let components: Vec<ComponentDefinition> = next
+            .components
+            .iter()
+            .filter(|c| next.root.is_none() || reachable.contains(c.id.as_str()))
+            .filter(|c| restart || self.component(&c.id) != Some(*c))
+            .cloned()
+            .collect();
+        if !components.is_empty() {
+            messages.push(A2uiMessage::SurfaceUpdate(SurfaceUpdate {
+                surface_id: next.surface_id.clone(),
+                components,
+            }));
+        }
+
+        let mut updates = Vec::new();
+        diff_data(
+            "/",
+            self.data().as_object(),
+            next.data().as_object(),
+            &mut updates,
+        );
+        messages.extend(updates.into_iter().map(|(path, contents)| {
+            A2uiMessage::DataModelUpdate(DataModelUpdate {
+                surface_id: next.surface_id.clone(),
+                path,
+                contents,
+                version: None,
+            })
+        }));
+
+        messages
+    }
+
+    /// IDs of the components reachable from the root
+    fn reachable(&self) -> HashSet<&str> {
+        let mut reachable = HashSet::new();
+        let mut pending: Vec<&str> = self.root.as_deref().into_iter().collect();
+        while let Some(id) = pending.pop() {
+            if !reachable.insert(id) {
+                continue;
+            }
+            if let Some(component) = self.component(id) {
+                pending.extend(referenced_ids(&component.component));
+            }
+        }
+        reachable
+    }
+}
+
+/// Changed keys of `new` by parent path, recursing into objects on both sides
+fn diff_data(
+    path: &str,
+    old: Option<&Map<String, Value>>,
+    new: Option<&Map<String, Value>>,
+    updates: &mut Vec<(String, Vec<DataContent>)>,
+) {
+    let Some(new) = new else {
+        return;
+    };
+
+    let mut contents = Vec::new();
+    let mut nested = Vec::new();
+    for (key, value) in new {
+        let previous = old.and_then(|old| old.get(key));
+        match (previous, value) {
+            (Some(previous), value) if previous == value => {}
+            (Some(Value::Object(previous)), Value::Object(value)) => {
+                nested.push((key, previous, value));
+            }
+            _ => {
+                if let Some(value) = DataValue::from_json(value) {
+                    contents.push(DataContent {
+                        key: key.clone(),
+                        value,
+                    });
+                }
+            }
+        }
+    }
+
+    if !contents.is_empty() {
+        updates.push((path.to_string(), contents));
+    }
+    for (key, previous, value) in nested {
+        let child = format!("{}/{}", path.trim_end_matches('/'), key);
+        diff_data(&child, Some(previous), Some(value), updates);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::{A2uiMessageProcessor, SurfaceBuilder};
+
+    fn counter(count: &str, title: &str) -> Vec<A2uiMessage> {
+        SurfaceBuilder::new("main")
+            .column("root", |c| {
+                c.text(title)
+                    .id("title")
+                    .text(count)
+                    .id("count")
+                    .button("Add", "add")
+            })
+            .data("count", 1.0)
+            .data(
+                "user",
+                DataValue::from_json(&serde_json::json!({"name": "Ada", "city": "London"}))
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_diff_sends_only_changes() {
+        let sent = SurfaceState::from_messages("main", &counter("1", "Counter"));
+        assert_eq!(sent.messages().len(), 3);
+
+        let mut messages = counter("2", "Counter");
+        messages.push(A2uiMessage::DataModelUpdate(DataModelUpdate {
+            surface_id: "main".to_string(),
+            path: "/user".to_string(),
+            contents: vec![DataContent {
+                key: "city".to_string(),
+                value: DataValue::from("Paris"),
+            }],
+            version: None,
+        }));
+        let next = SurfaceState::from_messages("main", &messages);
+
+        let diff = sent.diff(&next);
+        assert_eq!(diff.len(), 2, "{:?}", diff);
+        let A2uiMessage::SurfaceUpdate(update) = &diff[0] else {
+            panic!("expected a surfaceUpdate: {:?}", diff[0]);
+        };
+        assert_eq!(update.components.len(), 1);
+        let A2uiMessage::DataModelUpdate(update) = &diff[1] else {
+            panic!("expected a dataModelUpdate: {:?}", diff[1]);
+        };
+        assert_eq!(update.path, "/user");
+        assert_eq!(update.contents[0].key, "city");
+
+        // The client ends up where a full rebuild would have taken it
+        let mut incremental = A2uiMessageProcessor::with_standard_catalog();
+        incremental.process_messages(sent.messages());
+        incremental.process_messages(diff);
+        let mut full = A2uiMessageProcessor::with_standard_catalog();
+        full.process_messages(next.messages());
+        assert_eq!(
+            incremental.get_data_model("main").unwrap().as_value(),
+            full.get_data_model("main").unwrap().as_value()
+        );
+        assert_eq!(
+            incremental.get_surface("main").unwrap().components,
+            full.get_surface("main").unwrap().components
+        );
+
+        assert!(next.diff(&next).is_empty());
+    }
+
+    #[test]
+    fn test_prune_and_root_change() {
+        let mut state = SurfaceState::from_messages("main", &counter("1", "Counter"));
+        let count = state.components().len();
+        let mut orphan = state.component("title").unwrap().clone();
+        orphan.id = "orphan".to_string();
+        state.set_component(orphan.clone());
+        assert_eq!(state.prune(), vec!["orphan".to_string()]);
+        assert_eq!(state.components().len(), count);
+
+        // A new root starts over with the reachable components
+        let mut next = state.clone();
+        next.apply(&A2uiMessage::BeginRendering(BeginRendering {
+            surface_id: "main".to_string(),
+            root: "other".to_string(),
+            styles: None,
+        }));
+        orphan.id = "other".to_string();
+        next.set_component(orphan);
+        let diff = state.diff(&next);
+        assert!(matches!(&diff[0], A2uiMessage::BeginRendering(b) if b.root == "other"));
+        assert!(matches!(&diff[1], A2uiMessage::SurfaceUpdate(u) if u.components.len() == 1));
+        assert_eq!(diff.len(), 2);
+    }
+}
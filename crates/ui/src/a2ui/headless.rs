@@ -0,0 +1,1024 @@
+//! Headless A2UI Surface
+//!
+//! Runs A2UI surfaces without a window or GPU so interactions can be tested in
+//! CI. `HeadlessSurface` processes messages, lays components out for a viewport
+//! using the same sizes and spacing as the `A2uiSurface` renderer, and simulates
+//! clicks, typing and slider drags by component ID. Interactions emit the same
+//! `A2uiSurfaceAction`s the widget emits.
+//!
+//! Text is measured with a fixed glyph width (0.6 × font size) instead of real
+//! font metrics, so rects are close to, but not pixel-identical with, the
+//! rendered surface.
+//!
+//! # Limitations
+//!
+//! Only message processing and value resolution are shared with the widget
+//! (through `A2uiMessageProcessor` and the `resolve_*` functions). Layout,
+//! hit-testing and the handling of clicks, typing and slider drags are a
+//! separate model of what `A2uiSurface::draw_walk` and `handle_event` do,
+//! because the widget computes them inside Makepad's draw pass. The two can
+//! drift: a change to the widget's sizes or interaction rules must be made
+//! here too, and a passing headless test does not prove the widget behaves the
+//! same. In particular:
+//!
+//! - Sizes and spacing are copies of the widget's constants, not read from it.
+//! - Components the widget doesn't draw yet (Tabs, Modal, MultipleChoice,
+//!   Divider, Icon) are skipped along with their children, and must be
+//!   removed from that list by hand once the widget renders them.
+//! - Keyboard focus and cursor movement are simplified: typing appends and
+//!   backspace deletes from the end.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let mut surface = HeadlessSurface::new(800.0, 600.0);
+//! surface.process_json(json)?;
+//!
+//! surface.type_text("name-field", "Alice")?;
+//! surface.click("submit-btn")?;
+//!
+//! assert_eq!(surface.data_model().unwrap().get_string("/name"), Some("Alice"));
+//! assert!(matches!(surface.take_actions()[1], A2uiSurfaceAction::UserAction(_)));
+//! ```
+
+use serde_json::Value;
+
+use super::{
+    data_model::DataModel,
+    message::*,
+    processor::{
+        resolve_boolean_value_scoped, resolve_component_enabled_scoped,
+        resolve_component_visible_scoped, resolve_string_value_scoped, A2uiMessageProcessor,
+        ProcessorEvent, Surface,
+    },
+    registry::component_type_of,
+    surface::A2uiSurfaceAction,
+};
+
+// Layout metrics, shared with the software rasterizer so drawing lines up with
+// hit-testing.
+
+/// Approximate glyph width relative to the font size
+pub(crate) const GLYPH_WIDTH: f64 = 0.6;
+
+/// Line height relative to the font size (matches the renderer's line spacing)
+pub(crate) const LINE_HEIGHT: f64 = 1.4;
+
+/// Body text font size
+pub(crate) const BODY_FONT_SIZE: f64 = 14.0;
+
+/// Horizontal and vertical padding inside a card
+pub(crate) const CARD_PADDING: (f64, f64) = (16.0, 12.0);
+
+/// Audio player title, artist and icon font sizes
+pub(crate) const AUDIO_TITLE_FONT_SIZE: f64 = 16.0;
+pub(crate) const AUDIO_ARTIST_FONT_SIZE: f64 = 12.0;
+pub(crate) const AUDIO_ICON_FONT_SIZE: f64 = 20.0;
+
+/// Height of the audio player's bars
+pub(crate) const AUDIO_BARS_HEIGHT: f64 = 35.0;
+
+/// Gap between the audio player's title row and play button
+pub(crate) const AUDIO_ROW_GAP: f64 = 8.0;
+
+/// An axis-aligned rectangle in surface coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LayoutRect {
+    /// Create a rectangle
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        LayoutRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Center point
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Check if a point lies inside the rectangle
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// Check if the rectangles overlap
+    pub fn intersects(&self, other: &LayoutRect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+/// A laid-out component instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutNode {
+    /// Component ID
+    pub component_id: String,
+
+    /// Component type name
+    pub component_type: &'static str,
+
+    /// Template item scope (e.g., "/products/0")
+    pub scope: Option<String>,
+
+    /// Hit-test rectangle (includes the renderer's minimum click area)
+    pub rect: LayoutRect,
+
+    /// Whether the component accepts input
+    pub enabled: bool,
+
+    /// Index of the parent node in the layout, `None` for the root
+    pub parent: Option<usize>,
+}
+
+/// A windowless A2UI surface for tests.
+///
+/// Data model changes from interactions are applied to the data model (as the
+/// demo app does for two-way binding) unless disabled with
+/// `set_apply_data_changes(false)`.
+pub struct HeadlessSurface {
+    processor: A2uiMessageProcessor,
+    surface_id: String,
+    viewport: (f64, f64),
+    nodes: Vec<LayoutNode>,
+    actions: Vec<A2uiSurfaceAction>,
+    apply_data_changes: bool,
+
+    /// Focused text field (index into `nodes`) and its input buffer
+    focused: Option<(usize, String)>,
+}
+
+impl HeadlessSurface {
+    /// Create a headless surface for the "main" surface ID with a viewport size
+    pub fn new(width: f64, height: f64) -> Self {
+        Self::with_processor(A2uiMessageProcessor::with_standard_catalog(), width, height)
+    }
+
+    /// Create a headless surface around an existing processor
+    pub fn with_processor(processor: A2uiMessageProcessor, width: f64, height: f64) -> Self {
+        let mut surface = HeadlessSurface {
+            processor,
+            surface_id: "main".to_string(),
+            viewport: (width, height),
+            nodes: Vec::new(),
+            actions: Vec::new(),
+            apply_data_changes: true,
+            focused: None,
+        };
+        surface.relayout();
+        surface
+    }
+
+    /// Select which surface ID is laid out and interacted with
+    pub fn set_surface_id(&mut self, surface_id: impl Into<String>) {
+        self.surface_id = surface_id.into();
+        self.relayout();
+    }
+
+    /// Surface ID that is laid out and interacted with
+    pub fn surface_id(&self) -> &str {
+        &self.surface_id
+    }
+
+    /// Change the viewport size and lay out again
+    pub fn set_viewport(&mut self, width: f64, height: f64) {
+        self.viewport = (width, height);
+        self.relayout();
+    }
+
+    /// Viewport size (width, height)
+    pub fn viewport(&self) -> (f64, f64) {
+        self.viewport
+    }
+
+    /// Enable or disable applying `DataModelChanged` actions to the data model
+    pub fn set_apply_data_changes(&mut self, apply: bool) {
+        self.apply_data_changes = apply;
+    }
+
+    /// Get the processor
+    pub fn processor(&self) -> &A2uiMessageProcessor {
+        &self.processor
+    }
+
+    /// Get the mutable processor (call `relayout` after direct changes)
+    pub fn processor_mut(&mut self) -> &mut A2uiMessageProcessor {
+        &mut self.processor
+    }
+
+    /// Get the data model of the current surface
+    pub fn data_model(&self) -> Option<&DataModel> {
+        self.processor.get_data_model(&self.surface_id)
+    }
+
+    /// Process A2UI JSON messages and lay out again
+    pub fn process_json(&mut self, json: &str) -> Result<Vec<ProcessorEvent>, serde_json::Error> {
+        let events = self.processor.process_json(json)?;
+        self.relayout();
+        Ok(events)
+    }
+
+    /// Process a single A2UI message and lay out again
+    pub fn process_message(&mut self, message: A2uiMessage) -> Vec<ProcessorEvent> {
+        let events = self.processor.process_message(message);
+        self.relayout();
+        events
+    }
+
+    // ========================================================================
+    // Layout queries
+    // ========================================================================
+
+    /// All laid-out component instances in render order
+    pub fn layout(&self) -> &[LayoutNode] {
+        &self.nodes
+    }
+
+    /// Rect of the first rendered instance of a component
+    pub fn rect(&self, component_id: &str) -> Option<LayoutRect> {
+        self.find(component_id, 0).map(|idx| self.nodes[idx].rect)
+    }
+
+    /// Rects of all rendered instances of a component (template items)
+    pub fn rects(&self, component_id: &str) -> Vec<LayoutRect> {
+        self.nodes
+            .iter()
+            .filter(|n| n.component_id == component_id)
+            .map(|n| n.rect)
+            .collect()
+    }
+
+    /// Check if a component is rendered (exists and is visible)
+    pub fn is_rendered(&self, component_id: &str) -> bool {
+        self.find(component_id, 0).is_some()
+    }
+
+    /// Check if a component's rect overlaps the viewport
+    pub fn is_in_viewport(&self, component_id: &str) -> bool {
+        let viewport = LayoutRect::new(0.0, 0.0, self.viewport.0, self.viewport.1);
+        self.rect(component_id)
+            .is_some_and(|rect| rect.intersects(&viewport))
+    }
+
+    /// The innermost component at a point
+    pub fn component_at(&self, x: f64, y: f64) -> Option<&LayoutNode> {
+        // Children are laid out after their parents, so search backwards
+        self.nodes.iter().rev().find(|n| n.rect.contains(x, y))
+    }
+
+    /// Recompute the layout from the processor state
+    pub fn relayout(&mut self) {
+        let focused_key = self.focused.as_ref().map(|(idx, _)| {
+            let node = &self.nodes[*idx];
+            (node.component_id.clone(), node.scope.clone())
+        });
+
+        self.nodes = match (
+            self.processor.get_surface(&self.surface_id),
+            self.processor.get_data_model(&self.surface_id),
+        ) {
+            (Some(surface), Some(data_model)) if !surface.root.is_empty() => {
+                let mut layout = LayoutPass::new(surface, data_model);
+                layout.component(&surface.root, 0.0, 0.0, self.viewport.0);
+                layout.nodes
+            }
+            _ => Vec::new(),
+        };
+
+        // Keep focus on the same text field if it is still rendered and enabled
+        let focused = self.focused.take();
+        if let (Some((id, scope)), Some((_, buffer))) = (focused_key, focused) {
+            let idx = self
+                .nodes
+                .iter()
+                .position(|n| n.component_id == id && n.scope == scope && n.enabled);
+            self.focused = idx.map(|idx| (idx, buffer));
+        }
+    }
+
+    // ========================================================================
+    // Simulated input
+    // ========================================================================
+
+    /// Click the first rendered instance of a component
+    ///
+    /// Clicking a disabled component is an error; the rendered widget ignores
+    /// the click, so no action is emitted.
+    pub fn click(&mut self, component_id: &str) -> Result<(), String> {
+        self.click_nth(component_id, 0)
+    }
+
+    /// Click the `index`-th rendered instance of a component (template items)
+    pub fn click_nth(&mut self, component_id: &str, index: usize) -> Result<(), String> {
+        let idx = self.require(component_id, index)?;
+        self.click_node(idx)
+    }
+
+    /// Click at a point in surface coordinates
+    pub fn click_at(&mut self, x: f64, y: f64) -> Result<(), String> {
+        let idx = self
+            .nodes
+            .iter()
+            .rposition(|n| n.rect.contains(x, y) && is_interactive(n.component_type))
+            .ok_or_else(|| format!("No interactive component at ({}, {})", x, y))?;
+        self.click_node(idx)
+    }
+
+    /// Type text into a text field (focusing it first)
+    pub fn type_text(&mut self, component_id: &str, text: &str) -> Result<(), String> {
+        let idx = self.focus_text_field(component_id)?;
+        if let Some((_, buffer)) = &mut self.focused {
+            buffer.push_str(text);
+        }
+        self.emit_text_change(idx);
+        Ok(())
+    }
+
+    /// Delete characters before the cursor of a text field (focusing it first)
+    pub fn backspace(&mut self, component_id: &str, count: usize) -> Result<(), String> {
+        let idx = self.focus_text_field(component_id)?;
+        if let Some((_, buffer)) = &mut self.focused {
+            for _ in 0..count {
+                buffer.pop();
+            }
+        }
+        self.emit_text_change(idx);
+        Ok(())
+    }
+
+    /// Remove focus from the focused text field
+    pub fn blur(&mut self) {
+        self.focused = None;
+    }
+
+    /// ID of the focused text field, if any
+    pub fn focused_component(&self) -> Option<&str> {
+        self.focused
+            .as_ref()
+            .map(|(idx, _)| self.nodes[*idx].component_id.as_str())
+    }
+
+    /// Drag a slider to a position along its track (0.0 = start, 1.0 = end)
+    pub fn drag_slider(&mut self, component_id: &str, fraction: f64) -> Result<(), String> {
+        let idx = self.require(component_id, 0)?;
+        let node = self.nodes[idx].clone();
+        let ComponentType::Slider(slider) = self.component(&node)?.component.clone() else {
+            return Err(format!("Component {} is not a Slider", component_id));
+        };
+        if !node.enabled {
+            return Err(format!("Slider {} is disabled", component_id));
+        }
+
+        let min = slider.min.unwrap_or(0.0);
+        let max = slider.max.unwrap_or(100.0);
+        let value = min + (max - min) * fraction.clamp(0.0, 1.0);
+        if let Some(path) = slider.value.as_path() {
+            self.emit_data_change(
+                binding_path(path, node.scope.as_deref()),
+                Value::from(value),
+            );
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Emitted actions
+    // ========================================================================
+
+    /// Actions emitted so far
+    pub fn actions(&self) -> &[A2uiSurfaceAction] {
+        &self.actions
+    }
+
+    /// Take the emitted actions (clears the list)
+    pub fn take_actions(&mut self) -> Vec<A2uiSurfaceAction> {
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Take only the emitted user actions
+    pub fn take_user_actions(&mut self) -> Vec<UserAction> {
+        self.take_actions()
+            .into_iter()
+            .filter_map(|action| match action {
+                A2uiSurfaceAction::UserAction(user_action) => Some(user_action),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // ========================================================================
+    // Private helpers
+    // ========================================================================
+
+    fn find(&self, component_id: &str, index: usize) -> Option<usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.component_id == component_id)
+            .nth(index)
+            .map(|(idx, _)| idx)
+    }
+
+    fn require(&self, component_id: &str, index: usize) -> Result<usize, String> {
+        self.find(component_id, index).ok_or_else(|| {
+            format!(
+                "Component {} (instance {}) is not rendered",
+                component_id, index
+            )
+        })
+    }
+
+    fn component(&self, node: &LayoutNode) -> Result<&ComponentDefinition, String> {
+        self.processor
+            .get_surface(&self.surface_id)
+            .and_then(|s| s.get_component(&node.component_id))
+            .ok_or_else(|| format!("Component {} is not defined", node.component_id))
+    }
+
+    fn click_node(&mut self, idx: usize) -> Result<(), String> {
+        let node = self.nodes[idx].clone();
+        if !node.enabled {
+            return Err(format!("{} {} is disabled", node.component_type, node.component_id));
+        }
+        let component = self.component(&node)?.clone();
+        let Some(data_model) = self.data_model() else {
+            return Err(format!("Surface {} has no data model", self.surface_id));
+        };
+        let scope = node.scope.as_deref();
+
+        match &component.component {
+            ComponentType::Button(btn) => {
+                if let Some(action_def) = &btn.action {
+                    let user_action = self.processor.create_action(
+                        &self.surface_id,
+                        &node.component_id,
+                        action_def,
+                        scope,
+                    );
+                    self.actions
+                        .push(A2uiSurfaceAction::UserAction(user_action));
+                }
+            }
+            ComponentType::CheckBox(checkbox) => {
+                let checked = resolve_boolean_value_scoped(&checkbox.value, data_model, scope);
+                if let Some(path) = checkbox.value.as_path() {
+                    self.emit_data_change(binding_path(path, scope), Value::Bool(!checked));
+                }
+            }
+            ComponentType::TextField(text_field) => {
+                let value = resolve_string_value_scoped(&text_field.text, data_model, scope);
+                self.focused = Some((idx, value));
+            }
+            ComponentType::AudioPlayer(audio_player) => {
+                let url = resolve_string_value_scoped(&audio_player.url, data_model, scope);
+                let title = audio_player
+                    .title
+                    .as_ref()
+                    .map(|t| resolve_string_value_scoped(t, data_model, scope))
+                    .unwrap_or_else(|| "Audio".to_string());
+                self.actions.push(A2uiSurfaceAction::PlayAudio {
+                    component_id: node.component_id.clone(),
+                    url,
+                    title,
+                });
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn focus_text_field(&mut self, component_id: &str) -> Result<usize, String> {
+        let idx = self.require(component_id, 0)?;
+        let node = self.nodes[idx].clone();
+        if !matches!(
+            self.component(&node)?.component,
+            ComponentType::TextField(_)
+        ) {
+            return Err(format!("Component {} is not a TextField", component_id));
+        }
+        if !node.enabled {
+            return Err(format!("TextField {} is disabled", component_id));
+        }
+        if self.focused.as_ref().map(|(i, _)| *i) != Some(idx) {
+            self.click_node(idx)?;
+        }
+        Ok(idx)
+    }
+
+    fn emit_text_change(&mut self, idx: usize) {
+        let node = self.nodes[idx].clone();
+        let Ok(component) = self.component(&node) else {
+            return;
+        };
+        let ComponentType::TextField(text_field) = &component.component else {
+            return;
+        };
+        let Some(path) = text_field.text.as_path() else {
+            return;
+        };
+        let path = binding_path(path, node.scope.as_deref());
+        let value = self
+            .focused
+            .as_ref()
+            .map(|(_, buffer)| buffer.clone())
+            .unwrap_or_default();
+        self.emit_data_change(path, Value::String(value));
+    }
+
+    fn emit_data_change(&mut self, path: String, value: Value) {
+        if self.apply_data_changes {
+            self.processor
+                .set_local_value(&self.surface_id, &path, value.clone());
+        }
+        self.actions.push(A2uiSurfaceAction::DataModelChanged {
+            surface_id: self.surface_id.clone(),
+            path,
+            value,
+        });
+        if self.apply_data_changes {
+            self.relayout();
+        }
+    }
+}
+
+/// Two-way binding path as computed by the renderer: inside a template every
+/// path is relative to the item scope.
+fn binding_path(path: &str, scope: Option<&str>) -> String {
+    match scope {
+        Some(scope) => format!("{}/{}", scope, path.trim_start_matches('/')),
+        None => path.to_string(),
+    }
+}
+
+fn is_interactive(component_type: &str) -> bool {
+    matches!(
+        component_type,
+        "Button" | "CheckBox" | "TextField" | "Slider" | "AudioPlayer"
+    )
+}
+
+pub(crate) fn text_size(text: &str, font_size: f64) -> (f64, f64) {
+    (
+        text.chars().count() as f64 * font_size * GLYPH_WIDTH,
+        font_size * LINE_HEIGHT,
+    )
+}
+
+/// Font size of a Text component
+pub(crate) fn text_font_size(usage_hint: Option<&TextUsageHint>) -> f64 {
+    match usage_hint {
+        Some(TextUsageHint::H1) => 28.0,
+        Some(TextUsageHint::H2) => 22.0,
+        Some(TextUsageHint::H3) => 18.0,
+        Some(TextUsageHint::H4) => 16.0,
+        Some(TextUsageHint::H5) => 14.0,
+        Some(TextUsageHint::Caption) => 12.0,
+        Some(TextUsageHint::Code) => 13.0,
+        _ => BODY_FONT_SIZE,
+    }
+}
+
+/// Heights of the audio player's title/artist column and of its title row
+pub(crate) fn audio_title_row(has_artist: bool) -> (f64, f64) {
+    let mut info_h = AUDIO_TITLE_FONT_SIZE * LINE_HEIGHT;
+    if has_artist {
+        info_h += 2.0 + AUDIO_ARTIST_FONT_SIZE * LINE_HEIGHT;
+    }
+    let row_h = info_h
+        .max(AUDIO_BARS_HEIGHT)
+        .max(AUDIO_ICON_FONT_SIZE * LINE_HEIGHT);
+    (info_h, row_h)
+}
+
+/// One layout pass over a surface, mirroring `A2uiSurface::render_component`
+struct LayoutPass<'a> {
+    surface: &'a Surface,
+    data_model: &'a DataModel,
+    nodes: Vec<LayoutNode>,
+    scope: Option<String>,
+    inside_disabled: bool,
+    inside_card: bool,
+    parent: Option<usize>,
+}
+
+impl<'a> LayoutPass<'a> {
+    fn new(surface: &'a Surface, data_model: &'a DataModel) -> Self {
+        LayoutPass {
+            surface,
+            data_model,
+            nodes: Vec::new(),
+            scope: None,
+            inside_disabled: false,
+            inside_card: false,
+            parent: None,
+        }
+    }
+
+    /// Lay out a component at (x, y) with the available width.
+    /// Returns the size it occupies in its parent's flow, `None` if nothing is drawn.
+    fn component(&mut self, component_id: &str, x: f64, y: f64, width: f64) -> Option<(f64, f64)> {
+        self.component_with_min_width(component_id, x, y, width, None)
+    }
+
+    fn component_with_min_width(
+        &mut self,
+        component_id: &str,
+        x: f64,
+        y: f64,
+        width: f64,
+        row_column_width: Option<f64>,
+    ) -> Option<(f64, f64)> {
+        let surface = self.surface;
+        let data_model = self.data_model;
+        let component_def = surface.get_component(component_id)?;
+
+        let scope = self.scope.clone();
+        if !resolve_component_visible_scoped(component_def, data_model, scope.as_deref()) {
+            return None;
+        }
+
+        let was_disabled = self.inside_disabled;
+        if !resolve_component_enabled_scoped(component_def, data_model, scope.as_deref()) {
+            self.inside_disabled = true;
+        }
+
+        let node_idx = self.nodes.len();
+        self.nodes.push(LayoutNode {
+            component_id: component_id.to_string(),
+            component_type: component_type_of(&component_def.component).name(),
+            scope: scope.clone(),
+            rect: LayoutRect::new(x, y, 0.0, 0.0),
+            enabled: !self.inside_disabled,
+            parent: self.parent,
+        });
+        let previous_parent = self.parent.replace(node_idx);
+
+        let scope = scope.as_deref();
+        let (rect, size) = match &component_def.component {
+            ComponentType::Column(col) => match row_column_width {
+                // Column inside a Row: Fit (or fixed) width, tighter spacing
+                // (only explicit children are rendered there)
+                Some(fixed) => {
+                    let inner = if fixed > 0.0 { fixed } else { width };
+                    let (w, h) = match &col.children {
+                        ChildrenRef::ExplicitList(_) => {
+                            self.children(&col.children, x, y, inner, 4.0, false).0
+                        }
+                        ChildrenRef::Template { .. } => (0.0, 0.0),
+                    };
+                    let w = if fixed > 0.0 { fixed } else { w };
+                    (LayoutRect::new(x, y, w, h), (w, h))
+                }
+                None => {
+                    let ((_, h), _) = self.children(&col.children, x, y, width, 8.0, false);
+                    (LayoutRect::new(x, y, width, h), (width, h))
+                }
+            },
+            ComponentType::List(list) => {
+                let ((_, h), _) = self.children(&list.children, x, y, width, 8.0, false);
+                (LayoutRect::new(x, y, width, h), (width, h))
+            }
+            ComponentType::Row(row) => {
+                let h = self.row(&row.children, x, y, width);
+                (LayoutRect::new(x, y, width, h), (width, h))
+            }
+            ComponentType::Card(card) => {
+                let was_in_card = self.inside_card;
+                self.inside_card = true;
+                let (pad_x, pad_y) = CARD_PADDING;
+                let (_, child_h) = self
+                    .component(&card.child, x + pad_x, y + 8.0 + pad_y, width - 2.0 * pad_x)
+                    .unwrap_or_default();
+                self.inside_card = was_in_card;
+                let h = child_h + 2.0 * pad_y;
+                (LayoutRect::new(x, y + 8.0, width, h), (width, h + 16.0))
+            }
+            ComponentType::Text(text) => {
+                let value = resolve_string_value_scoped(&text.text, data_model, scope);
+                let (w, h) = text_size(&value, text_font_size(text.usage_hint.as_ref()));
+                (LayoutRect::new(x, y, w, h), (w, h))
+            }
+            ComponentType::Image(img) => {
+                let (w, h) = match img.usage_hint {
+                    Some(ImageUsageHint::Icon) => (24.0, 24.0),
+                    Some(ImageUsageHint::Avatar) => (48.0, 48.0),
+                    Some(ImageUsageHint::SmallFeature) => (64.0, 64.0),
+                    Some(ImageUsageHint::MediumFeature) => (120.0, 80.0),
+                    Some(ImageUsageHint::LargeFeature) => (200.0, 150.0),
+                    Some(ImageUsageHint::Header) => (300.0, 100.0),
+                    _ => (80.0, 80.0),
+                };
+                (LayoutRect::new(x, y, w, h), (w, h))
+            }
+            ComponentType::Button(btn) => {
+                let (child_w, child_h) = self
+                    .component(&btn.child, x + 16.0, y + 8.0, width - 32.0)
+                    .unwrap_or_default();
+                let (w, h) = (child_w + 32.0, child_h + 16.0);
+                (LayoutRect::new(x, y, w, h), (w, h))
+            }
+            ComponentType::TextField(_) => (LayoutRect::new(x, y, 200.0, 36.0), (200.0, 36.0)),
+            ComponentType::CheckBox(checkbox) => {
+                let label = checkbox
+                    .label
+                    .as_ref()
+                    .map(|l| resolve_string_value_scoped(l, data_model, scope))
+                    .unwrap_or_default();
+                let (label_w, label_h) = text_size(&label, BODY_FONT_SIZE);
+                let (w, h) = (20.0 + 8.0 + label_w, label_h.max(20.0));
+                // The renderer enforces a minimum click area of 200x28
+                (LayoutRect::new(x, y, w.max(200.0), h.max(28.0)), (w, h))
+            }
+            ComponentType::Slider(_) => (LayoutRect::new(x, y, 200.0, 18.0), (200.0, 18.0)),
+            ComponentType::Skeleton(skeleton) => {
+                let h = skeleton.height.unwrap_or(20.0);
+                (LayoutRect::new(x, y, width, h), (width, h))
+            }
+            ComponentType::AudioPlayer(audio_player) => {
+                let (_, title_row_h) = audio_title_row(audio_player.artist.is_some());
+                let button_h = BODY_FONT_SIZE * LINE_HEIGHT + 20.0;
+                let content_h = title_row_h + AUDIO_ROW_GAP + button_h;
+                if self.inside_card {
+                    (LayoutRect::new(x, y, width, content_h), (width, content_h))
+                } else {
+                    let h = content_h + 2.0 * CARD_PADDING.1;
+                    (LayoutRect::new(x, y + 8.0, width, h), (width, h + 16.0))
+                }
+            }
+            ComponentType::Icon(_)
+            | ComponentType::Divider(_)
+            | ComponentType::MultipleChoice(_)
+            | ComponentType::Modal(_)
+            | ComponentType::Tabs(_) => {
+                // Not rendered by A2uiSurface yet
+                self.nodes.truncate(node_idx);
+                self.parent = previous_parent;
+                self.inside_disabled = was_disabled;
+                return None;
+            }
+        };
+
+        self.nodes[node_idx].rect = rect;
+        self.parent = previous_parent;
+        self.inside_disabled = was_disabled;
+        Some(size)
+    }
+
+    /// Lay out children along the flow direction.
+    /// Returns (width, height) of the content and the first node index and
+    /// cross-axis size of every drawn child.
+    fn children(
+        &mut self,
+        children: &ChildrenRef,
+        x: f64,
+        y: f64,
+        width: f64,
+        spacing: f64,
+        horizontal: bool,
+    ) -> ((f64, f64), Vec<(usize, f64)>) {
+        let placed: Vec<(String, Option<String>)> = match children {
+            ChildrenRef::ExplicitList(ids) => ids.iter().map(|id| (id.clone(), None)).collect(),
+            ChildrenRef::Template {
+                component_id,
+                data_binding,
+            } => {
+                let count = self
+                    .data_model
+                    .get_array(data_binding)
+                    .map_or(0, |a| a.len());
+                (0..count)
+                    .map(|index| {
+                        let item_path = format!("{}/{}", data_binding, index);
+                        (component_id.clone(), Some(item_path))
+                    })
+                    .collect()
+            }
+        };
+
+        // Explicit Row children get the Row-specific Column layout, template items don't
+        let row_column_width = matches!(children, ChildrenRef::ExplicitList(_)).then_some(0.0);
+
+        let mut offset = 0.0;
+        let mut cross: f64 = 0.0;
+        let mut drawn = Vec::new();
+        for (id, item_scope) in placed {
+            let previous_scope = self.scope.clone();
+            if item_scope.is_some() {
+                self.scope = item_scope;
+            }
+
+            let gap = if drawn.is_empty() { 0.0 } else { spacing };
+            let start = self.nodes.len();
+            let size = if horizontal {
+                let child_x = x + offset + gap;
+                let remaining = (width - offset - gap).max(0.0);
+                self.component_with_min_width(&id, child_x, y, remaining, row_column_width)
+            } else {
+                self.component(&id, x, y + offset + gap, width)
+            };
+            self.scope = previous_scope;
+
+            if let Some((w, h)) = size {
+                if horizontal {
+                    offset += gap + w;
+                    cross = cross.max(h);
+                    drawn.push((start, h));
+                } else {
+                    offset += gap + h;
+                    cross = cross.max(w);
+                    drawn.push((start, w));
+                }
+            }
+        }
+
+        let size = if horizontal {
+            (offset, cross)
+        } else {
+            (cross, offset)
+        };
+        (size, drawn)
+    }
+
+    /// Lay out a Row (spacing 16, children centered vertically). Returns the height.
+    fn row(&mut self, children: &ChildrenRef, x: f64, y: f64, width: f64) -> f64 {
+        let (height, drawn) = match children {
+            ChildrenRef::ExplicitList(ids) if self.last_is_button(ids) => {
+                // Non-button children get a fixed 280px width so buttons align
+                let mut offset = 0.0;
+                let mut height: f64 = 0.0;
+                let mut drawn = Vec::new();
+                for (i, id) in ids.iter().enumerate() {
+                    let column_width = if i + 1 < ids.len() { 280.0 } else { 0.0 };
+                    let gap = if drawn.is_empty() { 0.0 } else { 16.0 };
+                    let start = self.nodes.len();
+                    let remaining = (width - offset - gap).max(0.0);
+                    let size = self.component_with_min_width(
+                        id,
+                        x + offset + gap,
+                        y,
+                        remaining,
+                        Some(column_width),
+                    );
+                    if let Some((w, h)) = size {
+                        offset += gap + w;
+                        height = height.max(h);
+                        drawn.push((start, h));
+                    }
+                }
+                (height, drawn)
+            }
+            _ => {
+                let ((_, height), drawn) = self.children(children, x, y, width, 16.0, true);
+                (height, drawn)
+            }
+        };
+
+        // Align { y: 0.5 }: shift each child (and its descendants) to the row center
+        for (i, (start, h)) in drawn.iter().enumerate() {
+            let end = drawn.get(i + 1).map_or(self.nodes.len(), |(next, _)| *next);
+            let dy = (height - h) / 2.0;
+            for node in &mut self.nodes[*start..end] {
+                node.rect.y += dy;
+            }
+        }
+        height
+    }
+
+    fn last_is_button(&self, ids: &[String]) -> bool {
+        ids.len() > 1
+            && ids.last().is_some_and(|id| {
+                self.surface
+                    .get_component(id)
+                    .is_some_and(|c| matches!(c.component, ComponentType::Button(_)))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORM_JSON: &str = r#"[
+        {"beginRendering": {"surfaceId": "main", "root": "root"}},
+        {"surfaceUpdate": {"surfaceId": "main", "components": [
+            {"id": "root", "component": {"Column": {"children": {"explicitList": ["title", "name", "agree", "volume", "items", "submit"]}}}},
+            {"id": "title", "component": {"Text": {"text": {"literalString": "Checkout"}, "usageHint": "h1"}}},
+            {"id": "name", "component": {"TextField": {"text": {"path": "/form/name"}}}},
+            {"id": "agree", "component": {"CheckBox": {"value": {"path": "/form/agree"}, "label": {"literalString": "I agree"}}}},
+            {"id": "volume", "component": {"Slider": {"value": {"path": "/form/volume"}, "min": 0, "max": 10}}},
+            {"id": "items", "component": {"List": {"children": {"template": {"componentId": "buy", "dataBinding": "/items"}}}}},
+            {"id": "buy", "component": {"Button": {"child": "buy-label", "action": {"name": "buy", "context": [{"key": "sku", "value": {"path": "sku"}}]}}}},
+            {"id": "buy-label", "component": {"Text": {"text": {"path": "sku"}}}},
+            {"id": "submit", "enabled": {"path": "/form/agree"}, "component": {"Button": {"child": "submit-label", "action": {"name": "submit", "context": [{"key": "name", "value": {"path": "/form/name"}}]}}}},
+            {"id": "submit-label", "component": {"Text": {"text": {"literalString": "Submit"}}}}
+        ]}},
+        {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": [
+            {"key": "form", "valueMap": [
+                {"key": "name", "valueString": ""},
+                {"key": "agree", "valueBoolean": false},
+                {"key": "volume", "valueNumber": 5}
+            ]},
+            {"key": "items", "valueArray": [
+                {"valueMap": [{"key": "sku", "valueString": "A-1"}]},
+                {"valueMap": [{"key": "sku", "valueString": "B-2"}]}
+            ]}
+        ]}}
+    ]"#;
+
+    fn form() -> HeadlessSurface {
+        let mut surface = HeadlessSurface::new(800.0, 600.0);
+        surface.process_json(FORM_JSON).unwrap();
+        surface
+    }
+
+    #[test]
+    fn test_layout_rects() {
+        let surface = form();
+
+        let title = surface.rect("title").unwrap();
+        assert_eq!(
+            title,
+            LayoutRect::new(0.0, 0.0, 8.0 * 28.0 * 0.6, 28.0 * 1.4)
+        );
+
+        let name = surface.rect("name").unwrap();
+        assert_eq!(name.y, title.height + 8.0);
+        assert_eq!((name.width, name.height), (200.0, 36.0));
+
+        assert_eq!(surface.rects("buy").len(), 2);
+        assert!(surface.rect("root").unwrap().width == 800.0);
+        assert!(surface.is_in_viewport("submit"));
+
+        let (x, y) = name.center();
+        assert_eq!(surface.component_at(x, y).unwrap().component_id, "name");
+    }
+
+    #[test]
+    fn test_typing_and_checkbox_update_data_model() {
+        let mut surface = form();
+
+        surface.type_text("name", "Alicf").unwrap();
+        surface.backspace("name", 1).unwrap();
+        surface.type_text("name", "e").unwrap();
+        surface.click("agree").unwrap();
+
+        let data_model = surface.data_model().unwrap();
+        assert_eq!(data_model.get_string("/form/name"), Some("Alice"));
+        assert_eq!(data_model.get_bool("/form/agree"), Some(true));
+
+        let actions = surface.take_actions();
+        assert_eq!(actions.len(), 4);
+        assert!(matches!(
+            &actions[3],
+            A2uiSurfaceAction::DataModelChanged { path, value, .. }
+                if path == "/form/agree" && *value == Value::Bool(true)
+        ));
+    }
+
+    #[test]
+    fn test_disabled_button_ignores_clicks() {
+        let mut surface = form();
+
+        let e = surface.click("submit").unwrap_err();
+        assert!(e.contains("disabled"), "{}", e);
+        assert!(surface.take_actions().is_empty());
+
+        surface.type_text("name", "Bob").unwrap();
+        surface.click("agree").unwrap();
+        surface.click("submit").unwrap();
+
+        let actions = surface.take_user_actions();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action.name, "submit");
+        assert_eq!(actions[0].action.context["name"], Value::from("Bob"));
+    }
+
+    #[test]
+    fn test_template_button_and_slider() {
+        let mut surface = form();
+
+        surface.click_nth("buy", 1).unwrap();
+        let actions = surface.take_user_actions();
+        assert_eq!(actions[0].action.context["sku"], Value::from("B-2"));
+
+        surface.drag_slider("volume", 0.25).unwrap();
+        assert_eq!(
+            surface.data_model().unwrap().get_number("/form/volume"),
+            Some(2.5)
+        );
+
+        assert!(surface.click("missing").is_err());
+        assert!(surface.drag_slider("name", 0.5).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! A2UI Message History
+//!
+//! Keeps a bounded log of the messages a processor applied, the events each one
+//! produced and the processor state right after it. Debugging tools such as the
+//! `A2uiInspector` use it to answer "which message did this" and to rewind the
+//! processor to any earlier point.
+
+use std::collections::VecDeque;
+
+use super::message::A2uiMessage;
+use super::processor::ProcessorEvent;
+use super::recording::now_ms;
+use super::snapshot::ProcessorSnapshot;
+
+/// Default number of entries kept by a history
+pub const DEFAULT_HISTORY_CAPACITY: usize = 256;
+
+/// A single processed message and its outcome
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// Monotonic sequence number (never reused, survives eviction)
+    pub sequence: u64,
+
+    /// Wall-clock time the message was applied, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+
+    /// The applied message
+    pub message: A2uiMessage,
+
+    /// Events the processor emitted for the message
+    pub events: Vec<ProcessorEvent>,
+
+    /// Processor state after the message was applied
+    pub state: ProcessorSnapshot,
+}
+
+impl HistoryEntry {
+    /// One-line description of the message (e.g., "surfaceUpdate main: 3 components")
+    pub fn summary(&self) -> String {
+        describe_message(&self.message)
+    }
+
+    /// Data model paths updated by this message, per surface
+    pub fn updated_paths(&self) -> Vec<(&str, &str)> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                ProcessorEvent::DataModelUpdated(e) => Some(e),
+                _ => None,
+            })
+            .flat_map(|e| {
+                e.updated_paths
+                    .iter()
+                    .map(move |path| (e.surface_id.as_str(), path.as_str()))
+            })
+            .collect()
+    }
+}
+
+/// Bounded history of processed messages with a rewind cursor.
+///
+/// While the cursor points at an older entry the processor shows that entry's
+/// state. Pushing a new entry in that situation discards the entries after
+/// the cursor, like typing after an undo in a text editor. (The processor
+/// returns to the latest entry before applying a message, so its history
+/// keeps them.)
+#[derive(Debug, Clone)]
+pub struct ProcessorHistory {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+    next_sequence: u64,
+    cursor: Option<u64>,
+}
+
+impl ProcessorHistory {
+    /// Create a history keeping at most `capacity` entries (at least one)
+    pub fn new(capacity: usize) -> Self {
+        ProcessorHistory {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+            next_sequence: 0,
+            cursor: None,
+        }
+    }
+
+    /// Maximum number of entries kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of entries currently kept
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the history is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over entries, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+
+    /// Get an entry by sequence number
+    pub fn get(&self, sequence: u64) -> Option<&HistoryEntry> {
+        self.index_of(sequence).map(|index| &self.entries[index])
+    }
+
+    /// The most recent entry
+    pub fn latest(&self) -> Option<&HistoryEntry> {
+        self.entries.back()
+    }
+
+    /// Sequence number of the entry the processor was rewound to, `None` when live
+    pub fn cursor(&self) -> Option<u64> {
+        self.cursor
+    }
+
+    /// Check if the processor currently shows an older state
+    pub fn is_rewound(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// The entry whose state the processor currently shows
+    pub fn current(&self) -> Option<&HistoryEntry> {
+        match self.cursor {
+            Some(sequence) => self.get(sequence),
+            None => self.latest(),
+        }
+    }
+
+    /// Remove all entries (sequence numbers keep counting)
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.cursor = None;
+    }
+
+    /// Record an applied message
+    ///
+    /// If the history was rewound, entries after the cursor are discarded first.
+    pub fn push(
+        &mut self,
+        message: A2uiMessage,
+        events: Vec<ProcessorEvent>,
+        state: ProcessorSnapshot,
+    ) -> u64 {
+        if let Some(cursor) = self.cursor.take() {
+            self.entries.retain(|entry| entry.sequence <= cursor);
+        }
+
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.entries.push_back(HistoryEntry {
+            sequence,
+            timestamp_ms: now_ms(),
+            message,
+            events,
+            state,
+        });
+        sequence
+    }
+
+    /// Move the cursor to an entry and return it
+    ///
+    /// Selecting the latest entry returns the history to live mode.
+    pub fn seek(&mut self, sequence: u64) -> Result<&HistoryEntry, String> {
+        let index = self
+            .index_of(sequence)
+            .ok_or_else(|| format!("No history entry with sequence {}", sequence))?;
+
+        self.cursor = (index + 1 < self.entries.len()).then_some(sequence);
+        Ok(&self.entries[index])
+    }
+
+    /// Position of an entry in the deque (sequence numbers are contiguous)
+    fn index_of(&self, sequence: u64) -> Option<usize> {
+        let first = self.entries.front()?.sequence;
+        let index = usize::try_from(sequence.checked_sub(first)?).ok()?;
+        (index < self.entries.len()).then_some(index)
+    }
+}
+
+impl Default for ProcessorHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+/// One-line description of an A2UI message
+pub fn describe_message(message: &A2uiMessage) -> String {
+    match message {
+        A2uiMessage::BeginRendering(msg) => {
+            format!("beginRendering {} (root: {})", msg.surface_id, msg.root)
+        }
+        A2uiMessage::SurfaceUpdate(msg) => {
+            let ids: Vec<&str> = msg.components.iter().map(|c| c.id.as_str()).collect();
+            format!(
+                "surfaceUpdate {}: {} components [{}]",
+                msg.surface_id,
+                ids.len(),
+                ids.join(", ")
+            )
+        }
+        A2uiMessage::DataModelUpdate(msg) => {
+            let keys: Vec<&str> = msg.contents.iter().map(|c| c.key.as_str()).collect();
+            format!(
+                "dataModelUpdate {} {}: {}",
+                msg.surface_id,
+                msg.path,
+                keys.join(", ")
+            )
+        }
+        A2uiMessage::DeleteSurface(msg) => format!("deleteSurface {}", msg.surface_id),
+        A2uiMessage::UserAction(msg) => {
+            format!("userAction {}: {}", msg.surface_id, msg.action.name)
+        }
+    }
+}
+
+/// One-line description of a processor event
+pub fn describe_event(event: &ProcessorEvent) -> String {
+    match event {
+        ProcessorEvent::SurfaceCreated(e) => format!("SurfaceCreated {}", e.surface_id),
+        ProcessorEvent::SurfaceUpdated(e) => format!(
+            "SurfaceUpdated {} [{}]",
+            e.surface_id,
+            e.updated_components.join(", ")
+        ),
+        ProcessorEvent::SurfaceDeleted(e) => format!("SurfaceDeleted {}", e.surface_id),
+        ProcessorEvent::DataModelUpdated(e) => format!(
+            "DataModelUpdated {} [{}]",
+            e.surface_id,
+            e.updated_paths.join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::message::{DeleteSurface, UserAction, UserActionPayload};
+
+    fn delete(surface_id: &str) -> A2uiMessage {
+        A2uiMessage::DeleteSurface(DeleteSurface {
+            surface_id: surface_id.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_history_capacity_and_lookup() {
+        let mut history = ProcessorHistory::new(2);
+        for id in ["a", "b", "c"] {
+            history.push(delete(id), vec![], ProcessorSnapshot::new());
+        }
+
+        assert_eq!(history.len(), 2);
+        assert!(history.get(0).is_none());
+        assert_eq!(history.get(1).unwrap().summary(), "deleteSurface b");
+        assert_eq!(history.latest().unwrap().sequence, 2);
+    }
+
+    #[test]
+    fn test_history_seek_and_truncate() {
+        let mut history = ProcessorHistory::default();
+        for id in ["a", "b", "c"] {
+            history.push(delete(id), vec![], ProcessorSnapshot::new());
+        }
+
+        history.seek(0).unwrap();
+        assert_eq!(history.cursor(), Some(0));
+        assert_eq!(history.current().unwrap().summary(), "deleteSurface a");
+
+        // Seeking the latest entry returns to live mode
+        history.seek(2).unwrap();
+        assert!(!history.is_rewound());
+        assert!(history.seek(7).is_err());
+
+        // New messages after a rewind drop the entries after the cursor
+        history.seek(1).unwrap();
+        let action = A2uiMessage::UserAction(UserAction {
+            surface_id: "main".to_string(),
+            action: UserActionPayload {
+                name: "submit".to_string(),
+                context: Default::default(),
+            },
+            component_id: None,
+        });
+        let sequence = history.push(action, vec![], ProcessorSnapshot::new());
+
+        let sequences: Vec<u64> = history.entries().map(|e| e.sequence).collect();
+        assert_eq!(sequences, vec![0, 1, sequence]);
+        assert_eq!(sequence, 3);
+        assert_eq!(
+            history.latest().unwrap().summary(),
+            "userAction main: submit"
+        );
+    }
+}
@@ -1,28 +1,28 @@
 //! A2UI Host
 //!
-//! Manages the connection between an A2A agent and the A2uiSurface widget.
-//! Handles streaming, message processing, and user action forwarding.
+//! Manages the connection between an agent and the A2uiSurface widget.
+//! Handles streaming, message processing, and user action forwarding. The
+//! agent is reached through an `A2uiTransport` (an A2A server by default).
 
 use std::collections::HashMap;
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Instant;
 
 use makepad_widgets::*;
 use serde_json::Value;
 
-use super::a2a_client::{A2aClient, A2aStreamEvent, A2aEventStream};
+use super::a2a_client::{A2aStreamEvent, TaskState};
+use super::a2a_part::MessageInput;
+use super::agent_card::AgentCard;
+use super::action_queue::{ActionDeliveryEvent, ActionQueueConfig, ActionWorker};
+use super::data_sync::DataModelChange;
 use super::message::{A2uiMessage, UserAction};
-use super::processor::ProcessorEvent;
+use super::processor::{A2uiMessageProcessor, ProcessorEvent};
+use super::recording::SessionRecorder;
 use super::surface::{A2uiSurface, A2uiSurfaceAction};
-
-/// A2UI Host configuration
-#[derive(Clone, Debug)]
-pub struct A2uiHostConfig {
-    /// A2A server URL
-    pub url: String,
-    /// Optional authentication token
-    pub auth_token: Option<String>,
-}
+use super::transport::{A2aTransport, A2uiEventStream, A2uiHostConfig, A2uiTransport};
 
 /// Events from A2UI host
 #[derive(Clone, Debug)]
@@ -31,47 +31,121 @@ pub enum A2uiHostEvent {
     Connected,
     /// Received A2UI message
     Message(A2uiMessage),
+    /// Text from the agent, for chat transcripts
+    Text(String),
     /// Task status update
-    TaskStatus { task_id: String, state: String },
+    TaskStatus { task_id: String, state: TaskState },
     /// Error occurred
     Error(String),
     /// Disconnected from server
     Disconnected,
+    /// Delivery status of a user action sent with `send_action`
+    ActionDelivery(ActionDeliveryEvent),
+    /// A batch of data model edits couldn't be sent (it is retried with the next batch)
+    DataSyncFailed { surface_id: String, error: String },
+}
+
+/// Background thread sending data sync batches
+struct DataSyncWorker {
+    sender: Sender<DataModelChange>,
+    failures: Receiver<(DataModelChange, String)>,
 }
 
-/// A2UI Host manages streaming connection to an A2A server
-pub struct A2uiHost {
-    config: A2uiHostConfig,
-    client: Option<A2aClient>,
+/// A2UI Host manages streaming connection to an agent
+pub struct A2uiHost<T: A2uiTransport = A2aTransport> {
+    transport: Arc<Mutex<T>>,
+    task_id: Arc<Mutex<Option<String>>>,
     event_receiver: Option<Receiver<A2uiHostEvent>>,
     event_sender: Option<Sender<A2uiHostEvent>>,
+    /// Results of task requests (e.g., `stop`) made from background threads
+    task_event_sender: Sender<A2uiHostEvent>,
+    task_event_receiver: Receiver<A2uiHostEvent>,
     is_connected: bool,
     pending_messages: Vec<A2uiMessage>,
+    recorder: Option<SessionRecorder>,
+    action_config: ActionQueueConfig,
+    action_worker: Option<ActionWorker>,
+    data_sync_worker: Option<DataSyncWorker>,
+    failed_data_changes: Vec<DataModelChange>,
 }
 
-impl A2uiHost {
-    /// Create a new A2UI host with the given configuration
+impl A2uiHost<A2aTransport> {
+    /// Create a new A2UI host for the A2A server in the given configuration
     pub fn new(config: A2uiHostConfig) -> Self {
+        Self::with_transport(A2aTransport::new(config))
+    }
+
+    /// Fetch the agent card and check that the agent supports A2UI
+    ///
+    /// `config.url` may be the agent's base URL instead of its RPC endpoint.
+    /// On success `connect` uses the JSON-RPC endpoint from the card.
+    pub fn discover(&mut self) -> Result<AgentCard, String> {
+        // Fetch without holding the transport, which workers may be using
+        let url = self
+            .transport
+            .lock()
+            .map_err(|_| "Transport lock poisoned")?
+            .config()
+            .url
+            .clone();
+        let card = AgentCard::fetch(&url)?;
+
+        let mut transport = self.transport.lock().map_err(|_| "Transport lock poisoned")?;
+        transport.use_agent_card(card).cloned()
+    }
+
+    /// Agent card fetched by `discover`
+    pub fn agent_card(&self) -> Option<AgentCard> {
+        self.transport.lock().ok()?.agent_card().cloned()
+    }
+}
+
+impl<T: A2uiTransport> A2uiHost<T> {
+    /// Create a new A2UI host talking to its agent through `transport`
+    pub fn with_transport(transport: T) -> Self {
         let (tx, rx) = mpsc::channel();
+        let (task_tx, task_rx) = mpsc::channel();
         A2uiHost {
-            config,
-            client: None,
+            transport: Arc::new(Mutex::new(transport)),
+            task_id: Arc::new(Mutex::new(None)),
             event_receiver: Some(rx),
             event_sender: Some(tx),
+            task_event_sender: task_tx,
+            task_event_receiver: task_rx,
             is_connected: false,
             pending_messages: Vec::new(),
+            recorder: None,
+            action_config: ActionQueueConfig::default(),
+            action_worker: None,
+            data_sync_worker: None,
+            failed_data_changes: Vec::new(),
         }
     }
 
-    /// Connect to the A2A server and send initial message
-    pub fn connect(&mut self, initial_message: &str) -> Result<(), String> {
-        let mut client = A2aClient::new(&self.config.url);
-        if let Some(token) = &self.config.auth_token {
-            client = client.with_auth(token);
-        }
+    /// Configure debouncing, retries and persistence of outbound actions
+    ///
+    /// Takes effect when the first action is sent.
+    pub fn set_action_queue_config(&mut self, config: ActionQueueConfig) {
+        self.action_config = config;
+    }
+
+    /// Attach a session recorder (or detach with `None`)
+    ///
+    /// Records every received message as inbound and every sent action as outbound.
+    pub fn set_recorder(&mut self, recorder: Option<SessionRecorder>) {
+        self.recorder = recorder;
+    }
 
+    /// Connect to the agent and send initial message
+    ///
+    /// The message can be plain text or a `MessageInput` with files attached.
+    pub fn connect(&mut self, initial_message: impl Into<MessageInput>) -> Result<(), String> {
         // Start streaming
-        let stream = client.message_stream(initial_message)?;
+        let stream = self
+            .transport
+            .lock()
+            .map_err(|_| "Transport lock poisoned")?
+            .connect(initial_message.into())?;
 
         // Take sender for background thread
         let tx = self.event_sender.take().ok_or("Already connected")?;
@@ -81,74 +155,90 @@ impl A2uiHost {
             Self::process_stream(stream, tx);
         });
 
-        self.client = Some(client);
-        self.is_connected = true;
+        self.set_connected(true);
 
         Ok(())
     }
 
-    /// Connect to SSE endpoint using GET (for /live real-time streaming)
-    pub fn connect_sse(&mut self) -> Result<(), String> {
-        use super::sse::{SseClient, SseEvent};
-
-        let mut sse_client = SseClient::new(&self.config.url);
-        if let Some(token) = &self.config.auth_token {
-            sse_client = sse_client.auth(token);
+    /// Resume streaming the current task after its stream ended (`tasks/resubscribe`)
+    pub fn resubscribe(&mut self) -> Result<(), String> {
+        if self.is_connected {
+            return Err("Already connected".to_string());
         }
+        let task_id = self.task_id().ok_or("No task to resubscribe to")?;
 
-        // Start GET SSE stream
-        let rx = sse_client.get()?;
-
-        // Take sender for background thread
-        let tx = self.event_sender.take().ok_or("Already connected")?;
+        let stream = self
+            .transport
+            .lock()
+            .map_err(|_| "Transport lock poisoned")?
+            .resubscribe(&task_id)?;
 
-        // Spawn thread to process SSE events
+        let (tx, rx) = mpsc::channel();
+        self.event_receiver = Some(rx);
         thread::spawn(move || {
-            let _ = tx.send(A2uiHostEvent::Connected);
-
-            while let Ok(event) = rx.recv() {
-                match event {
-                    SseEvent::Data(data) => {
-                        // Parse A2UI messages from SSE data
-                        if let Ok(messages) = serde_json::from_str::<Vec<serde_json::Value>>(&data) {
-                            for msg_value in messages {
-                                if let Ok(msg) = serde_json::from_value::<A2uiMessage>(msg_value) {
-                                    if tx.send(A2uiHostEvent::Message(msg)).is_err() {
-                                        return;
-                                    }
-                                }
-                            }
-                        } else if let Ok(msg) = serde_json::from_str::<A2uiMessage>(&data) {
-                            if tx.send(A2uiHostEvent::Message(msg)).is_err() {
-                                return;
-                            }
-                        }
-                    }
-                    SseEvent::Error(e) => {
-                        let _ = tx.send(A2uiHostEvent::Error(e));
-                    }
-                    SseEvent::Done => {
-                        break;
-                    }
-                    _ => {}
-                }
-            }
+            Self::process_stream(stream, tx);
+        });
+
+        self.set_connected(true);
+        Ok(())
+    }
+
+    /// Stop the agent's current task (`tasks/cancel`)
+    ///
+    /// Fails right away if the transport can't cancel tasks (e.g., an A2A
+    /// agent over WebSocket). Otherwise the request is sent from a background
+    /// thread, and the outcome is reported as an `A2uiHostEvent::TaskStatus`
+    /// with the state the agent returned, or as an `A2uiHostEvent::Error`. The
+    /// stream ends when the agent closes it.
+    pub fn stop(&mut self) -> Result<(), String> {
+        let task_id = self.task_id().ok_or("No active task to stop")?;
+        // Workers only hold the transport to prepare requests, so this is quick
+        let request = self
+            .transport
+            .lock()
+            .map_err(|_| "Transport lock poisoned")?
+            .cancel_request(&task_id)?;
+        let tx = self.task_event_sender.clone();
 
-            let _ = tx.send(A2uiHostEvent::Disconnected);
+        thread::spawn(move || {
+            let event = match request() {
+                Ok(task) => A2uiHostEvent::TaskStatus {
+                    task_id: task.id,
+                    state: task.status.state,
+                },
+                Err(e) => A2uiHostEvent::Error(format!("Failed to cancel task: {}", e)),
+            };
+            let _ = tx.send(event);
         });
 
-        self.is_connected = true;
         Ok(())
     }
 
-    fn process_stream(mut stream: A2aEventStream, tx: Sender<A2uiHostEvent>) {
+    /// ID of the agent task this host talks to, once the agent reported it
+    pub fn task_id(&self) -> Option<String> {
+        self.task_id.lock().unwrap().clone()
+    }
+
+    fn set_connected(&mut self, connected: bool) {
+        self.is_connected = connected;
+        // A new connection retries actions waiting for backoff right away.
+        // The end of a stream doesn't pause delivery: actions are separate requests.
+        if connected {
+            if let Some(worker) = &self.action_worker {
+                worker.set_online(true);
+            }
+        }
+    }
+
+    fn process_stream(stream: A2uiEventStream, tx: Sender<A2uiHostEvent>) {
         // Send connected event
         let _ = tx.send(A2uiHostEvent::Connected);
 
         // Process events
-        while let Some(event) = stream.next() {
+        for event in stream {
             let host_event = match event {
                 A2aStreamEvent::A2uiMessage(msg) => A2uiHostEvent::Message(msg),
+                A2aStreamEvent::Text(text) => A2uiHostEvent::Text(text),
                 A2aStreamEvent::TaskStatus { task_id, state } => {
                     A2uiHostEvent::TaskStatus { task_id, state }
                 }
@@ -167,15 +257,39 @@ impl A2uiHost {
 
     /// Poll for pending events (non-blocking)
     pub fn poll(&mut self) -> Option<A2uiHostEvent> {
+        if let Some(event) = self.action_worker.as_ref().and_then(|w| w.try_event()) {
+            return Some(A2uiHostEvent::ActionDelivery(event));
+        }
+
+        if let Ok(event) = self.task_event_receiver.try_recv() {
+            return Some(event);
+        }
+
+        if let Some(worker) = &self.data_sync_worker {
+            if let Ok((change, error)) = worker.failures.try_recv() {
+                let surface_id = change.surface_id.clone();
+                self.failed_data_changes.push(change);
+                return Some(A2uiHostEvent::DataSyncFailed { surface_id, error });
+            }
+        }
+
         if let Some(rx) = &self.event_receiver {
             match rx.try_recv() {
                 Ok(event) => {
                     // If it's a message, also store it
                     if let A2uiHostEvent::Message(ref msg) = event {
+                        if let Some(recorder) = &self.recorder {
+                            recorder.record_inbound(msg);
+                        }
                         self.pending_messages.push(msg.clone());
                     }
+                    // Actions are addressed to the task the agent created
+                    // (the transport itself may be busy sending on the action thread)
+                    if let A2uiHostEvent::TaskStatus { task_id, .. } = &event {
+                        *self.task_id.lock().unwrap() = Some(task_id.clone());
+                    }
                     if let A2uiHostEvent::Disconnected = event {
-                        self.is_connected = false;
+                        self.set_connected(false);
                         // Clear receiver to prevent returning Disconnected repeatedly
                         self.event_receiver = None;
                     }
@@ -183,7 +297,7 @@ impl A2uiHost {
                 }
                 Err(TryRecvError::Empty) => None,
                 Err(TryRecvError::Disconnected) => {
-                    self.is_connected = false;
+                    self.set_connected(false);
                     // Clear receiver to prevent returning Disconnected repeatedly
                     self.event_receiver = None;
                     Some(A2uiHostEvent::Disconnected)
@@ -203,17 +317,90 @@ impl A2uiHost {
         events
     }
 
-    /// Send a user action to the server
+    /// Queue a user action for delivery to the server
+    ///
+    /// Actions are sent from a background thread; delivery status is reported
+    /// through `A2uiHostEvent::ActionDelivery` events. Actions that can't be
+    /// delivered (e.g., while disconnected) are kept and retried with backoff.
     pub fn send_action(&mut self, action: &UserAction) -> Result<(), String> {
-        if let Some(client) = &mut self.client {
-            let component_id = action.component_id.as_deref().unwrap_or("");
-            client.send_action(
-                &action.action.name,
-                component_id,
-                action.action.context.clone(),
+        if let Some(recorder) = &self.recorder {
+            recorder.record_outbound(action);
+        }
+
+        let worker = self.action_worker.get_or_insert_with(|| {
+            let transport = Arc::clone(&self.transport);
+            let task_id = Arc::clone(&self.task_id);
+            ActionWorker::spawn(
+                self.action_config.clone(),
+                true,
+                move |action| {
+                    // Prepare under the lock, send after releasing it
+                    let request = {
+                        let mut transport =
+                            transport.lock().map_err(|_| "Transport lock poisoned")?;
+                        if let Some(task_id) = task_id.lock().unwrap().clone() {
+                            transport.set_task_id(task_id);
+                        }
+                        transport.action_request(action)?
+                    };
+                    request()
+                },
             )
-        } else {
-            Err("Not connected".to_string())
+        });
+        worker.send(action.clone())
+    }
+
+    /// Send the processor's due data sync batches to the server
+    ///
+    /// Does nothing unless data sync is enabled on the processor (see
+    /// `A2uiMessageProcessor::set_data_sync`). Batches that failed to send are
+    /// handed back to the processor first, so they go out with the next batch.
+    /// Call it regularly, e.g. whenever host events are polled.
+    pub fn sync_data(&mut self, processor: &mut A2uiMessageProcessor) {
+        let Some(data_sync) = processor.data_sync_mut() else {
+            return;
+        };
+        for change in self.failed_data_changes.drain(..) {
+            data_sync.requeue(change);
+        }
+
+        let changes = processor.take_data_changes(Instant::now());
+        if changes.is_empty() {
+            return;
+        }
+
+        let worker = self.data_sync_worker.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel::<DataModelChange>();
+            let (failure_tx, failure_rx) = mpsc::channel();
+            let transport = Arc::clone(&self.transport);
+            let task_id = Arc::clone(&self.task_id);
+            thread::spawn(move || {
+                while let Ok(change) = rx.recv() {
+                    // Prepare under the lock, send after releasing it
+                    let request = match transport.lock() {
+                        Ok(mut transport) => {
+                            if let Some(task_id) = task_id.lock().unwrap().clone() {
+                                transport.set_task_id(task_id);
+                            }
+                            transport.data_change_request(&change)
+                        }
+                        Err(_) => Err("Transport lock poisoned".to_string()),
+                    };
+                    let result = request.and_then(|request| request());
+                    if let Err(error) = result {
+                        if failure_tx.send((change, error)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+            DataSyncWorker {
+                sender: tx,
+                failures: failure_rx,
+            }
+        });
+        for change in changes {
+            let _ = worker.sender.send(change);
         }
     }
 
@@ -229,8 +416,8 @@ impl A2uiHost {
 }
 
 /// Helper function to process A2UI host events and update the surface
-pub fn process_host_events(
-    host: &mut A2uiHost,
+pub fn process_host_events<T: A2uiTransport>(
+    host: &mut A2uiHost<T>,
     surface: &mut A2uiSurface,
 ) -> Vec<ProcessorEvent> {
     let mut all_events = Vec::new();
@@ -245,6 +432,9 @@ pub fn process_host_events(
                 let processor_events = surface.process_message(msg);
                 all_events.extend(processor_events);
             }
+            A2uiHostEvent::Text(text) => {
+                log!("A2UI Agent: {}", text);
+            }
             A2uiHostEvent::Error(e) => {
                 log!("A2UI Host Error: {}", e);
             }
@@ -257,6 +447,21 @@ pub fn process_host_events(
             A2uiHostEvent::TaskStatus { task_id, state } => {
                 log!("A2UI Task {}: {}", task_id, state);
             }
+            A2uiHostEvent::ActionDelivery(event) => {
+                log!("A2UI Action delivery: {:?}", event);
+            }
+            A2uiHostEvent::DataSyncFailed { surface_id, error } => {
+                log!("A2UI Data sync failed for {}: {}", surface_id, error);
+            }
+        }
+    }
+
+    if let Some(processor) = surface.processor_mut() {
+        host.sync_data(processor);
+        if let Some(data_sync) = processor.data_sync_mut() {
+            for (conflict, resolution) in data_sync.take_conflicts() {
+                log!("A2UI Data conflict at {}: {:?}", conflict.path, resolution);
+            }
         }
     }
 
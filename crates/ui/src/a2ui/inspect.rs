@@ -0,0 +1,406 @@
+//! A2UI Inspection Helpers
+//!
+//! Flattens a surface's component tree and data model into rows for debugging
+//! tools such as the `A2uiInspector` panel. Bindings are resolved against the
+//! data model the same way the `A2uiSurface` renderer resolves them.
+
+use serde_json::Value;
+
+use super::data_model::DataModel;
+use super::message::*;
+use super::processor::{
+    resolve_component_enabled_scoped, resolve_component_visible_scoped, resolve_path, Surface,
+};
+use super::registry::component_type_of;
+use super::value::{BooleanValue, NumberValue, StringValue};
+
+/// A component in the flattened component tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentTreeRow {
+    /// Nesting depth (the root component is 0)
+    pub depth: usize,
+
+    /// Component ID
+    pub component_id: String,
+
+    /// Component type name, `None` if the ID is referenced but not defined
+    pub component_type: Option<&'static str>,
+
+    /// Template item scope (e.g., "/products/0")
+    pub scope: Option<String>,
+
+    /// Resolved `visible` condition
+    pub visible: bool,
+
+    /// Resolved `enabled` condition
+    pub enabled: bool,
+
+    /// Resolved property bindings
+    pub bindings: Vec<ResolvedBinding>,
+}
+
+/// A component property and its resolved value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedBinding {
+    /// Property name (e.g., "text")
+    pub property: &'static str,
+
+    /// Absolute data model path for bound properties, `None` for literals
+    pub path: Option<String>,
+
+    /// Resolved value (`Null` when a bound path is missing)
+    pub value: Value,
+}
+
+impl ComponentTreeRow {
+    /// One-line description, e.g. `Text title  text="Alice" <- /user/name`
+    pub fn label(&self) -> String {
+        let mut label = format!(
+            "{} {}",
+            self.component_type.unwrap_or("<missing>"),
+            self.component_id
+        );
+        if let Some(scope) = &self.scope {
+            label.push_str(&format!(" @{}", scope));
+        }
+        if !self.visible {
+            label.push_str(" (hidden)");
+        }
+        if !self.enabled {
+            label.push_str(" (disabled)");
+        }
+        for binding in &self.bindings {
+            label.push_str(&format!("  {}={}", binding.property, binding.value));
+            if let Some(path) = &binding.path {
+                label.push_str(&format!(" <- {}", path));
+            }
+        }
+        label
+    }
+}
+
+/// A line of the data model JSON tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataModelRow {
+    /// Nesting depth (top-level keys are 0)
+    pub depth: usize,
+
+    /// JSON Pointer path of the value
+    pub path: String,
+
+    /// Display text, e.g. `name: "Alice"` or `items: [3]`
+    pub text: String,
+
+    /// Whether the path is dirty in the data model
+    pub dirty: bool,
+}
+
+/// Flatten a surface's component tree, starting at its root
+///
+/// Template children are expanded once per data item, like the renderer does.
+/// Components referenced from their own subtree are listed but not descended into.
+pub fn component_tree_rows(surface: &Surface, data_model: &DataModel) -> Vec<ComponentTreeRow> {
+    let mut rows = Vec::new();
+    let mut ancestors = Vec::new();
+    if !surface.root.is_empty() {
+        collect_component_rows(
+            surface,
+            data_model,
+            &surface.root,
+            None,
+            0,
+            &mut ancestors,
+            &mut rows,
+        );
+    }
+    rows
+}
+
+/// Flatten a data model into rows, marking dirty paths
+pub fn data_model_rows(data_model: &DataModel) -> Vec<DataModelRow> {
+    let mut rows = Vec::new();
+    collect_value_rows(data_model, data_model.as_value(), "", 0, &mut rows);
+    rows
+}
+
+fn collect_component_rows<'a>(
+    surface: &'a Surface,
+    data_model: &DataModel,
+    component_id: &'a str,
+    scope: Option<&str>,
+    depth: usize,
+    ancestors: &mut Vec<&'a str>,
+    rows: &mut Vec<ComponentTreeRow>,
+) {
+    let Some(component) = surface.get_component(component_id) else {
+        rows.push(ComponentTreeRow {
+            depth,
+            component_id: component_id.to_string(),
+            component_type: None,
+            scope: scope.map(str::to_string),
+            visible: false,
+            enabled: false,
+            bindings: Vec::new(),
+        });
+        return;
+    };
+
+    rows.push(ComponentTreeRow {
+        depth,
+        component_id: component_id.to_string(),
+        component_type: Some(component_type_of(&component.component).name()),
+        scope: scope.map(str::to_string),
+        visible: resolve_component_visible_scoped(component, data_model, scope),
+        enabled: resolve_component_enabled_scoped(component, data_model, scope),
+        bindings: component_bindings(&component.component, data_model, scope),
+    });
+
+    if ancestors.contains(&component_id) {
+        return;
+    }
+
+    ancestors.push(component_id);
+    for (child_id, child_scope) in child_refs(&component.component, data_model, scope) {
+        collect_component_rows(
+            surface,
+            data_model,
+            child_id,
+            child_scope.as_deref(),
+            depth + 1,
+            ancestors,
+            rows,
+        );
+    }
+    ancestors.pop();
+}
+
+/// Child component IDs with the scope each one renders in
+fn child_refs<'a>(
+    component: &'a ComponentType,
+    data_model: &DataModel,
+    scope: Option<&str>,
+) -> Vec<(&'a str, Option<String>)> {
+    let inherited = || scope.map(str::to_string);
+    let from_children = |children: &'a ChildrenRef| match children {
+        ChildrenRef::ExplicitList(ids) => ids
+            .iter()
+            .map(|id| (id.as_str(), inherited()))
+            .collect::<Vec<_>>(),
+        ChildrenRef::Template {
+            component_id,
+            data_binding,
+        } => {
+            let count = data_model.get_array(data_binding).map_or(0, |a| a.len());
+            (0..count)
+                .map(|index| {
+                    (
+                        component_id.as_str(),
+                        Some(format!("{}/{}", data_binding, index)),
+                    )
+                })
+                .collect()
+        }
+    };
+
+    match component {
+        ComponentType::Column(c) => from_children(&c.children),
+        ComponentType::Row(c) => from_children(&c.children),
+        ComponentType::List(c) => from_children(&c.children),
+        ComponentType::Modal(c) => from_children(&c.children),
+        ComponentType::Card(c) => vec![(c.child.as_str(), inherited())],
+        ComponentType::Button(c) => vec![(c.child.as_str(), inherited())],
+        ComponentType::Tabs(c) => c
+            .tabs
+            .iter()
+            .map(|tab| (tab.content.as_str(), inherited()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Resolved data-bindable properties of a component
+fn component_bindings(
+    component: &ComponentType,
+    data_model: &DataModel,
+    scope: Option<&str>,
+) -> Vec<ResolvedBinding> {
+    let string = |property, value: &StringValue| match value {
+        StringValue::Literal { literal_string } => literal(property, literal_string.as_str()),
+        StringValue::Path { path } => bound(property, path, data_model, scope),
+    };
+    let number = |property, value: &NumberValue| match value {
+        NumberValue::Literal { literal_number } => literal(property, *literal_number),
+        NumberValue::Path { path } => bound(property, path, data_model, scope),
+    };
+    let boolean = |property, value: &BooleanValue| match value {
+        BooleanValue::Literal { literal_boolean } => literal(property, *literal_boolean),
+        BooleanValue::Path { path } => bound(property, path, data_model, scope),
+    };
+
+    let mut bindings = Vec::new();
+    match component {
+        ComponentType::Text(c) => bindings.push(string("text", &c.text)),
+        ComponentType::Image(c) => bindings.push(string("url", &c.url)),
+        ComponentType::Icon(c) => bindings.push(string("name", &c.name)),
+        ComponentType::Button(c) => {
+            if let Some(action) = &c.action {
+                bindings.push(literal("action", action.name.as_str()));
+            }
+        }
+        ComponentType::TextField(c) => {
+            bindings.push(string("text", &c.text));
+            bindings.extend(c.label.as_ref().map(|v| string("label", v)));
+            bindings.extend(c.placeholder.as_ref().map(|v| string("placeholder", v)));
+        }
+        ComponentType::CheckBox(c) => {
+            bindings.push(boolean("value", &c.value));
+            bindings.extend(c.label.as_ref().map(|v| string("label", v)));
+        }
+        ComponentType::Slider(c) => bindings.push(number("value", &c.value)),
+        ComponentType::MultipleChoice(c) => bindings.push(string("value", &c.value)),
+        ComponentType::Modal(c) => bindings.push(boolean("visible", &c.visible)),
+        ComponentType::Tabs(c) => {
+            bindings.extend(c.selected.as_ref().map(|v| string("selected", v)));
+        }
+        ComponentType::AudioPlayer(c) => {
+            bindings.push(string("url", &c.url));
+            bindings.extend(c.title.as_ref().map(|v| string("title", v)));
+            bindings.extend(c.artist.as_ref().map(|v| string("artist", v)));
+        }
+        ComponentType::Column(_)
+        | ComponentType::Row(_)
+        | ComponentType::List(_)
+        | ComponentType::Card(_)
+        | ComponentType::Divider(_)
+        | ComponentType::Skeleton(_) => {}
+    }
+    bindings
+}
+
+fn literal(property: &'static str, value: impl Into<Value>) -> ResolvedBinding {
+    ResolvedBinding {
+        property,
+        path: None,
+        value: value.into(),
+    }
+}
+
+fn bound(
+    property: &'static str,
+    path: &str,
+    data_model: &DataModel,
+    scope: Option<&str>,
+) -> ResolvedBinding {
+    let path = resolve_path(path, scope);
+    ResolvedBinding {
+        property,
+        value: data_model.get(&path).cloned().unwrap_or(Value::Null),
+        path: Some(path),
+    }
+}
+
+fn collect_value_rows(
+    data_model: &DataModel,
+    value: &Value,
+    path: &str,
+    depth: usize,
+    rows: &mut Vec<DataModelRow>,
+) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        _ => return,
+    };
+
+    for (key, child) in children {
+        let child_path = format!("{}/{}", path, key);
+        let text = match child {
+            Value::Object(map) => format!("{}: {{{}}}", key, map.len()),
+            Value::Array(items) => format!("{}: [{}]", key, items.len()),
+            scalar => format!("{}: {}", key, scalar),
+        };
+        rows.push(DataModelRow {
+            depth,
+            dirty: data_model.is_dirty(&child_path),
+            path: child_path.clone(),
+            text,
+        });
+        collect_value_rows(data_model, child, &child_path, depth + 1, rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::processor::A2uiMessageProcessor;
+
+    const TREE_JSON: &str = r#"[
+        {"beginRendering": {"surfaceId": "main", "root": "root"}},
+        {"surfaceUpdate": {"surfaceId": "main", "components": [
+            {"id": "root", "component": {"Column": {"children": {"explicitList": ["title", "list", "ghost"]}}}},
+            {"id": "title", "component": {"Text": {"text": {"path": "/title"}}}},
+            {"id": "list", "component": {"List": {"children": {"template": {"componentId": "item", "dataBinding": "/items"}}}}},
+            {"id": "item", "visible": {"path": "shown"}, "component": {"Text": {"text": {"path": "name"}}}}
+        ]}},
+        {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": [
+            {"key": "title", "valueString": "Shop"},
+            {"key": "items", "valueArray": [
+                {"valueMap": [{"key": "name", "valueString": "Mouse"}, {"key": "shown", "valueBoolean": true}]},
+                {"valueMap": [{"key": "name", "valueString": "Keyboard"}]}
+            ]}
+        ]}}
+    ]"#;
+
+    #[test]
+    fn test_component_tree_rows() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor.process_json(TREE_JSON).unwrap();
+
+        let surface = processor.get_surface("main").unwrap();
+        let data_model = processor.get_data_model("main").unwrap();
+        let rows = component_tree_rows(surface, data_model);
+
+        let ids: Vec<(usize, &str)> = rows
+            .iter()
+            .map(|r| (r.depth, r.component_id.as_str()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                (0, "root"),
+                (1, "title"),
+                (1, "list"),
+                (2, "item"),
+                (2, "item"),
+                (1, "ghost")
+            ]
+        );
+
+        assert_eq!(rows[1].label(), r#"Text title  text="Shop" <- /title"#);
+        assert_eq!(rows[3].scope.as_deref(), Some("/items/0"));
+        assert_eq!(rows[3].bindings[0].value, Value::from("Mouse"));
+        assert!(rows[3].visible);
+        assert!(!rows[4].visible);
+        assert_eq!(rows[5].component_type, None);
+    }
+
+    #[test]
+    fn test_data_model_rows_mark_dirty_paths() {
+        let mut model = DataModel::with_data(serde_json::json!({
+            "user": {"name": "Alice"},
+            "items": [1, 2]
+        }));
+        model.set("/user/name", Value::from("Bob"));
+
+        let rows = data_model_rows(&model);
+        let texts: Vec<(&str, bool)> = rows.iter().map(|r| (r.text.as_str(), r.dirty)).collect();
+        assert!(texts.contains(&("user: {1}", true)));
+        assert!(texts.contains(&(r#"name: "Bob""#, true)));
+        assert!(texts.contains(&("items: [2]", false)));
+        assert!(texts.contains(&("1: 2", false)));
+    }
+}
@@ -0,0 +1,470 @@
+//! A2UI Inspector Widget
+//!
+//! A debug panel meant to be docked next to an `A2uiSurface`. It lists the
+//! processed messages with the events they produced, the component tree with
+//! resolved bindings, and the live data model with dirty paths highlighted.
+//! Clicking a message asks the app to rewind the processor to that point.
+
+use makepad_widgets::*;
+
+use super::{
+    history::describe_event,
+    inspect::{component_tree_rows, data_model_rows},
+    processor::{A2uiMessageProcessor, ProcessorEvent},
+};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+
+    // A2UI Inspector - time-travel debug panel for an A2uiSurface
+    pub A2uiInspector = {{A2uiInspector}} {
+        width: Fill
+        height: Fit
+        flow: Down
+        padding: 10.0
+        spacing: 2.0
+
+        surface_id: "main"
+        indent: 12.0
+        max_line_chars: 96
+
+        row_color: #00000000
+        row_color_hover: #24243c
+        row_color_selected: #2d4f8a
+        row_color_highlight: #5a4a1a
+
+        draw_bg: {
+            instance bg_color: #11111f
+
+            fn pixel(self) -> vec4 {
+                return self.bg_color;
+            }
+        }
+
+        // Section headings
+        draw_heading: {
+            text_style: <THEME_FONT_BOLD> { font_size: 12.0 }
+            color: #ccccdd
+        }
+
+        // Row background (selection, hover and highlight)
+        draw_row: {
+            color: #00000000
+        }
+
+        // Row text
+        draw_row_text: {
+            text_style: <THEME_FONT_CODE> { font_size: 10.0 }
+            color: #bbbbcc
+        }
+
+        // Secondary text (processor events, empty-state hints)
+        draw_detail_text: {
+            text_style: <THEME_FONT_CODE> { font_size: 9.0 }
+            color: #777788
+        }
+    }
+}
+
+/// Actions emitted by A2uiInspector widget
+#[derive(Clone, Debug, DefaultNone)]
+pub enum A2uiInspectorAction {
+    None,
+    /// A message was clicked; rewind the processor to this history sequence number
+    Rewind(u64),
+}
+
+/// A processed message as shown in the message list
+#[derive(Clone, Debug)]
+struct MessageLine {
+    sequence: u64,
+    summary: String,
+    events: Vec<String>,
+}
+
+/// A component tree or data model line
+#[derive(Clone, Debug)]
+struct TreeLine {
+    depth: usize,
+    text: String,
+    highlight: bool,
+}
+
+/// Debug panel showing the message history, component tree and data model
+/// of a processor.
+#[derive(Live, LiveHook, Widget)]
+pub struct A2uiInspector {
+    #[redraw]
+    #[live]
+    draw_bg: DrawQuad,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    /// Draw section headings
+    #[live]
+    draw_heading: DrawText,
+
+    /// Draw row backgrounds
+    #[redraw]
+    #[live]
+    draw_row: DrawColor,
+
+    /// Draw row text
+    #[live]
+    draw_row_text: DrawText,
+
+    /// Draw secondary text
+    #[live]
+    draw_detail_text: DrawText,
+
+    /// Surface whose component tree and data model are shown
+    #[live]
+    surface_id: ArcStringMut,
+
+    /// Indentation per tree level
+    #[live(12.0)]
+    indent: f64,
+
+    /// Lines longer than this are truncated
+    #[live(96usize)]
+    max_line_chars: usize,
+
+    #[live]
+    row_color: Vec4,
+    #[live]
+    row_color_hover: Vec4,
+    #[live]
+    row_color_selected: Vec4,
+    #[live]
+    row_color_highlight: Vec4,
+
+    /// Processed messages, oldest first (`None` when history is disabled)
+    #[rust]
+    messages: Option<Vec<MessageLine>>,
+
+    /// Sequence number of the message whose state is shown
+    #[rust]
+    selected_sequence: Option<u64>,
+
+    /// Flattened component tree
+    #[rust]
+    component_lines: Vec<TreeLine>,
+
+    /// Flattened data model
+    #[rust]
+    data_lines: Vec<TreeLine>,
+
+    /// Processor state the panel was last built from
+    #[rust]
+    fingerprint: Option<(u64, u64, Option<u64>, Option<u64>)>,
+
+    /// Click areas for message rows
+    #[rust]
+    message_areas: Vec<Area>,
+
+    #[rust]
+    hovered_message_idx: Option<usize>,
+}
+
+impl Widget for A2uiInspector {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let mut needs_redraw = false;
+        let message_count = self.messages.as_ref().map_or(0, |m| m.len());
+
+        for (idx, area) in self.message_areas.iter().enumerate().take(message_count) {
+            match event.hits(cx, *area) {
+                Hit::FingerHoverIn(_) => {
+                    self.hovered_message_idx = Some(idx);
+                    cx.set_cursor(MouseCursor::Hand);
+                    needs_redraw = true;
+                }
+                Hit::FingerHoverOut(_) => {
+                    if self.hovered_message_idx == Some(idx) {
+                        self.hovered_message_idx = None;
+                        cx.set_cursor(MouseCursor::Default);
+                        needs_redraw = true;
+                    }
+                }
+                Hit::FingerDown(_) => {
+                    // Must handle FingerDown to receive FingerUp
+                }
+                Hit::FingerUp(fe) => {
+                    if fe.is_over {
+                        let sequence = self
+                            .messages
+                            .as_ref()
+                            .and_then(|m| m.get(idx))
+                            .map(|m| m.sequence);
+                        if let Some(sequence) = sequence {
+                            cx.widget_action(
+                                self.widget_uid(),
+                                &scope.path,
+                                A2uiInspectorAction::Rewind(sequence),
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if needs_redraw {
+            self.redraw(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.draw_bg.begin(cx, walk, self.layout);
+
+        // Messages
+        let messages = self.messages.take();
+        match &messages {
+            Some(messages) => {
+                self.draw_section_heading(cx, &format!("Messages ({})", messages.len()));
+                if messages.is_empty() {
+                    self.draw_hint(cx, "No messages processed yet");
+                }
+                for (idx, message) in messages.iter().enumerate() {
+                    self.draw_message(cx, idx, message);
+                }
+                self.message_areas.truncate(messages.len());
+            }
+            None => {
+                self.draw_section_heading(cx, "Messages");
+                self.draw_hint(cx, "History disabled (A2uiSurface::enable_history)");
+                self.message_areas.clear();
+            }
+        }
+        self.messages = messages;
+
+        // Component tree
+        self.draw_section_heading(cx, "Components");
+        if self.component_lines.is_empty() {
+            self.draw_hint(cx, "No surface");
+        }
+        let component_lines = std::mem::take(&mut self.component_lines);
+        for line in &component_lines {
+            self.draw_line(cx, line);
+        }
+        self.component_lines = component_lines;
+
+        // Data model
+        self.draw_section_heading(cx, "Data Model");
+        if self.data_lines.is_empty() {
+            self.draw_hint(cx, "Empty");
+        }
+        let data_lines = std::mem::take(&mut self.data_lines);
+        for line in &data_lines {
+            self.draw_line(cx, line);
+        }
+        self.data_lines = data_lines;
+
+        self.draw_bg.end(cx);
+        DrawStep::done()
+    }
+}
+
+impl A2uiInspector {
+    /// Rebuild the panel from a processor
+    ///
+    /// Cheap to call every frame: nothing is rebuilt unless the processor
+    /// state changed since the last call. Returns whether the panel changed.
+    pub fn update(&mut self, processor: &A2uiMessageProcessor) -> bool {
+        let surface_id = self.surface_id.as_ref().to_string();
+        let history = processor.history();
+        let fingerprint = (
+            processor.revision(),
+            processor
+                .get_data_model(&surface_id)
+                .map_or(0, |m| m.version()),
+            history.and_then(|h| h.latest()).map(|e| e.sequence),
+            history.and_then(|h| h.cursor()),
+        );
+        if self.fingerprint == Some(fingerprint) {
+            return false;
+        }
+        self.fingerprint = Some(fingerprint);
+
+        self.messages = history.map(|history| {
+            history
+                .entries()
+                .map(|entry| MessageLine {
+                    sequence: entry.sequence,
+                    summary: entry.summary(),
+                    events: entry.events.iter().map(describe_event).collect(),
+                })
+                .collect()
+        });
+
+        let current = history.and_then(|h| h.current());
+        self.selected_sequence = current.map(|entry| entry.sequence);
+
+        // Components touched by the current message are highlighted
+        let updated_components: Vec<&str> = current
+            .into_iter()
+            .flat_map(|entry| entry.events.iter())
+            .filter_map(|event| match event {
+                ProcessorEvent::SurfaceUpdated(e) if e.surface_id == surface_id => {
+                    Some(e.updated_components.iter().map(String::as_str))
+                }
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        self.component_lines = match (
+            processor.get_surface(&surface_id),
+            processor.get_data_model(&surface_id),
+        ) {
+            (Some(surface), Some(data_model)) => component_tree_rows(surface, data_model)
+                .into_iter()
+                .map(|row| TreeLine {
+                    depth: row.depth,
+                    highlight: updated_components.contains(&row.component_id.as_str()),
+                    text: row.label(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        self.data_lines = processor
+            .get_data_model(&surface_id)
+            .map(|data_model| {
+                data_model_rows(data_model)
+                    .into_iter()
+                    .map(|row| TreeLine {
+                        depth: row.depth,
+                        text: row.text,
+                        highlight: row.dirty,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        true
+    }
+
+    fn draw_section_heading(&mut self, cx: &mut Cx2d, text: &str) {
+        let layout = Layout {
+            padding: Padding {
+                left: 0.0,
+                right: 0.0,
+                top: 8.0,
+                bottom: 4.0,
+            },
+            ..Layout::default()
+        };
+        cx.begin_turtle(Walk::fill_fit(), layout);
+        self.draw_heading
+            .draw_walk(cx, Walk::fit(), Align::default(), text);
+        cx.end_turtle();
+    }
+
+    fn draw_hint(&mut self, cx: &mut Cx2d, text: &str) {
+        let text = self.truncate(text);
+        cx.begin_turtle(Walk::fill_fit(), self.row_layout(0));
+        self.draw_detail_text
+            .draw_walk(cx, Walk::fit(), Align::default(), &text);
+        cx.end_turtle();
+    }
+
+    fn draw_line(&mut self, cx: &mut Cx2d, line: &TreeLine) {
+        self.draw_row.color = if line.highlight {
+            self.row_color_highlight
+        } else {
+            self.row_color
+        };
+        let text = self.truncate(&line.text);
+        self.draw_row
+            .begin(cx, Walk::fill_fit(), self.row_layout(line.depth));
+        self.draw_row_text
+            .draw_walk(cx, Walk::fit(), Align::default(), &text);
+        self.draw_row.end(cx);
+    }
+
+    fn draw_message(&mut self, cx: &mut Cx2d, idx: usize, message: &MessageLine) {
+        self.draw_row.color = if self.selected_sequence == Some(message.sequence) {
+            self.row_color_selected
+        } else if self.hovered_message_idx == Some(idx) {
+            self.row_color_hover
+        } else {
+            self.row_color
+        };
+
+        let layout = Layout {
+            flow: Flow::Down,
+            ..self.row_layout(0)
+        };
+        let text = self.truncate(&format!("#{} {}", message.sequence, message.summary));
+        self.draw_row.begin(cx, Walk::fill_fit(), layout);
+        self.draw_row_text
+            .draw_walk(cx, Walk::fit(), Align::default(), &text);
+        for event in &message.events {
+            let event_text = self.truncate(&format!("  → {}", event));
+            self.draw_detail_text
+                .draw_walk(cx, Walk::fit(), Align::default(), &event_text);
+        }
+        self.draw_row.end(cx);
+
+        // Reuse existing Area if available to maintain event tracking across frames
+        if idx < self.message_areas.len() {
+            self.message_areas[idx] = self.draw_row.area();
+        } else {
+            self.message_areas.push(self.draw_row.area());
+        }
+    }
+
+    fn row_layout(&self, depth: usize) -> Layout {
+        Layout {
+            padding: Padding {
+                left: 4.0 + depth as f64 * self.indent,
+                right: 4.0,
+                top: 2.0,
+                bottom: 2.0,
+            },
+            ..Layout::default()
+        }
+    }
+
+    fn truncate(&self, text: &str) -> String {
+        if text.chars().count() <= self.max_line_chars {
+            return text.to_string();
+        }
+        let mut truncated: String = text
+            .chars()
+            .take(self.max_line_chars.saturating_sub(1))
+            .collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+impl A2uiInspectorRef {
+    /// Rebuild the panel from a processor and redraw it if anything changed
+    pub fn update(&self, cx: &mut Cx, processor: &A2uiMessageProcessor) {
+        if let Some(mut inner) = self.borrow_mut() {
+            if inner.update(processor) {
+                inner.redraw(cx);
+            }
+        }
+    }
+
+    /// Check if a message was clicked
+    /// Returns the history sequence number to rewind to
+    pub fn rewind_requested(&self, actions: &Actions) -> Option<u64> {
+        if let Some(inner) = self.borrow() {
+            if let Some(action) = actions.find_widget_action(inner.widget_uid()) {
+                if let A2uiInspectorAction::Rewind(sequence) = action.cast::<A2uiInspectorAction>()
+                {
+                    return Some(sequence);
+                }
+            }
+        }
+        None
+    }
+}
@@ -3,6 +3,8 @@
 //! This module defines the Rust types for all A2UI protocol messages.
 //! Messages are serialized/deserialized using serde_json.
 
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -12,6 +14,7 @@ use super::value::{BooleanValue, NumberValue, StringValue};
 ///
 /// Each variant corresponds to one of the A2UI protocol message types.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum A2uiMessage {
     /// Initialize a new UI surface
@@ -60,6 +63,7 @@ impl A2uiMessage {
 /// }
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct BeginRendering {
     /// Unique identifier for this surface
@@ -75,6 +79,7 @@ pub struct BeginRendering {
 
 /// Style configuration for a surface
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SurfaceStyles {
     /// Primary color (hex format)
@@ -112,6 +117,7 @@ pub struct SurfaceStyles {
 /// }
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SurfaceUpdate {
     /// Target surface ID
@@ -123,6 +129,7 @@ pub struct SurfaceUpdate {
 
 /// A single component definition in the adjacency list.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ComponentDefinition {
     /// Unique component ID
@@ -132,12 +139,21 @@ pub struct ComponentDefinition {
     #[serde(default)]
     pub weight: Option<f64>,
 
+    /// Whether the component is rendered (literal or path-bound, default true)
+    #[serde(default)]
+    pub visible: Option<BooleanValue>,
+
+    /// Whether the component accepts input (literal or path-bound, default true)
+    #[serde(default)]
+    pub enabled: Option<BooleanValue>,
+
     /// The component type and properties
     pub component: ComponentType,
 }
 
 /// Component type enum - each variant is a different widget type.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum ComponentType {
     // Layout components
     Column(ColumnComponent),
@@ -164,10 +180,14 @@ pub enum ComponentType {
 
     // Media components
     AudioPlayer(AudioPlayerComponent),
+
+    // Placeholder components
+    Skeleton(SkeletonComponent),
 }
 
 /// Children reference - either explicit list or template-based
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum ChildrenRef {
     /// Explicit list of child component IDs
@@ -196,6 +216,7 @@ impl Default for ChildrenRef {
 
 /// Vertical layout container
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnComponent {
     /// Child component references
@@ -213,6 +234,7 @@ pub struct ColumnComponent {
 
 /// Horizontal layout container
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RowComponent {
     /// Child component references
@@ -230,6 +252,7 @@ pub struct RowComponent {
 
 /// Scrollable list container
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ListComponent {
     /// Child component references (usually template-based)
@@ -243,6 +266,7 @@ pub struct ListComponent {
 
 /// Card container with optional styling
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CardComponent {
     /// Single child component ID
@@ -259,6 +283,7 @@ pub struct CardComponent {
 
 /// Text display component
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TextComponent {
     /// Text content (literal or path-bound)
@@ -271,6 +296,7 @@ pub struct TextComponent {
 
 /// Image display component
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ImageComponent {
     /// Image URL (literal or path-bound)
@@ -287,6 +313,7 @@ pub struct ImageComponent {
 
 /// Icon component
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct IconComponent {
     /// Icon name (e.g., "settings", "check", "close")
@@ -299,6 +326,7 @@ pub struct IconComponent {
 
 /// Visual divider/separator
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DividerComponent {
     /// Orientation
@@ -312,6 +340,7 @@ pub struct DividerComponent {
 
 /// Clickable button component
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ButtonComponent {
     /// Child component ID (button content)
@@ -328,6 +357,7 @@ pub struct ButtonComponent {
 
 /// Text input field
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TextFieldComponent {
     /// Current value (path-bound for two-way binding)
@@ -348,6 +378,7 @@ pub struct TextFieldComponent {
 
 /// Checkbox component
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CheckBoxComponent {
     /// Current checked state (path-bound)
@@ -360,6 +391,7 @@ pub struct CheckBoxComponent {
 
 /// Slider component for numeric input
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SliderComponent {
     /// Current value (path-bound)
@@ -380,6 +412,7 @@ pub struct SliderComponent {
 
 /// Multiple choice selection
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct MultipleChoiceComponent {
     /// Selected value(s) (path-bound)
@@ -395,6 +428,7 @@ pub struct MultipleChoiceComponent {
 
 /// A single choice option
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ChoiceOption {
     /// Option value
@@ -409,6 +443,7 @@ pub struct ChoiceOption {
 
 /// Modal dialog overlay
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ModalComponent {
     /// Visibility state (path-bound)
@@ -421,6 +456,7 @@ pub struct ModalComponent {
 
 /// Tabbed interface
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TabsComponent {
     /// Tab definitions
@@ -433,6 +469,7 @@ pub struct TabsComponent {
 
 /// A single tab definition
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TabDefinition {
     /// Tab ID
@@ -449,6 +486,7 @@ pub struct TabDefinition {
 
 /// Audio player component for playing audio URLs
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct AudioPlayerComponent {
     /// Audio URL (literal or path-bound)
@@ -467,12 +505,30 @@ pub struct AudioPlayerComponent {
     pub autoplay: Option<bool>,
 }
 
+// ============================================================================
+// Placeholder Components
+// ============================================================================
+
+/// Loading placeholder for a component that hasn't arrived yet
+///
+/// Agents streaming a UI send it for IDs that are referenced before they are
+/// defined; the real definition later replaces it.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SkeletonComponent {
+    /// Height in logical pixels (default 20)
+    #[serde(default)]
+    pub height: Option<f64>,
+}
+
 // ============================================================================
 // Enums
 // ============================================================================
 
 /// Alignment options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum Alignment {
     Start,
@@ -483,6 +539,7 @@ pub enum Alignment {
 
 /// Distribution options for Row/Column
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum Distribution {
     Start,
@@ -495,6 +552,7 @@ pub enum Distribution {
 
 /// List scroll direction
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum ListDirection {
     #[default]
@@ -504,6 +562,7 @@ pub enum ListDirection {
 
 /// Text usage hints for styling
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum TextUsageHint {
     H1,
@@ -518,6 +577,7 @@ pub enum TextUsageHint {
 
 /// Image fit modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum ImageFit {
     #[default]
@@ -530,6 +590,7 @@ pub enum ImageFit {
 
 /// Image usage hints for sizing
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum ImageUsageHint {
     Icon,
@@ -542,6 +603,7 @@ pub enum ImageUsageHint {
 
 /// Orientation for dividers etc.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum Orientation {
     #[default]
@@ -551,6 +613,7 @@ pub enum Orientation {
 
 /// Text input types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum TextInputType {
     #[default]
@@ -568,6 +631,7 @@ pub enum TextInputType {
 
 /// Action definition for interactive components
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ActionDefinition {
     /// Action name (e.g., "addToCart", "submit")
@@ -578,8 +642,39 @@ pub struct ActionDefinition {
     pub context: Vec<ActionContextItem>,
 }
 
+impl ActionDefinition {
+    /// Create an action without context
+    pub fn new(name: impl Into<String>) -> Self {
+        ActionDefinition {
+            name: name.into(),
+            context: Vec::new(),
+        }
+    }
+
+    /// Add a context value
+    pub fn with_context(mut self, key: impl Into<String>, value: ActionValue) -> Self {
+        self.context.push(ActionContextItem {
+            key: key.into(),
+            value,
+        });
+        self
+    }
+
+    /// Add a context value read from a data model path when the action fires
+    pub fn with_path(self, key: impl Into<String>, path: impl Into<String>) -> Self {
+        self.with_context(key, ActionValue::String(StringValue::path(path)))
+    }
+}
+
+impl From<&str> for ActionDefinition {
+    fn from(name: &str) -> Self {
+        ActionDefinition::new(name)
+    }
+}
+
 /// A single context item for an action
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ActionContextItem {
     /// Key name
@@ -591,6 +686,7 @@ pub struct ActionContextItem {
 
 /// Value type for action context
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(untagged)]
 pub enum ActionValue {
     String(StringValue),
@@ -614,6 +710,7 @@ pub enum ActionValue {
 /// }
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DataModelUpdate {
     /// Target surface ID
@@ -625,6 +722,10 @@ pub struct DataModelUpdate {
 
     /// Data updates
     pub contents: Vec<DataContent>,
+
+    /// Server data model version after this update (optional, used by data sync)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
 }
 
 fn default_path() -> String {
@@ -633,6 +734,7 @@ fn default_path() -> String {
 
 /// A single data content item
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DataContent {
     /// Key name
@@ -645,6 +747,7 @@ pub struct DataContent {
 
 /// Data value types
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum DataValue {
     /// String value
@@ -659,8 +762,67 @@ pub enum DataValue {
     ValueArray(Vec<DataValue>),
 }
 
+impl DataValue {
+    /// Convert plain JSON; `None` for null
+    ///
+    /// Nulls inside objects and arrays are dropped.
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        use serde_json::Value;
+        match value {
+            Value::Null => None,
+            Value::String(s) => Some(DataValue::ValueString(s.clone())),
+            Value::Number(n) => n.as_f64().map(DataValue::ValueNumber),
+            Value::Bool(b) => Some(DataValue::ValueBoolean(*b)),
+            Value::Array(items) => Some(DataValue::ValueArray(
+                items.iter().filter_map(DataValue::from_json).collect(),
+            )),
+            Value::Object(map) => Some(DataValue::ValueMap(
+                map.iter()
+                    .filter_map(|(key, value)| {
+                        Some(DataContent {
+                            key: key.clone(),
+                            value: DataValue::from_json(value)?,
+                        })
+                    })
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl From<&str> for DataValue {
+    fn from(s: &str) -> Self {
+        DataValue::ValueString(s.to_string())
+    }
+}
+
+impl From<String> for DataValue {
+    fn from(s: String) -> Self {
+        DataValue::ValueString(s)
+    }
+}
+
+impl From<f64> for DataValue {
+    fn from(n: f64) -> Self {
+        DataValue::ValueNumber(n)
+    }
+}
+
+impl From<bool> for DataValue {
+    fn from(b: bool) -> Self {
+        DataValue::ValueBoolean(b)
+    }
+}
+
+impl From<Vec<DataValue>> for DataValue {
+    fn from(values: Vec<DataValue>) -> Self {
+        DataValue::ValueArray(values)
+    }
+}
+
 /// Delete a surface
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteSurface {
     /// Surface ID to delete
@@ -669,6 +831,7 @@ pub struct DeleteSurface {
 
 /// User action event (sent from client to server)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct UserAction {
     /// Source surface ID
@@ -684,6 +847,7 @@ pub struct UserAction {
 
 /// User action payload
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct UserActionPayload {
     /// Action name
@@ -778,6 +942,39 @@ mod tests {
         assert_eq!(messages.len(), 3);
     }
 
+    #[test]
+    fn test_parse_visible_and_enabled() {
+        let json = r##"{"surfaceUpdate": {"surfaceId": "main", "components": [
+            {
+                "id": "shipping-form",
+                "visible": {"path": "/delivery"},
+                "component": {"Column": {"children": {"explicitList": []}}}
+            },
+            {
+                "id": "submit",
+                "enabled": {"literalBoolean": false},
+                "component": {"Button": {"child": "submit-text"}}
+            }
+        ]}}"##;
+
+        let msg: A2uiMessage = serde_json::from_str(json).unwrap();
+        match msg {
+            A2uiMessage::SurfaceUpdate(su) => {
+                assert_eq!(
+                    su.components[0].visible.as_ref().and_then(|v| v.as_path()),
+                    Some("/delivery")
+                );
+                assert!(su.components[0].enabled.is_none());
+                assert!(su.components[1].visible.is_none());
+                assert_eq!(
+                    su.components[1].enabled.as_ref().and_then(|e| e.as_literal()),
+                    Some(false)
+                );
+            }
+            _ => panic!("Expected SurfaceUpdate"),
+        }
+    }
+
     #[test]
     fn test_parse_audio_player() {
         // Test parsing AudioPlayer component
@@ -35,28 +35,79 @@
 //! ```
 
 mod message;
+mod builder;
 mod data_model;
 mod processor;
 mod registry;
+#[cfg(feature = "schema")]
+mod schema;
+#[cfg(feature = "schema")]
+mod tools;
+mod diff;
+mod partial_json;
 mod surface;
 mod value;
 mod sse;
+#[cfg(feature = "websocket")]
+mod websocket;
 mod a2a_client;
+mod a2a_part;
+#[cfg(feature = "async-client")]
+mod a2a_async;
+mod transport;
+mod agent;
 mod host;
+mod snapshot;
+mod recording;
+mod history;
+mod inspect;
+mod inspector;
+mod headless;
+#[cfg(feature = "raster")]
+mod raster;
+mod action_queue;
+mod data_sync;
+mod agent_card;
 
 pub use message::*;
+pub use builder::*;
 pub use data_model::*;
 pub use processor::*;
 pub use registry::*;
+#[cfg(feature = "schema")]
+pub use schema::*;
+#[cfg(feature = "schema")]
+pub use tools::*;
+pub use diff::*;
+pub use partial_json::*;
 pub use surface::*;
 pub use value::*;
 pub use sse::*;
+#[cfg(feature = "websocket")]
+pub use websocket::*;
 pub use a2a_client::*;
+pub use a2a_part::*;
+#[cfg(feature = "async-client")]
+pub use a2a_async::*;
+pub use transport::*;
+pub use agent::*;
 pub use host::*;
+pub use snapshot::*;
+pub use recording::*;
+pub use history::*;
+pub use inspect::*;
+pub use inspector::*;
+pub use headless::*;
+#[cfg(feature = "raster")]
+pub use raster::*;
+pub use action_queue::*;
+pub use data_sync::*;
+pub use agent_card::*;
 
 use makepad_widgets::Cx;
 
 /// Initialize A2UI live design components
 pub fn live_design(cx: &mut Cx) {
     crate::a2ui::surface::live_design(cx);
+    crate::a2ui::inspector::live_design(cx);
 }
@@ -0,0 +1,391 @@
+//! Partial JSON
+//!
+//! Models stream tool-call arguments a few characters at a time. To show a
+//! component before its arguments are complete, `parse_partial_json` reads a
+//! truncated JSON document as if it had been closed at the end: open strings,
+//! arrays and objects are closed, and a key or value that was cut off before
+//! it means anything is dropped.
+//!
+//! ```rust,ignore
+//! let args = parse_partial_json(r#"{"id": "title", "text": "Hello, wo"#).unwrap();
+//! assert_eq!(args.value, json!({"id": "title", "text": "Hello, wo"}));
+//! assert_eq!(args.cut_off.as_deref(), Some("/text"));
+//! ```
+//!
+//! Strings keep whatever has arrived, so text grows as it streams in; a string
+//! that is still arriving is reported in `cut_off`, since an ID cut short names
+//! the wrong component. Numbers and `true`/`false`/`null` inside an array or
+//! object are only kept once something follows them, since `1` may still
+//! become `10`; a top-level scalar is taken as the whole document.
+
+use serde_json::{Map, Number, Value};
+
+/// A JSON document read as far as it has arrived
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialJson {
+    /// The document, closed where the input ended
+    pub value: Value,
+    /// Whether the whole document arrived
+    pub complete: bool,
+    /// JSON pointer to a string in `value` whose end hasn't arrived yet
+    pub cut_off: Option<String>,
+}
+
+/// Parse a JSON document that may have been cut off
+///
+/// Returns `None` if nothing usable has arrived yet or the input isn't JSON.
+pub fn parse_partial_json(input: &str) -> Option<PartialJson> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        path: Vec::new(),
+        cut_off: None,
+    };
+    let (value, complete) = match parser.value().ok()? {
+        Parsed::Complete(value) => {
+            parser.skip_whitespace();
+            if parser.peek().is_some() {
+                return None;
+            }
+            (value, true)
+        }
+        Parsed::Partial(value) => (value, false),
+        Parsed::Missing => return None,
+    };
+    Some(PartialJson {
+        value,
+        complete,
+        cut_off: parser.cut_off,
+    })
+}
+
+enum Parsed {
+    /// The whole value arrived
+    Complete(Value),
+    /// The input ended inside the value
+    Partial(Value),
+    /// The input ended before anything usable
+    Missing,
+}
+
+/// Malformed input
+struct Invalid;
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Keys and indices leading to the value being parsed
+    path: Vec<String>,
+    cut_off: Option<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Parsed, Invalid> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(Parsed::Missing),
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(match self.string()? {
+                (text, true) => Parsed::Complete(Value::String(text)),
+                (text, false) => {
+                    self.cut_off = Some(self.pointer());
+                    Parsed::Partial(Value::String(text))
+                }
+            }),
+            Some('t') => self.literal("true", Value::Bool(true)),
+            Some('f') => self.literal("false", Value::Bool(false)),
+            Some('n') => self.literal("null", Value::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => Err(Invalid),
+        }
+    }
+
+    fn object(&mut self) -> Result<Parsed, Invalid> {
+        self.pos += 1;
+        let mut map = Map::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(Parsed::Partial(Value::Object(map))),
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Parsed::Complete(Value::Object(map)));
+                }
+                Some('"') => {}
+                Some(_) => return Err(Invalid),
+            }
+
+            let (key, complete) = self.string()?;
+            self.skip_whitespace();
+            if !complete || self.peek().is_none() {
+                return Ok(Parsed::Partial(Value::Object(map)));
+            }
+            if self.peek() != Some(':') {
+                return Err(Invalid);
+            }
+            self.pos += 1;
+
+            self.path.push(key.clone());
+            let value = self.value()?;
+            self.path.pop();
+            match value {
+                Parsed::Complete(value) => {
+                    map.insert(key, value);
+                }
+                Parsed::Partial(value) => {
+                    map.insert(key, value);
+                    return Ok(Parsed::Partial(Value::Object(map)));
+                }
+                Parsed::Missing => return Ok(Parsed::Partial(Value::Object(map))),
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(Parsed::Partial(Value::Object(map))),
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Parsed::Complete(Value::Object(map)));
+                }
+                Some(_) => return Err(Invalid),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Parsed, Invalid> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Parsed::Complete(Value::Array(items)));
+        }
+        loop {
+            self.path.push(items.len().to_string());
+            let value = self.value()?;
+            self.path.pop();
+            match value {
+                Parsed::Complete(value) => items.push(value),
+                Parsed::Partial(value) => {
+                    items.push(value);
+                    return Ok(Parsed::Partial(Value::Array(items)));
+                }
+                Parsed::Missing => return Ok(Parsed::Partial(Value::Array(items))),
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(Parsed::Partial(Value::Array(items))),
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Parsed::Complete(Value::Array(items)));
+                }
+                Some(_) => return Err(Invalid),
+            }
+        }
+    }
+
+    /// JSON pointer to the value being parsed
+    fn pointer(&self) -> String {
+        self.path
+            .iter()
+            .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+            .collect()
+    }
+
+    /// The string's text so far, and whether its closing quote arrived
+    fn string(&mut self) -> Result<(String, bool), Invalid> {
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Ok((text, false));
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok((text, true)),
+                '\\' => {
+                    let Some(escape) = self.peek() else {
+                        return Ok((text, false));
+                    };
+                    self.pos += 1;
+                    match escape {
+                        '"' | '\\' | '/' => text.push(escape),
+                        'b' => text.push('\u{8}'),
+                        'f' => text.push('\u{c}'),
+                        'n' => text.push('\n'),
+                        'r' => text.push('\r'),
+                        't' => text.push('\t'),
+                        'u' => match self.unicode_escape()? {
+                            Some(c) => text.push(c),
+                            None => return Ok((text, false)),
+                        },
+                        _ => return Err(Invalid),
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    /// Character of a `\uXXXX` escape (or surrogate pair), `None` if cut off
+    fn unicode_escape(&mut self) -> Result<Option<char>, Invalid> {
+        let Some(high) = self.hex4()? else {
+            return Ok(None);
+        };
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(Some(char::from_u32(high).unwrap_or('\u{FFFD}')));
+        }
+
+        // High surrogate: the low half follows as another escape
+        for expected in ['\\', 'u'] {
+            match self.peek() {
+                None => return Ok(None),
+                Some(c) if c == expected => self.pos += 1,
+                Some(_) => return Ok(Some('\u{FFFD}')),
+            }
+        }
+        let Some(low) = self.hex4()? else {
+            return Ok(None);
+        };
+        let c = 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+        Ok(Some(char::from_u32(c).unwrap_or('\u{FFFD}')))
+    }
+
+    fn hex4(&mut self) -> Result<Option<u32>, Invalid> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let Some(c) = self.peek() else {
+                return Ok(None);
+            };
+            code = code * 16 + c.to_digit(16).ok_or(Invalid)?;
+            self.pos += 1;
+        }
+        Ok(Some(code))
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Parsed, Invalid> {
+        for expected in word.chars() {
+            match self.peek() {
+                None => return Ok(Parsed::Missing),
+                Some(c) if c == expected => self.pos += 1,
+                Some(_) => return Err(Invalid),
+            }
+        }
+        // Inside a container `true` may be cut off right after; a top-level
+        // literal that is followed by nothing is the whole document
+        if self.peek().is_none() && !self.path.is_empty() {
+            return Ok(Parsed::Missing);
+        }
+        Ok(Parsed::Complete(value))
+    }
+
+    fn number(&mut self) -> Result<Parsed, Invalid> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.pos += 1;
+        }
+        // Inside a container `1` may still become `10`
+        if self.peek().is_none() && !self.path.is_empty() {
+            return Ok(Parsed::Missing);
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        let number: Number = text.parse().map_err(|_| Invalid)?;
+        Ok(Parsed::Complete(Value::Number(number)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_prefixes_of_tool_arguments() {
+        let json = r#"{"id": "title", "text": "Hi \"you\" é", "weight": 2, "children": ["a", "b"], "bold": true}"#;
+        let full: Value = serde_json::from_str(json).unwrap();
+        let parsed = parse_partial_json(json).unwrap();
+        assert_eq!(parsed.value, full);
+        assert!(parsed.complete);
+        assert_eq!(parsed.cut_off, None);
+
+        let mut previous_keys = 0;
+        for end in (0..json.len()).filter(|end| json.is_char_boundary(*end)) {
+            let Some(parsed) = parse_partial_json(&json[..end]) else {
+                assert!(end < 1, "no value for {:?}", &json[..end]);
+                continue;
+            };
+            assert!(!parsed.complete);
+            // Keys only ever appear, each with a prefix of its final value
+            let object = parsed.value.as_object().unwrap();
+            assert!(object.len() >= previous_keys, "{:?}", &json[..end]);
+            previous_keys = object.len();
+            for (key, value) in object {
+                match (value, &full[key]) {
+                    (Value::String(partial), Value::String(done)) => {
+                        assert!(done.starts_with(partial.as_str()), "{:?}", &json[..end])
+                    }
+                    (Value::Array(partial), Value::Array(done)) => {
+                        assert!(partial.len() <= done.len())
+                    }
+                    (partial, done) => assert_eq!(partial, done, "{:?}", &json[..end]),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cut_off_values() {
+        let parse = |s| parse_partial_json(s).map(|parsed| parsed.value);
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("{"), Some(json!({})));
+        assert_eq!(parse(r#"{"te"#), Some(json!({})));
+        assert_eq!(parse(r#"{"text""#), Some(json!({})));
+        assert_eq!(parse(r#"{"text": "Hel"#), Some(json!({"text": "Hel"})));
+        assert_eq!(parse(r#"{"text": "a\"#), Some(json!({"text": "a"})));
+        assert_eq!(parse(r#"{"text": "\u00"#), Some(json!({"text": ""})));
+        assert_eq!(parse(r#"{"n": 12"#), Some(json!({})));
+        assert_eq!(parse(r#"{"n": 12,"#), Some(json!({"n": 12})));
+        assert_eq!(parse(r#"{"ok": tru"#), Some(json!({})));
+        assert_eq!(parse(r#"{"ok": true}"#), Some(json!({"ok": true})));
+        assert_eq!(parse("[1, 2"), Some(json!([1])));
+        assert_eq!(parse("tr"), None);
+
+        // A scalar document has nothing left to close
+        let scalar = |s| parse_partial_json(s).map(|parsed| (parsed.value, parsed.complete));
+        assert_eq!(scalar("12"), Some((json!(12), true)));
+        assert_eq!(scalar("true"), Some((json!(true), true)));
+        assert_eq!(scalar(" null "), Some((json!(null), true)));
+        assert_eq!(
+            parse(r#"{"children": {"explicitList": ["a", "b"#),
+            Some(json!({"children": {"explicitList": ["a", "b"]}}))
+        );
+
+        let cut_off = |s| parse_partial_json(s).unwrap().cut_off;
+        assert_eq!(cut_off(r#"{"id": "ti"#), Some("/id".to_string()));
+        assert_eq!(cut_off(r#"{"id": "title", "#), None);
+        assert_eq!(
+            cut_off(r#"{"children": {"explicitList": ["a", "b"#),
+            Some("/children/explicitList/1".to_string())
+        );
+
+        // Not JSON at all
+        assert_eq!(parse("{'text': 1}"), None);
+        assert_eq!(parse(r#"{"a": 1} x"#), None);
+    }
+}
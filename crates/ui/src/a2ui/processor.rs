@@ -2,15 +2,24 @@
 //!
 //! Processes incoming A2UI messages and updates the component tree and data model.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use super::{
     data_model::{DataModel, SurfaceDataModels},
+    data_sync::{DataModelChange, DataSync},
+    history::ProcessorHistory,
     message::*,
+    recording::SessionRecorder,
     registry::ComponentRegistry,
+    snapshot::{ProcessorSnapshot, SurfaceSnapshot, SNAPSHOT_FORMAT_VERSION},
     value::{BooleanValue, NumberValue, StringValue},
 };
 
+/// Default minimum time between two autosaves
+pub const DEFAULT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Represents a UI surface with its component tree and configuration.
 #[derive(Debug, Clone)]
 pub struct Surface {
@@ -137,6 +146,30 @@ pub struct A2uiMessageProcessor {
 
     /// Pending user actions to send
     pending_actions: Vec<UserAction>,
+
+    /// Snapshot file written automatically after state changes
+    autosave_path: Option<PathBuf>,
+
+    /// Number of state-changing messages processed
+    revision: u64,
+
+    /// State fingerprint (revision, summed data model versions) at the last autosave
+    autosaved_fingerprint: Option<(u64, u64)>,
+
+    /// Minimum time between two autosaves
+    autosave_interval: Duration,
+
+    /// When the autosave snapshot was last written
+    last_autosave: Option<Instant>,
+
+    /// Session recorder for processed messages and created actions
+    recorder: Option<SessionRecorder>,
+
+    /// History of applied messages for inspection and rewinding
+    history: Option<ProcessorHistory>,
+
+    /// Two-way data model sync state, if enabled
+    data_sync: Option<DataSync>,
 }
 
 impl A2uiMessageProcessor {
@@ -147,6 +180,14 @@ impl A2uiMessageProcessor {
             surfaces: HashMap::new(),
             data_models: SurfaceDataModels::new(),
             pending_actions: Vec::new(),
+            autosave_path: None,
+            revision: 0,
+            autosaved_fingerprint: None,
+            autosave_interval: DEFAULT_AUTOSAVE_INTERVAL,
+            last_autosave: None,
+            recorder: None,
+            history: None,
+            data_sync: None,
         }
     }
 
@@ -160,6 +201,129 @@ impl A2uiMessageProcessor {
         &self.registry
     }
 
+    /// Attach a session recorder (or detach with `None`)
+    ///
+    /// Every processed message is recorded as inbound and every action created
+    /// with `create_action` as outbound. Don't attach the same recorder to the
+    /// `A2uiHost` feeding this processor, or messages are recorded twice.
+    pub fn set_recorder(&mut self, recorder: Option<SessionRecorder>) {
+        self.recorder = recorder;
+    }
+
+    /// Get the session recorder, if recording
+    pub fn recorder(&self) -> Option<&SessionRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Enable message history (or disable with `None`)
+    ///
+    /// Each applied message keeps a snapshot of the resulting state, so size the
+    /// history capacity with the surface size in mind. Disabling the history
+    /// while rewound returns to the latest state first.
+    pub fn set_history(&mut self, history: Option<ProcessorHistory>) {
+        if let Err(e) = self.return_to_latest() {
+            makepad_widgets::log!("A2UI history: failed to return to latest state: {}", e);
+        }
+        self.history = history;
+    }
+
+    /// Get the message history, if enabled
+    pub fn history(&self) -> Option<&ProcessorHistory> {
+        self.history.as_ref()
+    }
+
+    /// Enable two-way data model sync (or disable with `None`)
+    ///
+    /// While enabled, edits made with `set_local_value` are collected for
+    /// `take_data_changes`, and data model updates from the server are
+    /// reconciled with edits that weren't acknowledged yet.
+    pub fn set_data_sync(&mut self, data_sync: Option<DataSync>) {
+        self.data_sync = data_sync;
+    }
+
+    /// Get the data sync state, if enabled
+    pub fn data_sync(&self) -> Option<&DataSync> {
+        self.data_sync.as_ref()
+    }
+
+    /// Get mutable data sync state, if enabled
+    pub fn data_sync_mut(&mut self) -> Option<&mut DataSync> {
+        self.data_sync.as_mut()
+    }
+
+    /// Apply a local edit (two-way binding) to a surface's data model
+    ///
+    /// Unlike writing to the data model directly, the edit is also recorded
+    /// for upstream sync when data sync is enabled.
+    pub fn set_local_value(&mut self, surface_id: &str, path: &str, value: serde_json::Value) {
+        let Some(data_model) = self.data_models.get_mut(surface_id) else {
+            return;
+        };
+        if let Some(data_sync) = &mut self.data_sync {
+            data_sync.record_local_change(surface_id, path, value.clone());
+        }
+        data_model.set(path, value);
+    }
+
+    /// Take the batches of local edits due to be sent upstream
+    ///
+    /// Always empty while data sync is disabled.
+    pub fn take_data_changes(&mut self, now: Instant) -> Vec<DataModelChange> {
+        self.data_sync
+            .as_mut()
+            .map(|data_sync| data_sync.take_changes(now))
+            .unwrap_or_default()
+    }
+
+    /// Rewind the processor state to a history entry
+    ///
+    /// The paths updated by that entry's message are marked dirty again.
+    /// Rewinding to the latest entry returns to live mode.
+    pub fn rewind_to(&mut self, sequence: u64) -> Result<Vec<ProcessorEvent>, String> {
+        let history = self
+            .history
+            .as_mut()
+            .ok_or_else(|| "Message history is not enabled".to_string())?;
+        let entry = history.seek(sequence)?;
+
+        let state = entry.state.clone();
+        let updated_paths: Vec<(String, String)> = entry
+            .updated_paths()
+            .into_iter()
+            .map(|(surface_id, path)| (surface_id.to_string(), path.to_string()))
+            .collect();
+
+        let events = self.restore_state(state)?;
+        for (surface_id, path) in updated_paths {
+            if let Some(data_model) = self.data_models.get_mut(&surface_id) {
+                data_model.mark_dirty(&path);
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Leave a rewound state for the latest history entry
+    ///
+    /// Does nothing (and returns no events) unless the history is rewound.
+    pub fn return_to_latest(&mut self) -> Result<Vec<ProcessorEvent>, String> {
+        let latest = self
+            .history
+            .as_ref()
+            .filter(|history| history.is_rewound())
+            .and_then(|history| history.latest())
+            .map(|entry| entry.sequence);
+        match latest {
+            Some(sequence) => self.rewind_to(sequence),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Number of state-changing messages processed (or snapshots restored)
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     /// Get a surface by ID
     pub fn get_surface(&self, surface_id: &str) -> Option<&Surface> {
         self.surfaces.get(surface_id)
@@ -187,9 +351,31 @@ impl A2uiMessageProcessor {
 
     /// Process a single A2UI message
     ///
-    /// Returns a list of events that occurred as a result of processing.
+    /// Returns a list of events that occurred as a result of processing. If the
+    /// history was rewound, the latest state is restored before the message is
+    /// applied, so incoming messages never build on a rewound state.
     pub fn process_message(&mut self, message: A2uiMessage) -> Vec<ProcessorEvent> {
-        match message {
+        let events = self.apply_message(message);
+        self.autosave();
+        events
+    }
+
+    /// Apply a message to the processor state without autosaving
+    fn apply_message(&mut self, message: A2uiMessage) -> Vec<ProcessorEvent> {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_inbound(&message);
+        }
+
+        if !matches!(message, A2uiMessage::UserAction(_)) {
+            if let Err(e) = self.return_to_latest() {
+                makepad_widgets::log!("A2UI history: failed to return to latest state: {}", e);
+            }
+            self.revision += 1;
+        }
+
+        let history_message = self.history.is_some().then(|| message.clone());
+
+        let events = match message {
             A2uiMessage::BeginRendering(msg) => self.process_begin_rendering(msg),
             A2uiMessage::SurfaceUpdate(msg) => self.process_surface_update(msg),
             A2uiMessage::DataModelUpdate(msg) => self.process_data_model_update(msg),
@@ -200,15 +386,25 @@ impl A2uiMessageProcessor {
                 self.pending_actions.push(msg);
                 vec![]
             }
+        };
+
+        if let Some(message) = history_message {
+            let state = self.snapshot();
+            if let Some(history) = &mut self.history {
+                history.push(message, events.clone(), state);
+            }
         }
+
+        events
     }
 
     /// Process multiple A2UI messages (e.g., from a JSON array)
     pub fn process_messages(&mut self, messages: Vec<A2uiMessage>) -> Vec<ProcessorEvent> {
         let mut events = Vec::new();
         for message in messages {
-            events.extend(self.process_message(message));
+            events.extend(self.apply_message(message));
         }
+        self.autosave();
         events
     }
 
@@ -297,14 +493,245 @@ impl A2uiMessageProcessor {
             }
         }
 
-        UserAction {
+        let action = UserAction {
             surface_id: surface_id.to_string(),
             action: UserActionPayload {
                 name: action_def.name.clone(),
                 context,
             },
             component_id: Some(component_id.to_string()),
+        };
+
+        if let Some(recorder) = &self.recorder {
+            recorder.record_outbound(&action);
         }
+
+        action
+    }
+
+    // ========================================================================
+    // Snapshots
+    // ========================================================================
+
+    /// Capture the full processor state (surfaces, components, styles, data models)
+    pub fn snapshot(&self) -> ProcessorSnapshot {
+        // Include data models that arrived before their surface
+        let surface_ids: BTreeSet<&String> = self
+            .surfaces
+            .keys()
+            .chain(self.data_models.surface_ids())
+            .collect();
+
+        let surfaces = surface_ids
+            .into_iter()
+            .map(|surface_id| {
+                let surface = self.surfaces.get(surface_id);
+                let data_model = self.data_models.get(surface_id);
+
+                let mut components: Vec<ComponentDefinition> = surface
+                    .map(|s| s.components.values().cloned().collect())
+                    .unwrap_or_default();
+                components.sort_by(|a, b| a.id.cmp(&b.id));
+
+                SurfaceSnapshot {
+                    surface_id: surface_id.clone(),
+                    root: surface.map(|s| s.root.clone()).unwrap_or_default(),
+                    styles: surface.and_then(|s| s.styles.clone()),
+                    components,
+                    data: data_model
+                        .map(|m| m.as_value().clone())
+                        .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new())),
+                    data_version: data_model.map_or(0, |m| m.version()),
+                }
+            })
+            .collect();
+
+        ProcessorSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            surfaces,
+        }
+    }
+
+    /// Replace the processor state with a snapshot
+    ///
+    /// Returns a `SurfaceCreated` event for every restored surface. Entries
+    /// holding only a data model (data that arrived before its surface) restore
+    /// the data model without creating a surface.
+    pub fn restore_snapshot(
+        &mut self,
+        snapshot: ProcessorSnapshot,
+    ) -> Result<Vec<ProcessorEvent>, String> {
+        let events = self.restore_state(snapshot)?;
+        self.autosave();
+        Ok(events)
+    }
+
+    /// Replace the processor state with a snapshot without autosaving
+    fn restore_state(
+        &mut self,
+        snapshot: ProcessorSnapshot,
+    ) -> Result<Vec<ProcessorEvent>, String> {
+        if snapshot.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported snapshot format version: {} (expected {})",
+                snapshot.format_version, SNAPSHOT_FORMAT_VERSION
+            ));
+        }
+
+        self.surfaces.clear();
+        self.data_models = SurfaceDataModels::new();
+
+        let mut events = Vec::new();
+        for surface_snapshot in snapshot.surfaces {
+            self.data_models.insert(
+                surface_snapshot.surface_id.clone(),
+                DataModel::with_data_and_version(
+                    surface_snapshot.data,
+                    surface_snapshot.data_version,
+                ),
+            );
+            if surface_snapshot.root.is_empty() {
+                continue;
+            }
+
+            let mut surface = Surface::new(
+                surface_snapshot.surface_id.clone(),
+                surface_snapshot.root,
+                surface_snapshot.styles,
+            );
+            for component in surface_snapshot.components {
+                surface.components.insert(component.id.clone(), component);
+            }
+
+            self.surfaces.insert(surface_snapshot.surface_id.clone(), surface);
+
+            events.push(ProcessorEvent::SurfaceCreated(SurfaceCreatedEvent {
+                surface_id: surface_snapshot.surface_id,
+            }));
+        }
+
+        self.revision += 1;
+
+        Ok(events)
+    }
+
+    /// Write a snapshot of the processor state to a file
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        self.snapshot().save(path)
+    }
+
+    /// Restore the processor state from a snapshot file
+    pub fn load_snapshot(&mut self, path: impl AsRef<Path>) -> Result<Vec<ProcessorEvent>, String> {
+        let snapshot = ProcessorSnapshot::load(path)?;
+        self.restore_snapshot(snapshot)
+    }
+
+    /// Remove all surfaces, data models and pending actions
+    ///
+    /// Autosave, history, data sync and the session recorder stay attached;
+    /// only the sync state of the removed surfaces is forgotten. The empty
+    /// state is written to the autosave file right away.
+    pub fn reset(&mut self) {
+        if let Some(data_sync) = self.data_sync.as_mut() {
+            for surface_id in self.surfaces.keys().chain(self.data_models.surface_ids()) {
+                data_sync.remove_surface(surface_id);
+            }
+        }
+        self.surfaces.clear();
+        self.data_models = SurfaceDataModels::new();
+        self.pending_actions.clear();
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+        self.revision += 1;
+        self.flush_autosave();
+    }
+
+    /// Enable or disable autosaving snapshots to a file after state changes
+    ///
+    /// A pending change for the previous path is written first.
+    pub fn set_autosave_path(&mut self, path: Option<PathBuf>) {
+        self.flush_autosave();
+        self.autosave_path = path;
+        self.autosaved_fingerprint = None;
+        self.last_autosave = None;
+    }
+
+    /// Set the minimum time between two autosaves
+    ///
+    /// Changes made sooner after a save are written by a later autosave, by
+    /// `flush_autosave`, or when the processor is dropped.
+    pub fn set_autosave_interval(&mut self, interval: Duration) {
+        self.autosave_interval = interval;
+    }
+
+    /// Get the autosave snapshot path, if autosave is enabled
+    pub fn autosave_path(&self) -> Option<&Path> {
+        self.autosave_path.as_deref()
+    }
+
+    /// Write the autosave snapshot if the state changed since the last save
+    /// and the autosave interval has passed
+    ///
+    /// Called automatically after processing messages. Call it after mutating
+    /// a data model directly (e.g., two-way binding) to persist the change.
+    pub fn autosave(&mut self) {
+        let due = self
+            .last_autosave
+            .is_none_or(|last| last.elapsed() >= self.autosave_interval);
+        if due {
+            self.flush_autosave();
+        }
+    }
+
+    /// Time until a pending change can be written by `autosave`
+    ///
+    /// Returns `None` when nothing is waiting. Hosts use this to schedule a
+    /// trailing-edge `autosave` so the last change of a burst is persisted.
+    pub fn autosave_due_in(&self) -> Option<Duration> {
+        if !self.autosave_pending() {
+            return None;
+        }
+        Some(
+            self.last_autosave
+                .map(|last| self.autosave_interval.saturating_sub(last.elapsed()))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Whether a state change is waiting for the next autosave
+    pub fn autosave_pending(&self) -> bool {
+        self.autosave_path.is_some()
+            && self.autosaved_fingerprint != Some(self.state_fingerprint())
+    }
+
+    /// Write the autosave snapshot now if the state changed since the last save
+    pub fn flush_autosave(&mut self) {
+        let Some(path) = &self.autosave_path else {
+            return;
+        };
+
+        let fingerprint = self.state_fingerprint();
+        if self.autosaved_fingerprint == Some(fingerprint) {
+            return;
+        }
+
+        self.last_autosave = Some(Instant::now());
+        match self.snapshot().save(path) {
+            Ok(()) => self.autosaved_fingerprint = Some(fingerprint),
+            Err(e) => makepad_widgets::log!("A2UI autosave failed: {}", e),
+        }
+    }
+
+    /// Fingerprint that changes whenever surfaces or data models change
+    fn state_fingerprint(&self) -> (u64, u64) {
+        let data_versions = self
+            .data_models
+            .surface_ids()
+            .filter_map(|id| self.data_models.get(id))
+            .map(|m| m.version())
+            .fold(0u64, |acc, v| acc.wrapping_add(v));
+        (self.revision, data_versions)
     }
 
     // ========================================================================
@@ -368,6 +795,10 @@ impl A2uiMessageProcessor {
 
         data_model.apply_updates(&msg.path, &msg.contents);
 
+        if let Some(data_sync) = &mut self.data_sync {
+            data_sync.reconcile(&msg, data_model);
+        }
+
         // Mark surface as needing redraw
         if let Some(surface) = self.surfaces.get_mut(&msg.surface_id) {
             surface.mark_dirty();
@@ -382,6 +813,9 @@ impl A2uiMessageProcessor {
     fn process_delete_surface(&mut self, msg: DeleteSurface) -> Vec<ProcessorEvent> {
         self.surfaces.remove(&msg.surface_id);
         self.data_models.remove(&msg.surface_id);
+        if let Some(data_sync) = &mut self.data_sync {
+            data_sync.remove_surface(&msg.surface_id);
+        }
 
         vec![ProcessorEvent::SurfaceDeleted(SurfaceDeletedEvent {
             surface_id: msg.surface_id,
@@ -392,7 +826,7 @@ impl A2uiMessageProcessor {
 /// Resolve a path with optional scope prefix.
 /// - If path starts with `/`, it's absolute (use as-is)
 /// - Otherwise, it's relative (prepend scope)
-fn resolve_path(path: &str, scope: Option<&str>) -> String {
+pub(crate) fn resolve_path(path: &str, scope: Option<&str>) -> String {
     if path.starts_with('/') {
         // Absolute path
         path.to_string()
@@ -468,9 +902,42 @@ pub fn resolve_boolean_value_scoped(
     }
 }
 
+/// Resolve a component's `visible` condition with optional scope.
+/// Components without a condition are always visible.
+pub fn resolve_component_visible_scoped(
+    component: &ComponentDefinition,
+    data_model: &DataModel,
+    scope: Option<&str>,
+) -> bool {
+    component
+        .visible
+        .as_ref()
+        .is_none_or(|v| resolve_boolean_value_scoped(v, data_model, scope))
+}
+
+/// Resolve a component's `enabled` condition with optional scope.
+/// Components without a condition are always enabled.
+pub fn resolve_component_enabled_scoped(
+    component: &ComponentDefinition,
+    data_model: &DataModel,
+    scope: Option<&str>,
+) -> bool {
+    component
+        .enabled
+        .as_ref()
+        .is_none_or(|e| resolve_boolean_value_scoped(e, data_model, scope))
+}
+
+impl Drop for A2uiMessageProcessor {
+    fn drop(&mut self) {
+        self.flush_autosave();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::a2ui::recording::SessionReplay;
 
     #[test]
     fn test_process_begin_rendering() {
@@ -511,6 +978,8 @@ mod tests {
             components: vec![ComponentDefinition {
                 id: "title".to_string(),
                 weight: None,
+                visible: None,
+                enabled: None,
                 component: ComponentType::Text(TextComponent {
                     text: StringValue::literal("Hello"),
                     usage_hint: Some(TextUsageHint::H1),
@@ -549,6 +1018,7 @@ mod tests {
                 key: "name".to_string(),
                 value: DataValue::ValueString("Alice".to_string()),
             }],
+            version: None,
         });
 
         let events = processor.process_message(msg);
@@ -572,4 +1042,286 @@ mod tests {
         let path = StringValue::path("/user/name");
         assert_eq!(resolve_string_value(&path, &data_model), "Bob");
     }
+
+    #[test]
+    fn test_resolve_component_visible_and_enabled() {
+        let mut data_model = DataModel::new();
+        data_model.set_bool("/delivery", true);
+        data_model.set_bool("/items/0/inStock", false);
+
+        let mut component = ComponentDefinition {
+            id: "shipping".to_string(),
+            weight: None,
+            visible: None,
+            enabled: None,
+            component: ComponentType::Divider(DividerComponent::default()),
+        };
+
+        // No conditions: visible and enabled
+        assert!(resolve_component_visible_scoped(&component, &data_model, None));
+        assert!(resolve_component_enabled_scoped(&component, &data_model, None));
+
+        // Path-bound visibility
+        component.visible = Some(BooleanValue::path("/delivery"));
+        assert!(resolve_component_visible_scoped(&component, &data_model, None));
+        data_model.set_bool("/delivery", false);
+        assert!(!resolve_component_visible_scoped(&component, &data_model, None));
+
+        // Missing path resolves to false
+        component.visible = Some(BooleanValue::path("/missing"));
+        assert!(!resolve_component_visible_scoped(&component, &data_model, None));
+
+        // Relative path resolved against template scope
+        component.enabled = Some(BooleanValue::path("inStock"));
+        assert!(!resolve_component_enabled_scoped(&component, &data_model, Some("/items/0")));
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                    {"beginRendering": {"surfaceId": "main", "root": "title"}},
+                    {"surfaceUpdate": {"surfaceId": "main", "components": [
+                        {"id": "title", "component": {"Text": {"text": {"path": "/name"}}}}
+                    ]}},
+                    {"dataModelUpdate": {"surfaceId": "main", "contents": [
+                        {"key": "name", "valueString": "Alice"}
+                    ]}}
+                ]"#,
+            )
+            .unwrap();
+        let version = processor.get_data_model("main").unwrap().version();
+
+        let snapshot = processor.snapshot();
+
+        let mut restored = A2uiMessageProcessor::with_standard_catalog();
+        let events = restored.restore_snapshot(snapshot.clone()).unwrap();
+        assert_eq!(events.len(), 1);
+
+        let surface = restored.get_surface("main").unwrap();
+        assert_eq!(surface.root, "title");
+        assert!(surface.get_component("title").is_some());
+
+        let data_model = restored.get_data_model("main").unwrap();
+        assert_eq!(data_model.get_string("/name"), Some("Alice"));
+        assert_eq!(data_model.version(), version);
+
+        assert_eq!(restored.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn test_autosave_on_change() {
+        let dir = std::env::temp_dir().join(format!("a2ui-autosave-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("autosave.json");
+
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor.set_autosave_path(Some(path.clone()));
+        processor.process_message(A2uiMessage::BeginRendering(BeginRendering {
+            surface_id: "main".to_string(),
+            root: "root".to_string(),
+            styles: None,
+        }));
+        assert_eq!(ProcessorSnapshot::load(&path).unwrap().surfaces.len(), 1);
+
+        // Changes within the autosave interval wait for a flush
+        processor.get_data_model_mut("main").unwrap().set_bool("/terms", true);
+        processor.autosave();
+        assert!(processor.autosave_pending());
+        processor.flush_autosave();
+        assert!(!processor.autosave_pending());
+
+        let mut restored = A2uiMessageProcessor::with_standard_catalog();
+        restored.load_snapshot(&path).unwrap();
+        assert_eq!(restored.get_data_model("main").unwrap().get_bool("/terms"), Some(true));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_autosave_trailing_edge() {
+        let dir = std::env::temp_dir().join(format!("a2ui-autosave-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("autosave.json");
+
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor.set_autosave_interval(Duration::from_millis(100));
+        processor.set_autosave_path(Some(path.clone()));
+
+        processor.process_message(A2uiMessage::BeginRendering(BeginRendering {
+            surface_id: "main".to_string(),
+            root: "root".to_string(),
+            styles: None,
+        }));
+        processor.get_data_model_mut("main").unwrap().set_bool("/terms", true);
+        processor.autosave();
+        let due_in = processor.autosave_due_in().unwrap();
+        assert!(due_in <= Duration::from_millis(100));
+
+        // Once the interval has passed, the last change of the burst is written
+        std::thread::sleep(due_in);
+        processor.autosave();
+        assert_eq!(processor.autosave_due_in(), None);
+        let mut restored = A2uiMessageProcessor::with_standard_catalog();
+        restored.load_snapshot(&path).unwrap();
+        assert_eq!(restored.get_data_model("main").unwrap().get_bool("/terms"), Some(true));
+
+        // Resetting writes the empty state immediately
+        processor.reset();
+        assert!(!processor.autosave_pending());
+        assert!(ProcessorSnapshot::load(&path).unwrap().surfaces.is_empty());
+        drop(processor);
+        assert!(ProcessorSnapshot::load(&path).unwrap().surfaces.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_restore_data_only_snapshot() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"{"dataModelUpdate": {"surfaceId": "later", "contents": [
+                    {"key": "name", "valueString": "Alice"}
+                ]}}"#,
+            )
+            .unwrap();
+        let snapshot = processor.snapshot();
+        assert_eq!(snapshot.surfaces[0].root, "");
+
+        let mut restored = A2uiMessageProcessor::with_standard_catalog();
+        let events = restored.restore_snapshot(snapshot).unwrap();
+        assert!(events.is_empty());
+        assert!(restored.get_surface("later").is_none());
+        assert_eq!(
+            restored.get_data_model("later").unwrap().get_string("/name"),
+            Some("Alice")
+        );
+    }
+
+    #[test]
+    fn test_history_rewind() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor.set_history(Some(ProcessorHistory::default()));
+        processor
+            .process_json(
+                r#"[
+                    {"beginRendering": {"surfaceId": "main", "root": "title"}},
+                    {"dataModelUpdate": {"surfaceId": "main", "contents": [
+                        {"key": "name", "valueString": "Alice"}
+                    ]}},
+                    {"dataModelUpdate": {"surfaceId": "main", "contents": [
+                        {"key": "name", "valueString": "Bob"}
+                    ]}}
+                ]"#,
+            )
+            .unwrap();
+        assert_eq!(processor.history().unwrap().len(), 3);
+
+        processor.rewind_to(1).unwrap();
+        let data_model = processor.get_data_model("main").unwrap();
+        assert_eq!(data_model.get_string("/name"), Some("Alice"));
+        assert!(data_model.is_dirty("/name"));
+        assert_eq!(processor.history().unwrap().cursor(), Some(1));
+
+        processor.rewind_to(2).unwrap();
+        let data_model = processor.get_data_model("main").unwrap();
+        assert_eq!(data_model.get_string("/name"), Some("Bob"));
+        assert!(!processor.history().unwrap().is_rewound());
+
+        processor.rewind_to(0).unwrap();
+        assert_eq!(processor.get_data_model("main").unwrap().get("/name"), None);
+        assert!(processor.rewind_to(9).is_err());
+
+        // A new message returns to the latest state before it's applied
+        processor
+            .process_json(
+                r#"{"dataModelUpdate": {"surfaceId": "main", "contents": [
+                    {"key": "age", "valueNumber": 30}
+                ]}}"#,
+            )
+            .unwrap();
+        let data_model = processor.get_data_model("main").unwrap();
+        assert_eq!(data_model.get_string("/name"), Some("Bob"));
+        assert_eq!(data_model.get_number("/age"), Some(30.0));
+        assert_eq!(processor.history().unwrap().len(), 4);
+        assert!(!processor.history().unwrap().is_rewound());
+    }
+
+    #[test]
+    fn test_data_sync_local_values() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor.set_data_sync(Some(DataSync::default()));
+        processor
+            .process_json(
+                r#"[
+                    {"beginRendering": {"surfaceId": "main", "root": "title"}},
+                    {"dataModelUpdate": {"surfaceId": "main", "version": 3, "contents": [
+                        {"key": "name", "valueString": "Alice"}
+                    ]}}
+                ]"#,
+            )
+            .unwrap();
+
+        processor.set_local_value("main", "/name", serde_json::json!("Bob"));
+        assert_eq!(
+            processor
+                .get_data_model("main")
+                .unwrap()
+                .get_string("/name"),
+            Some("Bob")
+        );
+
+        let changes = processor.take_data_changes(Instant::now());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].base_version, Some(3));
+        assert_eq!(changes[0].changes[0].path, "/name");
+
+        // A concurrent server edit wins by default
+        processor
+            .process_json(
+                r#"{"dataModelUpdate": {"surfaceId": "main", "version": 4, "contents": [
+                    {"key": "name", "valueString": "Carol"}
+                ]}}"#,
+            )
+            .unwrap();
+        let data_sync = processor.data_sync_mut().unwrap();
+        assert_eq!(data_sync.take_conflicts().len(), 1);
+        assert_eq!(data_sync.server_version("main"), Some(4));
+    }
+
+    #[test]
+    fn test_reset_keeps_data_sync_and_recorder() {
+        let path = std::env::temp_dir().join(format!("a2ui-reset-{}.jsonl", uuid::Uuid::new_v4()));
+
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor.set_data_sync(Some(DataSync::default()));
+        processor.set_recorder(Some(SessionRecorder::create(&path).unwrap()));
+        processor
+            .process_json(
+                r#"{"dataModelUpdate": {"surfaceId": "main", "version": 3, "contents": [
+                    {"key": "name", "valueString": "Alice"}
+                ]}}"#,
+            )
+            .unwrap();
+
+        processor.reset();
+        assert!(processor.recorder().is_some());
+        let data_sync = processor.data_sync().unwrap();
+        assert_eq!(data_sync.server_version("main"), None);
+
+        // Messages after the reset are still synced and recorded
+        processor
+            .process_json(
+                r#"{"dataModelUpdate": {"surfaceId": "main", "version": 1, "contents": [
+                    {"key": "name", "valueString": "Bob"}
+                ]}}"#,
+            )
+            .unwrap();
+        assert_eq!(processor.data_sync().unwrap().server_version("main"), Some(1));
+        drop(processor);
+        assert_eq!(SessionReplay::load(&path).unwrap().entries().len(), 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
 }
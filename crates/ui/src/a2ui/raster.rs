@@ -0,0 +1,1362 @@
+//! A2UI Software Rasterizer
+//!
+//! Renders A2UI surfaces to RGBA images on the CPU so visual regression tests
+//! can run in CI without a GPU. Layout comes from `HeadlessSurface`; drawing
+//! approximates the `A2uiSurface` draw shaders (rounded cards and buttons,
+//! text fields, checkboxes, slider tracks, audio players) using the colors of
+//! an `A2uiThemeColors`. Enabled with the `raster` feature, typically as a
+//! dev-dependency feature of the crate running the tests.
+//!
+//! Approximations compared to the GPU renderer:
+//! - Text uses a built-in 5x7 bitmap font; non-ASCII characters (emoji, symbols)
+//!   are drawn as outlined boxes.
+//! - Images are always drawn as the striped placeholder, since textures are not
+//!   decoded.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let messages: Vec<A2uiMessage> = serde_json::from_str(json)?;
+//! let png = render_surface_to_png(&messages, (800, 600), &A2uiThemeColors::default());
+//!
+//! let image = RgbaImage::from_png(&png)?;
+//! compare_with_golden(&image, "tests/golden/checkout.png", 2, 0)?;
+//! ```
+
+use std::path::Path;
+
+use makepad_widgets::Vec4;
+
+use super::{
+    data_model::DataModel,
+    headless::{
+        audio_title_row, text_font_size, text_size, HeadlessSurface, LayoutNode, LayoutRect,
+        AUDIO_ARTIST_FONT_SIZE, AUDIO_BARS_HEIGHT, AUDIO_ICON_FONT_SIZE, AUDIO_ROW_GAP,
+        AUDIO_TITLE_FONT_SIZE, BODY_FONT_SIZE, CARD_PADDING, GLYPH_WIDTH, LINE_HEIGHT,
+    },
+    message::*,
+    processor::{
+        resolve_boolean_value_scoped, resolve_number_value_scoped, resolve_string_value_scoped,
+    },
+    surface::A2uiThemeColors,
+};
+
+/// When this environment variable is `1`, `compare_with_golden` writes the
+/// rendered images as the golden files instead of comparing.
+pub const UPDATE_GOLDEN_ENV: &str = "A2UI_UPDATE_GOLDEN";
+
+/// Straight-alpha RGBA color with components in 0.0..=1.0
+type Color = [f32; 4];
+
+const WHITE: Color = [1.0, 1.0, 1.0, 1.0];
+const SKELETON: Color = [0.898, 0.906, 0.922, 1.0];
+
+// ============================================================================
+// RGBA Image
+// ============================================================================
+
+/// An 8-bit RGBA image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Create a transparent image
+    pub fn new(width: u32, height: u32) -> Self {
+        RgbaImage {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Create an image from RGBA pixel data (row-major, 4 bytes per pixel)
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, String> {
+        let expected = width as usize * height as usize * 4;
+        if pixels.len() != expected {
+            return Err(format!(
+                "Expected {} bytes for a {}x{} image, got {}",
+                expected,
+                width,
+                height,
+                pixels.len()
+            ));
+        }
+        Ok(RgbaImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Image width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Image height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// RGBA pixel data
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Get a pixel, `None` if out of bounds
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let idx = (y as usize * self.width as usize + x as usize) * 4;
+        let mut rgba = [0; 4];
+        rgba.copy_from_slice(&self.pixels[idx..idx + 4]);
+        Some(rgba)
+    }
+
+    /// Encode as PNG
+    pub fn to_png(&self) -> Vec<u8> {
+        encode_png(self.width, self.height, &self.pixels)
+    }
+
+    /// Decode a PNG produced by `to_png`
+    ///
+    /// Only 8-bit RGBA, non-interlaced images with uncompressed (stored)
+    /// deflate blocks are supported.
+    pub fn from_png(data: &[u8]) -> Result<Self, String> {
+        decode_png(data)
+    }
+
+    /// Write the image to a PNG file
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_png())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Read an image from a PNG file
+    pub fn load_png(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data =
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_png(&data).map_err(|e| format!("Failed to decode {}: {}", path.display(), e))
+    }
+
+    // ========================================================================
+    // Drawing primitives (coordinates in pixels, antialiased via SDF coverage)
+    // ========================================================================
+
+    /// Blend a color over a pixel with the given coverage
+    fn blend(&mut self, x: i64, y: i64, color: Color, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let alpha = color[3] * coverage.clamp(0.0, 1.0);
+        if alpha <= 0.0 {
+            return;
+        }
+        let idx = (y as usize * self.width as usize + x as usize) * 4;
+        for (channel, value) in color.iter().take(3).enumerate() {
+            let dst = self.pixels[idx + channel] as f32 / 255.0;
+            let out = value * alpha + dst * (1.0 - alpha);
+            self.pixels[idx + channel] = (out * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+        let dst_alpha = self.pixels[idx + 3] as f32 / 255.0;
+        let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+        self.pixels[idx + 3] = (out_alpha * 255.0).round().clamp(0.0, 255.0) as u8;
+    }
+
+    /// Run `shade` for every pixel center in a rect (expanded by `margin`),
+    /// blending the returned color with the returned coverage
+    fn shade_rect(
+        &mut self,
+        rect: LayoutRect,
+        margin: f64,
+        shade: impl Fn(f64, f64) -> (Color, f32),
+    ) {
+        let x0 = (rect.x - margin).floor().max(0.0) as i64;
+        let y0 = (rect.y - margin).floor().max(0.0) as i64;
+        let x1 = ((rect.x + rect.width + margin).ceil() as i64).min(self.width as i64);
+        let y1 = ((rect.y + rect.height + margin).ceil() as i64).min(self.height as i64);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (color, coverage) = shade(x as f64 + 0.5, y as f64 + 0.5);
+                self.blend(x, y, color, coverage);
+            }
+        }
+    }
+
+    /// Fill the whole image with an opaque color
+    fn clear(&mut self, color: Color) {
+        let rgba = to_bytes([color[0], color[1], color[2], 1.0]);
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+
+    fn fill_rounded_rect(&mut self, rect: LayoutRect, radius: f64, color: Color) {
+        self.paint_rounded_rect(rect, radius, |_, _| color);
+    }
+
+    fn paint_rounded_rect(
+        &mut self,
+        rect: LayoutRect,
+        radius: f64,
+        paint: impl Fn(f64, f64) -> Color,
+    ) {
+        self.shade_rect(rect, 1.0, |x, y| {
+            let distance = rounded_box_distance(rect, radius, x, y);
+            (paint(x, y), coverage(-distance))
+        });
+    }
+
+    fn stroke_rounded_rect(&mut self, rect: LayoutRect, radius: f64, width: f64, color: Color) {
+        self.shade_rect(rect, width + 1.0, |x, y| {
+            let distance = rounded_box_distance(rect, radius, x, y);
+            (color, coverage(width / 2.0 - distance.abs()))
+        });
+    }
+
+    fn stroke_line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: Color) {
+        let bounds = LayoutRect::new(
+            from.0.min(to.0),
+            from.1.min(to.1),
+            (from.0 - to.0).abs(),
+            (from.1 - to.1).abs(),
+        );
+        self.shade_rect(bounds, width + 1.0, |x, y| {
+            let distance = segment_distance(from, to, x, y);
+            (color, coverage(width / 2.0 - distance))
+        });
+    }
+
+    /// Draw a single line of text with the built-in bitmap font
+    fn draw_text(&mut self, x: f64, y: f64, text: &str, font_size: f64, color: Color) {
+        // A 6x8 font cell scaled to the layout's glyph width
+        let scale = font_size * GLYPH_WIDTH / 6.0;
+        let top = y + (font_size * LINE_HEIGHT - 8.0 * scale) / 2.0;
+        for (i, ch) in text.chars().enumerate() {
+            let cell_x = x + i as f64 * 6.0 * scale;
+            match glyph(ch) {
+                Some(columns) => {
+                    for (col, bits) in columns.iter().enumerate() {
+                        for row in 0..8 {
+                            if bits & (1 << row) != 0 {
+                                let pixel = LayoutRect::new(
+                                    cell_x + col as f64 * scale,
+                                    top + row as f64 * scale,
+                                    scale,
+                                    scale,
+                                );
+                                self.fill_rounded_rect(pixel, 0.0, color);
+                            }
+                        }
+                    }
+                }
+                None if ch.is_whitespace() => {}
+                None => {
+                    let tofu = LayoutRect::new(cell_x + scale, top, 4.0 * scale, 7.0 * scale);
+                    self.stroke_rounded_rect(tofu, 0.0, scale.max(1.0), color);
+                }
+            }
+        }
+    }
+}
+
+fn to_bytes(color: Color) -> [u8; 4] {
+    color.map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8)
+}
+
+fn coverage(signed_inside: f64) -> f32 {
+    (signed_inside + 0.5).clamp(0.0, 1.0) as f32
+}
+
+/// Signed distance from a point to a rounded box (negative inside), like `Sdf2d::box`
+fn rounded_box_distance(rect: LayoutRect, radius: f64, x: f64, y: f64) -> f64 {
+    let half_w = rect.width / 2.0;
+    let half_h = rect.height / 2.0;
+    let radius = radius.min(half_w).min(half_h).max(0.0);
+    let (cx, cy) = rect.center();
+    let qx = (x - cx).abs() - half_w + radius;
+    let qy = (y - cy).abs() - half_h + radius;
+    let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+    outside + qx.max(qy).min(0.0) - radius
+}
+
+/// Distance from a point to a line segment
+fn segment_distance(from: (f64, f64), to: (f64, f64), x: f64, y: f64) -> f64 {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((x - from.0) * dx + (y - from.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (px, py) = (from.0 + t * dx, from.1 + t * dy);
+    ((x - px).powi(2) + (y - py).powi(2)).sqrt()
+}
+
+fn rgba(color: Vec4) -> Color {
+    [color.x, color.y, color.z, color.w]
+}
+
+fn faded(mut color: Color, enabled: bool) -> Color {
+    if !enabled {
+        color[3] *= 0.5;
+    }
+    color
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+// ============================================================================
+// Surface Rendering
+// ============================================================================
+
+/// Process messages and render the "main" surface to a PNG
+pub fn render_surface_to_png(
+    messages: &[A2uiMessage],
+    size: (u32, u32),
+    theme: &A2uiThemeColors,
+) -> Vec<u8> {
+    let mut surface = HeadlessSurface::new(size.0 as f64, size.1 as f64);
+    surface.processor_mut().process_messages(messages.to_vec());
+    surface.relayout();
+    render_surface(&surface, theme).to_png()
+}
+
+/// Render the current state of a headless surface (viewport-sized image)
+pub fn render_surface(surface: &HeadlessSurface, theme: &A2uiThemeColors) -> RgbaImage {
+    let (width, height) = surface.viewport();
+    let mut image = RgbaImage::new(width.ceil() as u32, height.ceil() as u32);
+    image.clear(rgba(theme.bg_surface));
+
+    let processor = surface.processor();
+    let (Some(surface_def), Some(data_model)) = (
+        processor.get_surface(surface.surface_id()),
+        processor.get_data_model(surface.surface_id()),
+    ) else {
+        return image;
+    };
+
+    let nodes = surface.layout();
+    let mut painter = Painter {
+        image: &mut image,
+        theme,
+        data_model,
+        nodes,
+    };
+    // Nodes are in draw order: parents before children
+    for (idx, node) in nodes.iter().enumerate() {
+        if let Some(component_def) = surface_def.get_component(&node.component_id) {
+            painter.component(idx, node, &component_def.component);
+        }
+    }
+    image
+}
+
+struct Painter<'a> {
+    image: &'a mut RgbaImage,
+    theme: &'a A2uiThemeColors,
+    data_model: &'a DataModel,
+    nodes: &'a [LayoutNode],
+}
+
+impl Painter<'_> {
+    fn has_ancestor(&self, idx: usize, component_type: &str) -> bool {
+        let mut parent = self.nodes[idx].parent;
+        while let Some(p) = parent {
+            if self.nodes[p].component_type == component_type {
+                return true;
+            }
+            parent = self.nodes[p].parent;
+        }
+        false
+    }
+
+    fn component(&mut self, idx: usize, node: &LayoutNode, component: &ComponentType) {
+        let theme = self.theme;
+        let data_model = self.data_model;
+        let scope = node.scope.as_deref();
+        let rect = node.rect;
+
+        match component {
+            ComponentType::Card(_) => self.card(rect),
+            ComponentType::Text(text) => {
+                let value = resolve_string_value_scoped(&text.text, data_model, scope);
+                let font_size = text_font_size(text.usage_hint.as_ref());
+                // Button labels are white and fade with the button
+                let color = if self.has_ancestor(idx, "Button") {
+                    faded(WHITE, node.enabled)
+                } else {
+                    rgba(theme.text_primary)
+                };
+                self.image
+                    .draw_text(rect.x, rect.y, &value, font_size, color);
+            }
+            ComponentType::Image(_) => {
+                let inset = inset(rect, 1.0);
+                self.image.paint_rounded_rect(inset, 4.0, |x, y| {
+                    // Diagonal stripes, like draw_image_placeholder
+                    if (x - rect.x + y - rect.y).rem_euclid(16.0) >= 8.0 {
+                        [0.30, 0.33, 0.40, 1.0]
+                    } else {
+                        [0.25, 0.28, 0.35, 1.0]
+                    }
+                });
+                let (cx, cy) = rect.center();
+                let (label_w, label_h) = text_size("IMG", 11.0);
+                let label_x = cx - label_w / 2.0;
+                let label_y = cy - label_h / 2.0;
+                self.image
+                    .draw_text(label_x, label_y, "IMG", 11.0, rgba(theme.text_secondary));
+            }
+            ComponentType::Button(_) => {
+                let mut color = rgba(theme.accent);
+                if !node.enabled {
+                    color[3] = 0.4;
+                }
+                self.image.fill_rounded_rect(inset(rect, 1.0), 6.0, color);
+            }
+            ComponentType::TextField(text_field) => {
+                let enabled = node.enabled;
+                let field = inset(rect, 1.0);
+                let bg = faded(rgba(theme.input_bg), enabled);
+                let border = faded(rgba(theme.border_color), enabled);
+                self.image.fill_rounded_rect(field, 6.0, bg);
+                self.image.stroke_rounded_rect(field, 6.0, 1.0, border);
+
+                let value = resolve_string_value_scoped(&text_field.text, data_model, scope);
+                let (text, color) = if value.is_empty() {
+                    let placeholder = text_field
+                        .placeholder
+                        .as_ref()
+                        .map(|p| resolve_string_value_scoped(p, data_model, scope))
+                        .unwrap_or_default();
+                    (placeholder, rgba(theme.text_secondary))
+                } else {
+                    (value, rgba(theme.text_primary))
+                };
+                self.image
+                    .draw_text(rect.x + 12.0, rect.y + 8.0, &text, 14.0, color);
+            }
+            ComponentType::CheckBox(checkbox) => {
+                let enabled = node.enabled;
+                let checked = resolve_boolean_value_scoped(&checkbox.value, data_model, scope);
+                let size = 20.0;
+                let border_width = 1.5;
+                let check_box = inset(LayoutRect::new(rect.x, rect.y, size, size), border_width);
+                let bg = if checked {
+                    rgba(theme.control_fill)
+                } else {
+                    rgba(theme.input_bg)
+                };
+                self.image
+                    .fill_rounded_rect(check_box, 4.0, faded(bg, enabled));
+                self.image.stroke_rounded_rect(
+                    check_box,
+                    4.0,
+                    border_width,
+                    faded(rgba(theme.border_color), enabled),
+                );
+                if checked {
+                    let (cx, cy) = (rect.x + size * 0.5, rect.y + size * 0.5);
+                    let scale = size * 0.25;
+                    let mark = faded(WHITE, enabled);
+                    let corner = (cx - scale * 0.2, cy + scale * 0.6);
+                    self.image
+                        .stroke_line((cx - scale * 0.8, cy), corner, 2.0, mark);
+                    self.image
+                        .stroke_line(corner, (cx + scale * 0.8, cy - scale * 0.5), 2.0, mark);
+                }
+
+                if let Some(label) = &checkbox.label {
+                    let label = resolve_string_value_scoped(label, data_model, scope);
+                    self.image.draw_text(
+                        rect.x + size + 8.0,
+                        rect.y,
+                        &label,
+                        14.0,
+                        rgba(theme.text_primary),
+                    );
+                }
+            }
+            ComponentType::Slider(slider) => {
+                let enabled = node.enabled;
+                let value = resolve_number_value_scoped(&slider.value, data_model, scope);
+                let min = slider.min.unwrap_or(0.0);
+                let max = slider.max.unwrap_or(100.0);
+                let progress = if max > min {
+                    ((value - min) / (max - min)).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+
+                // The surface draws only the track (no thumb)
+                let track = LayoutRect::new(rect.x, rect.y + 6.0, rect.width, 6.0);
+                self.image
+                    .fill_rounded_rect(track, 3.0, faded(rgba(theme.slider_track), enabled));
+                if progress > 0.0 {
+                    let fill = LayoutRect::new(track.x, track.y, track.width * progress, 6.0);
+                    self.image.fill_rounded_rect(
+                        fill,
+                        3.0,
+                        faded(rgba(theme.control_fill), enabled),
+                    );
+                }
+            }
+            ComponentType::AudioPlayer(audio_player) => {
+                self.audio_player(idx, rect, audio_player);
+            }
+            ComponentType::Skeleton(_) => {
+                // MpSkeleton's base color, without the shimmer
+                self.image.fill_rounded_rect(rect, 4.0, SKELETON);
+            }
+            _ => {}
+        }
+    }
+
+    fn card(&mut self, rect: LayoutRect) {
+        let card = inset(rect, 1.0);
+        self.image
+            .fill_rounded_rect(card, 8.0, rgba(self.theme.bg_card));
+        self.image
+            .stroke_rounded_rect(card, 8.0, 1.0, rgba(self.theme.border_color));
+    }
+
+    fn audio_player(&mut self, idx: usize, rect: LayoutRect, audio_player: &AudioPlayerComponent) {
+        let data_model = self.data_model;
+        let scope = self.nodes[idx].scope.as_deref();
+        let title = audio_player
+            .title
+            .as_ref()
+            .map(|t| resolve_string_value_scoped(t, data_model, scope))
+            .unwrap_or_else(|| "Audio".to_string());
+        let artist = audio_player
+            .artist
+            .as_ref()
+            .map(|a| resolve_string_value_scoped(a, data_model, scope));
+
+        // Inside a Card the player draws no background of its own
+        let (x, y) = if self.has_ancestor(idx, "Card") {
+            (rect.x, rect.y)
+        } else {
+            self.card(rect);
+            (rect.x + CARD_PADDING.0, rect.y + CARD_PADDING.1)
+        };
+
+        // Title row: icon, title/artist column and audio bars, centered vertically
+        let (info_h, row_h) = audio_title_row(artist.is_some());
+
+        let text_primary = rgba(self.theme.text_primary);
+        let icon_h = text_size("", AUDIO_ICON_FONT_SIZE).1;
+        self.image.draw_text(
+            x,
+            y + (row_h - icon_h) / 2.0,
+            "🎵",
+            AUDIO_ICON_FONT_SIZE,
+            text_primary,
+        );
+
+        let info_x = x + 12.0 + 8.0;
+        let info_y = y + (row_h - info_h) / 2.0;
+        self.image
+            .draw_text(info_x, info_y, &title, AUDIO_TITLE_FONT_SIZE, text_primary);
+        let (mut info_w, title_h) = text_size(&title, AUDIO_TITLE_FONT_SIZE);
+        if let Some(artist) = &artist {
+            let artist_y = info_y + title_h + 2.0;
+            self.image.draw_text(
+                info_x,
+                artist_y,
+                artist,
+                AUDIO_ARTIST_FONT_SIZE,
+                [0.6, 0.6, 0.6, 1.0],
+            );
+            info_w = info_w.max(text_size(artist, AUDIO_ARTIST_FONT_SIZE).0);
+        }
+
+        let bars = LayoutRect::new(
+            info_x + info_w + 8.0 + 16.0,
+            y + (row_h - AUDIO_BARS_HEIGHT) / 2.0,
+            50.0,
+            AUDIO_BARS_HEIGHT,
+        );
+        self.audio_bars(bars);
+
+        // Play button
+        let button_y = y + row_h + AUDIO_ROW_GAP;
+        let label = "▶ Play";
+        let (label_w, label_h) = text_size(label, BODY_FONT_SIZE);
+        let button = LayoutRect::new(x, button_y, label_w + 40.0, label_h + 20.0);
+        self.image
+            .fill_rounded_rect(inset(button, 1.0), 6.0, rgba(self.theme.accent));
+        self.image
+            .draw_text(button.x + 20.0, button.y + 10.0, label, BODY_FONT_SIZE, WHITE);
+    }
+
+    /// Idle (not playing) state of `DrawAudioBars`
+    fn audio_bars(&mut self, rect: LayoutRect) {
+        self.image
+            .fill_rounded_rect(rect, 4.0, [0.08, 0.08, 0.15, 0.9]);
+
+        let padding = 4.0;
+        let gap = 3.0;
+        let bar_width = (rect.width - padding * 2.0 - gap * 4.0) / 5.0;
+        let bar_height = 0.2 * (rect.height - padding * 2.0);
+        let cyan = [0.0, 1.0, 1.0, 1.0];
+        let purple = [0.5, 0.0, 1.0, 1.0];
+        let pink = [1.0, 0.2, 0.6, 1.0];
+        for i in 0..5 {
+            let t = i as f32 / 4.0;
+            let color = mix(mix(cyan, purple, t), pink, t * t);
+            let bar = LayoutRect::new(
+                rect.x + padding + i as f64 * (bar_width + gap),
+                rect.y + rect.height - padding - bar_height,
+                bar_width,
+                bar_height,
+            );
+            self.image.fill_rounded_rect(bar, 1.5, color);
+        }
+    }
+}
+
+fn inset(rect: LayoutRect, amount: f64) -> LayoutRect {
+    LayoutRect::new(
+        rect.x + amount,
+        rect.y + amount,
+        (rect.width - amount * 2.0).max(0.0),
+        (rect.height - amount * 2.0).max(0.0),
+    )
+}
+
+// ============================================================================
+// Golden Image Comparison
+// ============================================================================
+
+/// Result of comparing two images
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImageDiff {
+    /// Pixels with any channel differing by more than the tolerance
+    pub differing_pixels: usize,
+
+    /// Total number of pixels compared
+    pub total_pixels: usize,
+
+    /// Largest channel difference found
+    pub max_channel_delta: u8,
+}
+
+impl ImageDiff {
+    /// Check if no pixel differs beyond the tolerance
+    pub fn is_match(&self) -> bool {
+        self.differing_pixels == 0
+    }
+}
+
+/// Compare two images of the same size, ignoring channel differences up to `tolerance`
+pub fn compare_images(
+    expected: &RgbaImage,
+    actual: &RgbaImage,
+    tolerance: u8,
+) -> Result<ImageDiff, String> {
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        return Err(format!(
+            "Image size mismatch: expected {}x{}, got {}x{}",
+            expected.width, expected.height, actual.width, actual.height
+        ));
+    }
+
+    let mut diff = ImageDiff {
+        total_pixels: expected.pixels.len() / 4,
+        ..ImageDiff::default()
+    };
+    for (a, b) in expected
+        .pixels
+        .chunks_exact(4)
+        .zip(actual.pixels.chunks_exact(4))
+    {
+        let delta = a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+        diff.max_channel_delta = diff.max_channel_delta.max(delta);
+        if delta > tolerance {
+            diff.differing_pixels += 1;
+        }
+    }
+    Ok(diff)
+}
+
+/// Compare an image with a golden PNG file
+///
+/// With `A2UI_UPDATE_GOLDEN=1` the image is written as the new golden. Otherwise
+/// a missing golden is an error, and when more than `max_differing_pixels`
+/// pixels differ, the rendered image is written next to the golden as
+/// `<name>.actual.png` and an error is returned. Both leave the rendered image
+/// next to the golden path for review.
+pub fn compare_with_golden(
+    actual: &RgbaImage,
+    golden_path: impl AsRef<Path>,
+    tolerance: u8,
+    max_differing_pixels: usize,
+) -> Result<ImageDiff, String> {
+    let golden_path = golden_path.as_ref();
+
+    let actual_path = golden_path.with_extension("actual.png");
+
+    if std::env::var(UPDATE_GOLDEN_ENV).is_ok_and(|v| v == "1") {
+        if let Some(parent) = golden_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        actual.save_png(golden_path)?;
+        return Ok(ImageDiff {
+            total_pixels: actual.pixels.len() / 4,
+            ..ImageDiff::default()
+        });
+    }
+
+    if !golden_path.exists() {
+        if let Some(parent) = golden_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        actual.save_png(&actual_path)?;
+        return Err(format!(
+            "Golden {} is missing (actual written to {}); run with {}=1 to create it",
+            golden_path.display(),
+            actual_path.display(),
+            UPDATE_GOLDEN_ENV
+        ));
+    }
+
+    let expected = RgbaImage::load_png(golden_path)?;
+    let diff = compare_images(&expected, actual, tolerance)?;
+    if diff.differing_pixels > max_differing_pixels {
+        actual.save_png(&actual_path)?;
+        return Err(format!(
+            "{} differs from the rendered image: {} of {} pixels (max delta {}), actual written to {}",
+            golden_path.display(),
+            diff.differing_pixels,
+            diff.total_pixels,
+            diff.max_channel_delta,
+            actual_path.display()
+        ));
+    }
+    Ok(diff)
+}
+
+// ============================================================================
+// PNG Encoding
+// ============================================================================
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Largest payload of an uncompressed deflate block
+const MAX_STORED_BLOCK: usize = 65535;
+
+/// Encode RGBA pixels as a PNG using uncompressed deflate blocks
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let stride = width as usize * 4;
+
+    // Scanlines with filter type 0 (None)
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    for row in rgba.chunks_exact(stride.max(1)).take(height as usize) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if raw.is_empty() {
+        vec![&[]]
+    } else {
+        raw.chunks(MAX_STORED_BLOCK).collect()
+    };
+    for (i, block) in blocks.iter().enumerate() {
+        let len = block.len() as u16;
+        zlib.push(u8::from(i + 1 == blocks.len()));
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // 8-bit RGBA, deflate, adaptive filtering, no interlace
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &zlib);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Decode an 8-bit RGBA, non-interlaced PNG
+///
+/// Other color types and bit depths (palette, RGB, grayscale, 16-bit) are
+/// rejected, so goldens should be written by `encode_png` or saved as RGBA.
+pub fn decode_png(data: &[u8]) -> Result<RgbaImage, String> {
+    if !data.starts_with(&PNG_SIGNATURE) {
+        return Err("Not a PNG file".to_string());
+    }
+
+    let mut pos = PNG_SIGNATURE.len();
+    let mut size = None;
+    let mut idat = Vec::new();
+    while pos + 12 <= data.len() {
+        let len =
+            u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let end = pos + 8 + len;
+        if end + 4 > data.len() {
+            return Err("Truncated PNG chunk".to_string());
+        }
+        let kind = &data[pos + 4..pos + 8];
+        let body = &data[pos + 8..end];
+        let crc = u32::from_be_bytes([data[end], data[end + 1], data[end + 2], data[end + 3]]);
+        if crc32(&data[pos + 4..end]) != crc {
+            return Err(format!(
+                "CRC mismatch in {} chunk",
+                String::from_utf8_lossy(kind)
+            ));
+        }
+
+        match kind {
+            b"IHDR" => {
+                if body.len() != 13 {
+                    return Err("Invalid IHDR chunk".to_string());
+                }
+                let width = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+                let height = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+                if body[8..] != [8, 6, 0, 0, 0] {
+                    return Err("Only 8-bit RGBA, non-interlaced PNGs are supported".to_string());
+                }
+                size = Some((width, height));
+            }
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos = end + 4;
+    }
+
+    let (width, height) = size.ok_or("Missing IHDR chunk")?;
+    let raw = inflate(&idat)?;
+    let pixels = unfilter(&raw, width as usize, height as usize)?;
+    RgbaImage::from_pixels(width, height, pixels)
+}
+
+/// Decompress a zlib stream (stored, fixed and dynamic Huffman blocks)
+fn inflate(zlib: &[u8]) -> Result<Vec<u8>, String> {
+    if zlib.len() < 2
+        || zlib[0] & 0x0F != 8
+        || !u16::from_be_bytes([zlib[0], zlib[1]]).is_multiple_of(31)
+    {
+        return Err("Invalid zlib header".to_string());
+    }
+    if zlib[1] & 0x20 != 0 {
+        return Err("Preset zlib dictionaries are not supported".to_string());
+    }
+
+    let mut bits = BitReader::new(&zlib[2..]);
+    let mut out = Vec::new();
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.align();
+                let len = bits.bits(16)? as usize;
+                let nlen = bits.bits(16)? as usize;
+                if len != !nlen & 0xFFFF {
+                    return Err("Corrupt stored deflate block".to_string());
+                }
+                for _ in 0..len {
+                    out.push(bits.bits(8)? as u8);
+                }
+            }
+            1 => {
+                let (lit, dist) = fixed_huffman();
+                inflate_block(&mut bits, &lit, &dist, &mut out)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_huffman(&mut bits)?;
+                inflate_block(&mut bits, &lit, &dist, &mut out)?;
+            }
+            _ => return Err("Invalid deflate block type".to_string()),
+        }
+        if last {
+            break;
+        }
+    }
+
+    bits.align();
+    let checksum = (0..4).try_fold(0u32, |acc, _| Ok::<_, String>(acc << 8 | bits.bits(8)?))?;
+    if checksum != adler32(&out) {
+        return Err("zlib checksum mismatch".to_string());
+    }
+    Ok(out)
+}
+
+/// LSB-first bit reader over a deflate stream
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader { data, pos: 0, bit: 0 }
+    }
+
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.pos).ok_or("Truncated deflate stream")?;
+            value |= u32::from((byte >> self.bit) & 1) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Skip to the next byte boundary
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// Canonical Huffman code: symbol counts per length and symbols by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, String> {
+        // Codes of each length are consecutive, starting after the shorter ones
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.bits(1)? as i32;
+            let count = i32::from(self.counts[len]);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("Invalid Huffman code".to_string())
+    }
+}
+
+fn fixed_huffman() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_huffman(bits: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    const ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+    let lit_count = bits.bits(5)? as usize + 257;
+    let dist_count = bits.bits(5)? as usize + 1;
+    let code_count = bits.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &ORDER[..code_count] {
+        code_lengths[symbol] = bits.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(lit_count + dist_count);
+    while lengths.len() < lit_count + dist_count {
+        let (value, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or("Repeat without a previous length")?;
+                (previous, 3 + bits.bits(2)?)
+            }
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() != lit_count + dist_count {
+        return Err("Code lengths overrun the Huffman tables".to_string());
+    }
+
+    let (lit, dist) = lengths.split_at(lit_count);
+    Ok((Huffman::new(lit), Huffman::new(dist)))
+}
+
+fn inflate_block(
+    bits: &mut BitReader,
+    lit: &Huffman,
+    dist: &Huffman,
+    out: &mut Vec<u8>,
+) -> Result<(), String> {
+    const LEN_BASE: [u16; 29] = [
+        3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+        131, 163, 195, 227, 258,
+    ];
+    const LEN_EXTRA: [u8; 29] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+    ];
+    const DIST_BASE: [u16; 30] = [
+        1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+        2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+    ];
+    const DIST_EXTRA: [u8; 30] = [
+        0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12,
+        13, 13,
+    ];
+
+    loop {
+        let symbol = lit.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LEN_BASE.len() {
+                    return Err("Invalid deflate length code".to_string());
+                }
+                let len = LEN_BASE[index] as usize + bits.bits(LEN_EXTRA[index].into())? as usize;
+                let index = dist.decode(bits)? as usize;
+                if index >= DIST_BASE.len() {
+                    return Err("Invalid deflate distance code".to_string());
+                }
+                let distance =
+                    DIST_BASE[index] as usize + bits.bits(DIST_EXTRA[index].into())? as usize;
+                if distance > out.len() {
+                    return Err("Deflate distance before the start of the data".to_string());
+                }
+                let start = out.len() - distance;
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+/// Reverse PNG scanline filtering (4 bytes per pixel)
+fn unfilter(raw: &[u8], width: usize, height: usize) -> Result<Vec<u8>, String> {
+    let stride = width * 4;
+    if raw.len() < (stride + 1) * height {
+        return Err("Not enough image data".to_string());
+    }
+
+    let mut pixels = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let line = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        for x in 0..stride {
+            let left = if x >= 4 {
+                pixels[y * stride + x - 4]
+            } else {
+                0
+            };
+            let up = if y > 0 {
+                pixels[(y - 1) * stride + x]
+            } else {
+                0
+            };
+            let up_left = if x >= 4 && y > 0 {
+                pixels[(y - 1) * stride + x - 4]
+            } else {
+                0
+            };
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                other => return Err(format!("Invalid PNG filter type {}", other)),
+            };
+            pixels[y * stride + x] = line[x].wrapping_add(predictor);
+        }
+    }
+    Ok(pixels)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+// ============================================================================
+// Bitmap Font
+// ============================================================================
+
+/// Columns of a printable ASCII glyph (bit 0 = top row), `None` otherwise
+fn glyph(ch: char) -> Option<&'static [u8; 5]> {
+    let code = ch as u32;
+    (0x20..=0x7E)
+        .contains(&code)
+        .then(|| &FONT_5X7[(code - 0x20) as usize])
+}
+
+/// Classic 5x7 font for ASCII 0x20..=0x7E, one byte per column
+#[rustfmt::skip]
+const FONT_5X7: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5F, 0x00, 0x00], // ' ' !
+    [0x00, 0x07, 0x00, 0x07, 0x00], [0x14, 0x7F, 0x14, 0x7F, 0x14], // " #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62], // $ %
+    [0x36, 0x49, 0x56, 0x20, 0x50], [0x00, 0x08, 0x07, 0x03, 0x00], // & '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], [0x00, 0x41, 0x22, 0x1C, 0x00], // ( )
+    [0x2A, 0x1C, 0x7F, 0x1C, 0x2A], [0x08, 0x08, 0x3E, 0x08, 0x08], // * +
+    [0x00, 0x80, 0x70, 0x30, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], // , -
+    [0x00, 0x00, 0x60, 0x60, 0x00], [0x20, 0x10, 0x08, 0x04, 0x02], // . /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], [0x00, 0x42, 0x7F, 0x40, 0x00], // 0 1
+    [0x72, 0x49, 0x49, 0x49, 0x46], [0x21, 0x41, 0x49, 0x4D, 0x33], // 2 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], [0x27, 0x45, 0x45, 0x45, 0x39], // 4 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x31], [0x41, 0x21, 0x11, 0x09, 0x07], // 6 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x46, 0x49, 0x49, 0x29, 0x1E], // 8 9
+    [0x00, 0x00, 0x14, 0x00, 0x00], [0x00, 0x40, 0x34, 0x00, 0x00], // : ;
+    [0x00, 0x08, 0x14, 0x22, 0x41], [0x14, 0x14, 0x14, 0x14, 0x14], // < =
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x59, 0x09, 0x06], // > ?
+    [0x3E, 0x41, 0x5D, 0x59, 0x4E], [0x7C, 0x12, 0x11, 0x12, 0x7C], // @ A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], [0x3E, 0x41, 0x41, 0x41, 0x22], // B C
+    [0x7F, 0x41, 0x41, 0x41, 0x3E], [0x7F, 0x49, 0x49, 0x49, 0x41], // D E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], [0x3E, 0x41, 0x41, 0x51, 0x73], // F G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], [0x00, 0x41, 0x7F, 0x41, 0x00], // H I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], [0x7F, 0x08, 0x14, 0x22, 0x41], // J K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], [0x7F, 0x02, 0x1C, 0x02, 0x7F], // L M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], [0x3E, 0x41, 0x41, 0x41, 0x3E], // N O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], [0x3E, 0x41, 0x51, 0x21, 0x5E], // P Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], [0x26, 0x49, 0x49, 0x49, 0x32], // R S
+    [0x03, 0x01, 0x7F, 0x01, 0x03], [0x3F, 0x40, 0x40, 0x40, 0x3F], // T U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], [0x3F, 0x40, 0x38, 0x40, 0x3F], // V W
+    [0x63, 0x14, 0x08, 0x14, 0x63], [0x03, 0x04, 0x78, 0x04, 0x03], // X Y
+    [0x61, 0x59, 0x49, 0x4D, 0x43], [0x00, 0x7F, 0x41, 0x41, 0x41], // Z [
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x41, 0x7F], // \ ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], [0x40, 0x40, 0x40, 0x40, 0x40], // ^ _
+    [0x00, 0x03, 0x07, 0x08, 0x00], [0x20, 0x54, 0x54, 0x78, 0x40], // ` a
+    [0x7F, 0x28, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x28], // b c
+    [0x38, 0x44, 0x44, 0x28, 0x7F], [0x38, 0x54, 0x54, 0x54, 0x18], // d e
+    [0x00, 0x08, 0x7E, 0x09, 0x02], [0x18, 0xA4, 0xA4, 0x9C, 0x78], // f g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7D, 0x40, 0x00], // h i
+    [0x20, 0x40, 0x40, 0x3D, 0x00], [0x7F, 0x10, 0x28, 0x44, 0x00], // j k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], [0x7C, 0x04, 0x78, 0x04, 0x78], // l m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], // n o
+    [0xFC, 0x18, 0x24, 0x24, 0x18], [0x18, 0x24, 0x24, 0x18, 0xFC], // p q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x24], // r s
+    [0x04, 0x04, 0x3F, 0x44, 0x24], [0x3C, 0x40, 0x40, 0x20, 0x7C], // t u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], [0x3C, 0x40, 0x30, 0x40, 0x3C], // v w
+    [0x44, 0x28, 0x10, 0x28, 0x44], [0x4C, 0x90, 0x90, 0x90, 0x7C], // x y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], // z {
+    [0x00, 0x00, 0x77, 0x00, 0x00], [0x00, 0x41, 0x36, 0x08, 0x00], // | }
+    [0x02, 0x01, 0x02, 0x04, 0x02],                                 // ~
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARD_JSON: &str = r#"[
+        {"beginRendering": {"surfaceId": "main", "root": "card"}},
+        {"surfaceUpdate": {"surfaceId": "main", "components": [
+            {"id": "card", "component": {"Card": {"child": "col"}}},
+            {"id": "col", "component": {"Column": {"children": {"explicitList": ["title", "agree", "ok"]}}}},
+            {"id": "title", "component": {"Text": {"text": {"literalString": "Hello"}, "usageHint": "h2"}}},
+            {"id": "agree", "component": {"CheckBox": {"value": {"path": "/agree"}, "label": {"literalString": "Agree"}}}},
+            {"id": "ok", "component": {"Button": {"child": "ok-label", "action": {"name": "ok"}}}},
+            {"id": "ok-label", "component": {"Text": {"text": {"literalString": "OK"}}}}
+        ]}},
+        {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": [
+            {"key": "agree", "valueBoolean": true}
+        ]}}
+    ]"#;
+
+    fn pixel_at(image: &RgbaImage, x: f64, y: f64) -> [u8; 4] {
+        image.pixel(x as u32, y as u32).unwrap()
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let mut image = RgbaImage::new(300, 200);
+        image.clear([0.2, 0.4, 0.6, 1.0]);
+        image.fill_rounded_rect(LayoutRect::new(10.0, 10.0, 50.0, 30.0), 6.0, WHITE);
+
+        let png = image.to_png();
+        assert!(png.starts_with(&PNG_SIGNATURE));
+        assert_eq!(RgbaImage::from_png(&png).unwrap(), image);
+        assert!(RgbaImage::from_png(&png[..40]).is_err());
+    }
+
+    #[test]
+    fn test_inflate_compressed_blocks() {
+        // zlib.compress(..., 9): a fixed Huffman block and a dynamic one
+        let fixed = [
+            120, 218, 75, 76, 74, 78, 132, 33, 133, 140, 212, 156, 156, 124, 8, 9, 0, 112, 18, 9,
+            1,
+        ];
+        assert_eq!(inflate(&fixed).unwrap(), b"abcabcabcabc hello hello");
+
+        let dynamic = [
+            120, 218, 13, 198, 73, 10, 0, 32, 8, 0, 192, 183, 154, 102, 11, 73, 184, 225, 247, 11,
+            230, 48, 208, 237, 198, 30, 130, 197, 174, 37, 199, 230, 16, 194, 5, 197, 25, 228, 134,
+            166, 77, 191, 31, 167, 72, 46, 88, 15, 26, 160, 21, 39,
+        ];
+        let expected: Vec<u8> = (0u32..50)
+            .map(|i| ((((i * i * 31 + i * 7) >> 3) % 23) + 97) as u8)
+            .collect();
+        assert_eq!(inflate(&dynamic).unwrap(), expected);
+
+        let mut corrupt = dynamic;
+        corrupt[51] ^= 1;
+        assert!(inflate(&corrupt).is_err());
+    }
+
+    #[test]
+    fn test_render_surface_uses_theme_colors() {
+        let messages: Vec<A2uiMessage> = serde_json::from_str(CARD_JSON).unwrap();
+        let theme = A2uiThemeColors::default();
+        let image =
+            RgbaImage::from_png(&render_surface_to_png(&messages, (400, 300), &theme)).unwrap();
+
+        let mut surface = HeadlessSurface::new(400.0, 300.0);
+        surface.process_json(CARD_JSON).unwrap();
+        let card = surface.rect("card").unwrap();
+        let button = surface.rect("ok").unwrap();
+        let check = surface.rect("agree").unwrap();
+
+        assert_eq!(
+            pixel_at(&image, 399.0, 299.0),
+            to_bytes(rgba(theme.bg_surface))
+        );
+        assert_eq!(
+            pixel_at(&image, card.x + card.width - 8.0, card.y + 4.0),
+            to_bytes(rgba(theme.bg_card))
+        );
+        assert_eq!(
+            pixel_at(&image, button.x + 4.0, button.y + button.height / 2.0),
+            to_bytes(rgba(theme.accent))
+        );
+        assert_eq!(
+            pixel_at(&image, check.x + 4.0, check.y + 4.0),
+            to_bytes(rgba(theme.control_fill))
+        );
+
+        // A different theme changes the output
+        let light = render_surface(&surface, &A2uiThemeColors::light());
+        let diff = compare_images(&image, &light, 0).unwrap();
+        assert!(!diff.is_match());
+    }
+
+    #[test]
+    fn test_compare_with_golden() {
+        let dir = std::env::temp_dir().join(format!("a2ui-golden-{}", std::process::id()));
+        let golden = dir.join("card.png");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut surface = HeadlessSurface::new(200.0, 150.0);
+        surface.process_json(CARD_JSON).unwrap();
+        let theme = A2uiThemeColors::default();
+        let image = render_surface(&surface, &theme);
+
+        // A missing golden fails instead of passing silently
+        let e = compare_with_golden(&image, &golden, 0, 0).unwrap_err();
+        assert!(e.contains(UPDATE_GOLDEN_ENV), "{}", e);
+        assert!(!golden.exists());
+
+        image.save_png(&golden).unwrap();
+        assert!(compare_with_golden(&image, &golden, 0, 0)
+            .unwrap()
+            .is_match());
+
+        surface.click("agree").unwrap();
+        let unchecked = render_surface(&surface, &theme);
+        assert!(compare_with_golden(&unchecked, &golden, 0, 0).is_err());
+        assert!(dir.join("card.actual.png").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
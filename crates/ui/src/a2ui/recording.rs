@@ -0,0 +1,461 @@
+//! A2UI Session Recording and Replay
+//!
+//! Records every inbound `A2uiMessage` and outbound `UserAction` with a
+//! timestamp to a JSONL file, and replays recorded sessions back through an
+//! `A2uiMessageProcessor` either at their original timing or one step at a time.
+//!
+//! # Recording Format
+//!
+//! JSONL: one `RecordedEntry` object per line, blank lines ignored:
+//!
+//! ```text
+//! {"timestampMs": 1718000000000, "inbound": {"beginRendering": {"surfaceId": "main", "root": "root"}}}
+//! {"timestampMs": 1718000000250, "outbound": {"surfaceId": "main", "action": {"name": "submit"}}}
+//! ```
+//!
+//! Replay also accepts a surface file: a JSON array of A2UI messages or a
+//! single message (the format of the watch-server `*.a2ui.json` files). Its
+//! messages are replayed as inbound entries, all due immediately.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::message::{A2uiMessage, UserAction};
+use super::processor::{A2uiMessageProcessor, ProcessorEvent};
+
+/// A single recorded event with its timestamp
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedEntry {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+
+    /// The recorded event
+    #[serde(flatten)]
+    pub event: RecordedEvent,
+}
+
+/// Direction and payload of a recorded event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordedEvent {
+    /// Message received from the agent
+    Inbound(A2uiMessage),
+    /// Action sent to the agent
+    Outbound(UserAction),
+}
+
+/// Writes A2UI session events to a JSONL file.
+///
+/// The recorder is a cheap handle: clones share the same file, so one
+/// recorder can be attached to both an `A2uiHost` and an `A2uiMessageProcessor`.
+#[derive(Debug, Clone)]
+pub struct SessionRecorder {
+    writer: Arc<Mutex<BufWriter<File>>>,
+}
+
+impl SessionRecorder {
+    /// Create a recorder writing to a new file (truncates an existing file)
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        Ok(Self::from_file(file))
+    }
+
+    /// Create a recorder appending to a file
+    pub fn append(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        Ok(Self::from_file(file))
+    }
+
+    fn from_file(file: File) -> Self {
+        SessionRecorder {
+            writer: Arc::new(Mutex::new(BufWriter::new(file))),
+        }
+    }
+
+    /// Record a message received from the agent
+    pub fn record_inbound(&self, message: &A2uiMessage) {
+        self.record(RecordedEvent::Inbound(message.clone()));
+    }
+
+    /// Record an action sent to the agent
+    pub fn record_outbound(&self, action: &UserAction) {
+        self.record(RecordedEvent::Outbound(action.clone()));
+    }
+
+    /// Record an event with the current timestamp
+    ///
+    /// Each line is flushed immediately so a crash never loses recorded events.
+    pub fn record(&self, event: RecordedEvent) {
+        let entry = RecordedEntry {
+            timestamp_ms: now_ms(),
+            event,
+        };
+
+        let line = match serde_json::to_string(&entry) {
+            Ok(line) => line,
+            Err(e) => {
+                makepad_widgets::log!("A2UI recorder: failed to serialize entry: {}", e);
+                return;
+            }
+        };
+
+        let Ok(mut writer) = self.writer.lock() else {
+            return;
+        };
+        if let Err(e) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+            makepad_widgets::log!("A2UI recorder: failed to write entry: {}", e);
+        }
+    }
+}
+
+/// How a replay advances through recorded entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplayMode {
+    /// Entries become due at their original relative timing
+    #[default]
+    Timed,
+    /// Entries are only returned by explicit steps
+    Stepped,
+}
+
+/// Replays a recorded A2UI session.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut replay = SessionReplay::load("session.jsonl")?;
+/// let mut processor = A2uiMessageProcessor::with_standard_catalog();
+///
+/// // Stepped: apply one recorded message at a time
+/// replay.set_mode(ReplayMode::Stepped);
+/// while let Some(events) = replay.replay_step(&mut processor) {
+///     println!("{:?}", events);
+/// }
+///
+/// // Timed: call every frame, applies messages as they become due
+/// replay.set_mode(ReplayMode::Timed);
+/// replay.reset();
+/// let events = replay.replay_due(&mut processor);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionReplay {
+    entries: Vec<RecordedEntry>,
+    position: usize,
+    mode: ReplayMode,
+    started_at: Option<Instant>,
+}
+
+impl SessionReplay {
+    /// Create a replay from recorded entries
+    pub fn new(entries: Vec<RecordedEntry>) -> Self {
+        SessionReplay {
+            entries,
+            position: 0,
+            mode: ReplayMode::default(),
+            started_at: None,
+        }
+    }
+
+    /// Parse a replay from a JSONL recording or a surface file
+    ///
+    /// See the module docs for both formats.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        // Surface file format: array of messages (or a single message)
+        if let Ok(messages) = serde_json::from_str::<Vec<A2uiMessage>>(content) {
+            return Ok(Self::from_messages(messages));
+        }
+        if let Ok(message) = serde_json::from_str::<A2uiMessage>(content) {
+            return Ok(Self::from_messages(vec![message]));
+        }
+
+        // Recording format: one entry per line
+        let mut entries = Vec::new();
+        for (line_no, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let entry: RecordedEntry = serde_json::from_str(line)
+                .map_err(|e| format!("Invalid recording entry on line {}: {}", line_no + 1, e))?;
+            entries.push(entry);
+        }
+        Ok(Self::new(entries))
+    }
+
+    /// Load a replay from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content)
+    }
+
+    /// Create a replay of inbound messages that are all due immediately
+    /// (the surface file format)
+    pub fn from_messages(messages: Vec<A2uiMessage>) -> Self {
+        Self::new(
+            messages
+                .into_iter()
+                .map(|message| RecordedEntry {
+                    timestamp_ms: 0,
+                    event: RecordedEvent::Inbound(message),
+                })
+                .collect(),
+        )
+    }
+
+    /// Set the replay mode
+    pub fn set_mode(&mut self, mode: ReplayMode) {
+        self.mode = mode;
+    }
+
+    /// Get the replay mode
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    /// Get all recorded entries
+    pub fn entries(&self) -> &[RecordedEntry] {
+        &self.entries
+    }
+
+    /// Index of the next entry to replay
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Check if all entries have been replayed
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.entries.len()
+    }
+
+    /// Rewind to the first entry and restart the replay clock
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.started_at = None;
+    }
+
+    /// Offset of an entry relative to the first recorded entry
+    pub fn offset_of(&self, index: usize) -> Option<Duration> {
+        let first = self.entries.first()?.timestamp_ms;
+        let entry = self.entries.get(index)?;
+        Some(Duration::from_millis(
+            entry.timestamp_ms.saturating_sub(first),
+        ))
+    }
+
+    /// Advance one entry regardless of timing
+    pub fn step(&mut self) -> Option<&RecordedEntry> {
+        let entry = self.entries.get(self.position)?;
+        self.position += 1;
+        Some(entry)
+    }
+
+    /// Take all entries due at `elapsed` time since the replay started
+    ///
+    /// Always empty in `Stepped` mode.
+    pub fn due_at(&mut self, elapsed: Duration) -> Vec<RecordedEntry> {
+        let mut due = Vec::new();
+        if self.mode == ReplayMode::Stepped {
+            return due;
+        }
+
+        while let Some(offset) = self.offset_of(self.position) {
+            if offset > elapsed {
+                break;
+            }
+            due.push(self.entries[self.position].clone());
+            self.position += 1;
+        }
+        due
+    }
+
+    /// Take all entries due now, starting the replay clock on first call
+    pub fn due(&mut self) -> Vec<RecordedEntry> {
+        let started_at = *self.started_at.get_or_insert_with(Instant::now);
+        self.due_at(started_at.elapsed())
+    }
+
+    /// Apply the next inbound message to a processor, skipping outbound actions
+    ///
+    /// Returns `None` when the replay is finished.
+    pub fn replay_step(
+        &mut self,
+        processor: &mut A2uiMessageProcessor,
+    ) -> Option<Vec<ProcessorEvent>> {
+        while let Some(entry) = self.step() {
+            if let RecordedEvent::Inbound(message) = &entry.event {
+                let message = message.clone();
+                return Some(processor.process_message(message));
+            }
+        }
+        None
+    }
+
+    /// Apply all inbound messages that are due now (timed mode)
+    pub fn replay_due(&mut self, processor: &mut A2uiMessageProcessor) -> Vec<ProcessorEvent> {
+        Self::apply(self.due(), processor)
+    }
+
+    /// Apply all remaining inbound messages immediately
+    pub fn replay_all(&mut self, processor: &mut A2uiMessageProcessor) -> Vec<ProcessorEvent> {
+        let remaining = self.entries[self.position.min(self.entries.len())..].to_vec();
+        self.position = self.entries.len();
+        Self::apply(remaining, processor)
+    }
+
+    fn apply(
+        entries: Vec<RecordedEntry>,
+        processor: &mut A2uiMessageProcessor,
+    ) -> Vec<ProcessorEvent> {
+        let messages = entries
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                RecordedEvent::Inbound(message) => Some(message),
+                RecordedEvent::Outbound(_) => None,
+            })
+            .collect();
+        processor.process_messages(messages)
+    }
+}
+
+/// Current Unix time in milliseconds
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::message::{BeginRendering, DeleteSurface, UserActionPayload};
+    use std::collections::HashMap;
+
+    fn begin(surface_id: &str) -> A2uiMessage {
+        A2uiMessage::BeginRendering(BeginRendering {
+            surface_id: surface_id.to_string(),
+            root: "root".to_string(),
+            styles: None,
+        })
+    }
+
+    fn entry(timestamp_ms: u64, event: RecordedEvent) -> RecordedEntry {
+        RecordedEntry {
+            timestamp_ms,
+            event,
+        }
+    }
+
+    #[test]
+    fn test_record_and_parse_jsonl() {
+        let path =
+            std::env::temp_dir().join(format!("a2ui-recording-{}.jsonl", uuid::Uuid::new_v4()));
+
+        let recorder = SessionRecorder::create(&path).unwrap();
+        recorder.record_inbound(&begin("main"));
+        recorder.record_outbound(&UserAction {
+            surface_id: "main".to_string(),
+            action: UserActionPayload {
+                name: "submit".to_string(),
+                context: HashMap::new(),
+            },
+            component_id: Some("submit-btn".to_string()),
+        });
+
+        let replay = SessionReplay::load(&path).unwrap();
+        assert_eq!(replay.entries().len(), 2);
+        assert!(matches!(
+            replay.entries()[0].event,
+            RecordedEvent::Inbound(_)
+        ));
+        assert!(matches!(
+            replay.entries()[1].event,
+            RecordedEvent::Outbound(_)
+        ));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_parse_surface_file_array() {
+        let json = r#"[
+            {"beginRendering": {"surfaceId": "main", "root": "root"}},
+            {"surfaceUpdate": {"surfaceId": "main", "components": []}}
+        ]"#;
+
+        let mut replay = SessionReplay::parse(json).unwrap();
+        assert_eq!(replay.entries().len(), 2);
+
+        // All messages are due immediately
+        assert_eq!(replay.due_at(Duration::ZERO).len(), 2);
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn test_timed_replay() {
+        let mut replay = SessionReplay::new(vec![
+            entry(1_000, RecordedEvent::Inbound(begin("a"))),
+            entry(1_500, RecordedEvent::Inbound(begin("b"))),
+            entry(3_000, RecordedEvent::Inbound(begin("c"))),
+        ]);
+
+        assert_eq!(replay.due_at(Duration::ZERO).len(), 1);
+        assert_eq!(replay.due_at(Duration::from_millis(499)).len(), 0);
+        assert_eq!(replay.due_at(Duration::from_millis(500)).len(), 1);
+        assert_eq!(replay.due_at(Duration::from_secs(10)).len(), 1);
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn test_stepped_replay_skips_outbound() {
+        let mut replay = SessionReplay::new(vec![
+            entry(0, RecordedEvent::Inbound(begin("main"))),
+            entry(
+                10,
+                RecordedEvent::Outbound(UserAction {
+                    surface_id: "main".to_string(),
+                    action: UserActionPayload {
+                        name: "close".to_string(),
+                        context: HashMap::new(),
+                    },
+                    component_id: None,
+                }),
+            ),
+            entry(
+                20,
+                RecordedEvent::Inbound(A2uiMessage::DeleteSurface(DeleteSurface {
+                    surface_id: "main".to_string(),
+                })),
+            ),
+        ]);
+        replay.set_mode(ReplayMode::Stepped);
+
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+
+        // Stepped mode never returns due entries
+        assert!(replay.due_at(Duration::from_secs(10)).is_empty());
+
+        replay.replay_step(&mut processor).unwrap();
+        assert!(processor.get_surface("main").is_some());
+
+        replay.replay_step(&mut processor).unwrap();
+        assert!(processor.get_surface("main").is_none());
+
+        assert!(replay.replay_step(&mut processor).is_none());
+    }
+}
@@ -32,6 +32,9 @@ pub enum A2uiComponentType {
 
     // Media
     AudioPlayer,
+
+    // Placeholder
+    Skeleton,
 }
 
 impl A2uiComponentType {
@@ -54,6 +57,7 @@ impl A2uiComponentType {
             A2uiComponentType::Modal => "Modal",
             A2uiComponentType::Tabs => "Tabs",
             A2uiComponentType::AudioPlayer => "AudioPlayer",
+            A2uiComponentType::Skeleton => "Skeleton",
         }
     }
 
@@ -76,6 +80,7 @@ impl A2uiComponentType {
             "Modal" => Some(A2uiComponentType::Modal),
             "Tabs" => Some(A2uiComponentType::Tabs),
             "AudioPlayer" => Some(A2uiComponentType::AudioPlayer),
+            "Skeleton" => Some(A2uiComponentType::Skeleton),
             _ => None,
         }
     }
@@ -99,6 +104,7 @@ impl A2uiComponentType {
             A2uiComponentType::Modal,
             A2uiComponentType::Tabs,
             A2uiComponentType::AudioPlayer,
+            A2uiComponentType::Skeleton,
         ]
     }
 }
@@ -274,6 +280,14 @@ impl ComponentRegistry {
             implemented: true,
         });
 
+        // Placeholder components
+        registry.register(ComponentMapping {
+            a2ui_type: A2uiComponentType::Skeleton,
+            makepad_widget: "MpSkeleton",
+            description: "Loading placeholder for a component still streaming in",
+            implemented: true,
+        });
+
         registry
     }
 
@@ -346,6 +360,7 @@ pub fn component_type_of(component: &super::message::ComponentType) -> A2uiCompo
         ComponentType::Modal(_) => A2uiComponentType::Modal,
         ComponentType::Tabs(_) => A2uiComponentType::Tabs,
         ComponentType::AudioPlayer(_) => A2uiComponentType::AudioPlayer,
+        ComponentType::Skeleton(_) => A2uiComponentType::Skeleton,
     }
 }
 
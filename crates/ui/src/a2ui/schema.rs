@@ -0,0 +1,446 @@
+//! A2UI JSON Schema
+//!
+//! Generates a JSON Schema (draft 2020-12) for `A2uiMessage` from the message
+//! types (enabled with the `schema` feature), so it can't drift from what the
+//! processor accepts. The schema can be narrowed to the components of a
+//! `ComponentRegistry`, saved for other tools (e.g., to constrain LLM output),
+//! and used to validate incoming payloads with errors that point at the
+//! offending field.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let schema = CatalogSchema::from_registry(&ComponentRegistry::with_standard_catalog());
+//! schema.save("a2ui.schema.json")?;
+//!
+//! if let Err(e) = schema.validate_str(payload) {
+//!     // e.g. "/surfaceUpdate/components/0/component: expected one of Column, Row, ..."
+//! }
+//! ```
+//!
+//! # Scope
+//!
+//! Only the built-in component types can be described. `ComponentType` is a
+//! closed enum, so the processor and renderer have no way to accept a custom
+//! component; a registry can narrow the catalog, not extend it. Custom
+//! components need a renderer extension point first and are out of scope here.
+//!
+//! # Validation
+//!
+//! `validate` uses a small built-in validator that covers the keywords the
+//! generated schema uses: `$ref` (local `#/...` pointers), `type`, `enum`,
+//! `minimum`/`maximum`, `properties`, `required`, `additionalProperties`,
+//! `items`, `allOf`, `anyOf`, `oneOf` and boolean schemas (`format`,
+//! `default` and `description` are annotations). Other keywords (e.g.,
+//! `pattern`, `minLength`, `const`) are ignored, so use a full validator for
+//! hand-edited schemas.
+
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use super::message::A2uiMessage;
+use super::registry::{A2uiComponentType, ComponentRegistry};
+
+/// JSON Schema of A2UI messages for a component catalog
+#[derive(Debug, Clone)]
+pub struct CatalogSchema {
+    schema: Value,
+}
+
+impl CatalogSchema {
+    /// Schema of all message types with every standard component
+    pub fn standard() -> Self {
+        let schema = schemars::schema_for!(A2uiMessage);
+        CatalogSchema {
+            schema: schema.into(),
+        }
+    }
+
+    /// Schema allowing only the components registered in `registry`
+    ///
+    /// Component descriptions are taken from the registry mappings.
+    pub fn from_registry(registry: &ComponentRegistry) -> Self {
+        let mut catalog = Self::standard();
+        if let Some(variants) = catalog.component_variants_mut() {
+            variants.retain(|variant| {
+                variant_name(variant)
+                    .and_then(A2uiComponentType::from_str)
+                    .is_some_and(|component_type| registry.contains(component_type))
+            });
+            for variant in variants.iter_mut() {
+                let mapping = variant_name(variant).and_then(|name| registry.get_by_name(name));
+                if let (Some(mapping), Some(variant)) = (mapping, variant.as_object_mut()) {
+                    variant.insert("description".to_string(), json!(mapping.description));
+                }
+            }
+        }
+        catalog
+    }
+
+    /// Names of the allowed component types
+    pub fn component_names(&self) -> Vec<&str> {
+        self.schema["$defs"]["ComponentType"]["oneOf"]
+            .as_array()
+            .map(|variants| variants.iter().filter_map(variant_name).collect())
+            .unwrap_or_default()
+    }
+
+    /// The schema document
+    pub fn as_value(&self) -> &Value {
+        &self.schema
+    }
+
+    /// Schema of a type defined in the document (e.g., "ComponentDefinition")
+    pub fn definition(&self, name: &str) -> Option<&Value> {
+        self.schema["$defs"].get(name)
+    }
+
+    /// The schema document as pretty-printed JSON
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(&self.schema).unwrap_or_default()
+    }
+
+    /// Write the schema document to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json_pretty())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Validate an A2UI message
+    pub fn validate(&self, message: &Value) -> Result<(), String> {
+        self.check(&self.schema, message, "")
+    }
+
+    /// Validate a value against a type defined in the document
+    ///
+    /// Errors point into `value` (e.g., "/usageHint: expected one of ...").
+    pub fn validate_definition(&self, name: &str, value: &Value) -> Result<(), String> {
+        if self.definition(name).is_none() {
+            return Err(format!("Unknown schema definition: {}", name));
+        }
+        self.check(&json!({ "$ref": format!("#/$defs/{}", name) }), value, "")
+    }
+
+    /// Validate an A2UI message given as JSON text
+    pub fn validate_str(&self, json: &str) -> Result<(), String> {
+        let message: Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+        self.validate(&message)
+    }
+
+    fn component_variants_mut(&mut self) -> Option<&mut Vec<Value>> {
+        self.schema
+            .pointer_mut("/$defs/ComponentType/oneOf")
+            .and_then(Value::as_array_mut)
+    }
+
+    /// Check a value against a (sub)schema
+    ///
+    /// Supports the keywords listed in the module docs; others are ignored.
+    fn check(&self, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+        let schema = match schema {
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => return Err(error(path, "no value is allowed here")),
+            Value::Object(schema) => schema,
+            _ => return Ok(()),
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let target = reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.schema.pointer(pointer))
+                .ok_or_else(|| {
+                    error(path, &format!("unresolved schema reference {}", reference))
+                })?;
+            self.check(target, value, path)?;
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let matches = match expected {
+                Value::String(name) => has_type(value, name),
+                Value::Array(names) => names
+                    .iter()
+                    .any(|name| name.as_str().is_some_and(|name| has_type(value, name))),
+                _ => true,
+            };
+            if !matches {
+                return Err(error(
+                    path,
+                    &format!("expected {}, found {}", type_list(expected), type_of(value)),
+                ));
+            }
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                return Err(error(
+                    path,
+                    &format!("expected one of {}, found {}", allowed.join(", "), value),
+                ));
+            }
+        }
+
+        if let Some(number) = value.as_f64() {
+            if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < minimum {
+                    return Err(error(path, &format!("{} is less than {}", number, minimum)));
+                }
+            }
+            if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > maximum {
+                    return Err(error(
+                        path,
+                        &format!("{} is greater than {}", number, maximum),
+                    ));
+                }
+            }
+        }
+
+        if let Value::Object(object) = value {
+            self.check_object(schema, object, path)?;
+        }
+
+        if let (Some(items), Value::Array(array)) = (schema.get("items"), value) {
+            for (index, item) in array.iter().enumerate() {
+                self.check(items, item, &format!("{}/{}", path, index))?;
+            }
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub_schema in all {
+                self.check(sub_schema, value, path)?;
+            }
+        }
+
+        for keyword in ["anyOf", "oneOf"] {
+            let Some(alternatives) = schema.get(keyword).and_then(Value::as_array) else {
+                continue;
+            };
+            let results: Vec<Result<(), String>> = alternatives
+                .iter()
+                .map(|alternative| self.check(alternative, value, path))
+                .collect();
+            let matched = results.iter().filter(|result| result.is_ok()).count();
+            if matched == 0 {
+                return Err(self.alternatives_error(alternatives, results, value, path));
+            }
+            if keyword == "oneOf" && matched > 1 {
+                return Err(error(path, "matches more than one alternative"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+    ) -> Result<(), String> {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return Err(error(path, &format!("missing property '{}'", key)));
+                }
+            }
+        }
+
+        for (key, item) in object {
+            let item_path = format!("{}/{}", path, key);
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(property) => self.check(property, item, &item_path)?,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return Err(error(path, &format!("unexpected property '{}'", key)));
+                    }
+                    Some(additional) => self.check(additional, item, &item_path)?,
+                    None => {}
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Explain why no alternative matched
+    ///
+    /// For tagged objects (`{"Text": {...}}`) the error of the alternative with
+    /// the value's tag is reported; an unknown tag lists the allowed ones.
+    fn alternatives_error(
+        &self,
+        alternatives: &[Value],
+        results: Vec<Result<(), String>>,
+        value: &Value,
+        path: &str,
+    ) -> String {
+        let tags: Vec<&str> = alternatives.iter().filter_map(variant_name).collect();
+        if let Value::Object(object) = value {
+            let tagged = alternatives.iter().zip(&results).find(|(alternative, _)| {
+                variant_name(alternative).is_some_and(|tag| object.contains_key(tag))
+            });
+            if let Some((_, Err(e))) = tagged {
+                return e.clone();
+            }
+            if tags.len() == alternatives.len() {
+                return error(path, &format!("expected one of {}", tags.join(", ")));
+            }
+        }
+
+        // Report the deepest error found below this value, the first one on ties
+        let depth = |e: &String| e.split(':').next().map_or(0, str::len);
+        results
+            .into_iter()
+            .filter_map(Result::err)
+            .reduce(|best, e| if depth(&e) > depth(&best) { e } else { best })
+            .unwrap_or_else(|| error(path, "doesn't match any alternative"))
+    }
+}
+
+impl Default for CatalogSchema {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Tag of an externally tagged alternative (`{"required": ["Text"], ...}`)
+fn variant_name(variant: &Value) -> Option<&str> {
+    match variant["required"].as_array()?.as_slice() {
+        [name] if variant["properties"].get(name.as_str()?).is_some() => name.as_str(),
+        _ => None,
+    }
+}
+
+fn error(path: &str, message: &str) -> String {
+    let path = if path.is_empty() { "/" } else { path };
+    format!("{}: {}", path, message)
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+fn type_list(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.as_str().unwrap_or("?").to_string(),
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SURFACE_UPDATE: &str = r#"{"surfaceUpdate": {"surfaceId": "main", "components": [
+        {"id": "root", "component": {"Column": {"children": {"explicitList": ["title"]}}}},
+        {"id": "title", "component": {"Text": {"text": {"literalString": "Hi"}, "usageHint": "h1"}}}
+    ]}}"#;
+
+    #[test]
+    fn test_validate_messages() {
+        let schema = CatalogSchema::standard();
+        schema.validate_str(SURFACE_UPDATE).unwrap();
+        schema
+            .validate_str(
+                r#"{"dataModelUpdate": {"surfaceId": "main", "contents": [
+                    {"key": "user", "valueMap": [{"key": "name", "valueString": "Alice"}]}
+                ]}}"#,
+            )
+            .unwrap();
+
+        let unknown = SURFACE_UPDATE.replace("\"Text\"", "\"Label\"");
+        let e = schema.validate_str(&unknown).unwrap_err();
+        assert!(
+            e.starts_with("/surfaceUpdate/components/1/component: expected one of Column, Row"),
+            "{}",
+            e
+        );
+
+        let bad_hint = SURFACE_UPDATE.replace("\"h1\"", "\"huge\"");
+        let e = schema.validate_str(&bad_hint).unwrap_err();
+        assert!(
+            e.starts_with("/surfaceUpdate/components/1/component/Text/usageHint: expected one of"),
+            "{}",
+            e
+        );
+
+        let missing = r#"{"beginRendering": {"surfaceId": "main"}}"#;
+        assert_eq!(
+            schema.validate_str(missing).unwrap_err(),
+            "/beginRendering: missing property 'root'"
+        );
+        assert_eq!(
+            schema.validate_str(r#"{"render": {}}"#).unwrap_err(),
+            "/: expected one of beginRendering, surfaceUpdate, dataModelUpdate, deleteSurface, userAction"
+        );
+    }
+
+    #[test]
+    fn test_registry_schema() {
+        let mut registry = ComponentRegistry::new();
+        for mapping in ComponentRegistry::with_standard_catalog().all_mappings() {
+            if mapping.a2ui_type != A2uiComponentType::Text {
+                registry.register(mapping.clone());
+            }
+        }
+
+        let schema = CatalogSchema::from_registry(&registry);
+        assert!(!schema.component_names().contains(&"Text"));
+        assert!(schema.component_names().contains(&"Column"));
+        let e = schema.validate_str(SURFACE_UPDATE).unwrap_err();
+        assert!(
+            e.starts_with("/surfaceUpdate/components/1/component: expected one of Column"),
+            "{}",
+            e
+        );
+
+        // Components are described by their registry mappings
+        let column = ComponentRegistry::with_standard_catalog()
+            .get_by_name("Column")
+            .unwrap()
+            .description;
+        let variants = schema.definition("ComponentType").unwrap()["oneOf"]
+            .as_array()
+            .unwrap();
+        let variant = variants
+            .iter()
+            .find(|variant| variant_name(variant) == Some("Column"))
+            .unwrap();
+        assert_eq!(variant["description"], json!(column));
+
+        // The exported document is plain JSON Schema
+        let exported: Value = serde_json::from_str(&schema.to_json_pretty()).unwrap();
+        assert_eq!(
+            exported["$schema"],
+            "https://json-schema.org/draft/2020-12/schema"
+        );
+    }
+}
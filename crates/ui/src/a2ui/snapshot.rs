@@ -0,0 +1,184 @@
+//! A2UI State Snapshots
+//!
+//! Serializes the full state of an `A2uiMessageProcessor` (surfaces, components,
+//! styles and per-surface data models) to a versioned JSON document so it can be
+//! restored after an application restart or crash.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::message::{ComponentDefinition, SurfaceStyles};
+
+/// Current snapshot format version.
+///
+/// Bump this when the snapshot layout changes incompatibly.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// A versioned snapshot of all surfaces held by a processor.
+///
+/// # Example JSON
+///
+/// ```text
+/// {
+///   "formatVersion": 1,
+///   "surfaces": [
+///     {
+///       "surfaceId": "main",
+///       "root": "root-column",
+///       "styles": null,
+///       "components": [...],
+///       "data": {"user": {"name": "Alice"}},
+///       "dataVersion": 3
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessorSnapshot {
+    /// Snapshot format version
+    pub format_version: u32,
+
+    /// Surfaces ordered by surface ID
+    pub surfaces: Vec<SurfaceSnapshot>,
+}
+
+/// Snapshot of a single surface and its data model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SurfaceSnapshot {
+    /// Surface ID
+    pub surface_id: String,
+
+    /// Root component ID
+    pub root: String,
+
+    /// Style configuration
+    #[serde(default)]
+    pub styles: Option<SurfaceStyles>,
+
+    /// Component definitions ordered by component ID
+    #[serde(default)]
+    pub components: Vec<ComponentDefinition>,
+
+    /// Data model contents
+    #[serde(default)]
+    pub data: Value,
+
+    /// Data model version counter
+    #[serde(default)]
+    pub data_version: u64,
+}
+
+impl ProcessorSnapshot {
+    /// Create an empty snapshot with the current format version
+    pub fn new() -> Self {
+        ProcessorSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            surfaces: Vec::new(),
+        }
+    }
+
+    /// Serialize the snapshot to a JSON string
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize snapshot: {}", e))
+    }
+
+    /// Parse a snapshot from a JSON string, rejecting unknown format versions
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let snapshot: ProcessorSnapshot =
+            serde_json::from_str(json).map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+
+        if snapshot.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported snapshot format version: {} (expected {})",
+                snapshot.format_version, SNAPSHOT_FORMAT_VERSION
+            ));
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot to a file
+    ///
+    /// The snapshot is written to a temporary file first and then renamed,
+    /// so a crash mid-write never leaves a truncated snapshot behind.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let json = self.to_json()?;
+
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+        }
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, json)
+            .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+        fs::rename(&tmp_path, path)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+        Ok(())
+    }
+
+    /// Read a snapshot from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&json)
+    }
+}
+
+impl Default for ProcessorSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_snapshot_json_roundtrip() {
+        let mut snapshot = ProcessorSnapshot::new();
+        snapshot.surfaces.push(SurfaceSnapshot {
+            surface_id: "main".to_string(),
+            root: "root".to_string(),
+            styles: None,
+            components: vec![],
+            data: json!({"name": "Alice"}),
+            data_version: 2,
+        });
+
+        let json = snapshot.to_json().unwrap();
+        let restored = ProcessorSnapshot::from_json(&json).unwrap();
+        assert_eq!(restored, snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_rejects_unknown_version() {
+        let json = r#"{"formatVersion": 99, "surfaces": []}"#;
+        assert!(ProcessorSnapshot::from_json(json).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("a2ui-snapshot-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("state.json");
+
+        let snapshot = ProcessorSnapshot::new();
+        snapshot.save(&path).unwrap();
+        assert_eq!(ProcessorSnapshot::load(&path).unwrap(), snapshot);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
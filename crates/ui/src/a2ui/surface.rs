@@ -7,9 +7,11 @@ use makepad_widgets::*;
 
 use super::{
     data_model::DataModel,
+    history::ProcessorHistory,
     message::*,
     processor::{
-        resolve_boolean_value_scoped, resolve_number_value_scoped,
+        resolve_boolean_value_scoped, resolve_component_enabled_scoped,
+        resolve_component_visible_scoped, resolve_number_value_scoped,
         resolve_string_value_scoped, A2uiMessageProcessor, ProcessorEvent,
     },
 };
@@ -176,7 +178,9 @@ live_design! {
             // Highlight border on focus
             let border = mix(self.border_color, vec4(0.231, 0.51, 0.965, 1.0), self.focus);
             sdf.stroke(border, self.border_width);
-            return sdf.result;
+
+            // Fade out when disabled
+            return sdf.result * (1.0 - 0.5 * self.disabled);
         }
     }
 
@@ -224,7 +228,8 @@ live_design! {
                 sdf.stroke(#FFFFFF, 2.0);
             }
 
-            return sdf.result;
+            // Fade out when disabled
+            return sdf.result * (1.0 - 0.5 * self.disabled);
         }
     }
 
@@ -250,7 +255,8 @@ live_design! {
                 sdf.fill(self.fill_color);
             }
 
-            return sdf.result;
+            // Fade out when disabled
+            return sdf.result * (1.0 - 0.5 * self.disabled);
         }
     }
 
@@ -322,6 +328,29 @@ live_design! {
         }
     }
 
+    // ============================================================================
+    // A2UI Skeleton - Placeholder for components that are still streaming in
+    // ============================================================================
+    DrawA2uiSkeleton = {{DrawA2uiSkeleton}} {
+        uniform color_base: #e5e7eb
+        uniform color_shimmer: #f3f4f6
+        uniform shimmer_speed: 0.8
+
+        // Same shimmer as MpSkeletonRounded
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+
+            let shimmer_pos = fract(self.time * self.shimmer_speed) * 1.6 - 0.3;
+            let dist = abs(self.pos.x - shimmer_pos);
+            let shimmer = 1.0 - smoothstep(0.0, 0.3, dist);
+            let color = mix(self.color_base, self.color_shimmer, shimmer);
+
+            sdf.box(0.0, 0.0, self.rect_size.x, self.rect_size.y, 4.0);
+            sdf.fill(color);
+            return sdf.result;
+        }
+    }
+
     // A2UI Surface - Root container for A2UI component rendering
     pub A2uiSurface = {{A2uiSurface}} {
         width: Fill
@@ -482,6 +511,9 @@ live_design! {
         // Audio bars visualization
         draw_audio_bars: <DrawAudioBars> {}
 
+        // Placeholder for components still streaming in
+        draw_skeleton: <DrawA2uiSkeleton> {}
+
         // Image resources
         img_headphones: dep("crate://self/resources/headphones.jpg")
         img_mouse: dep("crate://self/resources/mouse.jpg")
@@ -635,6 +667,8 @@ pub struct DrawA2uiTextField {
     draw_super: DrawQuad,
     #[live(0.0)]
     pub focus: f32,
+    #[live(0.0)]
+    pub disabled: f32,
 }
 
 // ============================================================================
@@ -650,6 +684,8 @@ pub struct DrawA2uiCheckBox {
     pub checked: f32,
     #[live(0.0)]
     pub hover: f32,
+    #[live(0.0)]
+    pub disabled: f32,
 }
 
 // ============================================================================
@@ -663,6 +699,8 @@ pub struct DrawA2uiSliderTrack {
     draw_super: DrawQuad,
     #[live(0.0)]
     pub progress: f32,
+    #[live(0.0)]
+    pub disabled: f32,
 }
 
 // ============================================================================
@@ -693,6 +731,17 @@ pub struct DrawAudioBars {
     pub is_playing: f32,
 }
 
+// ============================================================================
+// DrawA2uiSkeleton - for rendering Skeleton placeholders
+// ============================================================================
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawA2uiSkeleton {
+    #[deref]
+    draw_super: DrawQuad,
+}
+
 // ============================================================================
 // A2UI Surface Widget
 // ============================================================================
@@ -783,6 +832,11 @@ pub struct A2uiSurface {
     #[live]
     draw_audio_bars: DrawAudioBars,
 
+    /// Draw Skeleton placeholders
+    #[redraw]
+    #[live]
+    draw_skeleton: DrawA2uiSkeleton,
+
     /// Image sources (preloaded)
     #[live]
     img_headphones: LiveDependency,
@@ -826,13 +880,17 @@ pub struct A2uiSurface {
     #[rust]
     inside_button: bool,
 
+    /// Flag to track if we're inside a disabled component (inherited by descendants)
+    #[rust]
+    inside_disabled: bool,
+
     /// Button areas for event.hits() detection - each button has independent Area
     #[rust]
     button_areas: Vec<Area>,
 
-    /// Button metadata: (component_id, Option<ActionDefinition>, Option<scope>)
+    /// Button metadata: (component_id, Option<ActionDefinition>, Option<scope>, enabled)
     #[rust]
-    button_data: Vec<(String, Option<ActionDefinition>, Option<String>)>,
+    button_data: Vec<(String, Option<ActionDefinition>, Option<String>, bool)>,
 
     /// Currently hovered button index (only one at a time)
     #[rust]
@@ -855,9 +913,9 @@ pub struct A2uiSurface {
     #[rust]
     text_field_areas: Vec<Area>,
 
-    /// TextField metadata: (component_id, binding_path, current_value)
+    /// TextField metadata: (component_id, binding_path, current_value, enabled)
     #[rust]
-    text_field_data: Vec<(String, Option<String>, String)>,
+    text_field_data: Vec<(String, Option<String>, String, bool)>,
 
     /// Currently focused text field index
     #[rust]
@@ -879,9 +937,9 @@ pub struct A2uiSurface {
     #[rust]
     checkbox_areas: Vec<Area>,
 
-    /// CheckBox metadata: (component_id, binding_path, current_value)
+    /// CheckBox metadata: (component_id, binding_path, current_value, enabled)
     #[rust]
-    checkbox_data: Vec<(String, Option<String>, bool)>,
+    checkbox_data: Vec<(String, Option<String>, bool, bool)>,
 
     /// Currently hovered checkbox index
     #[rust]
@@ -895,9 +953,9 @@ pub struct A2uiSurface {
     #[rust]
     slider_areas: Vec<Area>,
 
-    /// Slider metadata: (component_id, binding_path, min, max, current_value)
+    /// Slider metadata: (component_id, binding_path, min, max, current_value, enabled)
     #[rust]
-    slider_data: Vec<(String, Option<String>, f64, f64, f64)>,
+    slider_data: Vec<(String, Option<String>, f64, f64, f64, bool)>,
 
     /// Currently dragging slider index
     #[rust]
@@ -926,6 +984,10 @@ pub struct A2uiSurface {
     /// Currently playing audio component ID (for Play/Stop toggle)
     #[rust]
     playing_component_id: Option<String>,
+
+    /// Timer for writing changes that arrived within the autosave interval
+    #[rust]
+    autosave_timer: Option<Timer>,
 }
 
 impl A2uiSurface {
@@ -937,9 +999,48 @@ impl A2uiSurface {
     }
 
     /// Clear all surfaces and reset the processor
+    ///
+    /// Autosave, history, data sync and session recording stay enabled.
     pub fn clear(&mut self) {
-        // Reset the processor to clear all surfaces and components
-        self.processor = Some(A2uiMessageProcessor::with_standard_catalog());
+        // Reset in place rather than replacing the processor, so dropping the
+        // old one cannot autosave the pre-clear state over the empty one
+        match self.processor.as_mut() {
+            Some(processor) => processor.reset(),
+            None => self.init_processor(),
+        }
+    }
+
+    /// Schedule a trailing-edge autosave for changes made within the autosave interval
+    fn schedule_autosave(&mut self, cx: &mut Cx) {
+        if self.autosave_timer.is_some() {
+            return;
+        }
+        if let Some(due_in) = self.processor.as_ref().and_then(|p| p.autosave_due_in()) {
+            self.autosave_timer = Some(cx.start_timeout(due_in.as_secs_f64()));
+        }
+    }
+
+    /// Keep a history of the last `capacity` processed messages for the inspector
+    pub fn enable_history(&mut self, capacity: usize) {
+        self.init_processor();
+        if let Some(processor) = self.processor.as_mut() {
+            processor.set_history(Some(ProcessorHistory::new(capacity)));
+        }
+    }
+
+    /// Stop keeping message history, returning to the latest state if rewound
+    pub fn disable_history(&mut self) {
+        if let Some(processor) = self.processor.as_mut() {
+            processor.set_history(None);
+        }
+    }
+
+    /// Rewind the processor to a history entry (see `A2uiMessageProcessor::rewind_to`)
+    pub fn rewind_to(&mut self, sequence: u64) -> Result<Vec<ProcessorEvent>, String> {
+        match self.processor.as_mut() {
+            Some(processor) => processor.rewind_to(sequence),
+            None => Err("No processor to rewind".to_string()),
+        }
     }
 
     /// Apply theme colors to all A2UI components
@@ -1132,6 +1233,27 @@ impl A2uiSurface {
         }
     }
 
+    /// Write a snapshot of all surfaces and data models to a file
+    pub fn save_snapshot(&self, path: impl AsRef<std::path::Path>) -> Result<(), String> {
+        match &self.processor {
+            Some(processor) => processor.save_snapshot(path),
+            None => Err("No processor to snapshot".to_string()),
+        }
+    }
+
+    /// Restore surfaces and data models from a snapshot file
+    pub fn load_snapshot(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<ProcessorEvent>, String> {
+        self.init_processor();
+        if let Some(processor) = self.processor.as_mut() {
+            processor.load_snapshot(path)
+        } else {
+            Ok(vec![])
+        }
+    }
+
     /// Get the current surface ID
     fn get_surface_id(&self) -> String {
         // For now, use "main" as default
@@ -1141,11 +1263,22 @@ impl A2uiSurface {
 
 impl Widget for A2uiSurface {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if self.autosave_timer.as_ref().is_some_and(|t| t.is_event(event).is_some()) {
+            self.autosave_timer = None;
+            if let Some(processor) = self.processor.as_mut() {
+                processor.autosave();
+            }
+            self.schedule_autosave(cx);
+        }
+
         let mut needs_redraw = false;
         let surface_id = self.get_surface_id();
 
-        // Handle text input events for focused text field
-        if let Some(focused_idx) = self.focused_text_field_idx {
+        // Handle text input events for focused text field (disabled fields ignore input)
+        let focused_enabled_idx = self
+            .focused_text_field_idx
+            .filter(|idx| self.text_field_data.get(*idx).is_some_and(|d| d.3));
+        if let Some(focused_idx) = focused_enabled_idx {
             if let Event::TextInput(te) = event {
                 // Insert text at cursor position
                 self.text_input_buffer.insert_str(self.cursor_pos, &te.input);
@@ -1153,7 +1286,7 @@ impl Widget for A2uiSurface {
                 needs_redraw = true;
 
                 // Emit data model change
-                if let Some((_, binding_path, _)) = self.text_field_data.get(focused_idx) {
+                if let Some((_, binding_path, _, _)) = self.text_field_data.get(focused_idx) {
                     if let Some(path) = binding_path {
                         cx.widget_action(
                             self.widget_uid(),
@@ -1177,7 +1310,7 @@ impl Widget for A2uiSurface {
                             needs_redraw = true;
 
                             // Emit data model change
-                            if let Some((_, binding_path, _)) = self.text_field_data.get(focused_idx) {
+                            if let Some((_, binding_path, _, _)) = self.text_field_data.get(focused_idx) {
                                 if let Some(path) = binding_path {
                                     cx.widget_action(
                                         self.widget_uid(),
@@ -1197,7 +1330,7 @@ impl Widget for A2uiSurface {
                             self.text_input_buffer.remove(self.cursor_pos);
                             needs_redraw = true;
 
-                            if let Some((_, binding_path, _)) = self.text_field_data.get(focused_idx) {
+                            if let Some((_, binding_path, _, _)) = self.text_field_data.get(focused_idx) {
                                 if let Some(path) = binding_path {
                                     cx.widget_action(
                                         self.widget_uid(),
@@ -1235,6 +1368,10 @@ impl Widget for A2uiSurface {
 
         // Handle button events
         for (idx, area) in self.button_areas.iter().enumerate() {
+            // Disabled buttons ignore input
+            if !self.button_data.get(idx).is_some_and(|b| b.3) {
+                continue;
+            }
             match event.hits(cx, *area) {
                 Hit::FingerHoverIn(_) => {
                     if self.hovered_button_idx != Some(idx) {
@@ -1262,7 +1399,7 @@ impl Widget for A2uiSurface {
 
                         // Check if released over this button (click confirmed)
                         if fe.is_over {
-                            if let Some((component_id, action_def, btn_scope)) =
+                            if let Some((component_id, action_def, btn_scope, _)) =
                                 self.button_data.get(idx)
                             {
                                 if let Some(action_def) = action_def {
@@ -1296,11 +1433,15 @@ impl Widget for A2uiSurface {
 
         // Handle text field events
         for (idx, area) in self.text_field_areas.iter().enumerate() {
+            // Disabled text fields can't be focused
+            if !self.text_field_data.get(idx).is_some_and(|t| t.3) {
+                continue;
+            }
             match event.hits(cx, *area) {
                 Hit::FingerDown(_) => {
                     // Focus this text field
                     self.focused_text_field_idx = Some(idx);
-                    if let Some((_, _, current_value)) = self.text_field_data.get(idx) {
+                    if let Some((_, _, current_value, _)) = self.text_field_data.get(idx) {
                         self.text_input_buffer = current_value.clone();
                         self.cursor_pos = self.text_input_buffer.len();
                     }
@@ -1313,6 +1454,10 @@ impl Widget for A2uiSurface {
 
         // Handle checkbox events
         for (idx, area) in self.checkbox_areas.iter().enumerate() {
+            // Disabled checkboxes ignore input
+            if !self.checkbox_data.get(idx).is_some_and(|c| c.3) {
+                continue;
+            }
             match event.hits(cx, *area) {
                 Hit::FingerHoverIn(_) => {
                     if self.hovered_checkbox_idx != Some(idx) {
@@ -1336,7 +1481,7 @@ impl Widget for A2uiSurface {
                 Hit::FingerUp(fe) => {
                     if fe.is_over {
                         // Toggle checkbox value
-                        if let Some((_, binding_path, current_value)) =
+                        if let Some((_, binding_path, current_value, _)) =
                             self.checkbox_data.get(idx).cloned()
                         {
                             let new_value = !current_value;
@@ -1400,6 +1545,10 @@ impl Widget for A2uiSurface {
 
         // Handle slider events
         for (idx, area) in self.slider_areas.iter().enumerate() {
+            // Disabled sliders ignore input
+            if !self.slider_data.get(idx).is_some_and(|s| s.5) {
+                continue;
+            }
             match event.hits(cx, *area) {
                 Hit::FingerHoverIn(_) => {
                     if self.hovered_slider_idx != Some(idx) {
@@ -1421,7 +1570,7 @@ impl Widget for A2uiSurface {
                     self.hovered_slider_idx = Some(idx);
 
                     // Calculate value from position
-                    if let Some((_, binding_path, min, max, _)) = self.slider_data.get(idx).cloned()
+                    if let Some((_, binding_path, min, max, _, _)) = self.slider_data.get(idx).cloned()
                     {
                         let rect = area.rect(cx);
                         let rel_x = (fe.abs.x - rect.pos.x) / rect.size.x;
@@ -1443,7 +1592,7 @@ impl Widget for A2uiSurface {
                 }
                 Hit::FingerMove(fe) => {
                     if self.dragging_slider_idx == Some(idx) {
-                        if let Some((_, binding_path, min, max, _)) =
+                        if let Some((_, binding_path, min, max, _, _)) =
                             self.slider_data.get(idx).cloned()
                         {
                             let rect = area.rect(cx);
@@ -1484,6 +1633,9 @@ impl Widget for A2uiSurface {
         // Load image textures if not loaded yet
         self.load_image_textures(cx);
 
+        // Changes are drawn after they are applied, so pick up pending autosaves here
+        self.schedule_autosave(cx);
+
         // Clear component data from previous frame
         // Keep areas - they will be updated in render_* to maintain event tracking
         self.button_data.clear();
@@ -1574,6 +1726,18 @@ impl A2uiSurface {
             return;
         };
 
+        // Skip components hidden by their `visible` condition
+        let scope_path = self.current_scope.as_deref();
+        if !resolve_component_visible_scoped(component_def, data_model, scope_path) {
+            return;
+        }
+
+        // A disabled component disables all of its descendants
+        let was_disabled = self.inside_disabled;
+        if !resolve_component_enabled_scoped(component_def, data_model, scope_path) {
+            self.inside_disabled = true;
+        }
+
         // Clone component data to avoid borrow issues
         let component = component_def.component.clone();
 
@@ -1611,10 +1775,15 @@ impl A2uiSurface {
             ComponentType::AudioPlayer(audio_player) => {
                 self.render_audio_player(cx, audio_player, data_model, component_id);
             }
+            ComponentType::Skeleton(skeleton) => {
+                self.render_skeleton(cx, skeleton);
+            }
             _ => {
                 // Unsupported component - skip for now
             }
         }
+
+        self.inside_disabled = was_disabled;
     }
 
     fn render_column(
@@ -1742,10 +1911,19 @@ impl A2uiSurface {
             return;
         };
 
+        if !resolve_component_visible_scoped(component_def, data_model, self.current_scope.as_deref()) {
+            return;
+        }
+
         let component = component_def.component.clone();
 
         match &component {
             ComponentType::Column(col) => {
+                let was_disabled = self.inside_disabled;
+                if !resolve_component_enabled_scoped(component_def, data_model, self.current_scope.as_deref()) {
+                    self.inside_disabled = true;
+                }
+
                 // Column with fixed width ensures buttons align
                 // Height is Fit to adapt to content
                 let walk = if min_width > 0.0 {
@@ -1770,6 +1948,8 @@ impl A2uiSurface {
                 }
 
                 cx.end_turtle();
+
+                self.inside_disabled = was_disabled;
             }
             _ => {
                 // Other components render normally
@@ -1994,17 +2174,21 @@ impl A2uiSurface {
     ) {
         // Get button index (this is the button we're about to render)
         let button_idx = self.button_data.len();
+        let enabled = !self.inside_disabled;
 
         // Get button state (hover/pressed) for this specific button
-        let is_hover = self.hovered_button_idx == Some(button_idx);
-        let is_pressed = self.pressed_button_idx == Some(button_idx);
+        let is_hover = enabled && self.hovered_button_idx == Some(button_idx);
+        let is_pressed = enabled && self.pressed_button_idx == Some(button_idx);
 
         // Set button color based on state
         let base_color = vec4(0.231, 0.51, 0.965, 1.0);     // #3B82F6 - blue
         let hover_color = vec4(0.145, 0.388, 0.922, 1.0);   // #2563EB - darker blue
         let pressed_color = vec4(0.114, 0.306, 0.847, 1.0); // #1D4ED8 - even darker
+        let disabled_color = vec4(0.231, 0.51, 0.965, 0.4); // faded blue
 
-        let color = if is_pressed {
+        let color = if !enabled {
+            disabled_color
+        } else if is_pressed {
             pressed_color
         } else if is_hover {
             hover_color
@@ -2034,12 +2218,19 @@ impl A2uiSurface {
         // Set flag to use button text (drawn after button background)
         self.inside_button = true;
 
+        // Dim the label of disabled buttons
+        let text_color = self.draw_button_text.color;
+        if !enabled {
+            self.draw_button_text.color.w *= 0.5;
+        }
+
         // Render button child (usually Text)
         let child = btn.child.clone();
         self.render_component(cx, scope, surface, data_model, &child);
 
-        // Reset flag
+        // Reset flag and label color
         self.inside_button = false;
+        self.draw_button_text.color = text_color;
 
         // End button background
         self.draw_button.end(cx);
@@ -2072,6 +2263,7 @@ impl A2uiSurface {
             component_id.to_string(),
             btn.action.clone(),
             self.current_scope.clone(),
+            enabled,
         ));
     }
 
@@ -2087,6 +2279,12 @@ impl A2uiSurface {
         component_id: &str,
     ) {
         let text_field_idx = self.text_field_data.len();
+        let enabled = !self.inside_disabled;
+
+        // A text field that becomes disabled loses focus
+        if !enabled && self.focused_text_field_idx == Some(text_field_idx) {
+            self.focused_text_field_idx = None;
+        }
         let is_focused = self.focused_text_field_idx == Some(text_field_idx);
 
         // Get current value - use input buffer if focused, otherwise from data model
@@ -2132,8 +2330,9 @@ impl A2uiSurface {
         // Record start position
         let start_pos = cx.turtle().pos();
 
-        // Set focus state
+        // Set focus and disabled state
         self.draw_text_field.focus = if is_focused { 1.0 } else { 0.0 };
+        self.draw_text_field.disabled = if enabled { 0.0 } else { 1.0 };
 
         // Draw background
         self.draw_text_field.begin(cx, walk, layout);
@@ -2182,6 +2381,7 @@ impl A2uiSurface {
             component_id.to_string(),
             binding_path,
             current_value,
+            enabled,
         ));
     }
 
@@ -2197,7 +2397,8 @@ impl A2uiSurface {
         component_id: &str,
     ) {
         let checkbox_idx = self.checkbox_data.len();
-        let is_hovered = self.hovered_checkbox_idx == Some(checkbox_idx);
+        let enabled = !self.inside_disabled;
+        let is_hovered = enabled && self.hovered_checkbox_idx == Some(checkbox_idx);
 
         // Get current checked state
         let is_checked =
@@ -2242,6 +2443,7 @@ impl A2uiSurface {
 
         self.draw_checkbox.checked = if is_checked { 1.0 } else { 0.0 };
         self.draw_checkbox.hover = if is_hovered { 1.0 } else { 0.0 };
+        self.draw_checkbox.disabled = if enabled { 0.0 } else { 1.0 };
         self.draw_checkbox.draw_walk(cx, checkbox_walk);
 
         // Draw label
@@ -2277,7 +2479,7 @@ impl A2uiSurface {
 
         // Store metadata
         self.checkbox_data
-            .push((component_id.to_string(), binding_path, is_checked));
+            .push((component_id.to_string(), binding_path, is_checked, enabled));
     }
 
     // ============================================================================
@@ -2292,6 +2494,7 @@ impl A2uiSurface {
         component_id: &str,
     ) {
         let slider_idx = self.slider_data.len();
+        let enabled = !self.inside_disabled;
         let _is_hovered = self.hovered_slider_idx == Some(slider_idx);
         let _is_dragging = self.dragging_slider_idx == Some(slider_idx);
 
@@ -2350,6 +2553,7 @@ impl A2uiSurface {
         };
 
         self.draw_slider_track.progress = progress as f32;
+        self.draw_slider_track.disabled = if enabled { 0.0 } else { 1.0 };
         self.draw_slider_track.draw_walk(cx, track_walk);
 
         cx.end_turtle();
@@ -2380,6 +2584,7 @@ impl A2uiSurface {
             min,
             max,
             current_value,
+            enabled,
         ));
     }
 
@@ -2606,6 +2811,22 @@ impl A2uiSurface {
         log!("[render_audio_player] idx={}, id={}, rect=({:.0},{:.0} {:.0}x{:.0})",
              audio_player_idx, component_id, rect.pos.x, rect.pos.y, rect.size.x, rect.size.y);
     }
+
+    // ============================================================================
+    // Skeleton Rendering
+    // ============================================================================
+
+    fn render_skeleton(&mut self, cx: &mut Cx2d, skeleton: &SkeletonComponent) {
+        let walk = Walk {
+            width: Size::fill(),
+            height: Size::Fixed(skeleton.height.unwrap_or(20.0)),
+            ..Walk::default()
+        };
+        self.draw_skeleton.draw_walk(cx, walk);
+
+        // Keep the shimmer moving until the real component replaces it
+        cx.new_next_frame();
+    }
 }
 
 impl A2uiSurfaceRef {
@@ -2678,6 +2899,17 @@ impl A2uiSurfaceRef {
             inner.set_playing_component(component_id);
         }
     }
+
+    /// Rewind the processor to a history entry and redraw
+    pub fn rewind_to(&self, cx: &mut Cx, sequence: u64) -> Result<Vec<ProcessorEvent>, String> {
+        if let Some(mut inner) = self.borrow_mut() {
+            let events = inner.rewind_to(sequence)?;
+            inner.redraw(cx);
+            Ok(events)
+        } else {
+            Err("A2uiSurface not found".to_string())
+        }
+    }
 }
 
 // ============================================================================
@@ -0,0 +1,779 @@
+//! LLM Tool Definitions
+//!
+//! Derives OpenAI-compatible function-calling tools from a `ComponentRegistry`
+//! and turns the model's tool calls back into A2UI messages. Each registered
+//! component gets a `create_<component>` tool whose parameters come from the
+//! `CatalogSchema`, so every catalog component can be built by a model without
+//! hand-written schemas. Two more tools set data (`set_data`) and choose the
+//! root component (`render_ui`).
+//!
+//! Models often shorten bound values, so `"text": "Hi"` is accepted for
+//! `{"literalString": "Hi"}`, a list of IDs for `{"explicitList": [...]}` and
+//! an action name for `{"name": ...}`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let toolkit = A2uiToolkit::from_registry(&ComponentRegistry::with_standard_catalog());
+//! let request = json!({"model": model, "messages": messages, "tools": toolkit.tools()});
+//!
+//! let mut builder = ToolCallBuilder::new(toolkit, "main");
+//! for call in tool_calls {
+//!     if let Err(e) = builder.call_json(&call.name, &call.arguments) {
+//!         // Report the error back to the model as the tool result
+//!     }
+//! }
+//! let messages = builder.messages();
+//! ```
+
+use serde_json::{json, Map, Value};
+
+use super::message::{
+    A2uiMessage, BeginRendering, ComponentDefinition, DataContent, DataModelUpdate, DataValue,
+    SurfaceUpdate,
+};
+use super::registry::ComponentRegistry;
+use super::schema::CatalogSchema;
+
+/// Name of the tool that sets a value in the data model
+pub const SET_DATA_TOOL: &str = "set_data";
+
+/// Name of the tool that chooses the root component
+pub const RENDER_UI_TOOL: &str = "render_ui";
+
+/// Result of a single tool call
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolOutput {
+    /// A component was created (or redefined)
+    Component(ComponentDefinition),
+    /// A data model value was set
+    Data(DataModelUpdate),
+    /// The surface should be rendered from this root
+    Render(BeginRendering),
+}
+
+/// Tool definitions for a component catalog
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let toolkit = A2uiToolkit::from_registry(&registry);
+/// assert_eq!(toolkit.component_for_tool("create_text_field"), Some("TextField"));
+///
+/// match toolkit.dispatch("main", "create_text", &json!({"id": "title", "text": "Hi"}))? {
+///     ToolOutput::Component(definition) => { /* ... */ }
+///     _ => {}
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct A2uiToolkit {
+    schema: CatalogSchema,
+    /// (tool name, component name) for each component tool
+    components: Vec<(String, String)>,
+}
+
+impl A2uiToolkit {
+    /// Tools for every standard component
+    pub fn standard() -> Self {
+        Self::new(CatalogSchema::standard())
+    }
+
+    /// Tools for the components registered in `registry`
+    pub fn from_registry(registry: &ComponentRegistry) -> Self {
+        Self::new(CatalogSchema::from_registry(registry))
+    }
+
+    fn new(schema: CatalogSchema) -> Self {
+        // Placeholders are sent by the agent while streaming, not by the model
+        let components = schema
+            .component_names()
+            .into_iter()
+            .filter(|name| *name != "Skeleton")
+            .map(|name| (Self::tool_name(name), name.to_string()))
+            .collect();
+        A2uiToolkit { schema, components }
+    }
+
+    /// Tool name for a component type (e.g., "TextField" -> "create_text_field")
+    pub fn tool_name(component: &str) -> String {
+        let mut name = String::from("create");
+        for c in component.chars() {
+            if c.is_uppercase() {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+        name
+    }
+
+    /// Component type created by a tool
+    pub fn component_for_tool(&self, tool: &str) -> Option<&str> {
+        self.components
+            .iter()
+            .find(|(name, _)| name == tool)
+            .map(|(_, component)| component.as_str())
+    }
+
+    /// Names of all tools
+    pub fn tool_names(&self) -> Vec<&str> {
+        self.components
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .chain([SET_DATA_TOOL, RENDER_UI_TOOL])
+            .collect()
+    }
+
+    /// The schema the tools are derived from
+    pub fn schema(&self) -> &CatalogSchema {
+        &self.schema
+    }
+
+    /// Tools array for an OpenAI-compatible chat completions request
+    pub fn tools(&self) -> Value {
+        let mut tools: Vec<Value> = self
+            .components
+            .iter()
+            .filter_map(|(_, component)| self.component_tool(component))
+            .collect();
+        tools.push(function(
+            SET_DATA_TOOL,
+            "Set a value in the data model. Components bound to the path (or below it) show the value.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "JSON pointer path (e.g., '/volume', '/user/name')"
+                    },
+                    "value": {
+                        "type": ["string", "number", "boolean", "object", "array"],
+                        "description": "Value to set; objects and arrays set nested data"
+                    }
+                },
+                "required": ["path", "value"]
+            }),
+        ));
+        tools.push(function(
+            RENDER_UI_TOOL,
+            "Render the UI from the given root component. Call this LAST after creating all components.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "rootId": {
+                        "type": "string",
+                        "description": "ID of the root component (usually a column or row)"
+                    }
+                },
+                "required": ["rootId"]
+            }),
+        ));
+        Value::Array(tools)
+    }
+
+    /// Tool definition creating one component type
+    pub fn component_tool(&self, component: &str) -> Option<Value> {
+        let defs = &self.schema.as_value()["$defs"];
+        let variant = self.variant(component)?;
+        let props = inline(defs, &variant["properties"][component]);
+
+        let mut properties = props["properties"].as_object().cloned().unwrap_or_default();
+        let mut required: Vec<Value> = vec![json!("id")];
+        let definition_fields: Vec<(String, Value)> = self
+            .definition_fields(&properties)
+            .map(|(name, schema)| (name.to_string(), inline(defs, schema)))
+            .collect();
+        properties.extend(definition_fields);
+        if let Some(component_required) = props["required"].as_array() {
+            required.extend(component_required.iter().cloned());
+        }
+
+        let description = variant["description"]
+            .as_str()
+            .or_else(|| props["description"].as_str())
+            .unwrap_or(component);
+        Some(function(
+            &Self::tool_name(component),
+            &format!("Create a {} component: {}", component, description),
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required
+            }),
+        ))
+    }
+
+    /// Convert a tool call into its output
+    ///
+    /// Errors are meant to be returned to the model as the tool result so it
+    /// can correct the call.
+    pub fn dispatch(
+        &self,
+        surface_id: &str,
+        tool: &str,
+        args: &Value,
+    ) -> Result<ToolOutput, String> {
+        let args = args
+            .as_object()
+            .ok_or_else(|| format!("{}: arguments must be an object", tool))?;
+
+        match tool {
+            SET_DATA_TOOL => set_data(surface_id, args).map(ToolOutput::Data),
+            RENDER_UI_TOOL => {
+                let root = args
+                    .get("rootId")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| format!("{}: missing 'rootId'", tool))?;
+                Ok(ToolOutput::Render(BeginRendering {
+                    surface_id: surface_id.to_string(),
+                    root: root.to_string(),
+                    styles: None,
+                }))
+            }
+            _ => {
+                let component = self
+                    .component_for_tool(tool)
+                    .ok_or_else(|| format!("Unknown tool: {}", tool))?;
+                self.component(component, args)
+                    .map(ToolOutput::Component)
+                    .map_err(|e| format!("{}: {}", tool, e))
+            }
+        }
+    }
+
+    fn component(
+        &self,
+        component: &str,
+        args: &Map<String, Value>,
+    ) -> Result<ComponentDefinition, String> {
+        let defs = &self.schema.as_value()["$defs"];
+        let props_schema = self
+            .variant(component)
+            .map(|variant| &variant["properties"][component])
+            .ok_or_else(|| format!("Unknown component: {}", component))?;
+        let props_properties = &resolve(defs, props_schema)["properties"];
+
+        let mut definition = Map::new();
+        let mut props = Map::new();
+        for (name, value) in args {
+            if value.is_null() {
+                continue;
+            }
+            let is_definition_field = props_properties.get(name).is_none()
+                && self.definition_schema()["properties"].get(name).is_some();
+            if is_definition_field {
+                let schema = &self.definition_schema()["properties"][name];
+                definition.insert(name.clone(), coerce(defs, schema, value.clone()));
+            } else {
+                props.insert(name.clone(), value.clone());
+            }
+        }
+        if !definition.get("id").is_some_and(Value::is_string) {
+            return Err("missing 'id'".to_string());
+        }
+
+        let props = coerce(defs, props_schema, Value::Object(props));
+        let props_definition = props_schema["$ref"]
+            .as_str()
+            .and_then(|reference| reference.strip_prefix("#/$defs/"))
+            .unwrap_or(component);
+        self.schema.validate_definition(props_definition, &props)?;
+
+        definition.insert("component".to_string(), json!({ component: props }));
+        let definition = Value::Object(definition);
+        self.schema
+            .validate_definition("ComponentDefinition", &definition)?;
+        serde_json::from_value(definition).map_err(|e| e.to_string())
+    }
+
+    /// Alternative of `ComponentType` for a component
+    fn variant(&self, component: &str) -> Option<&Value> {
+        self.schema.definition("ComponentType")?["oneOf"]
+            .as_array()?
+            .iter()
+            .find(|variant| variant["properties"].get(component).is_some())
+    }
+
+    fn definition_schema(&self) -> &Value {
+        self.schema
+            .definition("ComponentDefinition")
+            .unwrap_or(&Value::Null)
+    }
+
+    /// `ComponentDefinition` fields offered next to the component's own
+    /// properties, skipping names the component already uses
+    fn definition_fields<'a>(
+        &'a self,
+        component_properties: &'a Map<String, Value>,
+    ) -> impl Iterator<Item = (&'a str, &'a Value)> + 'a {
+        self.definition_schema()["properties"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(move |(name, _)| {
+                name.as_str() != "component" && !component_properties.contains_key(name.as_str())
+            })
+            .map(|(name, schema)| (name.as_str(), schema))
+    }
+}
+
+impl Default for A2uiToolkit {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Collects tool calls into the messages of one surface
+///
+/// Components created again with the same ID replace the earlier definition,
+/// so a model can correct itself.
+#[derive(Debug, Clone)]
+pub struct ToolCallBuilder {
+    toolkit: A2uiToolkit,
+    surface_id: String,
+    components: Vec<ComponentDefinition>,
+    data: Vec<DataModelUpdate>,
+    root: Option<String>,
+}
+
+impl ToolCallBuilder {
+    /// Create a builder for a surface
+    pub fn new(toolkit: A2uiToolkit, surface_id: impl Into<String>) -> Self {
+        ToolCallBuilder {
+            toolkit,
+            surface_id: surface_id.into(),
+            components: Vec::new(),
+            data: Vec::new(),
+            root: None,
+        }
+    }
+
+    /// Apply a tool call with parsed arguments
+    pub fn call(&mut self, tool: &str, args: &Value) -> Result<ToolOutput, String> {
+        let output = self.toolkit.dispatch(&self.surface_id, tool, args)?;
+        match &output {
+            ToolOutput::Component(definition) => {
+                match self.components.iter_mut().find(|c| c.id == definition.id) {
+                    Some(existing) => *existing = definition.clone(),
+                    None => self.components.push(definition.clone()),
+                }
+            }
+            ToolOutput::Data(update) => self.data.push(update.clone()),
+            ToolOutput::Render(begin) => self.root = Some(begin.root.clone()),
+        }
+        Ok(output)
+    }
+
+    /// Apply a tool call with arguments as JSON text, as sent by the model
+    pub fn call_json(&mut self, tool: &str, arguments: &str) -> Result<ToolOutput, String> {
+        let args: Value = if arguments.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(arguments)
+                .map_err(|e| format!("{}: invalid arguments: {}", tool, e))?
+        };
+        self.call(tool, &args)
+    }
+
+    /// The toolkit calls are dispatched with
+    pub fn toolkit(&self) -> &A2uiToolkit {
+        &self.toolkit
+    }
+
+    /// Components created so far
+    pub fn components(&self) -> &[ComponentDefinition] {
+        &self.components
+    }
+
+    /// Components created so far, for post-processing before `messages`
+    pub fn components_mut(&mut self) -> &mut [ComponentDefinition] {
+        &mut self.components
+    }
+
+    /// Root chosen by `render_ui`
+    pub fn root(&self) -> Option<&str> {
+        self.root.as_deref()
+    }
+
+    /// Messages rendering the surface
+    ///
+    /// Without a `render_ui` call the root defaults to "root", or to the first
+    /// component if there is none with that ID.
+    pub fn messages(&self) -> Vec<A2uiMessage> {
+        let root = self.root.clone().unwrap_or_else(|| {
+            match self.components.iter().any(|c| c.id == "root") {
+                true => "root".to_string(),
+                false => self
+                    .components
+                    .first()
+                    .map(|c| c.id.clone())
+                    .unwrap_or_else(|| "root".to_string()),
+            }
+        });
+
+        let mut messages = vec![
+            A2uiMessage::BeginRendering(BeginRendering {
+                surface_id: self.surface_id.clone(),
+                root,
+                styles: None,
+            }),
+            A2uiMessage::SurfaceUpdate(SurfaceUpdate {
+                surface_id: self.surface_id.clone(),
+                components: self.components.clone(),
+            }),
+        ];
+        messages.extend(self.data.iter().cloned().map(A2uiMessage::DataModelUpdate));
+        messages
+    }
+}
+
+fn function(name: &str, description: &str, parameters: Value) -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": name,
+            "description": description,
+            "parameters": parameters
+        }
+    })
+}
+
+fn set_data(surface_id: &str, args: &Map<String, Value>) -> Result<DataModelUpdate, String> {
+    let path = args
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("{}: missing 'path'", SET_DATA_TOOL))?;
+    let value = args
+        .get("value")
+        .and_then(DataValue::from_json)
+        .ok_or_else(|| format!("{}: missing 'value'", SET_DATA_TOOL))?;
+
+    let path = path.trim_end_matches('/');
+    let contents = match path.rsplit_once('/') {
+        Some((parent, key)) if !key.is_empty() => {
+            return Ok(DataModelUpdate {
+                surface_id: surface_id.to_string(),
+                path: if parent.is_empty() { "/" } else { parent }.to_string(),
+                contents: vec![DataContent {
+                    key: key.to_string(),
+                    value,
+                }],
+                version: None,
+            });
+        }
+        // The root path takes an object of top-level keys
+        _ => match value {
+            DataValue::ValueMap(contents) if path.is_empty() => contents,
+            _ => {
+                return Err(format!(
+                    "{}: path must start with '/' and name a key, or be '/' with an object value",
+                    SET_DATA_TOOL
+                ))
+            }
+        },
+    };
+    Ok(DataModelUpdate {
+        surface_id: surface_id.to_string(),
+        path: "/".to_string(),
+        contents,
+        version: None,
+    })
+}
+
+/// Follow `$ref`s to the schema they point at
+fn resolve<'a>(defs: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema["$ref"]
+        .as_str()
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        Some(name) => resolve(defs, &defs[name]),
+        None => schema,
+    }
+}
+
+/// Self-contained copy of a schema, as many providers don't follow `$ref`
+///
+/// Optional (nullable) types are reduced to the type itself, and keywords
+/// models don't need (`default`, `format`) are dropped.
+fn inline(defs: &Value, schema: &Value) -> Value {
+    let Value::Object(object) = schema else {
+        return schema.clone();
+    };
+
+    let mut result = Map::new();
+    if let Some(name) = object
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        if let Value::Object(target) = inline(defs, &defs[name]) {
+            result = target;
+        }
+    }
+
+    for (key, value) in object {
+        match key.as_str() {
+            "$ref" | "default" | "format" => {}
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, schema)| (name.clone(), inline(defs, schema)))
+                    .collect();
+                result.insert(key.clone(), Value::Object(properties));
+            }
+            "items" | "additionalProperties" => {
+                result.insert(key.clone(), inline(defs, value));
+            }
+            "anyOf" | "oneOf" | "allOf" => {
+                let alternatives: Vec<&Value> = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|alternative| alternative["type"] != "null")
+                    .collect();
+                match alternatives.as_slice() {
+                    [single] if key != "allOf" => {
+                        if let Value::Object(single) = inline(defs, single) {
+                            for (key, value) in single {
+                                result.entry(key).or_insert(value);
+                            }
+                        }
+                    }
+                    _ => {
+                        let alternatives = alternatives.iter().map(|a| inline(defs, a)).collect();
+                        result.insert(key.clone(), Value::Array(alternatives));
+                    }
+                }
+            }
+            "type" => {
+                let types: Vec<&Value> = value
+                    .as_array()
+                    .map(|types| types.iter().filter(|t| *t != "null").collect())
+                    .unwrap_or_else(|| vec![value]);
+                let value = match types.as_slice() {
+                    [single] => (*single).clone(),
+                    _ => Value::Array(types.into_iter().cloned().collect()),
+                };
+                result.insert(key.clone(), value);
+            }
+            _ => {
+                result.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    Value::Object(result)
+}
+
+/// Expand the shorthands models use for bound values, child lists and actions
+fn coerce(defs: &Value, schema: &Value, value: Value) -> Value {
+    let reference = schema["$ref"]
+        .as_str()
+        .and_then(|r| r.strip_prefix("#/$defs/"));
+    match (reference, value) {
+        (Some("StringValue" | "NumberValue" | "BooleanValue" | "ActionValue"), value)
+            if !value.is_object() && !value.is_array() =>
+        {
+            match value {
+                Value::String(s) => json!({ "literalString": s }),
+                Value::Number(n) => json!({ "literalNumber": n }),
+                Value::Bool(b) => json!({ "literalBoolean": b }),
+                other => other,
+            }
+        }
+        (Some("ChildrenRef"), Value::Array(ids)) => json!({ "explicitList": ids }),
+        (Some("ActionDefinition"), Value::String(name)) => json!({ "name": name }),
+        (Some(_), value) => coerce(defs, resolve(defs, schema), value),
+        (None, Value::Object(object)) => {
+            let object = object
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(name, value)| {
+                    let value = match schema["properties"].get(&name) {
+                        Some(property) => coerce(defs, property, value),
+                        None => value,
+                    };
+                    (name, value)
+                })
+                .collect();
+            Value::Object(object)
+        }
+        (None, Value::Array(items)) => match schema.get("items") {
+            Some(item) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|value| coerce(defs, item, value))
+                    .collect(),
+            ),
+            None => Value::Array(items),
+        },
+        (None, value) => {
+            // Optional values: `anyOf: [{"$ref": ...}, {"type": "null"}]`
+            match schema["anyOf"].as_array().map(|alternatives| {
+                alternatives
+                    .iter()
+                    .filter(|alternative| alternative["type"] != "null")
+                    .collect::<Vec<_>>()
+            }) {
+                Some(alternatives) if alternatives.len() == 1 => {
+                    coerce(defs, alternatives[0], value)
+                }
+                _ => value,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::{A2uiComponentType, ComponentType, StringValue, TextUsageHint};
+
+    #[test]
+    fn test_tools_cover_registry() {
+        let toolkit = A2uiToolkit::from_registry(&ComponentRegistry::with_standard_catalog());
+        let tools = toolkit.tools();
+        let names: Vec<&str> = tools
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["function"]["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, toolkit.tool_names());
+        // Every component but Skeleton, plus set_data and render_ui
+        assert_eq!(names.len(), A2uiComponentType::all().len() + 1);
+        assert!(!names.contains(&"create_skeleton"));
+        for name in [
+            "create_image",
+            "create_list",
+            "create_tabs",
+            "create_multiple_choice",
+        ] {
+            assert!(names.contains(&name), "{}", name);
+        }
+
+        // Parameters are self-contained
+        let text = toolkit.component_tool("Text").unwrap();
+        let parameters = &text["function"]["parameters"];
+        assert!(!parameters.to_string().contains("$ref"));
+        assert_eq!(parameters["required"], json!(["id", "text"]));
+        assert_eq!(parameters["properties"]["usageHint"]["type"], "string");
+        assert!(parameters["properties"]["weight"].is_object());
+
+        // Modal's own `visible` wins over the definition field
+        let modal = toolkit.component_tool("Modal").unwrap();
+        assert!(modal["function"]["parameters"]["properties"]["visible"]["anyOf"].is_array());
+    }
+
+    #[test]
+    fn test_dispatch_components() {
+        let toolkit = A2uiToolkit::standard();
+        let output = toolkit
+            .dispatch(
+                "main",
+                "create_text",
+                &json!({"id": "title", "text": "Hello", "usageHint": "h1", "weight": null}),
+            )
+            .unwrap();
+        let ToolOutput::Component(definition) = output else {
+            panic!("Expected a component");
+        };
+        assert_eq!(definition.id, "title");
+        match definition.component {
+            ComponentType::Text(text) => {
+                assert_eq!(text.text, StringValue::literal("Hello"));
+                assert_eq!(text.usage_hint, Some(TextUsageHint::H1));
+            }
+            other => panic!("Expected Text, got {:?}", other),
+        }
+
+        let output = toolkit
+            .dispatch(
+                "main",
+                "create_button",
+                &json!({"id": "ok", "child": "ok-text", "action": "submit", "enabled": {"path": "/valid"}}),
+            )
+            .unwrap();
+        let ToolOutput::Component(button) = output else {
+            panic!("Expected a component");
+        };
+        assert!(button.enabled.is_some());
+        assert!(
+            matches!(button.component, ComponentType::Button(b) if b.action.as_ref().unwrap().name == "submit")
+        );
+
+        let e = toolkit
+            .dispatch(
+                "main",
+                "create_text",
+                &json!({"id": "t", "text": "Hi", "usageHint": "huge"}),
+            )
+            .unwrap_err();
+        assert!(
+            e.starts_with("create_text: /usageHint: expected one of"),
+            "{}",
+            e
+        );
+        assert_eq!(
+            toolkit
+                .dispatch("main", "create_text", &json!({"text": "Hi"}))
+                .unwrap_err(),
+            "create_text: missing 'id'"
+        );
+        assert_eq!(
+            toolkit
+                .dispatch("main", "create_chart", &json!({}))
+                .unwrap_err(),
+            "Unknown tool: create_chart"
+        );
+    }
+
+    #[test]
+    fn test_builder_messages() {
+        let mut builder = ToolCallBuilder::new(A2uiToolkit::standard(), "main");
+        let calls = [
+            ("create_text", r#"{"id": "label", "text": "Volume"}"#),
+            (
+                "create_slider",
+                r#"{"id": "volume", "value": {"path": "/volume"}, "min": 0, "max": 100}"#,
+            ),
+            (
+                "create_row",
+                r#"{"id": "root", "children": ["label", "volume"]}"#,
+            ),
+            ("set_data", r#"{"path": "/volume", "value": 50}"#),
+            (
+                "set_data",
+                r#"{"path": "/", "value": {"user": {"name": "Alice"}}}"#,
+            ),
+            ("create_text", r#"{"id": "label", "text": "🔊 Volume"}"#),
+            ("render_ui", r#"{"rootId": "root"}"#),
+        ];
+        for (tool, arguments) in calls {
+            builder.call_json(tool, arguments).unwrap();
+        }
+        assert!(builder.call_json("set_data", r#"{"path": "/x"}"#).is_err());
+        assert!(builder.call_json("create_text", "{").is_err());
+
+        assert_eq!(builder.root(), Some("root"));
+        assert_eq!(builder.components().len(), 3);
+        let messages = serde_json::to_value(builder.messages()).unwrap();
+        let schema = CatalogSchema::standard();
+        for message in messages.as_array().unwrap() {
+            schema.validate(message).unwrap();
+        }
+        assert_eq!(messages[0]["beginRendering"]["root"], "root");
+        assert_eq!(
+            messages[1]["surfaceUpdate"]["components"][0]["component"]["Text"]["text"],
+            json!({"literalString": "🔊 Volume"})
+        );
+        assert_eq!(
+            messages[2]["dataModelUpdate"]["contents"],
+            json!([{"key": "volume", "valueNumber": 50.0}])
+        );
+        assert_eq!(
+            messages[3]["dataModelUpdate"]["contents"][0]["valueMap"],
+            json!([{"key": "name", "valueString": "Alice"}])
+        );
+    }
+}
@@ -0,0 +1,654 @@
+//! A2UI Transports
+//!
+//! `A2uiHost` talks to its agent through an `A2uiTransport`: something that
+//! yields A2UI messages and accepts user actions. Implementations:
+//!
+//! - `A2aTransport`: an A2A agent over SSE or WebSocket (`A2aClient`)
+//! - `LiveStreamTransport`: a raw stream of A2UI messages (GET `/live` or `/ws`)
+//! - `ReplayTransport`: a recorded session or a JSON file of messages
+//! - `ChannelTransport`: an in-process channel, for agents in the same app and tests
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let (transport, agent) = channel_transport();
+//! let mut host = A2uiHost::with_transport(transport);
+//! host.connect("Hello")?;
+//!
+//! agent.send_message(message)?;
+//! let action = agent.recv_action();
+//! ```
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::a2a_client::{A2aClient, A2aStreamEvent, A2aTask};
+use super::a2a_part::MessageInput;
+use super::agent_card::AgentCard;
+use super::data_sync::DataModelChange;
+use super::message::{A2uiMessage, UserAction};
+use super::recording::{RecordedEntry, RecordedEvent, ReplayMode, SessionReplay};
+use super::sse::{SseClient, SseEvent};
+#[cfg(feature = "websocket")]
+use super::websocket::{WebSocketConnection, WebSocketTransport};
+
+/// Error returned for `A2uiTransportKind::WebSocket` without the feature
+#[cfg(not(feature = "websocket"))]
+const WEBSOCKET_DISABLED: &str = "WebSocket support requires the `websocket` feature";
+
+/// Events of a transport session, ending when the session ends
+pub type A2uiEventStream = Box<dyn Iterator<Item = A2aStreamEvent> + Send>;
+
+/// A request prepared by a transport, sent by calling it
+pub type A2uiRequest<T = ()> = Box<dyn FnOnce() -> Result<T, String> + Send>;
+
+/// Connection between `A2uiHost` and an agent
+///
+/// The event stream returned by `connect` is read on a background thread.
+/// The host shares the transport between the UI thread and its worker
+/// threads, and only calls the `*_request` methods from the workers: they
+/// prepare a request while the transport is locked, and the worker sends it
+/// after releasing the lock. Transports doing network I/O override them so
+/// slow requests never block the UI thread; the defaults deliver right away.
+pub trait A2uiTransport: Send + 'static {
+    /// Start a session with the agent
+    fn connect(&mut self, initial_message: MessageInput) -> Result<A2uiEventStream, String>;
+
+    /// Deliver a user action to the agent
+    fn send_action(&mut self, action: &UserAction) -> Result<(), String>;
+
+    /// Send a batch of local data model edits to the agent
+    fn send_data_change(&mut self, _change: &DataModelChange) -> Result<(), String> {
+        Err("Data sync is not supported by this transport".to_string())
+    }
+
+    /// Address later requests to the task the agent reported
+    fn set_task_id(&mut self, _task_id: String) {}
+
+    /// Resume the events of a task after its stream ended
+    fn resubscribe(&mut self, _task_id: &str) -> Result<A2uiEventStream, String> {
+        Err("Resubscribing is not supported by this transport".to_string())
+    }
+
+    /// Cancel a task, returning it as reported by the agent
+    fn cancel_task(&mut self, _task_id: &str) -> Result<A2aTask, String> {
+        Err("Canceling tasks is not supported by this transport".to_string())
+    }
+
+    /// Prepare delivering a user action (see `send_action`)
+    fn action_request(&mut self, action: &UserAction) -> Result<A2uiRequest, String> {
+        let result = self.send_action(action);
+        Ok(Box::new(move || result))
+    }
+
+    /// Prepare sending a batch of data model edits (see `send_data_change`)
+    fn data_change_request(&mut self, change: &DataModelChange) -> Result<A2uiRequest, String> {
+        let result = self.send_data_change(change);
+        Ok(Box::new(move || result))
+    }
+
+    /// Prepare canceling a task (see `cancel_task`)
+    ///
+    /// Fails right away when the transport can't cancel tasks.
+    fn cancel_request(&mut self, task_id: &str) -> Result<A2uiRequest<A2aTask>, String> {
+        let result = self.cancel_task(task_id);
+        Ok(Box::new(move || result))
+    }
+}
+
+/// A2UI Host configuration
+///
+/// Build it with `new` and the `with_*` methods, or fill the remaining
+/// fields with `..Default::default()`, so new options don't break callers.
+#[derive(Clone, Debug, Default)]
+pub struct A2uiHostConfig {
+    /// A2A server URL
+    pub url: String,
+    /// Optional authentication token
+    pub auth_token: Option<String>,
+    /// How messages are exchanged with the server
+    pub transport: A2uiTransportKind,
+}
+
+impl A2uiHostConfig {
+    /// Create a configuration for the server at `url`, using SSE
+    pub fn new(url: impl Into<String>) -> Self {
+        A2uiHostConfig {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set authentication token
+    pub fn with_auth(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Set how messages are exchanged with the server
+    pub fn with_transport(mut self, transport: A2uiTransportKind) -> Self {
+        self.transport = transport;
+        self
+    }
+}
+
+/// Connection used by `A2uiHost`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum A2uiTransportKind {
+    /// HTTP POST requests with SSE responses
+    #[default]
+    Sse,
+    /// One WebSocket for messages and user actions (`ws://` or `http://` URL)
+    ///
+    /// Requires the `websocket` feature; connecting fails without it.
+    WebSocket,
+}
+
+/// A2A agent reached with `A2aClient`
+pub struct A2aTransport {
+    config: A2uiHostConfig,
+    agent_card: Option<AgentCard>,
+    client: Option<A2aClient>,
+}
+
+impl A2aTransport {
+    /// Create a transport for the agent at `config.url`
+    pub fn new(config: A2uiHostConfig) -> Self {
+        A2aTransport {
+            config,
+            agent_card: None,
+            client: None,
+        }
+    }
+
+    /// Fetch the agent card and check that the agent supports A2UI
+    ///
+    /// `config.url` may be the agent's base URL instead of its RPC endpoint.
+    /// On success `connect` uses the JSON-RPC endpoint from the card.
+    pub fn discover(&mut self) -> Result<&AgentCard, String> {
+        let card = AgentCard::fetch(&self.config.url)?;
+        self.use_agent_card(card)
+    }
+
+    /// Use an agent card fetched from `config.url` (see `discover`)
+    pub fn use_agent_card(&mut self, card: AgentCard) -> Result<&AgentCard, String> {
+        let endpoint = card.negotiate()?.to_string();
+        if card.requires_auth() && self.config.auth_token.is_none() {
+            makepad_widgets::log!(
+                "A2UI Host: agent '{}' requires authentication but no token is set",
+                card.name
+            );
+        }
+        self.config.url = endpoint;
+        Ok(self.agent_card.insert(card))
+    }
+
+    /// Agent card fetched by `discover`
+    pub fn agent_card(&self) -> Option<&AgentCard> {
+        self.agent_card.as_ref()
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &A2uiHostConfig {
+        &self.config
+    }
+
+    fn client(&mut self) -> Result<&mut A2aClient, String> {
+        self.client
+            .as_mut()
+            .ok_or_else(|| "Not connected".to_string())
+    }
+}
+
+impl A2uiTransport for A2aTransport {
+    fn connect(&mut self, initial_message: MessageInput) -> Result<A2uiEventStream, String> {
+        let mut client = match &self.agent_card {
+            Some(card) => A2aClient::from_agent_card(card.clone())?,
+            None => A2aClient::new(&self.config.url),
+        };
+        if let Some(token) = &self.config.auth_token {
+            client = client.with_auth(token);
+        }
+        if self.config.transport == A2uiTransportKind::WebSocket {
+            #[cfg(feature = "websocket")]
+            client.connect_websocket()?;
+            #[cfg(not(feature = "websocket"))]
+            return Err(WEBSOCKET_DISABLED.to_string());
+        }
+
+        let stream = client.message_stream(initial_message)?;
+        self.client = Some(client);
+        Ok(Box::new(stream))
+    }
+
+    fn send_action(&mut self, action: &UserAction) -> Result<(), String> {
+        self.client()?.send_action(
+            &action.action.name,
+            action.component_id.as_deref().unwrap_or(""),
+            action.action.context.clone(),
+        )
+    }
+
+    fn send_data_change(&mut self, change: &DataModelChange) -> Result<(), String> {
+        self.client()?.send_data_change(change)
+    }
+
+    fn set_task_id(&mut self, task_id: String) {
+        if let Some(client) = &mut self.client {
+            client.set_task_id(task_id);
+        }
+    }
+
+    fn resubscribe(&mut self, task_id: &str) -> Result<A2uiEventStream, String> {
+        let stream = self.client()?.resubscribe(task_id)?;
+        Ok(Box::new(stream))
+    }
+
+    fn cancel_task(&mut self, task_id: &str) -> Result<A2aTask, String> {
+        self.client()?.cancel_task(task_id)
+    }
+
+    fn action_request(&mut self, action: &UserAction) -> Result<A2uiRequest, String> {
+        let request = self.client()?.action_request(
+            &action.action.name,
+            action.component_id.as_deref().unwrap_or(""),
+            action.action.context.clone(),
+        )?;
+        Ok(Box::new(move || {
+            request
+                .send()
+                .map_err(|e| format!("Failed to send action: {}", e))
+        }))
+    }
+
+    fn data_change_request(&mut self, change: &DataModelChange) -> Result<A2uiRequest, String> {
+        let request = self.client()?.data_change_request(change)?;
+        Ok(Box::new(move || {
+            request
+                .send()
+                .map_err(|e| format!("Failed to send data change: {}", e))
+        }))
+    }
+
+    fn cancel_request(&mut self, task_id: &str) -> Result<A2uiRequest<A2aTask>, String> {
+        let request = self.client()?.cancel_request(task_id)?;
+        Ok(Box::new(move || request.call("tasks/cancel")))
+    }
+}
+
+/// Raw stream of A2UI messages, e.g., a watch server's `/live` or `/ws`
+///
+/// Each SSE event or WebSocket frame holds one message or a JSON array of
+/// messages. The initial message is not sent. Over a WebSocket, user actions
+/// are sent as `{"userAction": ...}` frames; a plain SSE stream can't take them.
+pub struct LiveStreamTransport {
+    config: A2uiHostConfig,
+    #[cfg(feature = "websocket")]
+    websocket: Option<WebSocketConnection>,
+}
+
+impl LiveStreamTransport {
+    /// Create a transport for the stream at `config.url`
+    pub fn new(config: A2uiHostConfig) -> Self {
+        LiveStreamTransport {
+            config,
+            #[cfg(feature = "websocket")]
+            websocket: None,
+        }
+    }
+
+    /// Open the socket, which stays open while the transport lives
+    #[cfg(feature = "websocket")]
+    fn connect_websocket(&mut self) -> Result<Receiver<SseEvent>, String> {
+        let mut transport = WebSocketTransport::new(&self.config.url);
+        if let Some(token) = &self.config.auth_token {
+            transport = transport.auth(token);
+        }
+        let connection = transport.connect()?;
+        let receiver = connection.subscribe();
+        self.websocket = Some(connection);
+        Ok(receiver)
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    fn connect_websocket(&mut self) -> Result<Receiver<SseEvent>, String> {
+        Err(WEBSOCKET_DISABLED.to_string())
+    }
+
+    /// Send a frame over the socket opened by `connect`
+    #[cfg(feature = "websocket")]
+    fn send_frame(&self, frame: String) -> Result<(), String> {
+        let websocket = self
+            .websocket
+            .as_ref()
+            .ok_or("The live stream doesn't accept user actions")?;
+        websocket.send(frame)
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    fn send_frame(&self, _frame: String) -> Result<(), String> {
+        Err("The live stream doesn't accept user actions".to_string())
+    }
+}
+
+impl A2uiTransport for LiveStreamTransport {
+    fn connect(&mut self, _initial_message: MessageInput) -> Result<A2uiEventStream, String> {
+        let receiver = match self.config.transport {
+            A2uiTransportKind::Sse => {
+                let mut sse_client = SseClient::new(&self.config.url);
+                if let Some(token) = &self.config.auth_token {
+                    sse_client = sse_client.auth(token);
+                }
+                sse_client.get()?
+            }
+            A2uiTransportKind::WebSocket => self.connect_websocket()?,
+        };
+
+        Ok(Box::new(LiveEvents {
+            receiver,
+            pending: VecDeque::new(),
+        }))
+    }
+
+    fn send_action(&mut self, action: &UserAction) -> Result<(), String> {
+        let frame = serde_json::json!({ "userAction": action });
+        self.send_frame(frame.to_string())
+    }
+}
+
+/// Messages parsed from the events of a live stream
+struct LiveEvents {
+    receiver: Receiver<SseEvent>,
+    /// Messages of an event that carried more than one
+    pending: VecDeque<A2aStreamEvent>,
+}
+
+impl Iterator for LiveEvents {
+    type Item = A2aStreamEvent;
+
+    fn next(&mut self) -> Option<A2aStreamEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+
+            match self.receiver.recv().ok()? {
+                SseEvent::Data(data) => {
+                    // Parse A2UI messages from SSE data
+                    if let Ok(messages) = serde_json::from_str::<Vec<serde_json::Value>>(&data) {
+                        self.pending.extend(
+                            messages
+                                .into_iter()
+                                .filter_map(|value| serde_json::from_value(value).ok())
+                                .map(A2aStreamEvent::A2uiMessage),
+                        );
+                    } else if let Ok(message) = serde_json::from_str::<A2uiMessage>(&data) {
+                        return Some(A2aStreamEvent::A2uiMessage(message));
+                    }
+                }
+                SseEvent::Error(e) => return Some(A2aStreamEvent::Error(e)),
+                SseEvent::Done => return None,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Recorded session played back as an agent
+///
+/// Inbound messages are delivered at their recorded timing (in
+/// `ReplayMode::Stepped` without delays); outbound entries are skipped. Each
+/// `connect` starts the replay from the beginning. User actions are accepted
+/// and dropped.
+pub struct ReplayTransport {
+    entries: Vec<RecordedEntry>,
+    mode: ReplayMode,
+}
+
+impl ReplayTransport {
+    /// Create a transport playing back a replay
+    pub fn new(replay: SessionReplay) -> Self {
+        ReplayTransport {
+            entries: replay.entries().to_vec(),
+            mode: replay.mode(),
+        }
+    }
+
+    /// Load a JSONL recording or a JSON array of messages
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        SessionReplay::load(path).map(Self::new)
+    }
+
+    /// Play back a fixed list of messages without delays
+    pub fn from_messages(messages: Vec<A2uiMessage>) -> Self {
+        let mut replay = SessionReplay::from_messages(messages);
+        replay.set_mode(ReplayMode::Stepped);
+        Self::new(replay)
+    }
+}
+
+impl A2uiTransport for ReplayTransport {
+    fn connect(&mut self, _initial_message: MessageInput) -> Result<A2uiEventStream, String> {
+        Ok(Box::new(ReplayEvents {
+            first_timestamp_ms: self.entries.first().map(|entry| entry.timestamp_ms),
+            entries: self.entries.clone().into_iter(),
+            timed: self.mode == ReplayMode::Timed,
+            started_at: Instant::now(),
+        }))
+    }
+
+    fn send_action(&mut self, _action: &UserAction) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Inbound messages of a recording, delayed to their recorded offsets
+struct ReplayEvents {
+    entries: std::vec::IntoIter<RecordedEntry>,
+    first_timestamp_ms: Option<u64>,
+    timed: bool,
+    started_at: Instant,
+}
+
+impl Iterator for ReplayEvents {
+    type Item = A2aStreamEvent;
+
+    fn next(&mut self) -> Option<A2aStreamEvent> {
+        for entry in self.entries.by_ref() {
+            let RecordedEvent::Inbound(message) = entry.event else {
+                continue;
+            };
+            if self.timed {
+                let offset = entry
+                    .timestamp_ms
+                    .saturating_sub(self.first_timestamp_ms.unwrap_or(entry.timestamp_ms));
+                let due = Duration::from_millis(offset);
+                if let Some(wait) = due.checked_sub(self.started_at.elapsed()) {
+                    thread::sleep(wait);
+                }
+            }
+            return Some(A2aStreamEvent::A2uiMessage(message));
+        }
+        None
+    }
+}
+
+/// Create an in-process transport and the agent end of it
+///
+/// The host's session ends when the `ChannelAgent` is dropped.
+pub fn channel_transport() -> (ChannelTransport, ChannelAgent) {
+    let (event_tx, event_rx) = mpsc::channel();
+    let (message_tx, message_rx) = mpsc::channel();
+    let (action_tx, action_rx) = mpsc::channel();
+    let (data_tx, data_rx) = mpsc::channel();
+
+    let transport = ChannelTransport {
+        events: Some(event_rx),
+        messages: message_tx,
+        actions: action_tx,
+        data_changes: data_tx,
+    };
+    let agent = ChannelAgent {
+        events: event_tx,
+        messages: message_rx,
+        actions: action_rx,
+        data_changes: data_rx,
+    };
+    (transport, agent)
+}
+
+/// Host end of an in-process transport, see `channel_transport`
+pub struct ChannelTransport {
+    events: Option<Receiver<A2aStreamEvent>>,
+    messages: Sender<MessageInput>,
+    actions: Sender<UserAction>,
+    data_changes: Sender<DataModelChange>,
+}
+
+impl A2uiTransport for ChannelTransport {
+    fn connect(&mut self, initial_message: MessageInput) -> Result<A2uiEventStream, String> {
+        let events = self.events.take().ok_or("Already connected")?;
+        self.messages
+            .send(initial_message)
+            .map_err(|_| "Agent disconnected".to_string())?;
+        Ok(Box::new(events.into_iter()))
+    }
+
+    fn send_action(&mut self, action: &UserAction) -> Result<(), String> {
+        self.actions
+            .send(action.clone())
+            .map_err(|_| "Agent disconnected".to_string())
+    }
+
+    fn send_data_change(&mut self, change: &DataModelChange) -> Result<(), String> {
+        self.data_changes
+            .send(change.clone())
+            .map_err(|_| "Agent disconnected".to_string())
+    }
+}
+
+/// Agent end of an in-process transport, see `channel_transport`
+pub struct ChannelAgent {
+    events: Sender<A2aStreamEvent>,
+    messages: Receiver<MessageInput>,
+    actions: Receiver<UserAction>,
+    data_changes: Receiver<DataModelChange>,
+}
+
+impl ChannelAgent {
+    /// Send an event to the host
+    pub fn send(&self, event: A2aStreamEvent) -> Result<(), String> {
+        self.events
+            .send(event)
+            .map_err(|_| "Host disconnected".to_string())
+    }
+
+    /// Send an A2UI message to the host
+    pub fn send_message(&self, message: A2uiMessage) -> Result<(), String> {
+        self.send(A2aStreamEvent::A2uiMessage(message))
+    }
+
+    /// Send text for the host's chat transcript
+    pub fn send_text(&self, text: impl Into<String>) -> Result<(), String> {
+        self.send(A2aStreamEvent::Text(text.into()))
+    }
+
+    /// Wait for the host's initial message (`None` once the host is gone)
+    pub fn recv_message(&self) -> Option<MessageInput> {
+        self.messages.recv().ok()
+    }
+
+    /// Wait for the next user action (`None` once the host is gone)
+    pub fn recv_action(&self) -> Option<UserAction> {
+        self.actions.recv().ok()
+    }
+
+    /// Take the next user action if there is one
+    pub fn try_recv_action(&self) -> Option<UserAction> {
+        self.actions.try_recv().ok()
+    }
+
+    /// Take the next batch of data model edits if there is one
+    pub fn try_recv_data_change(&self) -> Option<DataModelChange> {
+        self.data_changes.try_recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::UserActionPayload;
+
+    fn begin_rendering(surface_id: &str) -> A2uiMessage {
+        serde_json::from_value(serde_json::json!({
+            "beginRendering": {"surfaceId": surface_id, "root": "root"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_channel_transport() {
+        let (mut transport, agent) = channel_transport();
+        let mut events = transport.connect(MessageInput::from("Hello")).unwrap();
+        assert!(transport.connect(MessageInput::default()).is_err());
+        assert!(agent.recv_message().is_some());
+
+        agent.send_message(begin_rendering("main")).unwrap();
+        agent.send_text("Done").unwrap();
+        assert!(matches!(
+            events.next(),
+            Some(A2aStreamEvent::A2uiMessage(_))
+        ));
+        assert!(matches!(events.next(), Some(A2aStreamEvent::Text(t)) if t == "Done"));
+
+        let action = UserAction {
+            surface_id: "main".to_string(),
+            component_id: Some("submit".to_string()),
+            action: UserActionPayload {
+                name: "submit".to_string(),
+                context: Default::default(),
+            },
+        };
+        transport.send_action(&action).unwrap();
+        assert_eq!(agent.try_recv_action(), Some(action));
+
+        drop(agent);
+        assert!(events.next().is_none());
+        assert!(transport.cancel_task("task").is_err());
+    }
+
+    #[test]
+    fn test_replay_transport() {
+        let content = [
+            r#"{"timestampMs": 1000, "inbound": {"beginRendering": {"surfaceId": "a", "root": "root"}}}"#,
+            r#"{"timestampMs": 1010, "outbound": {"surfaceId": "a", "action": {"name": "go"}}}"#,
+            r#"{"timestampMs": 1030, "inbound": {"beginRendering": {"surfaceId": "b", "root": "root"}}}"#,
+        ]
+        .join("\n");
+        let mut transport = ReplayTransport::new(SessionReplay::parse(&content).unwrap());
+
+        let started = Instant::now();
+        let surfaces: Vec<String> = transport
+            .connect(MessageInput::default())
+            .unwrap()
+            .map(|event| match event {
+                A2aStreamEvent::A2uiMessage(message) => message.surface_id().to_string(),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(surfaces, vec!["a", "b"]);
+        assert!(started.elapsed() >= Duration::from_millis(30));
+
+        // Each connect replays from the start
+        let mut transport = ReplayTransport::from_messages(vec![begin_rendering("main")]);
+        for _ in 0..2 {
+            assert_eq!(
+                transport.connect(MessageInput::default()).unwrap().count(),
+                1
+            );
+        }
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Represents the primitive value types used in A2UI protocol for data binding.
 
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// A string value that can be either a literal or a data-bound path.
@@ -13,6 +15,7 @@ use serde::{Deserialize, Serialize};
 /// {"path": "/user/name"}
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(untagged)]
 pub enum StringValue {
     /// A literal string value
@@ -72,6 +75,18 @@ impl Default for StringValue {
     }
 }
 
+impl From<&str> for StringValue {
+    fn from(s: &str) -> Self {
+        StringValue::literal(s)
+    }
+}
+
+impl From<String> for StringValue {
+    fn from(s: String) -> Self {
+        StringValue::literal(s)
+    }
+}
+
 /// A number value that can be either a literal or a data-bound path.
 ///
 /// # Examples
@@ -81,6 +96,7 @@ impl Default for StringValue {
 /// {"path": "/count"}
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(untagged)]
 pub enum NumberValue {
     /// A literal number value
@@ -133,6 +149,12 @@ impl Default for NumberValue {
     }
 }
 
+impl From<f64> for NumberValue {
+    fn from(n: f64) -> Self {
+        NumberValue::literal(n)
+    }
+}
+
 /// A boolean value that can be either a literal or a data-bound path.
 ///
 /// # Examples
@@ -142,6 +164,7 @@ impl Default for NumberValue {
 /// {"path": "/enabled"}
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(untagged)]
 pub enum BooleanValue {
     /// A literal boolean value
@@ -194,6 +217,12 @@ impl Default for BooleanValue {
     }
 }
 
+impl From<bool> for BooleanValue {
+    fn from(b: bool) -> Self {
+        BooleanValue::literal(b)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
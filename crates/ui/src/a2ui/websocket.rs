@@ -0,0 +1,337 @@
+//! WebSocket Transport Layer
+//!
+//! Alternative to SSE for networks where proxies buffer event streams. One
+//! socket carries both directions:
+//! - Text frames from the server are delivered as `SseEvent::Data`, so they
+//!   go through the same parsing as SSE `data:` payloads
+//! - The client sends requests (e.g., user actions) as text frames
+//! - Pings keep the connection alive; a server that stops answering is
+//!   reported as an error
+//!
+//! Both directions are bounded: `send` fails once `MAX_QUEUED_FRAMES` frames
+//! are waiting for the socket, and events received while nobody subscribes
+//! keep only the latest `MAX_BACKLOG_EVENTS`.
+
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tungstenite::client::IntoClientRequest;
+use tungstenite::http::{HeaderName, HeaderValue};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use super::sse::SseEvent;
+
+/// Default interval between keep-alive pings
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long a socket read blocks before queued frames are sent
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Frames waiting to be sent before `send` reports the queue as full
+pub const MAX_QUEUED_FRAMES: usize = 256;
+
+/// Events kept for a later subscriber; older ones are dropped
+pub const MAX_BACKLOG_EVENTS: usize = 1024;
+
+/// WebSocket client configuration
+pub struct WebSocketTransport {
+    url: String,
+    headers: Vec<(String, String)>,
+    ping_interval: Duration,
+}
+
+impl WebSocketTransport {
+    /// Create a transport for a `ws://` or `wss://` URL
+    ///
+    /// `http://` and `https://` URLs are mapped to `ws://` and `wss://`.
+    pub fn new(url: impl Into<String>) -> Self {
+        let url = url.into();
+        let url = if let Some(rest) = url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else if let Some(rest) = url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else {
+            url
+        };
+        WebSocketTransport {
+            url,
+            headers: Vec::new(),
+            ping_interval: DEFAULT_PING_INTERVAL,
+        }
+    }
+
+    /// Add a header to the handshake request
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add authorization header
+    pub fn auth(self, token: impl Into<String>) -> Self {
+        self.header("Authorization", format!("Bearer {}", token.into()))
+    }
+
+    /// Set the keep-alive ping interval
+    ///
+    /// The connection fails if nothing is received for two intervals.
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// WebSocket URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Open the socket
+    ///
+    /// The connection is served by a background thread until it is closed by
+    /// either side or the returned `WebSocketConnection` is dropped.
+    pub fn connect(self) -> Result<WebSocketConnection, String> {
+        let mut request = self
+            .url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| format!("Invalid WebSocket URL {}: {}", self.url, e))?;
+        for (key, value) in &self.headers {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|e| format!("Invalid header {}: {}", key, e))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| format!("Invalid header {}: {}", key, e))?;
+            request.headers_mut().insert(name, value);
+        }
+
+        let (mut socket, _) = tungstenite::connect(request)
+            .map_err(|e| format!("WebSocket connect failed: {}", e))?;
+        set_read_timeout(&mut socket, Some(READ_POLL_INTERVAL))
+            .map_err(|e| format!("WebSocket setup failed: {}", e))?;
+
+        let (outgoing_tx, outgoing_rx) = mpsc::sync_channel(MAX_QUEUED_FRAMES);
+        let subscriber = Arc::new(Mutex::new(Subscriber::default()));
+        let worker_subscriber = Arc::clone(&subscriber);
+        let ping_interval = self.ping_interval;
+        thread::spawn(move || {
+            run_socket(socket, outgoing_rx, &worker_subscriber, ping_interval);
+        });
+
+        Ok(WebSocketConnection {
+            outgoing: outgoing_tx,
+            subscriber,
+        })
+    }
+}
+
+/// An open WebSocket connection
+///
+/// Clones share the socket. Dropping the last clone closes it.
+#[derive(Clone)]
+pub struct WebSocketConnection {
+    outgoing: SyncSender<String>,
+    subscriber: Arc<Mutex<Subscriber>>,
+}
+
+impl WebSocketConnection {
+    /// Receive the events of the socket
+    ///
+    /// Events arriving before the first call are kept for it. A new call takes
+    /// over from the previous receiver, whose channel then ends.
+    pub fn subscribe(&self) -> Receiver<SseEvent> {
+        let (tx, rx) = mpsc::channel();
+        let mut subscriber = self.subscriber.lock().unwrap();
+        for event in subscriber.backlog.drain(..) {
+            let _ = tx.send(event);
+        }
+        subscriber.sender = Some(tx);
+        rx
+    }
+
+    /// Send a text frame
+    ///
+    /// Fails without blocking if `MAX_QUEUED_FRAMES` frames are still waiting
+    /// for a stalled socket.
+    pub fn send(&self, text: impl Into<String>) -> Result<(), String> {
+        self.outgoing.try_send(text.into()).map_err(|e| match e {
+            TrySendError::Full(_) => "WebSocket send queue full".to_string(),
+            TrySendError::Disconnected(_) => "WebSocket closed".to_string(),
+        })
+    }
+}
+
+/// Receiver of socket events, with events that arrived while there was none
+#[derive(Default)]
+struct Subscriber {
+    sender: Option<Sender<SseEvent>>,
+    backlog: VecDeque<SseEvent>,
+}
+
+impl Subscriber {
+    fn deliver(&mut self, event: SseEvent) {
+        let event = match &self.sender {
+            Some(sender) => match sender.send(event) {
+                Ok(()) => return,
+                // Receiver dropped, keep the event for the next subscriber
+                Err(mpsc::SendError(event)) => event,
+            },
+            None => event,
+        };
+        self.sender = None;
+        if self.backlog.len() == MAX_BACKLOG_EVENTS {
+            self.backlog.pop_front();
+        }
+        self.backlog.push_back(event);
+    }
+}
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn set_read_timeout(socket: &mut Socket, timeout: Option<Duration>) -> std::io::Result<()> {
+    match socket.get_mut() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(timeout),
+        MaybeTlsStream::Rustls(stream) => stream.sock.set_read_timeout(timeout),
+        _ => Ok(()),
+    }
+}
+
+/// Serve the socket: send queued frames, ping, and deliver received frames
+fn run_socket(
+    mut socket: Socket,
+    outgoing: Receiver<String>,
+    subscriber: &Mutex<Subscriber>,
+    ping_interval: Duration,
+) {
+    let mut last_ping = Instant::now();
+    let mut last_received = Instant::now();
+
+    let result = 'serve: loop {
+        // Send queued frames
+        loop {
+            match outgoing.try_recv() {
+                Ok(text) => {
+                    if let Err(e) = socket.send(Message::Text(text)) {
+                        break 'serve Err(format!("WebSocket send failed: {}", e));
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // Connection handle dropped
+                    let _ = socket.close(None);
+                    let _ = socket.flush();
+                    break 'serve Ok(());
+                }
+            }
+        }
+
+        // Keep-alive
+        if last_received.elapsed() > ping_interval * 2 {
+            break Err("WebSocket ping timeout".to_string());
+        }
+        if last_ping.elapsed() >= ping_interval {
+            if let Err(e) = socket.send(Message::Ping(Vec::new())) {
+                break Err(format!("WebSocket send failed: {}", e));
+            }
+            last_ping = Instant::now();
+        }
+
+        match socket.read() {
+            Ok(message) => {
+                last_received = Instant::now();
+                let event = match message {
+                    Message::Text(text) => SseEvent::Data(text),
+                    Message::Binary(bytes) => {
+                        SseEvent::Data(String::from_utf8_lossy(&bytes).into_owned())
+                    }
+                    Message::Close(_) => break Ok(()),
+                    // Pongs to server pings are sent by tungstenite
+                    Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+                };
+                subscriber.lock().unwrap().deliver(event);
+            }
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                break Ok(());
+            }
+            Err(e) => break Err(format!("WebSocket error: {}", e)),
+        }
+    };
+
+    let mut subscriber = subscriber.lock().unwrap();
+    if let Err(e) = result {
+        subscriber.deliver(SseEvent::Error(e));
+    }
+    subscriber.deliver(SseEvent::Done);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_url_scheme() {
+        assert_eq!(
+            WebSocketTransport::new("http://localhost:8080/ws").url(),
+            "ws://localhost:8080/ws"
+        );
+        assert_eq!(
+            WebSocketTransport::new("https://agent.example/ws").url(),
+            "wss://agent.example/ws"
+        );
+        assert_eq!(
+            WebSocketTransport::new("ws://localhost/ws").url(),
+            "ws://localhost/ws"
+        );
+    }
+
+    #[test]
+    fn test_echo_roundtrip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            socket.send(Message::Text("hello".to_string())).unwrap();
+            loop {
+                match socket.read() {
+                    Ok(Message::Text(text)) => socket.send(Message::Text(text)).unwrap(),
+                    Ok(Message::Close(_)) | Err(_) => break,
+                    Ok(_) => {}
+                }
+            }
+        });
+
+        let connection = WebSocketTransport::new(format!("http://127.0.0.1:{}/ws", port))
+            .connect()
+            .unwrap();
+        // Frames received before subscribing are kept
+        thread::sleep(Duration::from_millis(100));
+        let events = connection.subscribe();
+        assert!(matches!(events.recv().unwrap(), SseEvent::Data(text) if text == "hello"));
+
+        connection.send(r#"{"userAction": {}}"#).unwrap();
+        assert!(
+            matches!(events.recv().unwrap(), SseEvent::Data(text) if text.contains("userAction"))
+        );
+
+        drop(connection);
+        assert!(matches!(events.recv().unwrap(), SseEvent::Done));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_backlog_keeps_latest_events() {
+        let mut subscriber = Subscriber::default();
+        for i in 0..MAX_BACKLOG_EVENTS + 10 {
+            subscriber.deliver(SseEvent::Data(i.to_string()));
+        }
+        assert_eq!(subscriber.backlog.len(), MAX_BACKLOG_EVENTS);
+        assert!(matches!(&subscriber.backlog[0], SseEvent::Data(text) if text == "10"));
+    }
+}